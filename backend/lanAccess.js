@@ -0,0 +1,236 @@
+import crypto from 'crypto';
+
+// When the desktop shell lets other devices on the network reach the server
+// (HOST=0.0.0.0) with a PIN, it passes a salted PBKDF2 hash of the PIN as
+// LOCAL_AGENT_LAN_PIN_HASH. Requests from anywhere but this computer then
+// need a session cookie, handed out for the right PIN. Wrong guesses lock a
+// device out for a while, and too many from anywhere lock out everyone, so
+// the PIN can't be guessed over the network.
+
+export const UNLOCK_PATH = '/lan/unlock';
+const SESSION_COOKIE = 'la_lan_session';
+const SESSION_TTL_MS = 12 * 60 * 60 * 1000;
+const MAX_FAILURES = 5;
+const MAX_TOTAL_FAILURES = 20;
+const LOCKOUT_MS = 15 * 60 * 1000;
+const MAX_BODY_BYTES = 1024;
+const HASH_BYTES = 32;
+
+export function isLoopback(address) {
+  if (!address) return false;
+  const ip = address.startsWith('::ffff:') ? address.slice('::ffff:'.length) : address;
+  return ip === '::1' || ip.startsWith('127.');
+}
+
+// `pbkdf2-sha256$<iterations>$<salt hex>$<hash hex>`, as the shell writes it
+export function parsePinHash(value) {
+  const parts = String(value ?? '').split('$');
+  if (parts.length !== 4 || parts[0] !== 'pbkdf2-sha256') return null;
+  const iterations = Number(parts[1]);
+  const salt = Buffer.from(parts[2], 'hex');
+  const hash = Buffer.from(parts[3], 'hex');
+  if (!Number.isInteger(iterations) || iterations < 1) return null;
+  if (salt.length === 0 || hash.length !== HASH_BYTES) return null;
+  return { iterations, salt, hash };
+}
+
+async function verifyPin(pin, parsed) {
+  if (!parsed || typeof pin !== 'string' || pin.length === 0 || pin.length > 64) return false;
+  const derived = await new Promise((resolve, reject) =>
+    crypto.pbkdf2(pin, parsed.salt, parsed.iterations, HASH_BYTES, 'sha256', (err, key) =>
+      err ? reject(err) : resolve(key)
+    )
+  );
+  return crypto.timingSafeEqual(derived, parsed.hash);
+}
+
+function readCookie(req, name) {
+  const header = req.headers.cookie;
+  if (!header) return null;
+  for (const part of header.split(';')) {
+    const [key, ...rest] = part.trim().split('=');
+    if (key === name) return rest.join('=');
+  }
+  return null;
+}
+
+function readBody(req) {
+  return new Promise((resolve, reject) => {
+    let body = '';
+    req.setEncoding('utf8');
+    req.on('data', (chunk) => {
+      body += chunk;
+      if (body.length > MAX_BODY_BYTES) {
+        reject(new Error('Request body too large'));
+        req.destroy();
+      }
+    });
+    req.on('end', () => resolve(body));
+    req.on('error', reject);
+  });
+}
+
+function readPin(body, contentType) {
+  if (String(contentType).includes('application/json')) {
+    return JSON.parse(body || '{}').pin;
+  }
+  return new URLSearchParams(body).get('pin');
+}
+
+function send(res, status, body, headers = {}) {
+  res.writeHead(status, { 'Cache-Control': 'no-store', ...headers });
+  res.end(body);
+}
+
+function sendJson(res, status, payload, headers = {}) {
+  send(res, status, JSON.stringify(payload), {
+    'Content-Type': 'application/json; charset=utf-8',
+    ...headers,
+  });
+}
+
+const PIN_PAGE = `<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Local Agent</title>
+<style>
+  body { background: #1a1a1a; color: #e0e0e0; font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif; display: flex; justify-content: center; padding-top: 20vh; }
+  form { display: flex; flex-direction: column; gap: 0.75rem; width: 16rem; }
+  input, button { font-size: 1rem; padding: 0.5rem; border-radius: 6px; border: 1px solid #444; background: #2a2a2a; color: inherit; }
+</style>
+</head>
+<body>
+<form method="post" action="${UNLOCK_PATH}">
+  <label for="pin">Enter the PIN shown in Local Agent on the host computer</label>
+  <input id="pin" name="pin" type="password" inputmode="numeric" autocomplete="off" autofocus>
+  <button type="submit">Unlock</button>
+</form>
+</body>
+</html>`;
+
+// For the desktop shell's own endpoints (shutdown, maintenance). Only the
+// shell sends the header, and browsers can't without a CORS preflight, which
+// other origins fail. Any device on the network could send it once LAN
+// access is on, so it also has to come from this computer.
+export function createShellOnly({
+  isLocal = (req) => isLoopback(req.socket?.remoteAddress),
+} = {}) {
+  return (req, res, next) => {
+    if (req.headers['x-local-agent-shell'] !== '1' || !isLocal(req)) {
+      return sendJson(res, 403, { error: 'Only the desktop shell can do this' });
+    }
+    return next();
+  };
+}
+
+export function createLanAccess({
+  pinHash = process.env.LOCAL_AGENT_LAN_PIN_HASH,
+  isLocal = (req) => isLoopback(req.socket?.remoteAddress),
+  now = Date.now,
+} = {}) {
+  const parsed = pinHash ? parsePinHash(pinHash) : null;
+  if (pinHash && !parsed) {
+    console.error('[lan] LOCAL_AGENT_LAN_PIN_HASH is not a PIN hash; every PIN will be refused');
+  }
+  // token -> expiry
+  const sessions = new Map();
+  // address -> { count, lockedUntil }
+  const failures = new Map();
+  const total = { count: 0, resetAt: 0, lockedUntil: 0 };
+
+  function hasSession(req) {
+    const token = readCookie(req, SESSION_COOKIE);
+    const expiresAt = token ? sessions.get(token) : undefined;
+    if (expiresAt === undefined) return false;
+    if (expiresAt <= now()) {
+      sessions.delete(token);
+      return false;
+    }
+    return true;
+  }
+
+  function lockedFor(address) {
+    const lockedUntil = Math.max(failures.get(address)?.lockedUntil ?? 0, total.lockedUntil);
+    return Math.max(lockedUntil - now(), 0);
+  }
+
+  // Counted before the PIN is checked, so parallel guesses can't slip past
+  // the limit while the hash is computed
+  function countAttempt(address) {
+    const record = failures.get(address) ?? { count: 0, lockedUntil: 0 };
+    record.count += 1;
+    if (record.count >= MAX_FAILURES) {
+      record.count = 0;
+      record.lockedUntil = now() + LOCKOUT_MS;
+      console.warn(`[lan] Too many wrong PINs from ${address}; locked out for 15 minutes`);
+    }
+    failures.set(address, record);
+
+    if (now() > total.resetAt) {
+      total.count = 0;
+      total.resetAt = now() + LOCKOUT_MS;
+    }
+    total.count += 1;
+    if (total.count >= MAX_TOTAL_FAILURES) {
+      total.count = 0;
+      total.lockedUntil = now() + LOCKOUT_MS;
+      console.warn('[lan] Too many wrong PINs; LAN access locked for 15 minutes');
+    }
+  }
+
+  async function unlock(req, res) {
+    const address = req.socket?.remoteAddress ?? 'unknown';
+    const wait = lockedFor(address);
+    if (wait > 0) {
+      return sendJson(
+        res,
+        429,
+        { error: 'Too many wrong PINs; try again later', code: 'LAN_PIN_LOCKED' },
+        { 'Retry-After': String(Math.ceil(wait / 1000)) }
+      );
+    }
+
+    let pin;
+    try {
+      pin = readPin(await readBody(req), req.headers['content-type']);
+    } catch {
+      return sendJson(res, 400, { error: 'Send the PIN as `pin`' });
+    }
+    countAttempt(address);
+    if (!(await verifyPin(pin, parsed))) {
+      return sendJson(res, 401, { error: 'Wrong PIN', code: 'LAN_PIN_INVALID' });
+    }
+    failures.delete(address);
+    total.count = Math.max(total.count - 1, 0);
+
+    const token = crypto.randomBytes(32).toString('hex');
+    sessions.set(token, now() + SESSION_TTL_MS);
+    const cookie = `${SESSION_COOKIE}=${token}; HttpOnly; SameSite=Strict; Path=/; Max-Age=${
+      SESSION_TTL_MS / 1000
+    }`;
+    if (String(req.headers['content-type']).includes('application/json')) {
+      return sendJson(res, 200, { ok: true }, { 'Set-Cookie': cookie });
+    }
+    return send(res, 303, '', { 'Set-Cookie': cookie, Location: '/' });
+  }
+
+  function askForPin(req, res) {
+    if (req.method === 'GET' && String(req.headers.accept).includes('text/html')) {
+      return send(res, 401, PIN_PAGE, { 'Content-Type': 'text/html; charset=utf-8' });
+    }
+    return sendJson(res, 401, {
+      error: 'Enter the PIN shown in Local Agent to use it from this device',
+      code: 'LAN_PIN_REQUIRED',
+    });
+  }
+
+  return async (req, res, next) => {
+    if (!pinHash || isLocal(req)) return next();
+    if (hasSession(req)) return next();
+    const pathname = new URL(req.url, 'http://localhost').pathname;
+    if (req.method === 'POST' && pathname === UNLOCK_PATH) return unlock(req, res);
+    return askForPin(req, res);
+  };
+}
@@ -0,0 +1,49 @@
+import fs from 'fs';
+import path from 'path';
+
+// Housekeeping the desktop shell calls on its maintenance schedule
+// (POST /api/maintenance/<name>), so the server needs no timer of its own.
+// The shell holds runs back while work is in flight, and each task only
+// touches data nobody would miss.
+
+// A chat nobody wrote in that has sat this long was abandoned
+const EMPTY_CHAT_MIN_AGE_MS = 24 * 60 * 60 * 1000;
+
+// Removes saved chats with no messages that aren't pinned and are older
+// than a day. Files that don't parse are left alone.
+export function pruneEmptyChats(
+  chatsDir,
+  { now = Date.now, minAgeMs = EMPTY_CHAT_MIN_AGE_MS } = {}
+) {
+  if (!fs.existsSync(chatsDir)) return { removed: 0, kept: 0 };
+
+  let removed = 0;
+  let kept = 0;
+  for (const file of fs.readdirSync(chatsDir).filter((name) => name.endsWith('.json'))) {
+    const filepath = path.join(chatsDir, file);
+    let data;
+    try {
+      data = JSON.parse(fs.readFileSync(filepath, 'utf8'));
+    } catch {
+      kept += 1;
+      continue;
+    }
+    const empty = Array.isArray(data.messages) && data.messages.length === 0;
+    const old = now() - fs.statSync(filepath).mtimeMs >= minAgeMs;
+    if (empty && old && !data.pinned) {
+      fs.rmSync(filepath);
+      removed += 1;
+    } else {
+      kept += 1;
+    }
+  }
+  return { removed, kept };
+}
+
+// Maintenance tasks by the name in their endpoint
+export function createMaintenanceTasks({ toolEventLogger, chatsDir }) {
+  return new Map([
+    ['compact-tool-log', () => toolEventLogger.compact()],
+    ['prune-empty-chats', () => pruneEmptyChats(chatsDir)],
+  ]);
+}
@@ -3,6 +3,7 @@ import path from 'path';
 
 const DEFAULT_MAX_STRING = 8_000;
 const DEFAULT_MAX_ARRAY = 200;
+const DEFAULT_KEEP_EVENTS = 5_000;
 const REDACTED = '[REDACTED]';
 const SENSITIVE_KEY_PATTERNS = [
   'password',
@@ -216,6 +217,23 @@ export function createToolEventLogger(projectRoot) {
     return filtered.slice(0, Math.max(0, Number(limit) || 100));
   }
 
+  // Drops all but the newest `keep` events (and any unreadable lines), so
+  // the log doesn't grow without bound. Written to a temporary file and
+  // renamed over the log, so a crash mid-way leaves the old one intact.
+  function compact(keep = DEFAULT_KEEP_EVENTS) {
+    const content = fs.readFileSync(logFilePath, 'utf8');
+    const lines = content.split('\n').filter((line) => line.trim());
+    const events = parseEvents(logFilePath);
+    const kept = events
+      .sort((a, b) => (a.sequence || 0) - (b.sequence || 0))
+      .slice(Math.max(0, events.length - Math.max(0, keep)));
+
+    const tmpPath = `${logFilePath}.tmp`;
+    fs.writeFileSync(tmpPath, kept.map((event) => `${JSON.stringify(event)}\n`).join(''));
+    fs.renameSync(tmpPath, logFilePath);
+    return { kept: kept.length, removed: lines.length - kept.length };
+  }
+
   return {
     startEvent,
     finalizeSuccess,
@@ -223,5 +241,6 @@ export function createToolEventLogger(projectRoot) {
     persist,
     getEventById,
     listEvents,
+    compact,
   };
 }
@@ -0,0 +1,38 @@
+#!/usr/bin/env node
+/**
+ * Integrity manifest – writes integrity.json with the SHA-256 of every
+ * backend file the desktop shell runs, so it can refuse a damaged install.
+ * Run: npm run integrity-manifest (before packaging)
+ */
+import crypto from 'crypto';
+import fs from 'fs';
+import path from 'path';
+import { fileURLToPath } from 'url';
+
+const __dirname = path.dirname(fileURLToPath(import.meta.url));
+const PROJECT_ROOT = path.resolve(__dirname, '..');
+
+const FILES = ['server.js', 'package.json'];
+const DIRS = ['backend', 'shared'];
+
+function walk(dir) {
+  const full = path.join(PROJECT_ROOT, dir);
+  if (!fs.existsSync(full)) return [];
+  return fs.readdirSync(full, { withFileTypes: true }).flatMap((entry) => {
+    const relative = path.posix.join(dir, entry.name);
+    if (entry.isDirectory()) return walk(relative);
+    return entry.isFile() && entry.name.endsWith('.js') ? [relative] : [];
+  });
+}
+
+const files = {};
+for (const relative of [...FILES, ...DIRS.flatMap(walk)].sort()) {
+  const contents = fs.readFileSync(path.join(PROJECT_ROOT, relative));
+  files[relative] = crypto.createHash('sha256').update(contents).digest('hex');
+}
+
+fs.writeFileSync(
+  path.join(PROJECT_ROOT, 'integrity.json'),
+  `${JSON.stringify({ files }, null, 2)}\n`
+);
+console.log(`Wrote integrity.json for ${Object.keys(files).length} files`);
@@ -2,6 +2,7 @@ import express from 'express';
 import cors from 'cors';
 import fs from 'fs';
 import path from 'path';
+import readline from 'readline';
 import dotenv from 'dotenv';
 import { fileURLToPath } from 'url';
 import { createProxyMiddleware } from 'http-proxy-middleware';
@@ -10,6 +11,8 @@ import { createMcpConfigStore } from './backend/mcpConfigStore.js';
 import { createAppConfigStore } from './backend/appConfigStore.js';
 import { call_tool, list_tools, withMcpConnection } from './backend/mcpClient.js';
 import { createToolEventLogger } from './backend/toolEventLogger.js';
+import { createLanAccess, createShellOnly, isLoopback } from './backend/lanAccess.js';
+import { createMaintenanceTasks } from './backend/maintenance.js';
 import { runSync, readSyncState } from './backend/syncSkills.js';
 
 dotenv.config();
@@ -18,10 +21,27 @@ const __filename = fileURLToPath(import.meta.url);
 const __dirname = path.dirname(__filename);
 const PROJECT_ROOT = path.resolve(__dirname);
 const SKILLS_DIR = path.join(__dirname, '.agents', 'skills');
-const CHATS_DIR = path.join(__dirname, 'chats');
+// The desktop shell points this at the active profile's data directory
+const DATA_DIR = process.env.LOCAL_AGENT_DATA_DIR
+  ? path.resolve(process.env.LOCAL_AGENT_DATA_DIR)
+  : __dirname;
+const CHATS_DIR = path.join(DATA_DIR, 'chats');
+
+// error | warn | info | debug; the desktop shell sets it from Preferences
+const LOG_LEVELS = ['error', 'warn', 'info', 'debug'];
+const logLevel = LOG_LEVELS.indexOf(process.env.LOG_LEVEL);
+if (logLevel !== -1) {
+  const silence = () => {};
+  if (logLevel < 3) console.debug = silence;
+  if (logLevel < 2) console.log = console.info = silence;
+  if (logLevel < 1) console.warn = silence;
+}
 
 const app = express();
 const PORT = process.env.PORT || 3001;
+// The desktop shell sets 0.0.0.0 when the user lets other devices on the
+// network in
+const HOST = process.env.HOST || '127.0.0.1';
 
 const mcpConfigStore = createMcpConfigStore(PROJECT_ROOT);
 const appConfigStore = createAppConfigStore(PROJECT_ROOT);
@@ -39,15 +59,64 @@ const allowedOrigins = (
   .map((origin) => origin.trim())
   .filter(Boolean);
 
+// Other devices need the LAN PIN before anything else
+app.use(createLanAccess());
 app.use(
-  cors({
-    origin: (origin, callback) => {
-      if (!origin) return callback(null, true);
-      if (allowedOrigins.includes(origin)) return callback(null, true);
-      return callback(new Error('Not allowed by CORS'));
-    },
+  cors((req, callback) => {
+    const origin = req.get('origin');
+    // The UI served to another device on the network calls back to itself
+    const sameOrigin =
+      !isLoopback(req.socket.remoteAddress) && origin === `${req.protocol}://${req.get('host')}`;
+    if (!origin || sameOrigin || allowedOrigins.includes(origin)) {
+      return callback(null, { origin: true });
+    }
+    return callback(new Error('Not allowed by CORS'));
   })
 );
+// Request tracing – the desktop shell sends x-trace-id per user action; tagged
+// lines on stdout are correlated back into the shell's trace timeline.
+app.use((req, res, next) => {
+  const traceId = req.get('x-trace-id');
+  if (!traceId || !/^[A-Za-z0-9-]{1,64}$/.test(traceId)) return next();
+  const startedAt = Date.now();
+  console.log(`[trace ${traceId}] -> ${req.method} ${req.originalUrl}`);
+  res.on('finish', () => {
+    console.log(
+      `[trace ${traceId}] <- ${res.statusCode} ${req.method} ${req.originalUrl} (${Date.now() - startedAt}ms)`
+    );
+  });
+  next();
+});
+
+// Counters served on /metrics for the desktop shell's dashboard
+const metrics = { requests: 0, tokensGenerated: 0 };
+app.use((req, _res, next) => {
+  // The shell's own polling isn't traffic
+  if (req.path !== '/health' && req.path !== '/metrics') metrics.requests += 1;
+  next();
+});
+
+// Work in progress, which the desktop shell checks before quitting so it
+// can offer to wait rather than cut a generation or tool run short
+const activeJobs = new Map();
+let nextJobId = 1;
+const trackJob = (kind) => (req, res, next) => {
+  const id = nextJobId++;
+  activeJobs.set(id, {
+    id,
+    kind,
+    path: req.originalUrl,
+    started_at: new Date().toISOString(),
+  });
+  res.on('close', () => activeJobs.delete(id));
+  next();
+};
+app.use('/v1', trackJob('generation'));
+app.use('/api/tools', (req, res, next) =>
+  req.method === 'POST' ? trackJob('tool')(req, res, next) : next()
+);
+app.post('/api/skills/sync', trackJob('sync'));
+
 // Model API proxy – forwards /v1/* to configured model base URL (LM Studio, Ollama, etc.)
 app.use(
   '/v1',
@@ -62,9 +131,22 @@ app.use(
     on: {
       proxyReq: (proxyReq, req, res) => {
         console.log(`[Proxy] Request: ${req.method} ${req.url}`);
+        // A closed window or cancelled stream stops generation upstream too
+        res.on('close', () => {
+          if (!res.writableFinished) proxyReq.destroy();
+        });
       },
       proxyRes: (proxyRes, req, res) => {
         console.log(`[Proxy] Response: ${proxyRes.statusCode} from ${req.url}`);
+        // Usage comes last, in the JSON body or the final stream chunk
+        let tail = '';
+        proxyRes.on('data', (chunk) => {
+          tail = (tail + chunk.toString('utf8')).slice(-4096);
+        });
+        proxyRes.on('end', () => {
+          const matches = [...tail.matchAll(/"completion_tokens"\s*:\s*(\d+)/g)];
+          if (matches.length > 0) metrics.tokensGenerated += Number(matches.at(-1)[1]);
+        });
       },
       error: (err, req, res) => {
         console.error('Model proxy error:', err.message);
@@ -744,6 +826,67 @@ app.get('/api/chats/:filename', (req, res) => {
 // Health check endpoint (must respond quickly, never block)
 app.get('/health', (_req, res) => res.json({ status: 'ok' }));
 
+// Compiled backends may not ship package.json
+let packageVersion = null;
+try {
+  packageVersion = JSON.parse(fs.readFileSync(path.join(__dirname, 'package.json'), 'utf8')).version;
+} catch {
+  // Reported as unknown
+}
+app.get('/api/version', (_req, res) =>
+  res.json({ version: packageVersion, node: process.versions.node })
+);
+
+// Prometheus text format
+app.get('/metrics', (_req, res) => {
+  res.type('text/plain; version=0.0.4').send(
+    [
+      '# HELP local_agent_requests_total HTTP requests received.',
+      '# TYPE local_agent_requests_total counter',
+      `local_agent_requests_total ${metrics.requests}`,
+      '# HELP local_agent_tokens_generated_total Completion tokens reported by the model server.',
+      '# TYPE local_agent_tokens_generated_total counter',
+      `local_agent_tokens_generated_total ${metrics.tokensGenerated}`,
+      '# HELP local_agent_queue_depth Generations, tool runs and syncs in progress.',
+      '# TYPE local_agent_queue_depth gauge',
+      `local_agent_queue_depth ${activeJobs.size}`,
+      '',
+    ].join('\n')
+  );
+});
+
+app.get('/api/activity', (_req, res) => {
+  const jobs = [...activeJobs.values()];
+  res.json({ busy: jobs.length > 0, jobs });
+});
+
+const requireShell = createShellOnly();
+
+// Stops taking requests and exits once in-flight work is done
+app.post('/api/shutdown', requireShell, (_req, res) => {
+  res.status(202).json({ jobs: activeJobs.size });
+  res.on('finish', shutdown);
+});
+
+// Housekeeping the desktop shell runs on its maintenance schedule
+const maintenanceTasks = createMaintenanceTasks({ toolEventLogger, chatsDir: CHATS_DIR });
+app.post('/api/maintenance/:task', requireShell, (req, res) => {
+  const task = maintenanceTasks.get(req.params.task);
+  if (!task) {
+    return res.status(404).json({ error: `Unknown maintenance task: ${req.params.task}` });
+  }
+  try {
+    const result = task();
+    console.log(`[maintenance] ${req.params.task}: ${JSON.stringify(result)}`);
+    return res.json(result);
+  } catch (err) {
+    console.error(`[maintenance] ${req.params.task} failed:`, err);
+    return res
+      .status(500)
+      .json({ error: err instanceof Error ? err.message : 'Maintenance task failed' });
+  }
+});
+
 // Serve built frontend in production (when not behind Vite dev server)
 if (process.env.NODE_ENV === 'production') {
   const distPath = path.join(__dirname, 'dist');
@@ -756,6 +899,52 @@ if (process.env.NODE_ENV === 'production') {
   }
 }
 
-app.listen(PORT, '127.0.0.1', () => {
-  console.log(`Tool Execution Server running on http://127.0.0.1:${PORT}`);
-});
+// The desktop shell can ask for a Unix socket (named pipe on Windows)
+// instead of a port, and forwards the UI's requests to it
+const SOCKET = process.env.LOCAL_AGENT_SOCKET;
+if (SOCKET && process.platform !== 'win32') {
+  // Left behind by a previous run that didn't exit cleanly
+  fs.rmSync(SOCKET, { force: true });
+}
+
+const onListening = () => {
+  if (SOCKET && process.platform !== 'win32') fs.chmodSync(SOCKET, 0o600);
+  console.log(`Tool Execution Server running on ${SOCKET || `http://${HOST}:${PORT}`}`);
+  // Tells the desktop shell we're up without waiting for its next health
+  // poll; written directly so LOG_LEVEL can't silence it
+  process.stdout.write(SOCKET ? '::ready\n' : `::ready port=${PORT}\n`);
+};
+const server = SOCKET ? app.listen(SOCKET, onListening) : app.listen(PORT, HOST, onListening);
+
+let stopping = false;
+function shutdown() {
+  if (stopping) return;
+  stopping = true;
+  console.log('[control] Graceful shutdown requested');
+  server.close(() => process.exit(0));
+  // Keep-alive connections can hold close() open; the shell kills us anyway
+  server.closeIdleConnections?.();
+}
+
+// Control messages from the desktop shell, one `::name` per line on stdin.
+// They still arrive when HTTP is wedged, and are the only polite way to stop
+// the server on Windows.
+if (process.env.LOCAL_AGENT_CONTROL === 'stdin') {
+  const controls = {
+    'graceful-shutdown': shutdown,
+    'reload-config': () => {
+      dotenv.config({ override: true });
+      console.log('[control] Reloaded environment');
+    },
+    flush: () => process.stdout.write('', () => process.stdout.write('::flushed\n')),
+  };
+
+  readline.createInterface({ input: process.stdin }).on('line', (line) => {
+    const name = line.trim().replace(/^::/, '');
+    const handler = controls[name];
+    if (handler) handler();
+    else console.warn(`[control] Unknown command: ${line.trim()}`);
+  });
+  // The shell went away without asking
+  process.stdin.on('end', shutdown);
+}
@@ -1,3 +1,191 @@
 fn main() {
-    tauri_build::build()
+    println!("cargo:rerun-if-changed=../package.json");
+    println!(
+        "cargo:rustc-env=LOCAL_AGENT_MIN_NODE={}",
+        min_node_version()
+    );
+    println!(
+        "cargo:rustc-env=LOCAL_AGENT_BACKEND_VERSION={}",
+        backend_version()
+    );
+    println!(
+        "cargo:rustc-env=LOCAL_AGENT_TARGET={}",
+        std::env::var("TARGET").unwrap()
+    );
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    check_commands();
+    tauri_build::try_build(
+        tauri_build::Attributes::new()
+            .app_manifest(tauri_build::AppManifest::new().commands(COMMANDS)),
+    )
+    .expect("failed to run tauri-build");
+}
+
+/// Every command in `generate_handler!`. Each gets `allow-*` and `deny-*`
+/// permissions, and a window may only call the ones its capability grants
+/// (see `permissions/windows.toml` and `capabilities/`).
+const COMMANDS: &[&str] = &[
+    "restart_backend",
+    "restart_backend_soft",
+    "upgrade_backend",
+    "rollback_backend_upgrade",
+    "check_backend_health_once",
+    "cancel_health_check",
+    "ack_backend_ready",
+    "send_backend_command",
+    "open_logs_folder",
+    "open_log_file",
+    "get_wake_status",
+    "set_wake_on_schedule",
+    "set_remote_control_enabled",
+    "start_remote_pairing",
+    "list_paired_devices",
+    "revoke_paired_device",
+    "push_remote_prompt",
+    "clear_remote_prompt",
+    "set_mdns_advertise",
+    "discover_agents",
+    "start_trace",
+    "record_trace_event",
+    "get_trace",
+    "list_sidecars",
+    "get_sidecar_status",
+    "restart_sidecar",
+    "open_terminal",
+    "terminal_start",
+    "terminal_write",
+    "terminal_resize",
+    "get_ollama_status",
+    "start_ollama",
+    "list_local_models",
+    "get_disk_report",
+    "check_download_space",
+    "clean_rotated_logs",
+    "clean_crash_reports",
+    "get_uninstall_plan",
+    "prepare_uninstall",
+    "export_app_data",
+    "import_app_data",
+    "get_app_info",
+    "get_system_capabilities",
+    "run_preflight_checks",
+    "get_resource_usage",
+    "get_metrics_history",
+    "get_maintenance_status",
+    "get_startup_stats",
+    "get_startup_timings",
+    "set_log_streaming",
+    "open_log_viewer",
+    "get_log_history",
+    "read_log_range",
+    "get_log_metadata",
+    "get_recent_events",
+    "get_error_context",
+    "get_recent_logs",
+    "tail_logs",
+    "untail_logs",
+    "get_effective_config",
+    "list_profiles",
+    "create_profile",
+    "switch_profile",
+    "list_secrets",
+    "set_secret",
+    "get_secret",
+    "delete_secret",
+    "pick_directory",
+    "list_workspaces",
+    "revoke_workspace",
+    "read_workspace_file",
+    "watch_directory",
+    "unwatch_directory",
+    "capture_screenshot",
+    "answer_screenshot_prompt",
+    "open_window",
+    "stream_backend",
+    "cancel_backend_stream",
+    "open_preferences",
+    "open_about",
+    "get_settings",
+    "set_zoom",
+    "get_zoom",
+    "get_setting",
+    "set_setting",
+    "get_autostart",
+    "set_autostart",
+    "get_last_shutdown_report",
+    "get_restart_history",
+    "get_crash_reports",
+    "get_backend_state",
+    "get_offline_mode",
+    "get_backend_activity",
+    "confirm_quit",
+    "check_for_updates",
+    "test_connectivity",
+    "get_network_status",
+    "get_network_exposure",
+    "set_network_exposure",
+    "run_self_test",
+    "open_update_page",
+    "open_issue_report",
+];
+
+/// Fails the build when `COMMANDS` and `generate_handler!` in lib.rs
+/// disagree: a command left out here could never be called.
+fn check_commands() {
+    let lib = std::fs::read_to_string("src/lib.rs").expect("failed to read src/lib.rs");
+    let start = lib
+        .find("generate_handler![")
+        .expect("no generate_handler! in src/lib.rs")
+        + "generate_handler![".len();
+    let end = start + lib[start..].find(']').unwrap();
+    let registered: Vec<&str> = lib[start..end]
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect();
+    let missing: Vec<&&str> = registered
+        .iter()
+        .filter(|name| !COMMANDS.contains(name))
+        .collect();
+    let stale: Vec<&&str> = COMMANDS
+        .iter()
+        .filter(|name| !registered.contains(name))
+        .collect();
+    if !missing.is_empty() || !stale.is_empty() {
+        panic!(
+            "build.rs COMMANDS is out of date; add {:?} and remove {:?}",
+            missing, stale
+        );
+    }
+}
+
+/// Reads the minimum Node.js version from `engines.node` in the root
+/// package.json (e.g. ">=20.0.0") so the shell and the backend agree.
+fn min_node_version() -> String {
+    let fallback = "20.0.0".to_string();
+    let Ok(contents) = std::fs::read_to_string("../package.json") else {
+        return fallback;
+    };
+    let Ok(package) = serde_json::from_str::<serde_json::Value>(&contents) else {
+        return fallback;
+    };
+    package["engines"]["node"]
+        .as_str()
+        .map(|range| {
+            range
+                .trim_start_matches(|c: char| !c.is_ascii_digit())
+                .to_string()
+        })
+        .filter(|v| !v.is_empty())
+        .unwrap_or(fallback)
+}
+
+/// The backend version bundled with this build, from the root package.json,
+/// so the shell can refuse a backend from another release.
+fn backend_version() -> String {
+    std::fs::read_to_string("../package.json")
+        .ok()
+        .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+        .and_then(|package| package["version"].as_str().map(str::to_string))
+        .unwrap_or_else(|| "0.0.0".to_string())
 }
@@ -0,0 +1,150 @@
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::{pages, template};
+
+pub const ABOUT_LABEL: &str = "about";
+
+/// Third-party software shipped in the app and its license, shown on the
+/// About page. Their own dependencies carry the same kinds of licenses.
+const ACKNOWLEDGEMENTS: &[(&str, &str)] = &[
+    ("Tauri", "Apache-2.0 OR MIT"),
+    ("Node.js", "MIT"),
+    ("Tokio", "MIT"),
+    ("reqwest", "Apache-2.0 OR MIT"),
+    ("Serde", "Apache-2.0 OR MIT"),
+    ("chrono", "Apache-2.0 OR MIT"),
+    ("regex", "Apache-2.0 OR MIT"),
+    ("sysinfo", "MIT"),
+    ("keyring", "Apache-2.0 OR MIT"),
+    ("mdns-sd", "Apache-2.0 OR MIT"),
+    ("portable-pty", "MIT"),
+    ("qrcode", "Apache-2.0 OR MIT"),
+    ("zip", "MIT"),
+];
+
+pub fn open_window(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(ABOUT_LABEL) {
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+        return Ok(());
+    }
+
+    WebviewWindowBuilder::new(
+        app,
+        ABOUT_LABEL,
+        WebviewUrl::CustomProtocol(pages::page_url("about")),
+    )
+    .title(template::t("about_title"))
+    .inner_size(440.0, 520.0)
+    .min_inner_size(320.0, 360.0)
+    .build()
+    .map(|_| ())
+    .map_err(|e| format!("Failed to open About window: {}", e))
+}
+
+const ABOUT_CSS: &str = r#"
+  body { padding: 1.25rem; }
+  h1 { font-size: 1.25rem; margin-bottom: 0.75rem; }
+  h2 { color: var(--fg-muted); font-size: 0.8rem; font-weight: normal; margin: 1rem 0 0.25rem; }
+  table { width: 100%; border-collapse: collapse; font-size: 0.85rem; }
+  td { padding: 0.2rem 0; vertical-align: top; }
+  td:first-child { color: var(--fg-muted); padding-right: 1rem; white-space: nowrap; }
+  #acknowledgements {
+    max-height: 9rem;
+    overflow-y: auto;
+    background: var(--bg-inset);
+    border: 1px solid var(--border);
+    border-radius: 0.375rem;
+    padding: 0.25rem 0.5rem;
+  }
+  .actions { margin-top: 1.25rem; }
+  #status { font-size: 0.8rem; color: var(--fg-muted); margin-top: 0.5rem; min-height: 1.2em; }
+  #status.error { color: var(--accent-error); }
+"#;
+
+const ABOUT_BODY: &str = r#"<body data-checking="{{t:about_checking}}" data-up-to-date="{{t:about_up_to_date}}" data-available="{{t:about_update_available}}" data-unknown="{{t:about_unknown}}" data-get-update="{{t:get_update}}">
+  <h1>Local Agent</h1>
+  <table>
+    <tr><td>{{t:about_app}}</td><td class="mono" data-field="app_version"></td></tr>
+    <tr><td>{{t:about_backend}}</td><td class="mono" data-field="backend_version"></td></tr>
+    <tr><td>Node.js</td><td class="mono" data-field="node_version"></td></tr>
+    <tr><td>Tauri</td><td class="mono" data-field="tauri_version"></td></tr>
+    <tr><td>{{t:about_os}}</td><td class="mono" data-field="platform"></td></tr>
+    <tr><td>{{t:about_license}}</td><td>{{license}}</td></tr>
+  </table>
+  <h2>{{t:about_acknowledgements}}</h2>
+  <table id="acknowledgements"><!-- acknowledgements --></table>
+  <div class="actions">
+    <button id="update">{{t:check_for_updates}}</button>
+    <button id="logs">{{t:open_logs_folder}}</button>
+  </div>
+  <div id="status"></div>
+  <script>
+    const data = document.body.dataset;
+    const status = document.getElementById('status');
+    const update = document.getElementById('update');
+    let available = false;
+
+    function show(message, error) {
+      status.textContent = message;
+      status.classList.toggle('error', !!error);
+    }
+
+    window.__invoke('get_app_info').then((info) => {
+      info.platform = [info.os, info.os_version, info.arch].filter(Boolean).join(' · ');
+      for (const cell of document.querySelectorAll('[data-field]')) {
+        cell.textContent = info[cell.dataset.field] || data.unknown;
+      }
+    });
+
+    update.addEventListener('click', async () => {
+      if (available) {
+        window.__invoke('open_update_page').catch((e) => show(String(e), true));
+        return;
+      }
+      update.disabled = true;
+      show(data.checking);
+      try {
+        const info = await window.__invoke('check_for_updates');
+        available = info.available;
+        if (available) {
+          show(data.available.replace('{version}', info.latest));
+          update.textContent = data.getUpdate;
+        } else {
+          show(data.upToDate);
+        }
+      } catch (e) {
+        show(String(e), true);
+      } finally {
+        update.disabled = false;
+      }
+    });
+
+    document.getElementById('logs').addEventListener('click', () => {
+      window.__invoke('open_logs_folder').catch((e) => show(String(e), true));
+    });
+  </script>
+</body>"#;
+
+pub fn generate_about_html() -> String {
+    let license = match env!("CARGO_PKG_LICENSE") {
+        "" => template::t("about_unknown"),
+        license => license,
+    };
+    let rows: String = ACKNOWLEDGEMENTS
+        .iter()
+        .map(|(name, license)| {
+            format!(
+                "<tr><td>{}</td><td>{}</td></tr>",
+                template::html_escape(name),
+                template::html_escape(license)
+            )
+        })
+        .collect();
+    let body = template::fill(ABOUT_BODY, &[("license", license)]).replacen(
+        "<!-- acknowledgements -->",
+        &rows,
+        1,
+    );
+    template::render(template::t("about_title"), ABOUT_CSS, &body)
+}
@@ -0,0 +1,54 @@
+//! Requests from the shell to the backend's own API, over TCP or through
+//! the socket bridge, whichever this run uses.
+
+use std::time::Duration;
+use tauri::http::Request;
+use tauri::AppHandle;
+
+use crate::bridge;
+
+/// Marks requests as coming from the shell; the backend refuses its
+/// shell-only endpoints without it.
+pub const SHELL_HEADER: &str = "x-local-agent-shell";
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Sends `method path` with no body; `None` means the backend didn't
+/// answer in time.
+pub async fn call(app: &AppHandle, method: &str, path: &str) -> Option<(u16, Vec<u8>)> {
+    call_with_timeout(app, method, path, TIMEOUT).await
+}
+
+/// `call` for requests that take longer than a status check.
+pub async fn call_with_timeout(
+    app: &AppHandle,
+    method: &str,
+    path: &str,
+    timeout: Duration,
+) -> Option<(u16, Vec<u8>)> {
+    if let Some(socket) = bridge::active() {
+        let request = Request::builder()
+            .method(method)
+            .uri(path)
+            .header(SHELL_HEADER, "1")
+            .body(Vec::new())
+            .ok()?;
+        let response = tokio::time::timeout(timeout, bridge::send(socket, &request))
+            .await
+            .ok()??;
+        return Some((response.status().as_u16(), response.into_body()));
+    }
+
+    let client = reqwest::Client::builder().timeout(timeout).build().ok()?;
+    let response = client
+        .request(
+            method.parse().ok()?,
+            format!("{}{}", crate::backend_url(app), path),
+        )
+        .header(SHELL_HEADER, "1")
+        .send()
+        .await
+        .ok()?;
+    let status = response.status().as_u16();
+    let body = response.bytes().await.ok()?;
+    Some((status, body.to_vec()))
+}
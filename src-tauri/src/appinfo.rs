@@ -0,0 +1,78 @@
+//! Versions of everything Local Agent is made of, for the About dialog and
+//! bug reports.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use sysinfo::System;
+use tauri::AppHandle;
+
+use crate::{api, node};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppInfo {
+    pub app_version: String,
+    pub tauri_version: &'static str,
+    /// `None` when neither the backend nor its package.json could tell.
+    pub backend_version: Option<String>,
+    /// The runtime the backend runs on, or the `node` on PATH when the
+    /// backend didn't answer.
+    pub node_version: Option<String>,
+    pub os: &'static str,
+    pub os_version: Option<String>,
+    pub arch: &'static str,
+}
+
+/// What `/api/version` answers.
+#[derive(Debug, Default, Deserialize)]
+struct BackendVersion {
+    version: Option<String>,
+    node: Option<String>,
+}
+
+/// Asks the running backend first, then reads `package.json` in
+/// `backend_dir` and runs `node --version`.
+pub async fn collect(app: &AppHandle, backend_dir: Option<&Path>) -> AppInfo {
+    let reported = reported(app).await;
+    let backend_version = reported
+        .version
+        .or_else(|| backend_dir.and_then(package_version));
+    let node_version = match reported.node {
+        Some(version) => Some(version),
+        None => tokio::task::spawn_blocking(|| node::check().ok().map(|info| info.version))
+            .await
+            .ok()
+            .flatten(),
+    };
+
+    AppInfo {
+        app_version: app.package_info().version.to_string(),
+        tauri_version: tauri::VERSION,
+        backend_version,
+        node_version,
+        os: std::env::consts::OS,
+        os_version: System::long_os_version(),
+        arch: std::env::consts::ARCH,
+    }
+}
+
+/// The backend's version as it reports it, or from `package.json` in
+/// `backend_dir`.
+pub async fn backend_version(app: &AppHandle, backend_dir: Option<&Path>) -> Option<String> {
+    reported(app)
+        .await
+        .version
+        .or_else(|| backend_dir.and_then(package_version))
+}
+
+async fn reported(app: &AppHandle) -> BackendVersion {
+    match api::call(app, "GET", "/api/version").await {
+        Some((200, body)) => serde_json::from_slice(&body).unwrap_or_default(),
+        _ => BackendVersion::default(),
+    }
+}
+
+fn package_version(dir: &Path) -> Option<String> {
+    let contents = std::fs::read_to_string(dir.join("package.json")).ok()?;
+    let package: serde_json::Value = serde_json::from_str(&contents).ok()?;
+    package.get("version")?.as_str().map(str::to_string)
+}
@@ -0,0 +1,146 @@
+//! Launch at login: a LaunchAgent on macOS, an HKCU Run value on Windows
+//! and an XDG autostart entry elsewhere, all pointing at the running
+//! executable. With "start minimized" the entry passes `--minimized`, and
+//! the app comes up in the tray with the backend running but no window.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::uninstall::{remove_file_if_exists, APP_IDENTIFIER};
+
+const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+const RUN_VALUE: &str = "Local Agent";
+pub const MINIMIZED_ARG: &str = "--minimized";
+
+/// This process was started by an entry asking for the tray only.
+pub fn launched_minimized() -> bool {
+    std::env::args().any(|arg| arg == MINIMIZED_ARG)
+}
+
+fn launch_agent_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| {
+        home.join("Library")
+            .join("LaunchAgents")
+            .join(format!("{}.plist", APP_IDENTIFIER))
+    })
+}
+
+fn desktop_entry_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|config| config.join("autostart").join("local-agent.desktop"))
+}
+
+/// Creates or replaces the login entry.
+pub fn enable(minimized: bool) -> Result<(), String> {
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Failed to locate the app executable: {}", e))?;
+    let exe = exe.to_string_lossy();
+    let extra = if minimized { MINIMIZED_ARG } else { "" };
+
+    if cfg!(windows) {
+        let output = Command::new("reg")
+            .args([
+                "add",
+                RUN_KEY,
+                "/v",
+                RUN_VALUE,
+                "/t",
+                "REG_SZ",
+                "/d",
+                format!("\"{}\" {}", exe, extra).trim_end(),
+                "/f",
+            ])
+            .output()
+            .map_err(|e| format!("Failed to run reg: {}", e))?;
+        return if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Failed to add autostart entry: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        };
+    }
+
+    let (path, contents) = if cfg!(target_os = "macos") {
+        let path = launch_agent_path().ok_or("Home directory not found")?;
+        let plist = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <key>Label</key>
+  <string>{}</string>
+  <key>ProgramArguments</key>
+  <array><string>{}</string>{}</array>
+  <key>RunAtLoad</key>
+  <true/>
+</dict>
+</plist>
+"#,
+            APP_IDENTIFIER,
+            crate::template::html_escape(&exe),
+            if minimized {
+                format!("<string>{}</string>", MINIMIZED_ARG)
+            } else {
+                String::new()
+            }
+        );
+        (path, plist)
+    } else {
+        let path = desktop_entry_path().ok_or("Config directory not found")?;
+        let entry = format!(
+            "[Desktop Entry]\nType=Application\nName=Local Agent\nExec={}\n\
+             X-GNOME-Autostart-enabled=true\n",
+            format!("\"{}\" {}", exe, extra).trim_end()
+        );
+        (path, entry)
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Whether a login entry is registered, whoever created it.
+pub fn is_enabled() -> bool {
+    if cfg!(windows) {
+        return Command::new("reg")
+            .args(["query", RUN_KEY, "/v", RUN_VALUE])
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+    }
+    let path = if cfg!(target_os = "macos") {
+        launch_agent_path()
+    } else {
+        desktop_entry_path()
+    };
+    path.is_some_and(|path| path.is_file())
+}
+
+/// Removes whatever `enable` created; returns what was removed.
+pub fn disable() -> Result<Vec<String>, String> {
+    let mut removed = Vec::new();
+    if cfg!(target_os = "macos") {
+        if let Some(plist) = launch_agent_path() {
+            if remove_file_if_exists(&plist)? {
+                removed.push(plist.display().to_string());
+            }
+        }
+    } else if cfg!(windows) {
+        let output = Command::new("reg")
+            .args(["delete", RUN_KEY, "/v", RUN_VALUE, "/f"])
+            .output()
+            .map_err(|e| format!("Failed to run reg: {}", e))?;
+        if output.status.success() {
+            removed.push("HKCU Run entry".to_string());
+        }
+    } else if let Some(desktop) = desktop_entry_path() {
+        if remove_file_if_exists(&desktop)? {
+            removed.push(desktop.display().to_string());
+        }
+    }
+    Ok(removed)
+}
@@ -0,0 +1,154 @@
+//! Retry timing shared by sidecar spawn retries and health polling.
+
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant};
+
+/// Exponential backoff with jitter. Every field must be given when a policy
+/// is overridden in the config file, so a partial section cannot silently
+/// mix in another policy's values.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Backoff {
+    /// Wait after the first failed attempt.
+    pub initial_delay_ms: u64,
+    /// Growth factor per attempt; 1.0 keeps a fixed interval.
+    pub multiplier: f64,
+    /// Cap on a single wait before jitter.
+    pub max_delay_ms: u64,
+    /// Fraction each wait is randomly shortened or lengthened by (0.2 =
+    /// ±20%), so sidecars restarted together do not retry in lockstep.
+    #[serde(default)]
+    pub jitter: f64,
+    /// Total attempts including the first; unlimited when unset.
+    #[serde(default)]
+    pub max_attempts: Option<u32>,
+    /// Give up once this long has passed since the first attempt.
+    #[serde(default)]
+    pub max_elapsed_ms: Option<u64>,
+}
+
+impl Backoff {
+    /// Starting a process either works quickly or keeps failing, so give up
+    /// after a few attempts (about 1s, 2s between them).
+    pub fn spawn() -> Self {
+        Self {
+            initial_delay_ms: 1000,
+            multiplier: 2.0,
+            max_delay_ms: 8000,
+            jitter: 0.2,
+            max_attempts: Some(3),
+            max_elapsed_ms: None,
+        }
+    }
+
+    /// Polls often while the backend is likely to come up soon, then backs
+    /// off, until the overall deadline.
+    pub fn health() -> Self {
+        Self {
+            initial_delay_ms: 250,
+            multiplier: 1.5,
+            max_delay_ms: 1000,
+            jitter: 0.1,
+            max_attempts: None,
+            max_elapsed_ms: Some(15000),
+        }
+    }
+
+    pub fn start(&self) -> Attempts {
+        Attempts {
+            policy: *self,
+            failures: 0,
+            started: Instant::now(),
+        }
+    }
+
+    /// Wait after `failures` failed attempts, before jitter.
+    fn base_delay(&self, failures: u32) -> Duration {
+        let exponent = failures.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let ms = self.initial_delay_ms as f64 * self.multiplier.max(1.0).powi(exponent);
+        let ms = if ms.is_finite() {
+            ms.min(self.max_delay_ms as f64)
+        } else {
+            self.max_delay_ms as f64
+        };
+        Duration::from_millis(ms as u64)
+    }
+}
+
+/// One run of a [`Backoff`] policy.
+#[derive(Debug)]
+pub struct Attempts {
+    policy: Backoff,
+    failures: u32,
+    started: Instant,
+}
+
+impl Attempts {
+    /// Records a failed attempt and returns how long to wait before the
+    /// next one, or `None` when the policy is exhausted. The last wait is
+    /// shortened so the final attempt lands on the elapsed deadline.
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        self.failures += 1;
+        if let Some(max) = self.policy.max_attempts {
+            if self.failures >= max {
+                return None;
+            }
+        }
+
+        let delay = jittered(self.policy.base_delay(self.failures), self.policy.jitter);
+        match self.policy.max_elapsed_ms {
+            Some(max) => {
+                let remaining = Duration::from_millis(max).checked_sub(self.started.elapsed())?;
+                if remaining.is_zero() {
+                    return None;
+                }
+                Some(delay.min(remaining))
+            }
+            None => Some(delay),
+        }
+    }
+
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+fn jittered(delay: Duration, jitter: f64) -> Duration {
+    let jitter = if jitter.is_finite() {
+        jitter.clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    if jitter == 0.0 {
+        return delay;
+    }
+    // Uniform in [-1, 1); RandomState is freshly seeded by the OS each time
+    let unit = (RandomState::new().build_hasher().finish() >> 11) as f64 / (1u64 << 53) as f64;
+    delay.mul_f64(1.0 + jitter * (unit * 2.0 - 1.0))
+}
+
+/// Retry policies for the `retry` section of the shell config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Attempts to start a sidecar process.
+    pub spawn: Backoff,
+    /// Polling the backend `/health` endpoint during startup. When startup
+    /// auto-tuning has enough history it replaces the initial delay and the
+    /// elapsed deadline.
+    pub health: Backoff,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            spawn: Backoff::spawn(),
+            health: Backoff::health(),
+        }
+    }
+}
@@ -0,0 +1,528 @@
+//! Backups of everything needed to move Local Agent to another machine: the
+//! shell config, the backend's saved chats and the backend's own config
+//! and tool-call log from its project root, in one zip archive.
+//!
+//! ```text
+//! manifest.json
+//! config/shell-config.json
+//! data/chats/…
+//! backend/config/app-config.json
+//! backend/config/mcp-servers.json
+//! backend/logs/tool-calls.jsonl
+//! ```
+//!
+//! Secrets stay in the keychain and aren't archived; the shell config only
+//! carries their names.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Bumped when the layout changes; newer archives are refused. Version 1
+/// had no `backend/` files.
+pub const FORMAT_VERSION: u32 = 2;
+const MANIFEST: &str = "manifest.json";
+const CONFIG_ENTRY: &str = "config/shell-config.json";
+const DATA_PREFIX: &str = "data/";
+/// What the backend keeps under its data directory. The directory itself
+/// defaults to the project root, so it isn't archived whole.
+const DATA_SUBDIRS: &[&str] = &["chats"];
+const BACKEND_PREFIX: &str = "backend/";
+/// What the backend keeps under its project root (see `server.js`).
+const BACKEND_FILES: &[&str] = &[
+    "config/app-config.json",
+    "config/mcp-servers.json",
+    "logs/tool-calls.jsonl",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: u32,
+    pub app_version: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupSummary {
+    pub path: PathBuf,
+    pub manifest: Manifest,
+    pub files: usize,
+    pub bytes: u64,
+    /// Secrets the restored config named that aren't in this machine's
+    /// keychain, as `<scope>/<NAME>`; they were dropped from the config and
+    /// need setting again.
+    pub missing_secrets: Vec<String>,
+}
+
+/// Writes `config_file`, the backend data under `data_dir` and the
+/// backend's files under `backend_root` to `archive`. The archive only
+/// appears once it is complete.
+pub fn export(
+    archive: &Path,
+    config_file: &Path,
+    data_dir: &Path,
+    backend_root: &Path,
+) -> Result<BackupSummary, String> {
+    let partial = archive.with_extension("zip.part");
+    let result = write_archive(&partial, config_file, data_dir, backend_root).and_then(|summary| {
+        fs::rename(&partial, archive)
+            .map_err(|e| format!("Failed to save {}: {}", archive.display(), e))?;
+        Ok(BackupSummary {
+            path: archive.to_path_buf(),
+            ..summary
+        })
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&partial);
+    }
+    result
+}
+
+fn write_archive(
+    archive: &Path,
+    config_file: &Path,
+    data_dir: &Path,
+    backend_root: &Path,
+) -> Result<BackupSummary, String> {
+    let file = File::create(archive)
+        .map_err(|e| format!("Failed to create {}: {}", archive.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let manifest = Manifest {
+        format: FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: chrono::Local::now().to_rfc3339(),
+    };
+    let mut summary = BackupSummary {
+        path: archive.to_path_buf(),
+        manifest: manifest.clone(),
+        files: 0,
+        bytes: 0,
+        missing_secrets: Vec::new(),
+    };
+    let zip_err = |e: zip::result::ZipError| format!("Failed to write backup: {}", e);
+    let io_err = |e: io::Error| format!("Failed to write backup: {}", e);
+
+    zip.start_file(MANIFEST, options).map_err(zip_err)?;
+    let json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    zip.write_all(&json).map_err(io_err)?;
+
+    if config_file.is_file() {
+        add_file(&mut zip, options, config_file, CONFIG_ENTRY, &mut summary)?;
+    }
+    for name in DATA_SUBDIRS {
+        let dir = data_dir.join(name);
+        if dir.is_dir() {
+            add_dir(
+                &mut zip,
+                options,
+                &dir,
+                &format!("{}{}", DATA_PREFIX, name),
+                &mut summary,
+            )?;
+        }
+    }
+    for name in BACKEND_FILES {
+        let file = backend_root.join(name);
+        if file.is_file() {
+            let entry = format!("{}{}", BACKEND_PREFIX, name);
+            add_file(&mut zip, options, &file, &entry, &mut summary)?;
+        }
+    }
+
+    zip.finish().map_err(zip_err)?;
+    Ok(summary)
+}
+
+fn add_file(
+    zip: &mut ZipWriter<File>,
+    options: SimpleFileOptions,
+    path: &Path,
+    entry: &str,
+    summary: &mut BackupSummary,
+) -> Result<(), String> {
+    let mut source =
+        File::open(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    zip.start_file(entry, options)
+        .map_err(|e| format!("Failed to write backup: {}", e))?;
+    summary.bytes += io::copy(&mut source, zip)
+        .map_err(|e| format!("Failed to back up {}: {}", path.display(), e))?;
+    summary.files += 1;
+    Ok(())
+}
+
+fn add_dir(
+    zip: &mut ZipWriter<File>,
+    options: SimpleFileOptions,
+    dir: &Path,
+    entry: &str,
+    summary: &mut BackupSummary,
+) -> Result<(), String> {
+    zip.add_directory(entry, options)
+        .map_err(|e| format!("Failed to write backup: {}", e))?;
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    for item in entries.flatten() {
+        let path = item.path();
+        let name = format!("{}/{}", entry, item.file_name().to_string_lossy());
+        if path.is_dir() {
+            add_dir(zip, options, &path, &name, summary)?;
+        } else {
+            add_file(zip, options, &path, &name, summary)?;
+        }
+    }
+    Ok(())
+}
+
+/// Restores `archive` over `config_file`, the backend data under
+/// `data_dir` and the backend's files under `backend_root`. Everything is
+/// unpacked and checked before anything is replaced; what was there before
+/// is kept alongside with a `.before-import` suffix.
+pub fn import(
+    archive: &Path,
+    config_file: &Path,
+    data_dir: &Path,
+    backend_root: &Path,
+) -> Result<BackupSummary, String> {
+    let file =
+        File::open(archive).map_err(|e| format!("Failed to open {}: {}", archive.display(), e))?;
+    let mut zip = ZipArchive::new(file)
+        .map_err(|e| format!("{} is not a backup: {}", archive.display(), e))?;
+    let manifest = read_manifest(&mut zip)?;
+
+    fs::create_dir_all(data_dir)
+        .map_err(|e| format!("Failed to create {}: {}", data_dir.display(), e))?;
+    let staging = data_dir.join(format!(".local-agent-import-{}", uuid::Uuid::new_v4()));
+    let result = unpack(&mut zip, &staging).and_then(|(files, bytes)| {
+        swap_in(&staging, config_file, data_dir, backend_root)?;
+        Ok(BackupSummary {
+            path: archive.to_path_buf(),
+            manifest,
+            files,
+            bytes,
+            missing_secrets: Vec::new(),
+        })
+    });
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+fn read_manifest(zip: &mut ZipArchive<File>) -> Result<Manifest, String> {
+    let mut entry = zip
+        .by_name(MANIFEST)
+        .map_err(|_| "The archive is not a Local Agent backup".to_string())?;
+    let mut json = String::new();
+    entry
+        .read_to_string(&mut json)
+        .map_err(|e| format!("Failed to read backup manifest: {}", e))?;
+    let manifest: Manifest = serde_json::from_str(&json)
+        .map_err(|e| format!("Failed to parse backup manifest: {}", e))?;
+    if manifest.format > FORMAT_VERSION {
+        return Err(format!(
+            "The backup was made by a newer Local Agent ({}); update to restore it",
+            manifest.app_version
+        ));
+    }
+    Ok(manifest)
+}
+
+/// Extracts every entry under `staging`, skipping any whose path would
+/// escape it.
+fn unpack(zip: &mut ZipArchive<File>, staging: &Path) -> Result<(usize, u64), String> {
+    let (mut files, mut bytes) = (0, 0);
+    for i in 0..zip.len() {
+        let mut entry = zip
+            .by_index(i)
+            .map_err(|e| format!("Failed to read backup: {}", e))?;
+        let Some(relative) = entry.enclosed_name() else {
+            eprintln!("[tauri] Skipping unsafe backup entry {:?}", entry.name());
+            continue;
+        };
+        if relative == Path::new(MANIFEST) {
+            continue;
+        }
+        let target = staging.join(&relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&target)
+                .map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut out = File::create(&target)
+            .map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+        bytes += io::copy(&mut entry, &mut out)
+            .map_err(|e| format!("Failed to restore {}: {}", relative.display(), e))?;
+        files += 1;
+    }
+    Ok((files, bytes))
+}
+
+fn swap_in(
+    staging: &Path,
+    config_file: &Path,
+    data_dir: &Path,
+    backend_root: &Path,
+) -> Result<(), String> {
+    restore_file(&staging.join(CONFIG_ENTRY), config_file)?;
+    for name in BACKEND_FILES {
+        let restored = staging.join(BACKEND_PREFIX).join(name);
+        restore_file(&restored, &backend_root.join(name))?;
+    }
+    for name in DATA_SUBDIRS {
+        let restored = staging.join(DATA_PREFIX).join(name);
+        if !restored.is_dir() {
+            continue;
+        }
+        let target = data_dir.join(name);
+        set_aside(&target)?;
+        fs::rename(&restored, &target)
+            .map_err(|e| format!("Failed to restore {}: {}", target.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Copies `restored` over `target` when the archive had it.
+fn restore_file(restored: &Path, target: &Path) -> Result<(), String> {
+    if !restored.is_file() {
+        return Ok(());
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    set_aside(target)?;
+    fs::copy(restored, target)
+        .map_err(|e| format!("Failed to restore {}: {}", target.display(), e))?;
+    Ok(())
+}
+
+/// Moves `path` to `<path>.before-import`, replacing an earlier one.
+fn set_aside(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let mut aside = path.as_os_str().to_owned();
+    aside.push(".before-import");
+    let aside = PathBuf::from(aside);
+    if aside.is_dir() {
+        let _ = fs::remove_dir_all(&aside);
+    } else {
+        let _ = fs::remove_file(&aside);
+    }
+    fs::rename(path, &aside).map_err(|e| format!("Failed to move {} aside: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A config file, data directory and backend root under a fresh
+    /// directory.
+    struct Install {
+        root: PathBuf,
+        config_file: PathBuf,
+        data_dir: PathBuf,
+        backend_root: PathBuf,
+    }
+
+    impl Install {
+        fn new() -> Self {
+            let root =
+                std::env::temp_dir().join(format!("local-agent-backup-{}", uuid::Uuid::new_v4()));
+            fs::create_dir_all(&root).unwrap();
+            Self {
+                config_file: root.join("config").join("shell-config.json"),
+                data_dir: root.join("data"),
+                backend_root: root.join("backend"),
+                root,
+            }
+        }
+
+        fn write(&self, path: &Path, contents: &str) {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+
+        fn export(&self, archive: &Path) -> Result<BackupSummary, String> {
+            export(
+                archive,
+                &self.config_file,
+                &self.data_dir,
+                &self.backend_root,
+            )
+        }
+
+        fn import(&self, archive: &Path) -> Result<BackupSummary, String> {
+            import(
+                archive,
+                &self.config_file,
+                &self.data_dir,
+                &self.backend_root,
+            )
+        }
+    }
+
+    impl Drop for Install {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    /// An archive with a current manifest and `entries` as (name, contents).
+    fn archive_with(path: &Path, entries: &[(&str, &str)]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        let options = SimpleFileOptions::default();
+        let manifest = Manifest {
+            format: FORMAT_VERSION,
+            app_version: "test".to_string(),
+            created_at: String::new(),
+        };
+        zip.start_file(MANIFEST, options).unwrap();
+        zip.write_all(&serde_json::to_vec(&manifest).unwrap())
+            .unwrap();
+        for (name, contents) in entries {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn export_then_import_restores_everything() {
+        let from = Install::new();
+        from.write(&from.config_file, r#"{"profile":"work"}"#);
+        from.write(&from.data_dir.join("chats").join("a.json"), "chat a");
+        from.write(
+            &from.data_dir.join("chats").join("old").join("b.json"),
+            "chat b",
+        );
+        for (i, name) in BACKEND_FILES.iter().enumerate() {
+            from.write(&from.backend_root.join(name), &format!("backend {}", i));
+        }
+        let archive = from.root.join("backup.zip");
+        let exported = from.export(&archive).unwrap();
+        assert_eq!(exported.files, 3 + BACKEND_FILES.len());
+        assert!(!archive.with_extension("zip.part").exists());
+
+        let to = Install::new();
+        to.write(&to.data_dir.join("chats").join("local.json"), "local chat");
+        to.write(&to.backend_root.join(BACKEND_FILES[0]), "local backend");
+        let imported = to.import(&archive).unwrap();
+        assert_eq!(imported.files, exported.files);
+
+        let read = |path: PathBuf| fs::read_to_string(path).unwrap();
+        assert_eq!(read(to.config_file.clone()), r#"{"profile":"work"}"#);
+        assert_eq!(read(to.data_dir.join("chats").join("a.json")), "chat a");
+        assert_eq!(read(to.data_dir.join("chats/old/b.json")), "chat b");
+        for (i, name) in BACKEND_FILES.iter().enumerate() {
+            assert_eq!(read(to.backend_root.join(name)), format!("backend {}", i));
+        }
+        // What was there is kept alongside
+        assert!(!to.data_dir.join("chats").join("local.json").exists());
+        assert_eq!(
+            read(to.data_dir.join("chats.before-import").join("local.json")),
+            "local chat"
+        );
+        let mut aside = to.backend_root.join(BACKEND_FILES[0]).into_os_string();
+        aside.push(".before-import");
+        assert_eq!(read(PathBuf::from(aside)), "local backend");
+    }
+
+    #[test]
+    fn import_skips_entries_outside_the_install() {
+        let install = Install::new();
+        let archive = install.root.join("evil.zip");
+        archive_with(
+            &archive,
+            &[
+                ("../escaped.txt", "outside"),
+                ("data/../../escaped-too.txt", "outside"),
+                ("/absolute.txt", "outside"),
+                ("data/chats/ok.json", "inside"),
+            ],
+        );
+
+        let summary = install.import(&archive).unwrap();
+        assert_eq!(summary.files, 1);
+        assert_eq!(
+            fs::read_to_string(install.data_dir.join("chats").join("ok.json")).unwrap(),
+            "inside"
+        );
+        assert!(!install.data_dir.join("escaped.txt").exists());
+        assert!(!install.root.join("escaped.txt").exists());
+        assert!(!install.root.join("escaped-too.txt").exists());
+        assert!(!Path::new("/absolute.txt").exists());
+    }
+
+    #[test]
+    fn failed_staging_leaves_current_data_and_earlier_backup() {
+        let install = Install::new();
+        let chats = install.data_dir.join("chats");
+        install.write(&chats.join("current.json"), "current");
+        install.write(
+            &install
+                .data_dir
+                .join("chats.before-import")
+                .join("earlier.json"),
+            "earlier",
+        );
+        let archive = install.root.join("broken.zip");
+        // A file where the next entry needs a directory
+        archive_with(
+            &archive,
+            &[("data/chats", "not a dir"), ("data/chats/new.json", "new")],
+        );
+
+        assert!(install.import(&archive).is_err());
+        assert_eq!(
+            fs::read_to_string(chats.join("current.json")).unwrap(),
+            "current"
+        );
+        assert_eq!(
+            fs::read_to_string(
+                install
+                    .data_dir
+                    .join("chats.before-import")
+                    .join("earlier.json")
+            )
+            .unwrap(),
+            "earlier"
+        );
+        let staged = fs::read_dir(&install.data_dir)
+            .unwrap()
+            .flatten()
+            .any(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(".local-agent-import-")
+            });
+        assert!(!staged, "staging directory left behind");
+    }
+
+    #[test]
+    fn newer_backups_are_refused() {
+        let install = Install::new();
+        let archive = install.root.join("newer.zip");
+        let mut zip = ZipWriter::new(File::create(&archive).unwrap());
+        zip.start_file(MANIFEST, SimpleFileOptions::default())
+            .unwrap();
+        let manifest = serde_json::json!({
+            "format": FORMAT_VERSION + 1,
+            "app_version": "99.0.0",
+            "created_at": "",
+        });
+        zip.write_all(manifest.to_string().as_bytes()).unwrap();
+        zip.finish().unwrap();
+
+        let error = install.import(&archive).unwrap_err();
+        assert!(error.contains("99.0.0"), "{}", error);
+    }
+}
@@ -0,0 +1,243 @@
+//! `--bench-startup N`: cold-starts and stops the backend sidecar N times
+//! without opening any window, and reports per-phase timings so shell and
+//! backend changes can be checked for launch-time regressions.
+//!
+//! Run it from the repo (or pass `--bench-root <dir>`); the report goes to
+//! `--bench-out <file>` or `startup-bench-<timestamp>.json`.
+
+use serde::Serialize;
+use std::fs;
+use std::net::{SocketAddr, TcpStream};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::binaries;
+use crate::config::ShellConfig;
+use crate::health::{self, PollSettings, ProbeSpec};
+use crate::registry::BACKEND;
+use crate::sidecar::{SidecarManager, SidecarSpec};
+use crate::startup::{BootRecord, BootTimer};
+
+pub struct BenchOptions {
+    pub runs: u32,
+    pub root: Option<PathBuf>,
+    pub out: Option<PathBuf>,
+}
+
+impl BenchOptions {
+    /// `Some` when the shell was started with `--bench-startup N`.
+    pub fn from_args(args: &[String]) -> Option<Result<Self, String>> {
+        let value_of = |flag: &str| {
+            args.iter()
+                .position(|a| a == flag)
+                .map(|i| args.get(i + 1).cloned())
+        };
+        let runs = value_of("--bench-startup")?;
+        Some(
+            runs.and_then(|n| n.parse::<u32>().ok())
+                .filter(|n| *n > 0)
+                .ok_or_else(|| "--bench-startup needs a positive number of runs".to_string())
+                .map(|runs| Self {
+                    runs,
+                    root: value_of("--bench-root").flatten().map(PathBuf::from),
+                    out: value_of("--bench-out").flatten().map(PathBuf::from),
+                }),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseSummary {
+    pub phase: String,
+    pub samples: usize,
+    pub min_ms: u64,
+    pub median_ms: u64,
+    pub mean_ms: u64,
+    pub max_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub started_at: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub runs: Vec<BootRecord>,
+    pub errors: Vec<String>,
+    /// Over successful runs only.
+    pub summary: Vec<PhaseSummary>,
+}
+
+/// Runs the benchmark and returns the process exit code.
+pub fn run(options: BenchOptions) -> i32 {
+    match bench(&options) {
+        Ok(report) => {
+            print_summary(&report);
+            if report.errors.is_empty() {
+                0
+            } else {
+                1
+            }
+        }
+        Err(e) => {
+            eprintln!("[tauri] Startup benchmark failed: {}", e);
+            2
+        }
+    }
+}
+
+fn bench(options: &BenchOptions) -> Result<BenchReport, String> {
+    let config = ShellConfig::load();
+    let port = config.settings.backend_port;
+    if TcpStream::connect_timeout(
+        &SocketAddr::from(([127, 0, 0, 1], port)),
+        Duration::from_millis(200),
+    )
+    .is_ok()
+    {
+        return Err(format!(
+            "Port {} is in use; quit Local Agent and any dev backend first",
+            port
+        ));
+    }
+
+    let root = options.root.clone().unwrap_or_else(crate::dev_project_root);
+    let mut spec = match binaries::select(&root).map_err(|p| p.message())? {
+        Some(binary) => SidecarSpec::backend_binary(binary, root),
+        None => SidecarSpec::backend(root),
+    }
+    .with_port(port);
+    spec.log_file = Some("local-agent-bench.log".to_string());
+    spec.health_probe = config.health_probes.get(BACKEND).cloned();
+    let probe = spec
+        .probe()
+        .ok_or_else(|| "The backend has no health check".to_string())?;
+
+    let mut report = BenchReport {
+        started_at: chrono::Local::now().to_rfc3339(),
+        command: spec.command.clone(),
+        args: spec.args.clone(),
+        runs: Vec::new(),
+        errors: Vec::new(),
+        summary: Vec::new(),
+    };
+
+    for run in 1..=options.runs {
+        let (record, error) = cold_start(&spec, &probe);
+        println!(
+            "[tauri] Bench run {}/{}: {} in {}ms",
+            run,
+            options.runs,
+            if record.success { "ok" } else { "failed" },
+            record.total_ms
+        );
+        if let Some(e) = error {
+            report.errors.push(format!("run {}: {}", run, e));
+        }
+        report.runs.push(record);
+    }
+    report.summary = summarize(&report.runs);
+
+    let out = options.out.clone().unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "startup-bench-{}.json",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ))
+    });
+    let contents = serde_json::to_string_pretty(&report)
+        .map_err(|e| format!("Failed to serialize benchmark report: {}", e))?;
+    fs::write(&out, contents).map_err(|e| format!("Failed to write {}: {}", out.display(), e))?;
+    println!("[tauri] Benchmark report written to {}", out.display());
+    Ok(report)
+}
+
+/// One start/stop cycle against an empty data directory.
+fn cold_start(spec: &SidecarSpec, probe: &ProbeSpec) -> (BootRecord, Option<String>) {
+    let data_dir = std::env::temp_dir().join(format!("local-agent-bench-{}", uuid::Uuid::new_v4()));
+    let mut spec = spec.clone();
+    spec.env.insert(
+        "LOCAL_AGENT_DATA_DIR".to_string(),
+        data_dir.to_string_lossy().into_owned(),
+    );
+    let manager = SidecarManager::new(spec);
+
+    let mut timer = BootTimer::default();
+    timer.mark("spawning");
+    let mut error = manager.spawn().err();
+    if error.is_none() {
+        timer.mark("health");
+        error = tauri::async_runtime::block_on(health::poll_health_with(
+            probe,
+            &PollSettings::default(),
+            None,
+        ))
+        .err();
+    }
+    timer.mark("shutdown");
+    manager.shutdown();
+    let record = timer.finish(error.is_none()).unwrap();
+
+    let _ = fs::remove_dir_all(&data_dir);
+    (record, error)
+}
+
+fn summarize(runs: &[BootRecord]) -> Vec<PhaseSummary> {
+    let ok: Vec<&BootRecord> = runs.iter().filter(|r| r.success).collect();
+    let mut phases: Vec<String> = Vec::new();
+    for record in &ok {
+        for phase in &record.phases {
+            if !phases.contains(&phase.stage) {
+                phases.push(phase.stage.clone());
+            }
+        }
+    }
+    phases.push("total".to_string());
+
+    phases
+        .into_iter()
+        .filter_map(|phase| {
+            let mut samples: Vec<u64> = ok
+                .iter()
+                .filter_map(|r| match phase.as_str() {
+                    "total" => Some(r.total_ms),
+                    stage => r.phase_ms(stage),
+                })
+                .collect();
+            if samples.is_empty() {
+                return None;
+            }
+            samples.sort_unstable();
+            Some(PhaseSummary {
+                samples: samples.len(),
+                min_ms: samples[0],
+                median_ms: samples[samples.len() / 2],
+                mean_ms: samples.iter().sum::<u64>() / samples.len() as u64,
+                max_ms: samples[samples.len() - 1],
+                phase,
+            })
+        })
+        .collect()
+}
+
+fn print_summary(report: &BenchReport) {
+    let ok = report.runs.iter().filter(|r| r.success).count();
+    println!(
+        "\nStartup benchmark: {}/{} runs succeeded ({} {})",
+        ok,
+        report.runs.len(),
+        report.command,
+        report.args.join(" ")
+    );
+    println!(
+        "{:<10} {:>8} {:>8} {:>8} {:>8}",
+        "phase", "min", "median", "mean", "max"
+    );
+    for s in &report.summary {
+        println!(
+            "{:<10} {:>6}ms {:>6}ms {:>6}ms {:>6}ms",
+            s.phase, s.min_ms, s.median_ms, s.mean_ms, s.max_ms
+        );
+    }
+    for e in &report.errors {
+        println!("error: {}", e);
+    }
+}
@@ -0,0 +1,153 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory inside the app resources holding compiled backend builds
+/// (pkg/bun/deno compile), one per target:
+/// `local-agent-backend-<target-triple>[.exe]`. The same layout in
+/// `src-tauri/binaries` is what `bundle.externalBin` expects.
+pub const BINARY_DIR: &str = "binaries";
+pub const BACKEND_BINARY: &str = "local-agent-backend";
+/// Target triple this shell was compiled for, set by build.rs.
+pub const TARGET_TRIPLE: &str = env!("LOCAL_AGENT_TARGET");
+
+#[derive(Debug, Clone)]
+pub enum BinaryProblem {
+    Missing {
+        expected: PathBuf,
+        available: Vec<String>,
+    },
+    NotExecutable {
+        path: PathBuf,
+        error: String,
+    },
+}
+
+impl BinaryProblem {
+    pub fn title(&self) -> &'static str {
+        match self {
+            BinaryProblem::Missing { .. } => "Backend build missing for this platform",
+            BinaryProblem::NotExecutable { .. } => "Backend binary is not executable",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            BinaryProblem::Missing { expected, available } => format!(
+                "This build of Local Agent ships compiled backends, but none for {} ({}/{}). Expected {}. Found: {}.",
+                TARGET_TRIPLE,
+                std::env::consts::OS,
+                std::env::consts::ARCH,
+                expected.display(),
+                if available.is_empty() {
+                    "nothing".to_string()
+                } else {
+                    available.join(", ")
+                }
+            ),
+            BinaryProblem::NotExecutable { path, error } => format!(
+                "The backend binary at {} could not be made executable: {}",
+                path.display(),
+                error
+            ),
+        }
+    }
+
+    pub fn remediation(&self) -> Vec<String> {
+        match self {
+            BinaryProblem::Missing { .. } => vec![
+                "Download the Local Agent installer built for your operating system and CPU."
+                    .to_string(),
+                format!(
+                    "When building from source, compile the backend for {} into src-tauri/{}.",
+                    TARGET_TRIPLE, BINARY_DIR
+                ),
+            ],
+            BinaryProblem::NotExecutable { path, .. } => vec![
+                format!("Run: chmod +x \"{}\"", path.display()),
+                "Make sure the install location is not mounted noexec.".to_string(),
+                "Then click Retry.".to_string(),
+            ],
+        }
+    }
+}
+
+fn binary_name() -> String {
+    format!(
+        "{}-{}{}",
+        BACKEND_BINARY,
+        TARGET_TRIPLE,
+        std::env::consts::EXE_SUFFIX
+    )
+}
+
+/// Where the bundler puts an `externalBin` sidecar: next to the app
+/// executable, with the target triple stripped from the name.
+pub fn external_bin_path() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    Some(exe.parent()?.join(format!(
+        "{}{}",
+        BACKEND_BINARY,
+        std::env::consts::EXE_SUFFIX
+    )))
+}
+
+/// Picks the compiled backend for this OS/arch: an `externalBin` sidecar
+/// first, then a per-target build under `resource_dir`. Returns `Ok(None)`
+/// when the app ships the `node server.js` backend instead.
+pub fn select(resource_dir: &Path) -> Result<Option<PathBuf>, BinaryProblem> {
+    if let Some(external) = external_bin_path().filter(|p| p.is_file()) {
+        ensure_executable(&external).map_err(|error| BinaryProblem::NotExecutable {
+            path: external.clone(),
+            error,
+        })?;
+        println!(
+            "[tauri] Using bundled backend sidecar {}",
+            external.display()
+        );
+        return Ok(Some(external));
+    }
+
+    let dir = resource_dir.join(BINARY_DIR);
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(None);
+    };
+
+    let expected = dir.join(binary_name());
+    if !expected.is_file() {
+        let mut available: Vec<String> = entries
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .filter(|name| name.starts_with(BACKEND_BINARY))
+            .collect();
+        available.sort();
+        return Err(BinaryProblem::Missing {
+            expected,
+            available,
+        });
+    }
+
+    ensure_executable(&expected).map_err(|error| BinaryProblem::NotExecutable {
+        path: expected.clone(),
+        error,
+    })?;
+    println!("[tauri] Using compiled backend {}", expected.display());
+    Ok(Some(expected))
+}
+
+#[cfg(unix)]
+fn ensure_executable(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = fs::metadata(path).map_err(|e| e.to_string())?.permissions();
+    if permissions.mode() & 0o111 == 0o111 {
+        return Ok(());
+    }
+    // Archive extraction and some updaters drop the executable bit.
+    permissions.set_mode(permissions.mode() | 0o755);
+    fs::set_permissions(path, permissions).map_err(|e| e.to_string())
+}
+
+#[cfg(not(unix))]
+fn ensure_executable(_path: &Path) -> Result<(), String> {
+    Ok(())
+}
@@ -0,0 +1,314 @@
+//! The backend over a Unix socket (a named pipe on Windows) instead of TCP:
+//! no port to collide with, and nothing other local users can connect to.
+//! The backend listens where `LOCAL_AGENT_SOCKET` says, and the webview
+//! reaches it through the `localagent-app` protocol, which forwards each
+//! request over the socket.
+//!
+//! Responses through the protocol are forwarded whole, so a streamed
+//! response shows up at once when it ends. Model output is streamed through
+//! `stream_backend` instead, which hands each chunk to the webview as it
+//! arrives and drops the backend connection when the webview stops
+//! listening or cancels.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tauri::async_runtime::JoinHandle;
+use tauri::http::{Request, Response, StatusCode};
+use tauri::UriSchemeResponder;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+pub const SCHEME: &str = "localagent-app";
+/// Where the backend should listen instead of `PORT`.
+pub const SOCKET_ENV: &str = "LOCAL_AGENT_SOCKET";
+
+/// Request headers that describe the hop to the shell rather than the
+/// request; the forwarded request sets its own.
+const HOP_HEADERS: &[&str] = &[
+    "host",
+    "connection",
+    "keep-alive",
+    "transfer-encoding",
+    "content-length",
+    "upgrade",
+];
+
+static ACTIVE: OnceLock<PathBuf> = OnceLock::new();
+
+/// The socket for this session, once the backend was started on one.
+pub fn active() -> Option<&'static Path> {
+    ACTIVE.get().map(PathBuf::as_path)
+}
+
+pub fn activate(socket: PathBuf) {
+    let _ = ACTIVE.set(socket);
+}
+
+#[cfg(unix)]
+pub fn socket_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("local-agent")
+        .join("backend.sock")
+}
+
+#[cfg(windows)]
+pub fn socket_path() -> PathBuf {
+    // Pipe names are global; the user name keeps sessions apart
+    let user = std::env::var("USERNAME").unwrap_or_else(|_| "user".to_string());
+    PathBuf::from(format!(r"\\.\pipe\local-agent-backend-{}", user))
+}
+
+/// Creates the socket's directory, readable by the current user only.
+pub fn prepare(socket: &Path) -> Result<(), String> {
+    #[cfg(unix)]
+    if let Some(dir) = socket.parent() {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::create_dir_all(dir)
+            .and_then(|()| std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700)))
+            .map_err(|e| format!("Failed to prepare {}: {}", dir.display(), e))?;
+    }
+    #[cfg(windows)]
+    let _ = socket;
+    Ok(())
+}
+
+/// The app as loaded by the main window, like `pages::page_url`.
+pub fn app_url() -> String {
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://{}.localhost", SCHEME)
+    } else {
+        format!("{}://localhost", SCHEME)
+    }
+}
+
+trait Io: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Io for T {}
+
+#[cfg(unix)]
+async fn connect(socket: &Path) -> std::io::Result<Box<dyn Io>> {
+    Ok(Box::new(tokio::net::UnixStream::connect(socket).await?))
+}
+
+#[cfg(windows)]
+async fn connect(socket: &Path) -> std::io::Result<Box<dyn Io>> {
+    Ok(Box::new(
+        tokio::net::windows::named_pipe::ClientOptions::new().open(socket)?,
+    ))
+}
+
+/// Sends `request` to the backend on `socket` and reads the whole response.
+/// `None` means nothing answered.
+pub async fn send(socket: &Path, request: &Request<Vec<u8>>) -> Option<Response<Vec<u8>>> {
+    let mut stream = connect(socket).await.ok()?;
+    write_request(&mut stream, request).await.ok()?;
+
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw).await.ok()?;
+    parse_response(&raw)
+}
+
+async fn write_request(
+    stream: &mut Box<dyn Io>,
+    request: &Request<Vec<u8>>,
+) -> std::io::Result<()> {
+    let target = request
+        .uri()
+        .path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or("/");
+    // HTTP/1.0 so the body is never chunked and ends when the backend closes
+    let mut head = format!(
+        "{} {} HTTP/1.0\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n",
+        request.method(),
+        target,
+        request.body().len()
+    );
+    for (name, value) in request.headers() {
+        if HOP_HEADERS.contains(&name.as_str()) {
+            continue;
+        }
+        if let Ok(value) = value.to_str() {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(request.body()).await
+}
+
+/// Status and end-to-end headers from a response head.
+fn parse_head(head: &str) -> Option<(u16, Vec<(String, String)>)> {
+    let mut lines = head.split("\r\n");
+    // "HTTP/1.1 200 OK"
+    let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .filter(|(name, _)| !HOP_HEADERS.contains(&name.to_ascii_lowercase().as_str()))
+        .collect();
+    Some((status, headers))
+}
+
+fn parse_response(raw: &[u8]) -> Option<Response<Vec<u8>>> {
+    let split = raw.windows(4).position(|w| w == b"\r\n\r\n")?;
+    let (status, headers) = parse_head(std::str::from_utf8(&raw[..split]).ok()?)?;
+    let mut response = Response::builder().status(status);
+    for (name, value) in headers {
+        response = response.header(name, value);
+    }
+    response.body(raw[split + 4..].to_vec()).ok()
+}
+
+/// What `stream` reports, in order: one `Head`, any number of `Data`, then
+/// `End` or `Error`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum StreamEvent {
+    Head {
+        status: u16,
+        headers: Vec<(String, String)>,
+    },
+    Data {
+        bytes: Vec<u8>,
+    },
+    End,
+    Error {
+        message: String,
+    },
+}
+
+/// Largest response head accepted before the body starts.
+const HEAD_LIMIT: usize = 64 * 1024;
+
+/// Sends `request` to the backend on `socket` and passes the response to
+/// `on_event` as it arrives, without waiting for the end. Stops, closing
+/// the connection so the backend abandons the request, as soon as
+/// `on_event` returns false.
+pub async fn stream<F>(socket: &Path, request: &Request<Vec<u8>>, mut on_event: F)
+where
+    F: FnMut(StreamEvent) -> bool,
+{
+    let failed = |message: &str| StreamEvent::Error {
+        message: message.to_string(),
+    };
+    let Ok(mut stream) = connect(socket).await else {
+        on_event(failed("The Local Agent backend is not reachable"));
+        return;
+    };
+    if let Err(e) = write_request(&mut stream, request).await {
+        on_event(failed(&format!("Failed to send request to backend: {}", e)));
+        return;
+    }
+
+    let mut buffer = vec![0u8; 16 * 1024];
+    let mut head = Vec::new();
+    let mut in_body = false;
+    loop {
+        let read = match stream.read(&mut buffer).await {
+            Ok(0) if in_body => {
+                on_event(StreamEvent::End);
+                return;
+            }
+            Ok(0) => {
+                on_event(failed(
+                    "The backend closed the connection without answering",
+                ));
+                return;
+            }
+            Ok(read) => read,
+            Err(e) => {
+                on_event(failed(&format!("Failed to read from backend: {}", e)));
+                return;
+            }
+        };
+        let chunk = &buffer[..read];
+        let keep_going = if in_body {
+            on_event(StreamEvent::Data {
+                bytes: chunk.to_vec(),
+            })
+        } else {
+            head.extend_from_slice(chunk);
+            let Some(split) = head.windows(4).position(|w| w == b"\r\n\r\n") else {
+                if head.len() > HEAD_LIMIT {
+                    on_event(failed("The backend sent an oversized response head"));
+                    return;
+                }
+                continue;
+            };
+            let Some((status, headers)) = std::str::from_utf8(&head[..split])
+                .ok()
+                .and_then(parse_head)
+            else {
+                on_event(failed("The backend sent an invalid response"));
+                return;
+            };
+            in_body = true;
+            let rest = head.split_off(split + 4);
+            on_event(StreamEvent::Head { status, headers })
+                && (rest.is_empty() || on_event(StreamEvent::Data { bytes: rest }))
+        };
+        if !keep_going {
+            // Dropping the connection is what cancels the backend request
+            return;
+        }
+    }
+}
+
+/// Streams started by `stream_backend`, so they can be cancelled.
+#[derive(Default)]
+pub struct StreamTracker {
+    next_id: AtomicU64,
+    running: Mutex<HashMap<u64, JoinHandle<()>>>,
+}
+
+impl StreamTracker {
+    pub fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// Runs `task` as stream `id`.
+    pub fn spawn<F>(&self, id: u64, task: F)
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        // Held across the spawn so a task that ends at once can't call
+        // `finished` before it is inserted
+        let mut running = self.running.lock().unwrap();
+        running.insert(id, tauri::async_runtime::spawn(task));
+    }
+
+    pub fn finished(&self, id: u64) {
+        self.running.lock().unwrap().remove(&id);
+    }
+
+    /// Aborts stream `id`, closing its backend connection. False when it
+    /// already ended.
+    pub fn cancel(&self, id: u64) -> bool {
+        match self.running.lock().unwrap().remove(&id) {
+            Some(task) => {
+                task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// The `localagent-app` protocol handler.
+pub fn handle(request: Request<Vec<u8>>, responder: UriSchemeResponder) {
+    tauri::async_runtime::spawn(async move {
+        let response = match active() {
+            Some(socket) => send(socket, &request).await,
+            None => None,
+        };
+        responder.respond(response.unwrap_or_else(|| {
+            Response::builder()
+                .status(StatusCode::BAD_GATEWAY)
+                .body(b"The Local Agent backend is not reachable".to_vec())
+                .unwrap()
+        }));
+    });
+}
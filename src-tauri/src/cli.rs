@@ -0,0 +1,123 @@
+//! Launch flags for scripted and power-user starts. They override the
+//! shell config for this run only (nothing is saved) and reach the backend
+//! the usual way, as `PORT`, `LOG_LEVEL` and `LOCAL_AGENT_DATA_DIR`.
+//! Anything else on the command line is left alone: other flags, opened
+//! files and links have their own handling.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::config::ShellConfig;
+use crate::paths;
+
+pub const USAGE: &str = "Usage: local-agent [options]
+
+Options:
+  --port <port>      Run the backend on this port (over TCP)
+  --data-dir <dir>   Keep the backend's data in this directory
+  --home <dir>       Run the backend from this directory
+  --portable         Keep config, logs and data next to the executable
+  --verbose          Debug logging from the backend
+  --headless         Start without a window; the tray and backend keep running
+  --no-sidecar       Don't start the backend; use one that is already running
+  --help             Show this help";
+
+#[derive(Debug, Clone, Default)]
+pub struct CliOptions {
+    pub port: Option<u16>,
+    pub data_dir: Option<PathBuf>,
+    pub home: Option<PathBuf>,
+    pub portable: bool,
+    pub verbose: bool,
+    pub headless: bool,
+    pub no_sidecar: bool,
+    pub help: bool,
+}
+
+static OPTIONS: OnceLock<CliOptions> = OnceLock::new();
+
+/// The flags this process was started with.
+pub fn options() -> &'static CliOptions {
+    OPTIONS.get_or_init(CliOptions::default)
+}
+
+pub fn init(options: CliOptions) {
+    let _ = OPTIONS.set(options);
+}
+
+impl CliOptions {
+    /// Parses `args` (starting with the executable); `--flag value` and
+    /// `--flag=value` both work, and relative paths are taken from `cwd`.
+    pub fn parse(args: &[String], cwd: &Path) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = args.iter().skip(1);
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next().cloned())
+                    .ok_or_else(|| format!("{} needs a value", flag))
+            };
+            match flag {
+                "--port" => {
+                    let port = value()?;
+                    options.port = Some(
+                        port.parse::<u16>()
+                            .ok()
+                            .filter(|port| *port >= 1024)
+                            .ok_or_else(|| {
+                                format!("--port must be between 1024 and 65535, got {}", port)
+                            })?,
+                    );
+                }
+                "--data-dir" => options.data_dir = Some(cwd.join(value()?)),
+                "--home" => options.home = Some(cwd.join(value()?)),
+                "--portable" => options.portable = true,
+                "--verbose" => options.verbose = true,
+                "--headless" => options.headless = true,
+                "--no-sidecar" => options.no_sidecar = true,
+                "--help" | "-h" => options.help = true,
+                _ => {}
+            }
+        }
+        Ok(options)
+    }
+
+    /// Applies the flags to `config` for this run.
+    pub fn apply(&self, config: &mut ShellConfig) -> Result<(), String> {
+        if let Some(port) = self.port {
+            config.set_transient("/settings/backend_port", port.into())?;
+            config.set_transient("/settings/transport", "tcp".into())?;
+        }
+        if let Some(ref dir) = self.data_dir {
+            config.set_transient("/data_dir", dir.to_string_lossy().into())?;
+        }
+        if self.headless {
+            config.set_transient("/settings/run_in_background", true.into())?;
+        }
+        if self.verbose {
+            config.set_transient("/settings/log_level", "debug".into())?;
+        }
+        Ok(())
+    }
+}
+
+/// The shell config with this run's flags applied.
+pub fn load_config() -> ShellConfig {
+    let mut config = ShellConfig::load();
+    // A portable install keeps the backend's data with it unless a profile
+    // or --data-dir says otherwise
+    if let Some(dir) = paths::portable_data_dir().filter(|_| config.data_dir.is_none()) {
+        if let Err(e) = config.set_transient("/data_dir", dir.to_string_lossy().into()) {
+            eprintln!("[tauri] Failed to use the portable data directory: {}", e);
+        }
+    }
+    if let Err(e) = options().apply(&mut config) {
+        eprintln!("[tauri] Failed to apply launch flags: {}", e);
+    }
+    config
+}
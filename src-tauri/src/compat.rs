@@ -0,0 +1,84 @@
+//! Whether the backend belongs with this shell. A partial update (an
+//! antivirus restoring an old `server.js`, a dev backend from another
+//! branch) can leave them on different releases, and then the UI breaks in
+//! confusing ways instead of saying so.
+//!
+//! The shell accepts backends in the caret range of the backend version it
+//! was built with (same major version, or same minor version before 1.0),
+//! with any patch release of the oldest minor.
+
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::{appinfo, node};
+
+/// The backend version bundled with this build, from package.json.
+pub const BUILT_WITH: &str = env!("LOCAL_AGENT_BACKEND_VERSION");
+
+type Version = (u64, u64, u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompatRange {
+    min: Version,
+    /// Exclusive.
+    max: Version,
+}
+
+impl CompatRange {
+    /// `^version`, from the start of its minor release.
+    pub fn caret(version: &str) -> Option<Self> {
+        let min = node::parse_version(version)?;
+        let max = match min {
+            (0, minor, _) => (0, minor + 1, 0),
+            (major, _, _) => (major + 1, 0, 0),
+        };
+        Some(Self {
+            min: (min.0, min.1, 0),
+            max,
+        })
+    }
+
+    pub fn contains(&self, version: Version) -> bool {
+        self.min <= version && version < self.max
+    }
+}
+
+impl fmt::Display for CompatRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (min, max) = (self.min, self.max);
+        write!(
+            f,
+            ">={}.{}.{} <{}.{}.{}",
+            min.0, min.1, min.2, max.0, max.1, max.2
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Mismatch {
+    pub shell_version: String,
+    pub backend_version: String,
+    pub supported: String,
+}
+
+/// Compares the running backend's version with the supported range. A
+/// backend that doesn't say which version it is passes.
+pub async fn check(app: &AppHandle, backend_dir: Option<&Path>) -> Result<(), Mismatch> {
+    let Some(range) = CompatRange::caret(BUILT_WITH) else {
+        return Ok(());
+    };
+    let Some(reported) = appinfo::backend_version(app, backend_dir).await else {
+        println!("[tauri] Backend did not report a version; skipping compatibility check");
+        return Ok(());
+    };
+    match node::parse_version(&reported) {
+        Some(version) if !range.contains(version) => Err(Mismatch {
+            shell_version: app.package_info().version.to_string(),
+            backend_version: reported,
+            supported: range.to_string(),
+        }),
+        _ => Ok(()),
+    }
+}
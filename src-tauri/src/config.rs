@@ -0,0 +1,414 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::backoff::RetryConfig;
+use crate::crash::CrashReportConfig;
+use crate::diskspace::DiskConfig;
+use crate::environment::EnvConfig;
+use crate::error_page::ErrorPageConfig;
+use crate::exposure::ExposureConfig;
+use crate::hang::HangConfig;
+use crate::health::ProbeSpec;
+use crate::hooks::HooksConfig;
+use crate::idle::IdleConfig;
+use crate::ingest::IngestConfig;
+use crate::mdns::MdnsConfig;
+use crate::metrics::MetricsConfig;
+use crate::models::ModelsConfig;
+use crate::monitor::ResourcesConfig;
+use crate::network::NetworkConfig;
+use crate::offline::OfflineConfig;
+use crate::ollama::OllamaConfig;
+use crate::paths;
+use crate::profiles::{self, EffectiveConfig, DEFAULT_PROFILE};
+use crate::proxy::ProxyConfig;
+use crate::redact::RedactionConfig;
+use crate::remote::RemoteConfig;
+use crate::restarts::RestartConfig;
+use crate::scheduler::SchedulerConfig;
+use crate::secrets::SecretIndex;
+use crate::settings::SettingsConfig;
+use crate::shutdown::ShutdownConfig;
+use crate::sidecar::{BackendCommand, DevConfig, SidecarSpec};
+use crate::startup::StartupConfig;
+use crate::syslog::SystemLogConfig;
+use crate::terminal::TerminalConfig;
+use crate::upgrade::UpgradeConfig;
+use crate::wake::WakeConfig;
+use crate::workspace::WorkspaceConfig;
+
+/// Shell-side settings persisted as JSON next to the other app data. Each
+/// feature owns its own section; missing keys fall back to defaults so older
+/// files keep loading. Loaded values are the global settings with the active
+/// profile's overrides applied (see `profiles`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShellConfig {
+    pub wake: WakeConfig,
+    pub remote: RemoteConfig,
+    pub mdns: MdnsConfig,
+    pub hooks: HooksConfig,
+    pub terminal: TerminalConfig,
+    pub ollama: OllamaConfig,
+    pub models: ModelsConfig,
+    pub resources: ResourcesConfig,
+    pub metrics: MetricsConfig,
+    pub maintenance: SchedulerConfig,
+    pub startup: StartupConfig,
+    pub retry: RetryConfig,
+    pub restarts: RestartConfig,
+    pub hang: HangConfig,
+    pub idle: IdleConfig,
+    pub upgrade: UpgradeConfig,
+    pub shutdown: ShutdownConfig,
+    pub error_page: ErrorPageConfig,
+    pub offline: OfflineConfig,
+    pub disk: DiskConfig,
+    pub crash_reports: CrashReportConfig,
+    pub dev: DevConfig,
+    /// Custom command, args, cwd and env for the backend.
+    pub backend: BackendCommand,
+    pub settings: SettingsConfig,
+    /// Also send log lines to the OS log; read at startup.
+    pub system_log: SystemLogConfig,
+    /// What is hidden from sidecar output before it is logged; read at
+    /// startup.
+    pub redaction: RedactionConfig,
+    /// Variables from the shell's environment sidecars may see.
+    pub env: EnvConfig,
+    /// Proxies for the shell's requests and every sidecar; read at
+    /// startup.
+    pub proxy: ProxyConfig,
+    /// Whether other devices on the network can reach the backend.
+    pub exposure: ExposureConfig,
+    /// When large downloads wait for a better connection.
+    pub network: NetworkConfig,
+    /// Additional local processes supervised alongside the backend.
+    pub sidecars: Vec<SidecarSpec>,
+    /// Health checks for the built-in services (`backend`, `ollama`) when
+    /// their default HTTP check doesn't fit.
+    pub health_probes: BTreeMap<String, ProbeSpec>,
+    /// Where the backend runs from instead of the bundled resources; a
+    /// relative path is taken from the executable's directory.
+    pub project_root: Option<PathBuf>,
+    /// Where the backend keeps chats; usually set per profile. Defaults to
+    /// the backend's own directory.
+    pub data_dir: Option<PathBuf>,
+    /// Active profile; empty means `default`.
+    pub profile: String,
+    /// Partial overrides per profile, merged over the global settings.
+    pub profiles: BTreeMap<String, Map<String, Value>>,
+    /// Names of keychain secrets per scope (see `secrets`).
+    pub secret_names: SecretIndex,
+    /// What files dropped on a window are accepted (see `ingest`).
+    pub ingest: IngestConfig,
+    /// Project folders the user chose for the agent (see `workspace`).
+    pub workspaces: WorkspaceConfig,
+    /// The global layer as last read or written, to split saves back into
+    /// layers.
+    #[serde(skip)]
+    global: Value,
+    /// Values set for this run only (see `set_transient`).
+    #[serde(skip)]
+    transient: Vec<Transient>,
+    /// Why saving is refused: the file on disk couldn't be read and couldn't
+    /// be moved aside, so saving the defaults would overwrite it.
+    #[serde(skip)]
+    unsaveable: Option<String>,
+}
+
+/// A value set by `set_transient` and the one it replaced.
+#[derive(Debug, Clone)]
+struct Transient {
+    pointer: &'static str,
+    applied: Value,
+    replaced: Value,
+}
+
+impl ShellConfig {
+    pub fn path() -> PathBuf {
+        paths::config_dir().join("shell-config.json")
+    }
+
+    pub fn load() -> Self {
+        Self::load_from(&Self::path())
+    }
+
+    fn load_from(path: &Path) -> Self {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+        serde_json::from_str::<Value>(&contents)
+            .map_err(|e| e.to_string())
+            .and_then(Self::from_layers)
+            .unwrap_or_else(|e| Self::set_aside(path, &e))
+    }
+
+    /// Defaults in place of the unreadable config at `path`, which is moved
+    /// to `<name>.invalid-<timestamp>` first so saving them can't overwrite
+    /// the user's settings. When it can't be moved, saving is refused.
+    fn set_aside(path: &Path, error: &str) -> Self {
+        let mut config = Self::default();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let aside = path.with_file_name(format!(
+            "{}.invalid-{}",
+            name,
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        match fs::rename(path, &aside) {
+            Ok(()) => eprintln!(
+                "[tauri] Invalid shell config at {}, moved it to {} and using defaults: {}",
+                path.display(),
+                aside.display(),
+                error
+            ),
+            Err(e) => {
+                let reason = format!(
+                    "The shell config at {} is invalid ({}) and couldn't be moved aside ({}); \
+                     fix or remove it before changing settings",
+                    path.display(),
+                    error,
+                    e
+                );
+                eprintln!("[tauri] {}", reason);
+                config.unsaveable = Some(reason);
+            }
+        }
+        config
+    }
+
+    fn from_layers(raw: Value) -> Result<Self, String> {
+        let profile = raw
+            .get("profile")
+            .and_then(Value::as_str)
+            .filter(|p| !p.is_empty())
+            .unwrap_or(DEFAULT_PROFILE)
+            .to_string();
+        let mut effective = raw.clone();
+        profiles::merge(&mut effective, &profiles::overlay(&raw, &profile));
+        let mut config: Self = serde_json::from_value(effective).map_err(|e| e.to_string())?;
+        config.global = raw;
+        Ok(config)
+    }
+
+    pub fn active_profile(&self) -> &str {
+        if self.profile.is_empty() {
+            DEFAULT_PROFILE
+        } else {
+            &self.profile
+        }
+    }
+
+    /// Whether `pointer` was set for this run or by the active profile.
+    fn set_explicitly(&self, pointer: &str) -> bool {
+        self.transient.iter().any(|t| t.pointer == pointer)
+            || self
+                .profiles
+                .get(self.active_profile())
+                .is_some_and(|overrides| {
+                    Value::Object(overrides.clone()).pointer(pointer).is_some()
+                })
+    }
+
+    /// The port the backend listens on: the one set for this run or by the
+    /// profile, else one derived from the profile's name.
+    pub fn backend_port(&self) -> u16 {
+        if self.set_explicitly("/settings/backend_port") {
+            return self.settings.backend_port;
+        }
+        profiles::derived_port(self.settings.backend_port, self.active_profile())
+    }
+
+    /// Where the backend keeps its data: the directory set for this run or
+    /// by the profile, else the profile's own one. `None` (default profile
+    /// only) means the backend's working directory.
+    pub fn backend_data_dir(&self) -> Option<PathBuf> {
+        let profile = self.active_profile();
+        if profile == DEFAULT_PROFILE || self.set_explicitly("/data_dir") {
+            return self.data_dir.clone();
+        }
+        Some(profiles::derived_data_dir(profile))
+    }
+
+    /// The config as it would be with `profile` active, without switching.
+    pub fn as_profile(&self, profile: &str) -> Result<Self, String> {
+        let mut layers = self.to_layers()?;
+        layers["profile"] = Value::String(profile.to_string());
+        Self::from_layers(layers)
+    }
+
+    /// Adds `profile` with no overrides of its own yet.
+    pub fn create_profile(&mut self, profile: &str) -> Result<(), String> {
+        profiles::validate_name(profile)?;
+        if self.profile_names().iter().any(|name| name == profile) {
+            return Err(format!("Profile {:?} already exists", profile));
+        }
+        self.profiles.insert(profile.to_string(), Map::new());
+        self.save()
+    }
+
+    /// Profiles with overrides, plus `default`.
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.profiles.keys().cloned().collect();
+        if !self.profiles.contains_key(DEFAULT_PROFILE) {
+            names.insert(0, DEFAULT_PROFILE.to_string());
+        }
+        names
+    }
+
+    /// Splits the in-memory settings back into the global layer and the
+    /// active profile's overrides.
+    fn to_layers(&self) -> Result<Value, String> {
+        let mut global = serde_json::to_value(self)
+            .map_err(|e| format!("Failed to serialize shell config: {}", e))?;
+        for transient in &self.transient {
+            if let Some(slot) = global
+                .pointer_mut(transient.pointer)
+                .filter(|slot| **slot == transient.applied)
+            {
+                *slot = transient.replaced.clone();
+            }
+        }
+        let profile = self.active_profile().to_string();
+        let mut overlay = profiles::overlay(&global, &profile);
+        profiles::split(&mut global, &self.global, &mut overlay);
+        if !overlay.is_empty() {
+            global["profiles"][&profile] = Value::Object(overlay);
+        }
+        Ok(global)
+    }
+
+    pub fn effective(&self) -> Result<EffectiveConfig, String> {
+        let mut config = serde_json::to_value(self)
+            .map_err(|e| format!("Failed to serialize shell config: {}", e))?;
+        let overlay = profiles::overlay(&config, self.active_profile());
+        if let Some(config) = config.as_object_mut() {
+            for key in profiles::GLOBAL_ONLY {
+                config.remove(*key);
+            }
+        }
+        Ok(EffectiveConfig {
+            profile: self.active_profile().to_string(),
+            profiles: self.profile_names(),
+            config,
+            overridden: profiles::overridden_paths(&overlay),
+        })
+    }
+
+    /// Switches profiles: saves the current layers, then reloads with
+    /// `profile`'s overrides applied.
+    pub fn switch_profile(&mut self, profile: &str) -> Result<(), String> {
+        // Names end up in paths (see `backend_data_dir`)
+        profiles::validate_name(profile)?;
+        let mut layers = self.to_layers()?;
+        layers["profile"] = Value::String(profile.to_string());
+        let transient = std::mem::take(&mut self.transient);
+        *self = Self::from_layers(layers)?;
+        for Transient {
+            pointer, applied, ..
+        } in transient
+        {
+            self.set_transient(pointer, applied)?;
+        }
+        self.save()
+    }
+
+    /// Sets the value at `pointer` (e.g. `/settings/backend_port`) for this
+    /// run only: it reads like any other setting, but `save` writes back
+    /// the value it replaced unless that was changed since.
+    pub fn set_transient(&mut self, pointer: &'static str, value: Value) -> Result<(), String> {
+        let mut current = serde_json::to_value(&*self)
+            .map_err(|e| format!("Failed to serialize shell config: {}", e))?;
+        let slot = current
+            .pointer_mut(pointer)
+            .ok_or_else(|| format!("Unknown setting {}", pointer))?;
+        let replaced = std::mem::replace(slot, value.clone());
+        let mut updated: Self = serde_json::from_value(current)
+            .map_err(|e| format!("Invalid value for {}: {}", pointer, e))?;
+        updated.global = std::mem::take(&mut self.global);
+        updated.transient = std::mem::take(&mut self.transient);
+        // Overriding twice still restores the saved value
+        match updated.transient.iter_mut().find(|t| t.pointer == pointer) {
+            Some(transient) => transient.applied = value,
+            None => updated.transient.push(Transient {
+                pointer,
+                applied: value,
+                replaced,
+            }),
+        }
+        *self = updated;
+        Ok(())
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(reason) = &self.unsaveable {
+            return Err(reason.clone());
+        }
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let contents = serde_json::to_string_pretty(&self.to_layers()?)
+            .map_err(|e| format!("Failed to serialize shell config: {}", e))?;
+        fs::write(&path, contents).map_err(|e| format!("Failed to write shell config: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("local-agent-config-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn invalid_config_is_moved_aside_before_defaults_are_used() {
+        let dir = scratch_dir();
+        let path = dir.join("shell-config.json");
+        fs::write(&path, "{ \"remote\": { \"port\": 4000, ").unwrap();
+
+        let config = ShellConfig::load_from(&path);
+        assert_eq!(config.remote.port, RemoteConfig::default().port);
+        assert!(config.unsaveable.is_none());
+        assert!(!path.exists());
+        let aside: Vec<PathBuf> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(aside.len(), 1);
+        let name = aside[0].file_name().unwrap().to_string_lossy();
+        assert!(name.starts_with("shell-config.json.invalid-"));
+        assert_eq!(
+            fs::read_to_string(&aside[0]).unwrap(),
+            "{ \"remote\": { \"port\": 4000, "
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn config_that_cannot_be_moved_aside_is_never_saved_over() {
+        let dir = scratch_dir();
+        // Renaming fails here, as it would in a read-only directory
+        let path = dir.join("missing").join("shell-config.json");
+        let config = ShellConfig::set_aside(&path, "expected value");
+        assert!(config.unsaveable.is_some());
+        assert!(config.save().is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_config_loads_defaults() {
+        let dir = scratch_dir();
+        let config = ShellConfig::load_from(&dir.join("shell-config.json"));
+        assert!(config.unsaveable.is_none());
+        assert_eq!(config.remote.port, RemoteConfig::default().port);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,35 @@
+//! Control messages written to a sidecar's stdin, one per line in the same
+//! `::name` form as its ready line (see `readiness`). A sidecar opts in with
+//! `stdin_control` and is told so by `LOCAL_AGENT_CONTROL=stdin`. This works
+//! when its HTTP server is wedged, and is the only polite way to stop a
+//! process on Windows.
+
+use serde::{Deserialize, Serialize};
+
+/// Set for sidecars started with `stdin_control`.
+pub const CONTROL_ENV: &str = "LOCAL_AGENT_CONTROL";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ControlCommand {
+    /// Stop accepting work, finish what's in flight and exit.
+    GracefulShutdown,
+    /// Re-read configuration from disk and the environment file.
+    ReloadConfig,
+    /// Write out buffered logs and state.
+    Flush,
+}
+
+impl ControlCommand {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ControlCommand::GracefulShutdown => "graceful-shutdown",
+            ControlCommand::ReloadConfig => "reload-config",
+            ControlCommand::Flush => "flush",
+        }
+    }
+
+    pub fn line(self) -> String {
+        format!("::{}\n", self.as_str())
+    }
+}
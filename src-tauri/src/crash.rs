@@ -0,0 +1,195 @@
+//! Shell panics. The hook writes each panic with its backtrace to stderr and
+//! to `local-agent-shell.log` (release builds on Windows have no console),
+//! and replaces the main window with a page saying the shell crashed when a
+//! window is still around to show it.
+//!
+//! Each panic is also saved as a report. Reports are only sent anywhere when
+//! the user opts in, and then on the next launch rather than from a process
+//! that is already failing.
+
+use serde::{Deserialize, Serialize};
+use std::backtrace::Backtrace;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::logstream::Level;
+use crate::proxy;
+use crate::sidecar::SidecarManager;
+use crate::syslog;
+use crate::{error_page, navigation, template};
+
+/// Backtrace lines shown on the crash page; the log has all of them.
+const PAGE_BACKTRACE_LINES: usize = 40;
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+/// Set while a panic is being reported, so a panic inside the hook (or a
+/// second thread panicking at once) doesn't recurse.
+static REPORTING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrashReportConfig {
+    /// Send saved crash reports to `endpoint` on the next launch.
+    pub upload: bool,
+    pub endpoint: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub at: String,
+    pub version: String,
+    pub os: String,
+    pub thread: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+}
+
+pub fn shell_log_path() -> PathBuf {
+    SidecarManager::resolve_log_dir().join("local-agent-shell.log")
+}
+
+/// Reports waiting to be uploaded.
+pub fn reports_dir() -> PathBuf {
+    SidecarManager::resolve_log_dir().join("local-agent-crashes")
+}
+
+/// Installs the hook; call before anything else in `run`.
+pub fn install() {
+    std::panic::set_hook(Box::new(|info| {
+        if REPORTING.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Box<dyn Any>".to_string());
+        let report = CrashReport {
+            at: chrono::Local::now().to_rfc3339(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            os: format!("{} ({})", std::env::consts::OS, std::env::consts::ARCH),
+            thread: std::thread::current()
+                .name()
+                .unwrap_or("<unnamed>")
+                .to_string(),
+            message,
+            location: info.location().map(|l| l.to_string()),
+            backtrace: Backtrace::force_capture().to_string(),
+        };
+
+        let entry = format!(
+            "[{}] [tauri] Shell panicked in thread '{}' at {}: {}\n{}\n",
+            report.at,
+            report.thread,
+            report.location.as_deref().unwrap_or("unknown location"),
+            report.message,
+            report.backtrace
+        );
+        eprint!("{}", entry);
+        syslog::send("shell", Level::Error, entry.trim_end());
+        if let Err(e) = append_to_log(&entry) {
+            eprintln!("[tauri] Failed to write shell log: {}", e);
+        }
+        if let Err(e) = save_report(&report) {
+            eprintln!("[tauri] {}", e);
+        }
+        if let Some(app) = APP.get() {
+            show_crash_page(app, &report);
+        }
+        REPORTING.store(false, Ordering::SeqCst);
+    }));
+}
+
+/// Lets the hook render the crash page; call at the start of `setup`.
+pub fn attach(app: &AppHandle) {
+    let _ = APP.set(app.clone());
+}
+
+fn append_to_log(entry: &str) -> std::io::Result<()> {
+    let path = shell_log_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(entry.as_bytes())
+}
+
+fn save_report(report: &CrashReport) -> Result<(), String> {
+    let dir = reports_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!("{}.json", uuid::Uuid::new_v4()));
+    let contents = serde_json::to_string_pretty(report)
+        .map_err(|e| format!("Failed to serialize crash report: {}", e))?;
+    fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn show_crash_page(app: &AppHandle, report: &CrashReport) {
+    let message = format!(
+        "{}\n{}: {}",
+        template::t("shell_crashed_detail"),
+        report.location.as_deref().unwrap_or("unknown location"),
+        report.message
+    );
+    let lines: Vec<String> = report
+        .backtrace
+        .lines()
+        .take(PAGE_BACKTRACE_LINES)
+        .map(str::to_string)
+        .collect();
+    navigation::load_error(
+        app,
+        &error_page::generate_titled_error_html(template::t("shell_crashed"), &message, &lines),
+    );
+}
+
+/// Sends reports saved by earlier runs when uploads are enabled, deleting
+/// each one the endpoint accepts. Without opt-in the files just stay next
+/// to the logs.
+pub async fn upload_pending(config: &CrashReportConfig) {
+    let Some(endpoint) = config.endpoint.as_deref().filter(|_| config.upload) else {
+        return;
+    };
+    let Ok(entries) = fs::read_dir(reports_dir()) else {
+        return;
+    };
+    let client = match proxy::client_builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("[tauri] Failed to create HTTP client: {}", e);
+            return;
+        }
+    };
+
+    for path in entries.flatten().map(|e| e.path()) {
+        let Some(report) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|c| serde_json::from_str::<CrashReport>(&c).ok())
+        else {
+            continue;
+        };
+        match client.post(endpoint).json(&report).send().await {
+            Ok(resp) if resp.status().is_success() => {
+                println!("[tauri] Uploaded crash report from {}", report.at);
+                let _ = fs::remove_file(&path);
+            }
+            Ok(resp) => eprintln!("[tauri] Crash report upload returned {}", resp.status()),
+            Err(e) => {
+                eprintln!("[tauri] Failed to upload crash report: {}", e);
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! A file per unexpected sidecar exit, under `crashes/` in the logs
+//! directory: how it exited, how long it had been up, the environment it was
+//! started with and what it printed last. Unlike the crash snapshot, which
+//! only keeps the latest exit for the error page, these accumulate so a
+//! crash that happened overnight can still be looked at. Shell panics are
+//! `crash`'s business.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+
+use crate::environment;
+use crate::issue;
+use crate::sidecar::{ExitInfo, SidecarManager};
+
+/// Crash files kept; older ones are removed as new ones are written.
+const MAX_REPORTS: usize = 50;
+
+/// Variables whose values are never written, whatever they look like.
+const SECRET_NAMES: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD", "AUTH", "CREDENTIAL"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SidecarCrash {
+    pub sidecar: String,
+    pub at: String,
+    /// The exit status as printed, e.g. "exit status: 1".
+    pub status: String,
+    pub exit_code: Option<i32>,
+    /// The signal that ended the process (Unix only).
+    pub signal: Option<i32>,
+    pub uptime_ms: Option<u64>,
+    /// Variables the process was started with, secrets redacted.
+    pub env: BTreeMap<String, String>,
+    pub log_lines: Vec<String>,
+}
+
+impl SidecarCrash {
+    pub fn new(sidecar: &SidecarManager, exit: &ExitInfo, log_lines: Vec<String>) -> Self {
+        let spec = sidecar.spec();
+        let mut env: BTreeMap<String, String> = environment::inherited(&spec.inherit_env)
+            .into_iter()
+            .map(|(k, v)| {
+                (
+                    k.to_string_lossy().into_owned(),
+                    v.to_string_lossy().into_owned(),
+                )
+            })
+            .collect();
+        env.extend(spec.env.clone());
+        let env = env
+            .into_iter()
+            .map(|(name, value)| {
+                let value = redact_var(&name, &value);
+                (name, value)
+            })
+            .collect();
+
+        Self {
+            sidecar: spec.name.clone(),
+            at: chrono::Local::now().to_rfc3339(),
+            status: exit.status.to_string(),
+            exit_code: exit.status.code(),
+            signal: signal(&exit.status),
+            uptime_ms: exit.uptime.map(|u| u.as_millis() as u64),
+            env,
+            log_lines: log_lines.iter().map(|line| issue::redact(line)).collect(),
+        }
+    }
+}
+
+/// The signal that ended the process, on Unix.
+#[cfg(unix)]
+pub fn signal(status: &ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+pub fn signal(_status: &ExitStatus) -> Option<i32> {
+    None
+}
+
+fn redact_var(name: &str, value: &str) -> String {
+    let upper = name.to_ascii_uppercase();
+    if SECRET_NAMES.iter().any(|secret| upper.contains(secret)) {
+        "[redacted]".to_string()
+    } else {
+        issue::redact(value)
+    }
+}
+
+pub fn dir() -> PathBuf {
+    SidecarManager::resolve_log_dir().join("crashes")
+}
+
+/// Writes `crash` to its own file and prunes the oldest beyond the limit.
+pub fn save(crash: &SidecarCrash) -> Result<PathBuf, String> {
+    let dir = dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create crashes directory: {}", e))?;
+    let path = dir.join(format!(
+        "{}-{}.json",
+        crash.sidecar,
+        chrono::Local::now().format("%Y%m%d-%H%M%S%3f")
+    ));
+    let contents = serde_json::to_string_pretty(crash)
+        .map_err(|e| format!("Failed to serialize crash report: {}", e))?;
+    fs::write(&path, contents).map_err(|e| format!("Failed to write crash report: {}", e))?;
+
+    let files = files();
+    let excess = files.len().saturating_sub(MAX_REPORTS);
+    for old in &files[..excess] {
+        let _ = fs::remove_file(old);
+    }
+    Ok(path)
+}
+
+/// Crash files, oldest first (the names sort by time per sidecar, the
+/// modification time across them).
+fn files() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir()) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .map(|path| {
+            let modified = fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .unwrap_or(std::time::UNIX_EPOCH);
+            (modified, path)
+        })
+        .collect();
+    files.sort();
+    files.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Every saved crash, newest first; unreadable files are skipped.
+pub fn list() -> Vec<SidecarCrash> {
+    files()
+        .iter()
+        .rev()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .filter_map(|contents| serde_json::from_str(&contents).ok())
+        .collect()
+}
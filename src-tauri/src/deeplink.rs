@@ -0,0 +1,198 @@
+//! `localagent://` links. The OS hands them to a fresh process or, through
+//! the single-instance plugin, to the running one; either way they end up in
+//! [`DeepLinkRouter::handle`], which holds them until the backend is healthy
+//! and then forwards them to the frontend as "deep-link" events. Those are
+//! recorded for `get_recent_events`, so a page still loading can catch up.
+//! Files opened with the app (see `fileopen`) are held and forwarded the
+//! same way, as "file-opened" events.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::fileopen::{self, OpenedFile};
+use crate::uninstall::URL_SCHEME;
+use crate::{emit_event, AppState};
+
+/// A parsed link. `localagent://chat/42?draft=hi` becomes route "chat",
+/// path ["42"] and query {draft: "hi"}.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeepLink {
+    pub url: String,
+    pub route: String,
+    pub path: Vec<String>,
+    pub query: BTreeMap<String, String>,
+}
+
+impl DeepLink {
+    pub fn parse(url: &Url) -> Result<Self, String> {
+        if url.scheme() != URL_SCHEME {
+            return Err(format!("Not a {}:// link: {}", URL_SCHEME, url));
+        }
+        let route = url.host_str().unwrap_or_default().to_string();
+        if route.is_empty() {
+            return Err(format!("Deep link has no route: {}", url));
+        }
+        Ok(Self {
+            url: url.to_string(),
+            route,
+            path: url
+                .path_segments()
+                .map(|segments| {
+                    segments
+                        .filter(|s| !s.is_empty())
+                        .map(percent_decode)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            query: url.query_pairs().into_owned().collect(),
+        })
+    }
+}
+
+fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| segment.get(i + 1..i + 3))
+            .flatten()
+            .filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[derive(Default)]
+struct RouterState {
+    ready: bool,
+    pending: Vec<DeepLink>,
+    files: Vec<OpenedFile>,
+}
+
+/// Queues links and files that arrive before the frontend can act on them.
+#[derive(Default)]
+pub struct DeepLinkRouter {
+    state: Mutex<RouterState>,
+}
+
+impl DeepLinkRouter {
+    /// Brings the main window forward and routes every valid link in `urls`.
+    pub fn handle(&self, app: &AppHandle, urls: Vec<Url>) {
+        if let Some(main_window) = app.get_webview_window("main") {
+            let _ = main_window.unminimize();
+            let _ = main_window.set_focus();
+        }
+
+        let links: Vec<DeepLink> = urls
+            .iter()
+            .filter_map(|url| {
+                DeepLink::parse(url)
+                    .inspect_err(|e| eprintln!("[tauri] Ignoring deep link: {}", e))
+                    .ok()
+            })
+            .collect();
+
+        let mut state = self.state.lock().unwrap();
+        if state.ready {
+            drop(state);
+            links.into_iter().for_each(|link| forward(app, link));
+        } else {
+            state.pending.extend(links);
+        }
+    }
+
+    /// Brings the main window forward and forwards `files` once the backend
+    /// is ready.
+    pub fn handle_files(&self, app: &AppHandle, files: Vec<OpenedFile>) {
+        if files.is_empty() {
+            return;
+        }
+        if let Some(main_window) = app.get_webview_window("main") {
+            let _ = main_window.unminimize();
+            let _ = main_window.set_focus();
+        }
+
+        let mut state = self.state.lock().unwrap();
+        if state.ready {
+            drop(state);
+            files.into_iter().for_each(|file| forward_file(app, file));
+        } else {
+            state.files.extend(files);
+        }
+    }
+
+    /// Called once the backend answers health checks; flushes queued links
+    /// and files.
+    pub fn mark_ready(&self, app: &AppHandle) {
+        let (pending, files) = {
+            let mut state = self.state.lock().unwrap();
+            state.ready = true;
+            (
+                std::mem::take(&mut state.pending),
+                std::mem::take(&mut state.files),
+            )
+        };
+        pending.into_iter().for_each(|link| forward(app, link));
+        files.into_iter().for_each(|file| forward_file(app, file));
+    }
+}
+
+fn forward(app: &AppHandle, link: DeepLink) {
+    println!("[tauri] Opening deep link {}", link.url);
+    emit_event(app, "deep-link", link);
+}
+
+fn forward_file(app: &AppHandle, file: OpenedFile) {
+    println!("[tauri] Opening file {}", file.path.display());
+    emit_event(app, "file-opened", file);
+}
+
+/// Registers the scheme where that happens at runtime and starts routing
+/// links and files, including those this process was launched with.
+pub fn start(app: &AppHandle) {
+    // Installers register the scheme; this covers dev builds and AppImages
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        eprintln!("[tauri] Failed to register {}:// links: {}", URL_SCHEME, e);
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        // macOS delivers opened documents through the same event
+        let (files, urls) = fileopen::partition_urls(event.urls());
+        let router = &handle.state::<AppState>().deep_links;
+        router.handle_files(&handle, files);
+        if !urls.is_empty() {
+            router.handle(&handle, urls);
+        }
+    });
+
+    // macOS delivers the launch link through on_open_url as well
+    #[cfg(not(target_os = "macos"))]
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => app.state::<AppState>().deep_links.handle(app, urls),
+        Ok(None) => {}
+        Err(e) => eprintln!("[tauri] Failed to read launch deep link: {}", e),
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    if let Ok(cwd) = std::env::current_dir() {
+        let args: Vec<String> = std::env::args().collect();
+        let files = fileopen::from_args(&args, &cwd);
+        app.state::<AppState>().deep_links.handle_files(app, files);
+    }
+}
@@ -0,0 +1,298 @@
+//! Start order for the supervised processes, the mirror image of the stop
+//! order in `shutdown`: a sidecar starts once everything it depends on has
+//! started and passed its health check. The backend depends on every other
+//! sidecar (Ollama, a vector DB, …) unless that sidecar lists `backend` in
+//! its own `depends_on`; those start once the backend is healthy.
+//!
+//! A dependency that fails to start is logged and its dependents are started
+//! anyway, so the backend can come up with a feature missing rather than not
+//! at all. When a sidecar is restarted, the running sidecars that depend on
+//! it are restarted after it, in start order, so they reconnect.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::hooks::HookPoint;
+use crate::restarts::RestartReason;
+use crate::sidecar::SidecarManager;
+use crate::state::BackendState;
+use crate::{
+    backend_poll_settings, boot_milestone, health, ollama, set_backend_state, shutdown,
+    spawn_hooks, wait_for_backend, AppState, BACKEND,
+};
+
+/// How long a dependency gets to pass its health check.
+const HEALTH_TIMEOUT_MS: u64 = 15000;
+
+/// What each service uses, from `(name, depends_on)` pairs. Names that
+/// aren't among the services are dropped.
+pub fn graph<'a>(
+    services: impl IntoIterator<Item = (&'a str, &'a [String])>,
+) -> BTreeMap<String, BTreeSet<String>> {
+    let services: Vec<(&str, &[String])> = services.into_iter().collect();
+    let names: BTreeSet<&str> = services.iter().map(|(name, _)| *name).collect();
+    let uses_backend = |depends_on: &[String]| depends_on.iter().any(|d| d == BACKEND);
+    services
+        .iter()
+        .map(|(name, depends_on)| {
+            let mut deps: BTreeSet<String> = depends_on
+                .iter()
+                .filter(|d| names.contains(d.as_str()) && d != name)
+                .cloned()
+                .collect();
+            if *name == BACKEND {
+                deps.extend(
+                    services
+                        .iter()
+                        .filter(|(other, depends_on)| {
+                            *other != BACKEND && !uses_backend(depends_on)
+                        })
+                        .map(|(other, _)| other.to_string()),
+                );
+            }
+            (name.to_string(), deps)
+        })
+        .collect()
+}
+
+/// The dependency graph of everything in the registry.
+pub fn registry_graph(sidecars: &[Arc<SidecarManager>]) -> BTreeMap<String, BTreeSet<String>> {
+    graph(
+        sidecars
+            .iter()
+            .map(|s| (s.name(), s.spec().depends_on.as_slice())),
+    )
+}
+
+/// Groups services into start waves, dependencies first.
+pub fn start_waves(deps: &BTreeMap<String, BTreeSet<String>>) -> Vec<Vec<String>> {
+    let mut waves = shutdown::waves(deps);
+    waves.reverse();
+    waves
+}
+
+/// Everything that uses `name`, directly or through another service, in
+/// start order.
+pub fn dependents(deps: &BTreeMap<String, BTreeSet<String>>, name: &str) -> Vec<String> {
+    let mut affected = BTreeSet::from([name.to_string()]);
+    loop {
+        let more: Vec<String> = deps
+            .iter()
+            .filter(|(service, uses)| {
+                !affected.contains(*service) && uses.iter().any(|d| affected.contains(d))
+            })
+            .map(|(service, _)| service.clone())
+            .collect();
+        if more.is_empty() {
+            break;
+        }
+        affected.extend(more);
+    }
+    start_waves(deps)
+        .into_iter()
+        .flatten()
+        .filter(|service| service != name && affected.contains(service))
+        .collect()
+}
+
+/// The start waves for the registered sidecars plus Ollama when it is
+/// started with the app, split after the backend's wave: the first part
+/// starts before the backend is checked, the rest once it is healthy.
+pub fn plan(app: &AppHandle) -> (Vec<Vec<String>>, Vec<Vec<String>>) {
+    let state: State<AppState> = app.state();
+    let sidecars = state.sidecars.all();
+    let mut deps = registry_graph(&sidecars);
+    let auto_start = state.config.lock().unwrap().ollama.auto_start;
+    if auto_start && !deps.contains_key(ollama::OLLAMA_SIDECAR) {
+        deps.insert(ollama::OLLAMA_SIDECAR.to_string(), BTreeSet::new());
+        if let Some(backend) = deps.get_mut(BACKEND) {
+            backend.insert(ollama::OLLAMA_SIDECAR.to_string());
+        }
+    }
+    let mut waves = start_waves(&deps);
+    let split = waves
+        .iter()
+        .position(|wave| wave.iter().any(|name| name == BACKEND))
+        .map_or(waves.len(), |i| i + 1);
+    let after = waves.split_off(split);
+    (waves, after)
+}
+
+/// Starts `waves` in turn, each in parallel, waiting for one wave's health
+/// checks before starting the next. The backend is only spawned here; the
+/// caller waits for it. Only the backend failing to spawn is an error.
+pub async fn start(app: &AppHandle, waves: Vec<Vec<String>>) -> Result<(), String> {
+    for wave in waves {
+        let tasks: Vec<_> = wave
+            .into_iter()
+            .map(|name| {
+                let task = tauri::async_runtime::spawn(start_one(app.clone(), name.clone()));
+                (name, task)
+            })
+            .collect();
+        for (name, task) in tasks {
+            let started = task
+                .await
+                .map_err(|e| format!("Failed to start {}: {}", name, e))
+                .and_then(|result| result);
+            match started {
+                Err(e) if name == BACKEND => return Err(e),
+                Err(e) => eprintln!("[tauri] {}", e),
+                Ok(()) => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn start_one(app: AppHandle, name: String) -> Result<(), String> {
+    let state: State<AppState> = app.state();
+    let Some(sidecar) = state.sidecars.get(&name) else {
+        if name == ollama::OLLAMA_SIDECAR {
+            let (config, probe) = {
+                let config = state.config.lock().unwrap();
+                (
+                    config.ollama.clone(),
+                    config.health_probes.get(ollama::OLLAMA_SIDECAR).cloned(),
+                )
+            };
+            return ollama::ensure_running(&config, probe, &state.sidecars)
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("Failed to start Ollama: {}", e));
+        }
+        return Err(format!("No sidecar named {:?}", name));
+    };
+    let spawned = sidecar.clone();
+    tauri::async_runtime::spawn_blocking(move || spawned.spawn_with_retry())
+        .await
+        .map_err(|e| format!("Failed to start {}: {}", name, e))??;
+    if name == BACKEND {
+        boot_milestone(&app, "sidecar_spawned");
+        return Ok(());
+    }
+    health_gate(&sidecar).await
+}
+
+async fn health_gate(sidecar: &SidecarManager) -> Result<(), String> {
+    let Some(probe) = sidecar.spec().probe() else {
+        return Ok(());
+    };
+    health::poll_health(&probe, 250, HEALTH_TIMEOUT_MS, None)
+        .await
+        .map_err(|e| format!("{} did not become healthy: {}", sidecar.name(), e))
+}
+
+/// Once `name` is back up after a restart, restarts the running sidecars
+/// that depend on it.
+pub fn restart_dependents(app: &AppHandle, name: &str) {
+    let (sidecars, order) = {
+        let state: State<AppState> = app.state();
+        let sidecars = state.sidecars.all();
+        let order = dependents(&registry_graph(&sidecars), name);
+        (sidecars, order)
+    };
+    let running: Vec<Arc<SidecarManager>> = order
+        .iter()
+        .filter_map(|dependent| sidecars.iter().find(|s| s.name() == dependent))
+        .filter(|sidecar| sidecar.is_running())
+        .cloned()
+        .collect();
+    let Some(restarted) = sidecars.into_iter().find(|s| s.name() == name) else {
+        return;
+    };
+    if running.is_empty() {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // The watchdog may not have spawned it again yet
+        let deadline = Instant::now() + Duration::from_millis(HEALTH_TIMEOUT_MS);
+        while !restarted.is_running() && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+        let up = if restarted.is_running() {
+            health_gate(&restarted).await
+        } else {
+            Err(format!("{} did not start again", restarted.name()))
+        };
+        if let Err(e) = up {
+            eprintln!("[tauri] Not restarting what uses it: {}", e);
+            return;
+        }
+        for dependent in running {
+            println!(
+                "[tauri] Restarting {} after {} restarted",
+                dependent.name(),
+                restarted.name()
+            );
+            let detail = format!("{} restarted", restarted.name());
+            if dependent.name() == BACKEND {
+                restart_backend(&app, &detail).await;
+                continue;
+            }
+            app.state::<AppState>().restarts.record(
+                dependent.name(),
+                RestartReason::Dependency,
+                &detail,
+            );
+            let respawned = dependent.clone();
+            let result = tauri::async_runtime::spawn_blocking(move || {
+                respawned.shutdown();
+                respawned.spawn_with_retry()
+            })
+            .await
+            .map_err(|e| format!("Failed to restart {}: {}", dependent.name(), e))
+            .and_then(|result| result);
+            let healthy = match result {
+                Ok(()) => health_gate(&dependent).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = healthy {
+                eprintln!("[tauri] {}", e);
+            }
+        }
+    });
+}
+
+async fn restart_backend(app: &AppHandle, detail: &str) {
+    let allowed = {
+        let state: State<AppState> = app.state();
+        let restarts = state.config.lock().unwrap().restarts.clone();
+        state.restarts.allows(BACKEND, &restarts)
+    };
+    if !allowed {
+        eprintln!(
+            "[tauri] Not restarting the backend after {}: restarting too often",
+            detail
+        );
+        return;
+    }
+    let state: State<AppState> = app.state();
+    let _lifecycle = state.lifecycle.turn().await;
+    set_backend_state(app, BackendState::Restarting, Some(detail));
+    app.state::<AppState>()
+        .restarts
+        .record(BACKEND, RestartReason::Dependency, detail);
+    let sidecars = app.state::<AppState>().sidecars.clone();
+    let restarted = tauri::async_runtime::spawn_blocking(move || sidecars.restart(BACKEND))
+        .await
+        .map_err(|e| format!("Failed to restart backend: {}", e))
+        .and_then(|result| result.map(|_| ()));
+    let healthy = match restarted {
+        Ok(()) => wait_for_backend(app, &backend_poll_settings(app), None).await,
+        Err(e) => Err(e),
+    };
+    match healthy {
+        Ok(()) => {
+            set_backend_state(app, BackendState::Healthy, None);
+            spawn_hooks(app, HookPoint::PostBackendStart);
+        }
+        Err(e) => {
+            set_backend_state(app, BackendState::Failed, Some(&e));
+            spawn_hooks(app, HookPoint::PostCrash);
+        }
+    }
+}
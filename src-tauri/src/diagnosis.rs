@@ -0,0 +1,177 @@
+//! Tells backend failures apart from how the process exited and what it
+//! printed last, so the error page can say what went wrong and how to fix
+//! it instead of only showing the log. Log patterns win over the exit
+//! status: a port conflict also ends in exit code 1, but the log says which
+//! port.
+
+use std::time::Duration;
+
+use crate::crashes;
+use crate::sidecar::ExitInfo;
+
+/// A process that exits this soon after starting never got going.
+const IMMEDIATE_EXIT: Duration = Duration::from_secs(5);
+
+const SIGKILL: i32 = 9;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailureClass {
+    PortInUse {
+        port: Option<u16>,
+    },
+    MissingModule {
+        module: Option<String>,
+    },
+    OutOfMemory,
+    Signal {
+        signal: i32,
+    },
+    ExitedImmediately {
+        code: i32,
+    },
+    /// The backend answered but the main window never loaded the UI.
+    WindowLoadFailed,
+}
+
+/// Matches `exit` and the last `log_lines` against the known failure
+/// modes; `None` when nothing matches.
+pub fn classify(exit: Option<&ExitInfo>, log_lines: &[String]) -> Option<FailureClass> {
+    for line in log_lines.iter().rev() {
+        if line.contains("EADDRINUSE") || line.contains("address already in use") {
+            return Some(FailureClass::PortInUse {
+                port: port_in(line),
+            });
+        }
+        if line.contains("Cannot find module")
+            || line.contains("Cannot find package")
+            || line.contains("ERR_MODULE_NOT_FOUND")
+        {
+            return Some(FailureClass::MissingModule {
+                module: quoted(line),
+            });
+        }
+        if line.contains("JavaScript heap out of memory") || line.contains("Reached heap limit") {
+            return Some(FailureClass::OutOfMemory);
+        }
+    }
+
+    let exit = exit?;
+    match crashes::signal(&exit.status) {
+        // Nothing in the shell sends SIGKILL to a running backend; on Linux
+        // it is almost always the OOM killer
+        Some(SIGKILL) => return Some(FailureClass::OutOfMemory),
+        Some(signal) => return Some(FailureClass::Signal { signal }),
+        None => {}
+    }
+    match exit.status.code() {
+        // A shell wrapper reports a SIGKILL as 128 + 9
+        Some(137) => Some(FailureClass::OutOfMemory),
+        Some(code) if code != 0 && exit.uptime.is_some_and(|up| up < IMMEDIATE_EXIT) => {
+            Some(FailureClass::ExitedImmediately { code })
+        }
+        _ => None,
+    }
+}
+
+/// The port in e.g. `listen EADDRINUSE: address already in use :::3001`.
+fn port_in(line: &str) -> Option<u16> {
+    let start = line.find("in use").unwrap_or(0);
+    line[start..]
+        .split(|c: char| !c.is_ascii_digit())
+        .rfind(|part| !part.is_empty())
+        .and_then(|port| port.parse().ok())
+}
+
+/// The first quoted name in e.g. `Error: Cannot find module 'express'`.
+fn quoted(line: &str) -> Option<String> {
+    let start = line.find(['\'', '"'])?;
+    let quote = line[start..].chars().next()?;
+    let rest = &line[start + 1..];
+    let end = rest.find(quote)?;
+    Some(rest[..end].to_string()).filter(|name| !name.is_empty())
+}
+
+impl FailureClass {
+    pub fn title(&self) -> &'static str {
+        match self {
+            FailureClass::PortInUse { .. } => "Backend port already in use",
+            FailureClass::MissingModule { .. } => "Backend is missing a module",
+            FailureClass::OutOfMemory => "Backend ran out of memory",
+            FailureClass::Signal { .. } => "Backend was stopped by a signal",
+            FailureClass::ExitedImmediately { .. } => "Backend exited right after starting",
+            FailureClass::WindowLoadFailed => "Window failed to load the UI",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            FailureClass::PortInUse { port: Some(port) } => format!(
+                "The backend could not listen on port {} because another program is using it.",
+                port
+            ),
+            FailureClass::PortInUse { port: None } => {
+                "The backend could not listen on its port because another program is using it."
+                    .to_string()
+            }
+            FailureClass::MissingModule {
+                module: Some(module),
+            } => format!(
+                "The backend could not load `{}`, so its installation is incomplete.",
+                module
+            ),
+            FailureClass::MissingModule { module: None } => {
+                "The backend could not load one of its modules, so its installation is incomplete."
+                    .to_string()
+            }
+            FailureClass::OutOfMemory => {
+                "The backend was stopped because it used more memory than was available."
+                    .to_string()
+            }
+            FailureClass::Signal { signal } => format!(
+                "The backend was ended by signal {} from outside Local Agent.",
+                signal
+            ),
+            FailureClass::ExitedImmediately { code } => format!(
+                "The backend exited with code {} before it finished starting.",
+                code
+            ),
+            FailureClass::WindowLoadFailed => {
+                "The backend is running, but the window could not load the app from it.".to_string()
+            }
+        }
+    }
+
+    /// What to try, most likely fix first.
+    pub fn remediation(&self) -> Vec<String> {
+        match self {
+            FailureClass::PortInUse { .. } => vec![
+                "Quit any other copy of Local Agent or a dev server using the same port."
+                    .to_string(),
+                "Or choose another backend port in Preferences, then click Retry.".to_string(),
+            ],
+            FailureClass::MissingModule { .. } => vec![
+                "Reinstall Local Agent, or run `npm install` in the project folder for a dev setup."
+                    .to_string(),
+                "Then click Retry.".to_string(),
+            ],
+            FailureClass::OutOfMemory => vec![
+                "Close other memory-hungry programs, or use a smaller model.".to_string(),
+                "Setting `resources.memory_limit_mb` restarts the backend before the system has to."
+                    .to_string(),
+            ],
+            FailureClass::Signal { .. } => vec![
+                "Check whether security software or a script is ending node processes.".to_string(),
+                "Then click Retry.".to_string(),
+            ],
+            FailureClass::ExitedImmediately { .. } => vec![
+                "The log below shows the error it stopped on.".to_string(),
+                "Open the log file for the full output, then click Retry.".to_string(),
+            ],
+            FailureClass::WindowLoadFailed => vec![
+                "Click Retry to restart the backend and load it again.".to_string(),
+                "If it keeps failing, check that a firewall or proxy is not blocking 127.0.0.1."
+                    .to_string(),
+            ],
+        }
+    }
+}
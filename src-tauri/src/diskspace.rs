@@ -0,0 +1,214 @@
+//! Free space on the volumes the app writes to. The log and data volumes
+//! are checked before the backend starts, and a model download asks first
+//! with `check_download_space`; below `min_free_mb` the app gets a
+//! `disk-space-low` warning (a download is refused) along with what is
+//! taking the space and the cleanups that can free some of it.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::hardware;
+use crate::logfile;
+use crate::preflight::FixAction;
+use crate::uninstall::{dir_size, format_bytes};
+use crate::{crash, crashes};
+
+pub const EVENT: &str = "disk-space-low";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiskConfig {
+    /// Free space below this on the log or data volume is a warning, and
+    /// a download that would leave less is refused.
+    pub min_free_mb: u64,
+}
+
+impl Default for DiskConfig {
+    fn default() -> Self {
+        Self { min_free_mb: 2048 }
+    }
+}
+
+impl DiskConfig {
+    fn min_free_bytes(&self) -> u64 {
+        self.min_free_mb * 1024 * 1024
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Volume {
+    pub mount_point: PathBuf,
+    /// What the app keeps there: `logs`, `data`.
+    pub holds: Vec<&'static str>,
+    pub free_bytes: u64,
+    pub total_bytes: u64,
+    pub low: bool,
+}
+
+/// What the app's files take up.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SpaceUsage {
+    /// The whole logs directory, including the two below.
+    pub logs_bytes: u64,
+    pub rotated_logs_bytes: u64,
+    pub crash_reports_bytes: u64,
+    pub models_bytes: u64,
+    pub data_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskReport {
+    pub volumes: Vec<Volume>,
+    pub low: bool,
+    pub min_free_bytes: u64,
+    pub usage: SpaceUsage,
+    /// One-click cleanups worth offering.
+    pub cleanup: Vec<FixAction>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CleanupResult {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Where the app's files are, resolved by the caller.
+pub struct Locations<'a> {
+    /// The backend's current log file; rotations sit next to it.
+    pub log_path: &'a Path,
+    pub data_dir: &'a Path,
+    /// Sizes of the models found on disk.
+    pub models_bytes: u64,
+}
+
+/// Free space on the log and data volumes. `usage` walks the directories,
+/// so it is left empty unless the volumes are low or `always_usage` is set.
+pub fn report(config: &DiskConfig, at: &Locations, always_usage: bool) -> DiskReport {
+    let log_dir = at.log_path.parent().unwrap_or(at.log_path);
+    let mut volumes: Vec<Volume> = Vec::new();
+    for (holds, dir) in [("logs", log_dir), ("data", at.data_dir)] {
+        let Some(disk) = hardware::disk_for(dir) else {
+            continue;
+        };
+        match volumes
+            .iter_mut()
+            .find(|volume| volume.mount_point == disk.mount_point)
+        {
+            Some(volume) => volume.holds.push(holds),
+            None => volumes.push(Volume {
+                low: disk.free_bytes < config.min_free_bytes(),
+                mount_point: disk.mount_point,
+                holds: vec![holds],
+                free_bytes: disk.free_bytes,
+                total_bytes: disk.total_bytes,
+            }),
+        }
+    }
+    let low = volumes.iter().any(|volume| volume.low);
+    let usage = if low || always_usage {
+        usage(at, log_dir)
+    } else {
+        SpaceUsage::default()
+    };
+    DiskReport {
+        cleanup: cleanup_actions(&usage),
+        volumes,
+        low,
+        min_free_bytes: config.min_free_bytes(),
+        usage,
+    }
+}
+
+/// Refuses a download of `bytes` into `dir` that would leave less than the
+/// minimum free.
+pub fn check_download(config: &DiskConfig, dir: &Path, bytes: u64) -> Result<(), String> {
+    let Some(disk) = hardware::disk_for(dir) else {
+        // Unknown volumes aren't blocked
+        return Ok(());
+    };
+    let left = disk.free_bytes.saturating_sub(bytes);
+    if left < config.min_free_bytes() {
+        return Err(format!(
+            "Not enough disk space: the download needs {} and {} is free on {}, \
+             leaving less than the {} MB to keep free",
+            format_bytes(bytes),
+            format_bytes(disk.free_bytes),
+            disk.mount_point.display(),
+            config.min_free_mb
+        ));
+    }
+    Ok(())
+}
+
+fn usage(at: &Locations, log_dir: &Path) -> SpaceUsage {
+    SpaceUsage {
+        logs_bytes: dir_size(log_dir),
+        rotated_logs_bytes: logfile::rotated(at.log_path)
+            .iter()
+            .map(|log| log.size)
+            .sum(),
+        crash_reports_bytes: crash_dirs().iter().map(|dir| dir_size(dir)).sum(),
+        models_bytes: at.models_bytes,
+        data_bytes: dir_size(at.data_dir),
+    }
+}
+
+fn cleanup_actions(usage: &SpaceUsage) -> Vec<FixAction> {
+    let mut actions = Vec::new();
+    if usage.rotated_logs_bytes > 0 {
+        actions.push(FixAction::Invoke {
+            label: format!(
+                "Delete old logs ({})",
+                format_bytes(usage.rotated_logs_bytes)
+            ),
+            command: "clean_rotated_logs".to_string(),
+        });
+    }
+    if usage.crash_reports_bytes > 0 {
+        actions.push(FixAction::Invoke {
+            label: format!(
+                "Delete crash reports ({})",
+                format_bytes(usage.crash_reports_bytes)
+            ),
+            command: "clean_crash_reports".to_string(),
+        });
+    }
+    actions
+}
+
+/// Deletes the rotated copies of `log_path`; the current log is kept.
+pub fn clean_rotated_logs(log_path: &Path) -> CleanupResult {
+    let mut result = CleanupResult::default();
+    for log in logfile::rotated(log_path) {
+        if fs::remove_file(&log.path).is_ok() {
+            result.files += 1;
+            result.bytes += log.size;
+        }
+    }
+    result
+}
+
+/// Deletes saved sidecar crashes and unsent shell crash reports.
+pub fn clean_crash_reports() -> CleanupResult {
+    let mut result = CleanupResult::default();
+    for dir in crash_dirs() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if meta.is_file() && fs::remove_file(entry.path()).is_ok() {
+                result.files += 1;
+                result.bytes += meta.len();
+            }
+        }
+    }
+    result
+}
+
+fn crash_dirs() -> [PathBuf; 2] {
+    [crashes::dir(), crash::reports_dir()]
+}
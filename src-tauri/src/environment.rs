@@ -0,0 +1,197 @@
+//! What a sidecar sees of the shell's environment. Children start from an
+//! empty environment plus a small allowlist, so tokens and other variables
+//! from the user's shell don't end up in the backend (or in its crash
+//! output). The spec's own `env` is applied on top.
+//!
+//! Apps launched from the Dock or a desktop entry don't get what the user
+//! set up in their shell profile (a version-managed node, proxies, keys).
+//! With `login_shell` on, the login shell's environment is read once at
+//! startup and its allowed variables take precedence over the shell's own.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long the login shell gets to print its environment.
+const LOGIN_SHELL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The login shell's environment, once resolved.
+static LOGIN_ENV: OnceLock<HashMap<OsString, OsString>> = OnceLock::new();
+
+/// Always passed through: what node and the backend need to run at all.
+#[cfg(not(windows))]
+const BASE: &[&str] = &[
+    "PATH", "HOME", "NODE_ENV", "PORT", "TMPDIR", "LANG", "LC_*", "TZ",
+];
+
+/// Windows programs also need the system and profile directories; node
+/// fails to start without `SYSTEMROOT`.
+#[cfg(windows)]
+const BASE: &[&str] = &[
+    "PATH",
+    "HOME",
+    "NODE_ENV",
+    "PORT",
+    "PATHEXT",
+    "SYSTEMROOT",
+    "SYSTEMDRIVE",
+    "WINDIR",
+    "COMSPEC",
+    "TEMP",
+    "TMP",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "PROGRAMDATA",
+    "PROGRAMFILES",
+];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnvConfig {
+    /// Extra variables every sidecar inherits, e.g. `HTTPS_PROXY` or
+    /// `NODE_EXTRA_CA_CERTS`. A trailing `*` matches a prefix.
+    pub allow: Vec<String>,
+    /// Take allowed variables from `$SHELL -lc env` (macOS and Linux).
+    pub login_shell: bool,
+}
+
+fn matches(pattern: &str, name: &str) -> bool {
+    // Windows variable names are case-insensitive
+    let (pattern, name) = if cfg!(windows) {
+        (pattern.to_ascii_uppercase(), name.to_ascii_uppercase())
+    } else {
+        (pattern.to_string(), name.to_string())
+    };
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => name == pattern,
+    }
+}
+
+/// Whether `name` passes the base allowlist or one of `extra`.
+pub fn allowed(name: &str, extra: &[String]) -> bool {
+    BASE.iter()
+        .copied()
+        .chain(extra.iter().map(String::as_str))
+        .any(|pattern| matches(pattern, name))
+}
+
+/// The shell's variables a child with `extra` allowed inherits, with the
+/// login shell's values in place of the shell's own.
+pub fn inherited(extra: &[String]) -> Vec<(OsString, OsString)> {
+    let mut vars: HashMap<OsString, OsString> = std::env::vars_os().collect();
+    if let Some(login) = LOGIN_ENV.get() {
+        vars.extend(login.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+    vars.into_iter()
+        .filter(|(name, _)| name.to_str().is_some_and(|name| allowed(name, extra)))
+        .collect()
+}
+
+/// `name` as a sidecar would see it: from the login shell when resolved.
+pub fn var_os(name: &str) -> Option<OsString> {
+    LOGIN_ENV
+        .get()
+        .and_then(|login| login.get(&OsString::from(name)).cloned())
+        .or_else(|| std::env::var_os(name))
+}
+
+/// Parses `env` output. Values can span lines, so a line that doesn't
+/// start with `NAME=` continues the previous value.
+pub fn parse_env(output: &str) -> Vec<(String, String)> {
+    let mut vars: Vec<(String, String)> = Vec::new();
+    for line in output.lines() {
+        let entry = line.split_once('=').filter(|(name, _)| {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+        match (entry, vars.last_mut()) {
+            (Some((name, value)), _) => vars.push((name.to_string(), value.to_string())),
+            (None, Some((_, value))) => {
+                value.push('\n');
+                value.push_str(line);
+            }
+            // Login banners and the like before the first variable
+            (None, None) => {}
+        }
+    }
+    vars
+}
+
+/// Runs the user's login shell once and caches its environment for every
+/// later spawn. Does nothing on Windows, where GUI apps get the full
+/// environment anyway.
+pub fn resolve_login_shell(config: &EnvConfig) {
+    if !config.login_shell || cfg!(windows) || LOGIN_ENV.get().is_some() {
+        return;
+    }
+    let shell = std::env::var_os("SHELL").unwrap_or_else(|| "/bin/sh".into());
+    match login_shell_env(&shell) {
+        Ok(vars) => {
+            println!(
+                "[tauri] Resolved {} variables from login shell {}",
+                vars.len(),
+                shell.to_string_lossy()
+            );
+            let _ = LOGIN_ENV.set(
+                vars.into_iter()
+                    .map(|(k, v)| (k.into(), v.into()))
+                    .collect(),
+            );
+        }
+        Err(e) => eprintln!("[tauri] {}; using the inherited environment", e),
+    }
+}
+
+fn login_shell_env(shell: &OsString) -> Result<Vec<(String, String)>, String> {
+    let mut child = Command::new(shell)
+        .args(["-l", "-c", "env"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start login shell: {}", e))?;
+    let mut stdout = child.stdout.take().unwrap();
+    // Read on a thread so a profile that hangs can't hold up startup
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stdout.read_to_string(&mut output);
+        output
+    });
+
+    let start = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => break,
+            Ok(Some(status)) => return Err(format!("Login shell exited with {}", status)),
+            Ok(None) if start.elapsed() < LOGIN_SHELL_TIMEOUT => {
+                thread::sleep(Duration::from_millis(25));
+            }
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "Login shell did not finish within {:?}",
+                    LOGIN_SHELL_TIMEOUT
+                ));
+            }
+            Err(e) => return Err(format!("Failed to wait for login shell: {}", e)),
+        }
+    }
+    let output = reader
+        .join()
+        .map_err(|_| "Failed to read login shell output".to_string())?;
+    Ok(parse_env(&output))
+}
+
+/// Replaces `command`'s environment with the allowed part of the shell's.
+pub fn scrub(command: &mut Command, extra: &[String]) {
+    command.env_clear().envs(inherited(extra));
+}
@@ -1,95 +1,315 @@
-pub fn generate_error_html(message: &str, log_lines: &[String]) -> String {
-    let escaped_message = html_escape(message);
-    let escaped_logs = log_lines
-        .iter()
-        .map(|l| html_escape(l))
-        .collect::<Vec<_>>()
-        .join("\n");
-
-    format!(
-        r#"<!DOCTYPE html>
-<html>
-<head>
-<meta charset="utf-8">
-<style>
-  * {{ margin: 0; padding: 0; box-sizing: border-box; }}
-  body {{
-    background: #1a1a1a;
-    color: #e0e0e0;
-    font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif;
-    display: flex;
-    align-items: center;
-    justify-content: center;
-    min-height: 100vh;
-    padding: 2rem;
-  }}
-  .container {{
-    max-width: 640px;
-    width: 100%;
-  }}
-  h1 {{
-    color: #ff6b6b;
+use serde::{Deserialize, Serialize};
+
+use crate::compat::Mismatch;
+use crate::diagnosis::FailureClass;
+use crate::logstream::Level;
+use crate::restarts::{RestartConfig, RestartRecord};
+use crate::template;
+
+/// Where the error page's log excerpt comes from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogSource {
+    /// The in-memory ring of recent sidecar output.
+    Memory,
+    /// The tail of the backend log file, kept in memory as it is written.
+    #[default]
+    File,
+    /// Output captured when the backend last exited unexpectedly.
+    Crash,
+}
+
+impl LogSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            LogSource::Memory => "memory",
+            LogSource::File => "file",
+            LogSource::Crash => "crash",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ErrorPageConfig {
+    /// Log lines shown on the error page.
+    pub log_lines: usize,
+    /// Source selected when the page opens; the page can switch it.
+    pub log_source: LogSource,
+}
+
+impl Default for ErrorPageConfig {
+    fn default() -> Self {
+        Self {
+            log_lines: 20,
+            log_source: LogSource::default(),
+        }
+    }
+}
+
+const ERROR_CSS: &str = r#"
+  h1 {
+    color: var(--accent-error);
     font-size: 1.4rem;
+    line-height: 1.3;
     margin-bottom: 1rem;
-  }}
-  .message {{
+  }
+  .message {
     color: #aaa;
     margin-bottom: 1.5rem;
-    line-height: 1.5;
-  }}
-  .log-box {{
-    background: #111;
-    border: 1px solid #333;
-    border-radius: 6px;
+  }
+  .log-source {
+    display: flex;
+    flex-wrap: wrap;
+    align-items: center;
+    gap: 0.5rem;
+    margin-bottom: 0.5rem;
+    font-size: 0.85rem;
+    color: var(--fg-muted);
+  }
+  .log-source select {
+    background: var(--bg-inset);
+    color: var(--fg);
+    border: 1px solid var(--border);
+    border-radius: 0.375rem;
+    padding: 0.25em 0.5em;
+    font: inherit;
+  }
+  .log-box {
+    background: var(--bg-inset);
+    border: 1px solid var(--border);
+    border-radius: 0.375rem;
     padding: 1rem;
-    font-family: "SF Mono", "Fira Code", monospace;
     font-size: 0.75rem;
     line-height: 1.6;
     white-space: pre-wrap;
     word-break: break-all;
-    max-height: 300px;
+    max-height: 40vh;
     overflow-y: auto;
     margin-bottom: 1.5rem;
-    color: #999;
-  }}
-  button {{
-    background: #333;
-    color: #e0e0e0;
-    border: 1px solid #555;
-    border-radius: 6px;
-    padding: 0.6rem 1.5rem;
-    font-size: 0.9rem;
-    cursor: pointer;
-    transition: background 0.15s;
-  }}
-  button:hover {{
-    background: #444;
-  }}
-</style>
-</head>
-<body>
+    color: var(--fg-muted);
+  }
+  .log-line { display: block; }
+  .log-line.error { color: var(--accent-error); }
+  .log-line.warn { color: var(--accent-warn); }
+  .log-line.info { color: var(--fg); }
+  .last-error {
+    border-left: 3px solid var(--accent-error);
+    background: var(--bg-inset);
+    border-radius: 0.25rem;
+    padding: 0.5rem 0.75rem;
+    margin-bottom: 1rem;
+    font-size: 0.8rem;
+    text-align: left;
+  }
+  .last-error .label { color: var(--fg-muted); display: block; margin-bottom: 0.25rem; }
+  .last-error .text { color: var(--accent-error); white-space: pre-wrap; word-break: break-all; }
+  .restarts { margin-bottom: 1rem; font-size: 0.8rem; text-align: left; }
+  .restarts .label { color: var(--fg-muted); display: block; margin-bottom: 0.25rem; }
+  .restarts ol { margin: 0; padding-left: 1.25rem; color: var(--fg-muted); }
+  .fix { margin-bottom: 1rem; font-size: 0.85rem; text-align: left; }
+  .fix .label { color: var(--fg-muted); display: block; margin-bottom: 0.25rem; }
+  .fix ul { margin: 0; padding-left: 1.25rem; }
+"#;
+
+const ERROR_BODY: &str = r#"<body class="centered">
+  <div class="container">
+    <h1>{{title}}</h1>
+    <p class="message">{{message}}</p>
+    <!-- restarts -->
+    <!-- fix -->
+    <!-- last-error -->
+    <!-- log-source -->
+    <div class="log-box mono" id="log-box" data-empty="{{t:no_log_output}}"><!-- log-lines --></div>
+    <div class="actions">
+      <button onclick="window.__invoke('restart_backend')">{{t:retry}}</button>
+      <button onclick="window.__invoke('open_logs_folder')">{{t:open_logs_folder}}</button>
+      <button onclick="window.__invoke('open_log_file')">{{t:open_log_file}}</button>
+      <button onclick="window.__invoke('open_issue_report')">{{t:report_issue}}</button>
+    </div>
+  </div>
+</body>"#;
+
+/// Lets the user switch the excerpt between log sources via `get_error_context`.
+const LOG_SOURCE_PICKER: &str = r#"<label class="log-source">{{t:log_source}}
+      <select id="log-source" data-selected="{{selected}}">
+        <option value="memory">{{t:log_source_memory}}</option>
+        <option value="file">{{t:log_source_file}}</option>
+        <option value="crash">{{t:log_source_crash}}</option>
+      </select>
+    </label>
+    <script>
+      (() => {
+        const picker = document.getElementById('log-source');
+        picker.value = picker.dataset.selected;
+        picker.addEventListener('change', async () => {
+          // The log box comes after this script in the page
+          const box = document.getElementById('log-box');
+          const lastError = document.getElementById('last-error');
+          try {
+            const context = await window.__invoke('get_error_context', { source: picker.value });
+            box.replaceChildren(...context.lines.map((text, i) => {
+              const el = document.createElement('span');
+              el.className = 'log-line ' + context.levels[i];
+              el.textContent = text;
+              return el;
+            }));
+            if (!context.lines.length) box.textContent = box.dataset.empty;
+            const last = context.levels.lastIndexOf('error');
+            lastError.hidden = last < 0;
+            if (last >= 0) lastError.querySelector('.text').textContent = context.lines[last];
+          } catch (e) {
+            box.textContent = String(e);
+          }
+        });
+      })();
+    </script>"#;
+
+/// Backend failure page; `source` adds a picker for where the log lines
+/// came from.
+pub fn generate_error_html(
+    message: &str,
+    log_lines: &[String],
+    source: Option<LogSource>,
+) -> String {
+    let html = generate_titled_error_html(template::t("backend_failed"), message, log_lines);
+    with_log_source(html, source)
+}
+
+/// Backend failure page for a recognised failure mode: what happened and
+/// how to fix it, then the usual log excerpt.
+pub fn generate_diagnosed_error_html(
+    class: &FailureClass,
+    log_lines: &[String],
+    source: Option<LogSource>,
+) -> String {
+    let items: String = class
+        .remediation()
+        .iter()
+        .map(|step| format!("<li>{}</li>", template::html_escape(step)))
+        .collect();
+    let fix = format!(
+        r#"<div class="fix"><span class="label">{}</span><ul>{}</ul></div>"#,
+        template::html_escape(template::t("suggested_fix")),
+        items
+    );
+    let html = generate_titled_error_html(class.title(), &class.message(), log_lines).replacen(
+        "<!-- fix -->",
+        &fix,
+        1,
+    );
+    with_log_source(html, source)
+}
+
+const INCOMPATIBLE_BODY: &str = r#"<body class="centered">
   <div class="container">
-    <h1>Backend failed to start</h1>
-    <p class="message">{}</p>
-    <div class="log-box">{}</div>
-    <button onclick="window.__TAURI__?.invoke('restart_backend')">
-      Retry
-    </button>
+    <h1>{{t:backend_incompatible}}</h1>
+    <p class="message">{{message}}</p>
+    <div class="actions">
+      <button onclick="window.__invoke('open_update_page')">{{t:get_update}}</button>
+      <button data-url="{{app_url}}" onclick="location.replace(this.dataset.url)">{{t:continue_anyway}}</button>
+      <button onclick="window.__invoke('open_log_file')">{{t:open_log_file}}</button>
+    </div>
   </div>
-</body>
-</html>"#,
-        escaped_message,
-        if escaped_logs.is_empty() {
-            "No log output available.".to_string()
-        } else {
-            escaped_logs
+</body>"#;
+
+/// Page for a backend from another release than the shell; continuing loads
+/// `app_url` regardless.
+pub fn generate_incompatible_html(mismatch: &Mismatch, app_url: &str) -> String {
+    let message = format!(
+        "Local Agent {} works with backend versions {}, but the installed backend is {}. \
+         This usually means an update did not finish; installing the latest release fixes it.",
+        mismatch.shell_version, mismatch.supported, mismatch.backend_version
+    );
+    let title = template::t("backend_incompatible");
+    let body = template::fill(
+        INCOMPATIBLE_BODY,
+        &[("message", &message), ("app_url", app_url)],
+    );
+    template::render(title, ERROR_CSS, &body)
+}
+
+/// Adds the log source picker, with `source` selected.
+fn with_log_source(html: String, source: Option<LogSource>) -> String {
+    match source {
+        Some(source) => {
+            let picker = template::fill(LOG_SOURCE_PICKER, &[("selected", source.as_str())]);
+            html.replacen("<!-- log-source -->", &picker, 1)
         }
-    )
+        None => html,
+    }
 }
 
-fn html_escape(s: &str) -> String {
-    s.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
+/// Page for a backend the watchdog stopped restarting: the restarts that
+/// used up `config`'s allowance, then the usual log excerpt.
+pub fn generate_crash_loop_html(
+    config: &RestartConfig,
+    recent: &[RestartRecord],
+    log_lines: &[String],
+    source: Option<LogSource>,
+) -> String {
+    let message = format!(
+        "It stopped {} times in the last {} minutes, so Local Agent is no longer restarting it automatically. \
+         The log below shows what happened before the last exit; Retry starts it again.",
+        recent.len(),
+        config.window_minutes
+    );
+    let items: String = recent
+        .iter()
+        .map(|record| {
+            let at = record.at.get(11..19).unwrap_or(&record.at);
+            format!(
+                "<li>{} {}</li>",
+                template::html_escape(at),
+                template::html_escape(&record.detail)
+            )
+        })
+        .collect();
+    let restarts = format!(
+        r#"<div class="restarts"><span class="label">{}</span><ol class="mono">{}</ol></div>"#,
+        template::html_escape(template::t("recent_restarts")),
+        items
+    );
+    let html = generate_titled_error_html(template::t("crash_loop"), &message, log_lines).replacen(
+        "<!-- restarts -->",
+        &restarts,
+        1,
+    );
+    with_log_source(html, source)
+}
+
+pub fn generate_titled_error_html(title: &str, message: &str, log_lines: &[String]) -> String {
+    let lines: Vec<(Level, String)> = log_lines
+        .iter()
+        .map(|line| (Level::of_logged(line), template::sanitize_log_line(line)))
+        .collect();
+    let logs = if lines.is_empty() {
+        template::html_escape(template::t("no_log_output"))
+    } else {
+        lines
+            .iter()
+            .map(|(level, line)| {
+                format!(
+                    r#"<span class="log-line {}">{}</span>"#,
+                    level.as_str(),
+                    template::html_escape(line)
+                )
+            })
+            .collect()
+    };
+    // Kept (hidden) without an error so the log source picker can fill it in
+    let last_error = lines.iter().rev().find(|(level, _)| *level == Level::Error);
+    let last_error = format!(
+        r#"<div class="last-error" id="last-error"{}><span class="label">{}</span><span class="text mono">{}</span></div>"#,
+        if last_error.is_some() { "" } else { " hidden" },
+        template::html_escape(template::t("last_error")),
+        template::html_escape(last_error.map(|(_, line)| line.as_str()).unwrap_or(""))
+    );
+    // Spawn errors can quote backend output too
+    let message = template::sanitize_log_line(message);
+
+    let body = template::fill(ERROR_BODY, &[("title", title), ("message", &message)])
+        .replacen("<!-- last-error -->", &last_error, 1)
+        .replacen("<!-- log-lines -->", &logs, 1);
+    template::render(title, ERROR_CSS, &body)
 }
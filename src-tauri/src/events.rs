@@ -0,0 +1,76 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Events kept for replay. Log batches carry up to 200 lines each, so this
+/// bounds memory as well as how far back a reloaded webview can catch up.
+pub const REPLAY_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordedEvent {
+    /// Increases by one per event, starting at 1.
+    pub seq: u64,
+    pub at: String,
+    pub event: String,
+    pub payload: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EventReplay {
+    pub events: Vec<RecordedEvent>,
+    /// Pass this as `since` next time; 0 when nothing was emitted yet.
+    pub latest_seq: u64,
+    /// Events after `since` were evicted before they could be replayed; the
+    /// caller should reload full state (sidecar statuses etc.) instead.
+    pub missed: bool,
+}
+
+#[derive(Default)]
+struct Inner {
+    latest_seq: u64,
+    events: VecDeque<RecordedEvent>,
+}
+
+/// Recent lifecycle, log and progress events, so a webview that (re)loads
+/// after navigation or recovery can catch up on what it missed.
+#[derive(Default)]
+pub struct EventLog {
+    inner: Mutex<Inner>,
+}
+
+impl EventLog {
+    pub fn record(&self, event: &str, payload: serde_json::Value) -> u64 {
+        let mut inner = self.inner.lock().unwrap();
+        inner.latest_seq += 1;
+        let seq = inner.latest_seq;
+        if inner.events.len() >= REPLAY_CAPACITY {
+            inner.events.pop_front();
+        }
+        inner.events.push_back(RecordedEvent {
+            seq,
+            at: chrono::Local::now().to_rfc3339(),
+            event: event.to_string(),
+            payload,
+        });
+        seq
+    }
+
+    /// Events with a sequence number greater than `since`, oldest first.
+    pub fn since(&self, since: u64) -> EventReplay {
+        let inner = self.inner.lock().unwrap();
+        let missed = inner
+            .events
+            .front()
+            .is_some_and(|oldest| oldest.seq > since.saturating_add(1));
+        EventReplay {
+            events: inner
+                .events
+                .iter()
+                .filter(|e| e.seq > since)
+                .cloned()
+                .collect(),
+            latest_seq: inner.latest_seq,
+            missed,
+        }
+    }
+}
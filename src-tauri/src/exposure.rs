@@ -0,0 +1,159 @@
+//! Who can reach the backend: this computer only (it listens on
+//! 127.0.0.1, the default) or other devices on the network too (0.0.0.0).
+//! The shell decides by the `HOST` it starts the backend with, so a
+//! backend setting can't open it up on its own. LAN access can require a
+//! PIN. Only a salted PBKDF2 hash of it is kept; the backend gets that as
+//! `LOCAL_AGENT_LAN_PIN_HASH`, asks devices other than this one for the
+//! PIN and locks them out after repeated wrong guesses. Traffic from other
+//! devices is plain HTTP, so anyone on the network can read it.
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+
+use crate::remote;
+
+pub const EVENT: &str = "network-exposure-changed";
+pub const PIN_ENV: &str = "LOCAL_AGENT_LAN_PIN_HASH";
+const PIN_LENGTH: std::ops::RangeInclusive<usize> = 4..=8;
+/// PBKDF2-HMAC-SHA256 rounds; makes each guess against a copied hash slow.
+const PIN_ITERATIONS: u32 = 600_000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkExposure {
+    #[default]
+    Local,
+    Lan,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExposureConfig {
+    pub mode: NetworkExposure,
+    /// `pbkdf2-sha256$<iterations>$<salt>$<hash>` of the PIN other devices
+    /// must enter, in hex; none means no PIN.
+    pub pin_hash: Option<String>,
+}
+
+impl ExposureConfig {
+    /// Switches to `mode`; a PIN (from `hash_pin`) is only kept for LAN
+    /// access.
+    pub fn set(&mut self, mode: NetworkExposure, pin_hash: Option<String>) {
+        self.mode = mode;
+        self.pin_hash = pin_hash.filter(|_| mode == NetworkExposure::Lan);
+    }
+
+    /// `HOST` and, when set, the PIN hash for the backend.
+    pub fn env(&self) -> BTreeMap<String, String> {
+        let host = match self.mode {
+            NetworkExposure::Local => "127.0.0.1",
+            NetworkExposure::Lan => "0.0.0.0",
+        };
+        let mut env = BTreeMap::from([("HOST".to_string(), host.to_string())]);
+        if let (NetworkExposure::Lan, Some(ref pin)) = (self.mode, &self.pin_hash) {
+            env.insert(PIN_ENV.to_string(), pin.clone());
+        }
+        env
+    }
+}
+
+/// Checks `pin` and hashes it with a fresh salt. Slow on purpose; call it
+/// off the main thread.
+pub fn hash_pin(pin: &str) -> Result<String, String> {
+    if !PIN_LENGTH.contains(&pin.len()) || !pin.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!(
+            "The PIN must be {} to {} digits",
+            PIN_LENGTH.start(),
+            PIN_LENGTH.end()
+        ));
+    }
+    let salt = uuid::Uuid::new_v4().into_bytes();
+    Ok(format!(
+        "pbkdf2-sha256${}${}${}",
+        PIN_ITERATIONS,
+        hex(&salt),
+        hex(&pbkdf2(pin.as_bytes(), &salt, PIN_ITERATIONS))
+    ))
+}
+
+/// PBKDF2-HMAC-SHA256 with one 32-byte block, as node's
+/// `crypto.pbkdf2(pin, salt, iterations, 32, 'sha256')` computes it.
+fn pbkdf2(secret: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC takes keys of any length");
+    let mut first = mac.clone();
+    first.update(salt);
+    first.update(&1u32.to_be_bytes());
+    let mut round = first.finalize().into_bytes();
+    let mut key = round;
+    for _ in 1..iterations {
+        let mut next = mac.clone();
+        next.update(&round);
+        round = next.finalize().into_bytes();
+        key.iter_mut().zip(round.iter()).for_each(|(k, r)| *k ^= r);
+    }
+    key.into()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExposureStatus {
+    pub mode: NetworkExposure,
+    pub pin_required: bool,
+    /// Where other devices reach the backend, with LAN access on.
+    pub lan_url: Option<String>,
+    pub warning: Option<String>,
+}
+
+pub fn status(config: &ExposureConfig, port: u16) -> ExposureStatus {
+    let lan = config.mode == NetworkExposure::Lan;
+    let pin_required = lan && config.pin_hash.is_some();
+    let warning = match (lan, pin_required) {
+        (false, _) => None,
+        (true, true) => Some(
+            "Other devices on this network can reach Local Agent after entering the PIN. \
+             Their traffic isn't encrypted; only turn this on for networks you trust."
+                .to_string(),
+        ),
+        (true, false) => Some(
+            "Anyone on this network can use Local Agent, its chats and its tools without a PIN. \
+             Set a PIN, or only turn this on for networks you trust."
+                .to_string(),
+        ),
+    };
+    ExposureStatus {
+        mode: config.mode,
+        pin_required,
+        lan_url: lan
+            .then(remote::lan_ip)
+            .flatten()
+            .map(|ip| format!("http://{}:{}", ip, port)),
+        warning,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pbkdf2_matches_the_rfc_7914_vector() {
+        assert_eq!(
+            hex(&pbkdf2(b"passwd", b"salt", 1)),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc"
+        );
+    }
+
+    #[test]
+    fn pin_hashes_are_salted() {
+        let first = hash_pin("1234").unwrap();
+        assert!(first.starts_with("pbkdf2-sha256$600000$"));
+        assert_ne!(first, hash_pin("1234").unwrap());
+        assert!(hash_pin("12a4").is_err());
+        assert!(hash_pin("123").is_err());
+    }
+}
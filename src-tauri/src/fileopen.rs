@@ -0,0 +1,66 @@
+//! Documents the OS opens with Local Agent, for the agent to ingest. They
+//! arrive as launch arguments on Windows and Linux (handed over by the
+//! single-instance plugin when the app is already running) and as `file://`
+//! URLs on macOS, and are routed like deep links: held until the backend is
+//! healthy, then emitted as "file-opened" events.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::Url;
+
+/// Extensions registered in tauri.conf.json's `fileAssociations`.
+pub const EXTENSIONS: &[&str] = &["md", "markdown", "txt", "pdf"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenedFile {
+    pub path: PathBuf,
+    pub name: String,
+    pub extension: String,
+    pub size: u64,
+}
+
+impl OpenedFile {
+    /// `None` unless `path` is an existing file of a supported type.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        if !EXTENSIONS.contains(&extension.as_str()) {
+            return None;
+        }
+        let metadata = std::fs::metadata(path).ok().filter(|m| m.is_file())?;
+        Some(Self {
+            path: path.to_path_buf(),
+            name: path.file_name()?.to_string_lossy().into_owned(),
+            extension,
+            size: metadata.len(),
+        })
+    }
+}
+
+/// Files among a process's arguments; `args` includes the executable,
+/// relative paths are taken from `cwd`. Flags and URLs are skipped.
+pub fn from_args(args: &[String], cwd: &Path) -> Vec<OpenedFile> {
+    args.iter()
+        .skip(1)
+        .filter(|arg| !arg.starts_with('-') && !arg.contains("://"))
+        .filter_map(|arg| OpenedFile::from_path(&cwd.join(arg)))
+        .collect()
+}
+
+/// Splits `file://` URLs out of `urls`, returning the files they name and
+/// the remaining URLs.
+pub fn partition_urls(urls: Vec<Url>) -> (Vec<OpenedFile>, Vec<Url>) {
+    let (files, rest): (Vec<Url>, Vec<Url>) =
+        urls.into_iter().partition(|url| url.scheme() == "file");
+    let files = files
+        .iter()
+        .filter_map(|url| url.to_file_path().ok())
+        .filter_map(|path| {
+            let opened = OpenedFile::from_path(&path);
+            if opened.is_none() {
+                eprintln!("[tauri] Ignoring opened file {}", path.display());
+            }
+            opened
+        })
+        .collect();
+    (files, rest)
+}
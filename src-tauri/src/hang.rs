@@ -0,0 +1,252 @@
+//! Hangs, as opposed to crashes: a backend whose process is still running
+//! but whose health check stops answering. After `timeouts` checks in a row
+//! go unanswered, the shell saves what the backend printed last (after
+//! asking Node for a stack dump, when configured), tells the app with a
+//! `backend-hung` event and kills and restarts it. A wedged process won't
+//! act on a polite shutdown request, so it isn't sent one.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::hooks::HookPoint;
+use crate::restarts::RestartReason;
+use crate::sidecar::SidecarManager;
+use crate::state::BackendState;
+use crate::{
+    backend_poll_settings, backend_probe, emit_event, health, is_dev, set_backend_state,
+    spawn_hooks, wait_for_backend, AppState, BACKEND, CRASH_SNAPSHOT_LINES,
+};
+
+pub const EVENT: &str = "backend-hung";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HangConfig {
+    pub enabled: bool,
+    /// How often the running backend is checked.
+    pub interval_secs: u64,
+    /// How long a check waits for an answer.
+    pub timeout_ms: u64,
+    /// Unanswered checks in a row that count as a hang.
+    pub timeouts: u32,
+    /// Sent before the snapshot so the backend writes a stack dump to its
+    /// log, e.g. `SIGUSR2` for Node started with `--report-on-signal`.
+    /// Ignored on Windows.
+    pub dump_signal: Option<DumpSignal>,
+    /// How long to give the dump before taking the snapshot.
+    pub dump_wait_ms: u64,
+}
+
+impl Default for HangConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 10,
+            timeout_ms: 5000,
+            timeouts: 3,
+            dump_signal: None,
+            dump_wait_ms: 2000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum DumpSignal {
+    Sigquit,
+    Sigusr2,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HangEvent {
+    pub pid: Option<u32>,
+    pub timeouts: u32,
+    /// Where the snapshot was saved.
+    pub snapshot: Option<String>,
+    /// False when it is left running: in dev, or restarting too often.
+    pub restarting: bool,
+}
+
+/// Checks the backend while it is up and handles a hang when the checks
+/// stop being answered.
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut timeouts = 0;
+        loop {
+            let config = app.state::<AppState>().config.lock().unwrap().hang.clone();
+            tokio::time::sleep(Duration::from_secs(config.interval_secs.max(1))).await;
+
+            let backend = {
+                let state: State<AppState> = app.state();
+                // Starts and restarts have health checks of their own
+                let settled = matches!(
+                    state.backend_state.current().state,
+                    BackendState::Healthy | BackendState::Degraded
+                );
+                state
+                    .sidecars
+                    .get(BACKEND)
+                    .filter(|backend| config.enabled && settled && backend.is_running())
+            };
+            let Some(backend) = backend else {
+                timeouts = 0;
+                continue;
+            };
+
+            let settings = health::PollSettings {
+                connect_timeout_ms: config.timeout_ms,
+                ..health::PollSettings::default()
+            };
+            // An answer, even an unhealthy one, means it isn't stuck
+            match health::check_once(&backend_probe(&app), &settings).await {
+                Ok(result) if !result.answered => timeouts += 1,
+                _ => timeouts = 0,
+            }
+            if timeouts >= config.timeouts.max(1) {
+                handle_hang(&app, backend, &config, timeouts).await;
+                timeouts = 0;
+            }
+        }
+    });
+}
+
+async fn handle_hang(
+    app: &AppHandle,
+    backend: Arc<SidecarManager>,
+    config: &HangConfig,
+    timeouts: u32,
+) {
+    let pid = backend.pid();
+    eprintln!(
+        "[tauri] Backend (pid {}) is running but missed {} health checks in a row",
+        pid.map(|pid| pid.to_string()).unwrap_or_default(),
+        timeouts
+    );
+    if let (Some(signal), Some(pid)) = (config.dump_signal, pid) {
+        request_dump(pid, signal);
+        tokio::time::sleep(Duration::from_millis(config.dump_wait_ms)).await;
+    }
+    let lines = backend.recent_log_lines(CRASH_SNAPSHOT_LINES);
+    let snapshot = match save_snapshot(&backend, timeouts, &lines) {
+        Ok(path) => {
+            println!("[tauri] Hang snapshot saved to {}", path.display());
+            Some(path.to_string_lossy().into_owned())
+        }
+        Err(e) => {
+            eprintln!("[tauri] {}", e);
+            None
+        }
+    };
+
+    let allowed = {
+        let state: State<AppState> = app.state();
+        let restarts = state.config.lock().unwrap().restarts.clone();
+        state.restarts.allows(BACKEND, &restarts)
+    };
+    let restarting = allowed && !is_dev();
+    emit_event(
+        app,
+        EVENT,
+        HangEvent {
+            pid,
+            timeouts,
+            snapshot,
+            restarting,
+        },
+    );
+    if is_dev() {
+        set_backend_state(
+            app,
+            BackendState::Degraded,
+            Some("not answering its health check (dev mode, not restarting)"),
+        );
+        return;
+    }
+    if !allowed {
+        set_backend_state(
+            app,
+            BackendState::Degraded,
+            Some("not answering its health check; restarted too often, leaving it running"),
+        );
+        return;
+    }
+
+    set_backend_state(
+        app,
+        BackendState::Restarting,
+        Some("not answering its health check"),
+    );
+    app.state::<AppState>().restarts.record(
+        BACKEND,
+        RestartReason::Hang,
+        &format!("{} health checks unanswered", timeouts),
+    );
+    let restarted = tauri::async_runtime::spawn_blocking(move || {
+        // No grace period: it is killed straight away
+        backend.shutdown_within(Duration::ZERO);
+        backend.spawn_with_retry()
+    })
+    .await
+    .map_err(|e| format!("Failed to restart backend: {}", e))
+    .and_then(|result| result);
+    if let Err(e) = restarted {
+        set_backend_state(app, BackendState::Failed, Some(&e));
+        spawn_hooks(app, HookPoint::PostCrash);
+        return;
+    }
+
+    match wait_for_backend(app, &backend_poll_settings(app), None).await {
+        Ok(()) => {
+            set_backend_state(app, BackendState::Healthy, None);
+            spawn_hooks(app, HookPoint::PostBackendStart);
+        }
+        Err(e) => {
+            set_backend_state(app, BackendState::Failed, Some(&e));
+            spawn_hooks(app, HookPoint::PostCrash);
+        }
+    }
+}
+
+/// Next to the crash snapshot; replaced by the next hang.
+fn snapshot_path(backend: &SidecarManager) -> PathBuf {
+    SidecarManager::resolve_log_dir().join(format!("local-agent-{}-hang.log", backend.name()))
+}
+
+fn save_snapshot(
+    backend: &SidecarManager,
+    timeouts: u32,
+    lines: &[String],
+) -> Result<PathBuf, String> {
+    let mut contents = format!(
+        "{} stopped answering ({} health checks unanswered) at {}\n",
+        backend.name(),
+        timeouts,
+        chrono::Local::now().to_rfc3339()
+    );
+    for line in lines {
+        contents.push_str(line);
+        contents.push('\n');
+    }
+    let path = snapshot_path(backend);
+    fs::write(&path, contents).map_err(|e| format!("Failed to write hang snapshot: {}", e))?;
+    Ok(path)
+}
+
+#[cfg(unix)]
+fn request_dump(pid: u32, signal: DumpSignal) {
+    let signal = match signal {
+        DumpSignal::Sigquit => libc::SIGQUIT,
+        DumpSignal::Sigusr2 => libc::SIGUSR2,
+    };
+    println!("[tauri] Asking pid {} for a stack dump", pid);
+    unsafe {
+        libc::kill(pid as libc::pid_t, signal);
+    }
+}
+
+#[cfg(not(unix))]
+fn request_dump(_pid: u32, _signal: DumpSignal) {}
@@ -0,0 +1,221 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use sysinfo::{Disks, System};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GpuInfo {
+    pub name: String,
+    pub vram_bytes: Option<u64>,
+    /// Acceleration API local runtimes can use: "metal", "cuda" or "directml".
+    pub backend: Option<&'static str>,
+    /// Apple Silicon GPUs share system RAM instead of having dedicated VRAM.
+    pub unified_memory: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskInfo {
+    pub mount_point: PathBuf,
+    pub total_bytes: u64,
+    pub free_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemCapabilities {
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub cpu_brand: String,
+    pub physical_cores: Option<usize>,
+    pub logical_cores: usize,
+    pub total_memory_bytes: u64,
+    pub available_memory_bytes: u64,
+    pub gpus: Vec<GpuInfo>,
+    /// The volume holding the app's data directory.
+    pub data_disk: Option<DiskInfo>,
+}
+
+pub fn detect() -> SystemCapabilities {
+    let mut sys = System::new();
+    sys.refresh_memory();
+    sys.refresh_cpu_all();
+
+    let total_memory = sys.total_memory();
+    SystemCapabilities {
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        cpu_brand: sys
+            .cpus()
+            .first()
+            .map(|cpu| cpu.brand().trim().to_string())
+            .unwrap_or_default(),
+        physical_cores: sys.physical_core_count(),
+        logical_cores: sys.cpus().len(),
+        total_memory_bytes: total_memory,
+        available_memory_bytes: sys.available_memory(),
+        gpus: detect_gpus(total_memory),
+        data_disk: dirs::data_local_dir().and_then(|dir| disk_for(&dir)),
+    }
+}
+
+/// Picks the disk whose mount point is the longest prefix of `path`.
+pub fn disk_for(path: &Path) -> Option<DiskInfo> {
+    let disks = Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| DiskInfo {
+            mount_point: disk.mount_point().to_path_buf(),
+            total_bytes: disk.total_space(),
+            free_bytes: disk.available_space(),
+        })
+}
+
+fn detect_gpus(total_memory: u64) -> Vec<GpuInfo> {
+    if cfg!(target_os = "macos") {
+        return macos_gpus(total_memory);
+    }
+
+    let nvidia = nvidia_gpus();
+    if !nvidia.is_empty() {
+        return nvidia;
+    }
+
+    if cfg!(windows) {
+        windows_gpus()
+    } else {
+        linux_gpus()
+    }
+}
+
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn macos_gpus(total_memory: u64) -> Vec<GpuInfo> {
+    let Some(json) = run("system_profiler", &["SPDisplaysDataType", "-json"]) else {
+        return Vec::new();
+    };
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(&json) else {
+        return Vec::new();
+    };
+
+    value["SPDisplaysDataType"]
+        .as_array()
+        .map(|gpus| {
+            gpus.iter()
+                .map(|gpu| {
+                    let name = gpu["sppci_model"]
+                        .as_str()
+                        .or_else(|| gpu["_name"].as_str())
+                        .unwrap_or("Unknown GPU")
+                        .to_string();
+                    let vram = gpu["spdisplays_vram"]
+                        .as_str()
+                        .or_else(|| gpu["spdisplays_vram_shared"].as_str())
+                        .and_then(parse_size);
+                    let unified = vram.is_none() && std::env::consts::ARCH == "aarch64";
+                    GpuInfo {
+                        name,
+                        vram_bytes: if unified { Some(total_memory) } else { vram },
+                        backend: Some("metal"),
+                        unified_memory: unified,
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn nvidia_gpus() -> Vec<GpuInfo> {
+    let Some(csv) = run(
+        "nvidia-smi",
+        &[
+            "--query-gpu=name,memory.total",
+            "--format=csv,noheader,nounits",
+        ],
+    ) else {
+        return Vec::new();
+    };
+
+    csv.lines()
+        .filter_map(|line| {
+            let (name, mib) = line.rsplit_once(',')?;
+            Some(GpuInfo {
+                name: name.trim().to_string(),
+                vram_bytes: mib.trim().parse::<u64>().ok().map(|m| m * 1024 * 1024),
+                backend: Some("cuda"),
+                unified_memory: false,
+            })
+        })
+        .collect()
+}
+
+fn windows_gpus() -> Vec<GpuInfo> {
+    let Some(json) = run(
+        "powershell",
+        &[
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "Get-CimInstance Win32_VideoController | Select-Object Name,AdapterRAM | ConvertTo-Json",
+        ],
+    ) else {
+        return Vec::new();
+    };
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(&json) else {
+        return Vec::new();
+    };
+
+    // ConvertTo-Json emits a bare object when there is a single adapter.
+    let adapters = match value {
+        serde_json::Value::Array(items) => items,
+        other => vec![other],
+    };
+    adapters
+        .iter()
+        .filter_map(|adapter| {
+            Some(GpuInfo {
+                name: adapter["Name"].as_str()?.to_string(),
+                // AdapterRAM is a uint32 and saturates at 4 GB.
+                vram_bytes: adapter["AdapterRAM"].as_u64().filter(|b| *b > 0),
+                backend: Some("directml"),
+                unified_memory: false,
+            })
+        })
+        .collect()
+}
+
+fn linux_gpus() -> Vec<GpuInfo> {
+    let Some(output) = run("lspci", &[]) else {
+        return Vec::new();
+    };
+    output
+        .lines()
+        .filter(|line| line.contains("VGA compatible controller") || line.contains("3D controller"))
+        .filter_map(|line| line.split_once(": ").map(|(_, name)| name.trim()))
+        .map(|name| GpuInfo {
+            name: name.to_string(),
+            vram_bytes: None,
+            backend: None,
+            unified_memory: false,
+        })
+        .collect()
+}
+
+/// Parses sizes like "8 GB" or "1536 MB" as reported by system_profiler.
+fn parse_size(raw: &str) -> Option<u64> {
+    let mut parts = raw.split_whitespace();
+    let amount: u64 = parts.next()?.parse().ok()?;
+    let multiplier = match parts.next()?.to_ascii_uppercase().as_str() {
+        "GB" => 1024 * 1024 * 1024,
+        "MB" => 1024 * 1024,
+        _ => return None,
+    };
+    Some(amount * multiplier)
+}
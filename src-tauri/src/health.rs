@@ -1,44 +1,748 @@
-use std::time::Duration;
+//! Health checks. A service is polled with a [`HealthProbe`] built from its
+//! [`ProbeSpec`]: plain HTTP by default, or whichever check suits it when
+//! configured (a vector DB may only accept TCP, a worker may only touch a
+//! heartbeat file).
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Notify;
+
+use crate::backoff::Backoff;
+use crate::proxy;
+use crate::trace::TRACE_HEADER;
+
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct PollSettings {
+    pub connect_timeout_ms: u64,
+    /// Spacing between polls; `max_elapsed_ms` is the overall deadline.
+    pub backoff: Backoff,
+}
+
+impl Default for PollSettings {
+    fn default() -> Self {
+        Self {
+            connect_timeout_ms: 2000,
+            backoff: Backoff::health(),
+        }
+    }
+}
+
+/// How to tell a service is up, as written in the config:
+/// `{"type": "tcp", "address": "127.0.0.1:6333"}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProbeSpec {
+    /// The port accepts connections.
+    Tcp { address: String },
+    /// A GET answers with `status` (or one of `statuses`), or any 2xx when
+    /// neither is set.
+    Http {
+        url: String,
+        #[serde(default)]
+        status: Option<u16>,
+        #[serde(flatten)]
+        check: HealthCheckConfig,
+    },
+    /// A GET answers with JSON whose value at `pointer` (RFC 6901, e.g.
+    /// `/status`) equals `equals`.
+    HttpJson {
+        url: String,
+        pointer: String,
+        equals: Value,
+        #[serde(flatten)]
+        check: HealthCheckConfig,
+    },
+    /// A GET of `path` over the backend's Unix socket or named pipe (see
+    /// `bridge`) answers with `status`, or any 2xx when unset.
+    Socket {
+        socket: PathBuf,
+        path: String,
+        #[serde(default)]
+        status: Option<u16>,
+    },
+    /// The command exits with status 0.
+    Command {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        cwd: Option<PathBuf>,
+    },
+    /// The file was modified within the last `max_age_ms`.
+    Heartbeat { path: PathBuf, max_age_ms: u64 },
+}
+
+/// Request options for the HTTP probes, written alongside their other
+/// fields: `{"type": "http", "url": "http://127.0.0.1:3001/health",
+/// "scheme": "https", "headers": {"Authorization": "Bearer …"},
+/// "danger_accept_invalid_certs": true}`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthCheckConfig {
+    /// Replaces the URL's scheme, e.g. `https` for a backend behind local
+    /// TLS.
+    pub scheme: Option<String>,
+    /// Sent with every check, e.g. an auth header.
+    pub headers: BTreeMap<String, String>,
+    /// Accepted status codes; any 2xx when empty.
+    pub statuses: Vec<u16>,
+    /// Text the response body must contain.
+    pub body_contains: Option<String>,
+    /// Skip certificate validation, for self-signed certificates. Only
+    /// allowed for loopback hosts.
+    pub danger_accept_invalid_certs: bool,
+}
+
+impl HealthCheckConfig {
+    fn url(&self, url: &str) -> Result<reqwest::Url, String> {
+        let mut parsed = reqwest::Url::parse(url)
+            .map_err(|e| format!("Invalid health check URL {}: {}", url, e))?;
+        if let Some(ref scheme) = self.scheme {
+            if !matches!(scheme.as_str(), "http" | "https") || parsed.set_scheme(scheme).is_err() {
+                return Err(format!("Unsupported health check scheme {:?}", scheme));
+            }
+        }
+        if self.danger_accept_invalid_certs && !is_loopback(&parsed) {
+            return Err(format!(
+                "danger_accept_invalid_certs is only allowed for localhost, not {}",
+                parsed.host_str().unwrap_or_default()
+            ));
+        }
+        Ok(parsed)
+    }
+
+    fn header_map(&self) -> Result<reqwest::header::HeaderMap, String> {
+        self.headers
+            .iter()
+            .map(|(name, value)| {
+                let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|e| format!("Invalid health check header {:?}: {}", name, e))?;
+                let value = reqwest::header::HeaderValue::from_str(value).map_err(|e| {
+                    format!("Invalid value for health check header {}: {}", name, e)
+                })?;
+                Ok((name, value))
+            })
+            .collect()
+    }
+
+    fn status_matches(&self, status: u16, expected: Option<u16>) -> bool {
+        if self.statuses.is_empty() {
+            return expected_status(status, expected);
+        }
+        expected == Some(status) || self.statuses.contains(&status)
+    }
+
+    fn body_matches(&self, body: &str) -> Result<(), String> {
+        match self.body_contains {
+            Some(ref text) if !body.contains(text.as_str()) => {
+                Err(format!("body without {:?}", text))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// `expected`, or any 2xx when unset.
+fn expected_status(status: u16, expected: Option<u16>) -> bool {
+    match expected {
+        Some(expected) => status == expected,
+        None => (200..300).contains(&status),
+    }
+}
+
+fn is_loopback(url: &reqwest::Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    // IPv6 hosts keep their brackets here
+    match host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+    {
+        Ok(ip) => ip.is_loopback(),
+        Err(_) => host.eq_ignore_ascii_case("localhost"),
+    }
+}
+
+impl ProbeSpec {
+    pub fn http(url: &str) -> Self {
+        Self::Http {
+            url: url.to_string(),
+            status: None,
+            check: HealthCheckConfig::default(),
+        }
+    }
+
+    pub fn build(&self, settings: &PollSettings) -> Result<Box<dyn HealthProbe>, String> {
+        Ok(match self {
+            Self::Tcp { address } => Box::new(TcpProbe {
+                address: address.clone(),
+            }),
+            Self::Http { url, status, check } => {
+                Box::new(HttpProbe::new(url, *status, check, settings)?)
+            }
+            Self::HttpJson {
+                url,
+                pointer,
+                equals,
+                check,
+            } => Box::new(HttpJsonProbe {
+                url: check.url(url)?,
+                client: http_client(settings, check)?.map_err(|reason| {
+                    http_stack_error(&reason, "JSON health checks can't run without it")
+                })?,
+                pointer: pointer.clone(),
+                equals: equals.clone(),
+                check: check.clone(),
+            }),
+            Self::Socket {
+                socket,
+                path,
+                status,
+            } => Box::new(SocketProbe {
+                socket: socket.clone(),
+                path: path.clone(),
+                status: *status,
+            }),
+            Self::Command { command, args, cwd } => Box::new(CommandProbe {
+                command: command.clone(),
+                args: args.clone(),
+                cwd: cwd.clone(),
+            }),
+            Self::Heartbeat { path, max_age_ms } => Box::new(HeartbeatProbe {
+                path: path.clone(),
+                max_age: Duration::from_millis(*max_age_ms),
+            }),
+        })
+    }
+}
+
+impl ProbeSpec {
+    /// The same check against `path` on the same host. Only the HTTP and
+    /// socket probes have a path.
+    pub fn with_path(&self, path: &str) -> Result<Self, String> {
+        let mut probe = self.clone();
+        match probe {
+            Self::Http { ref mut url, .. } | Self::HttpJson { ref mut url, .. } => {
+                let mut parsed = reqwest::Url::parse(url)
+                    .map_err(|e| format!("Invalid health check URL {}: {}", url, e))?;
+                let (path, query) = match path.split_once('?') {
+                    Some((path, query)) => (path, Some(query)),
+                    None => (path, None),
+                };
+                parsed.set_path(path);
+                parsed.set_query(query);
+                *url = parsed.to_string();
+            }
+            Self::Socket {
+                path: ref mut socket_path,
+                ..
+            } => *socket_path = path.to_string(),
+            _ => return Err("Only HTTP and socket health checks have a path".to_string()),
+        }
+        Ok(probe)
+    }
+}
+
+/// Per-call overrides for a health check, as the frontend passes them:
+/// `{"timeout_ms": 5000, "interval_ms": 100, "path": "/ready",
+/// "cancel_token": "…"}`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HealthCheckOptions {
+    /// Overall deadline.
+    pub timeout_ms: Option<u64>,
+    /// Fixed spacing between polls, instead of backing off.
+    pub interval_ms: Option<u64>,
+    /// Replaces the path of an HTTP or socket probe.
+    pub path: Option<String>,
+    /// Chosen by the caller; `cancel_health_check` with it stops the poll.
+    pub cancel_token: Option<String>,
+}
+
+impl HealthCheckOptions {
+    /// `probe` and `settings` with these overrides applied.
+    pub fn apply(
+        &self,
+        probe: &ProbeSpec,
+        settings: &PollSettings,
+    ) -> Result<(ProbeSpec, PollSettings), String> {
+        let mut settings = *settings;
+        if let Some(timeout_ms) = self.timeout_ms {
+            settings.backoff.max_elapsed_ms = Some(timeout_ms);
+        }
+        if let Some(interval_ms) = self.interval_ms {
+            settings.backoff.initial_delay_ms = interval_ms;
+            settings.backoff.max_delay_ms = interval_ms;
+            settings.backoff.multiplier = 1.0;
+        }
+        let probe = match self.path {
+            Some(ref path) => probe.with_path(path)?,
+            None => probe.clone(),
+        };
+        Ok((probe, settings))
+    }
+}
+
+/// Health polls that can be cancelled by the token their caller chose.
+#[derive(Default)]
+pub struct HealthCancels {
+    tokens: Mutex<HashMap<String, Arc<Notify>>>,
+}
+
+impl HealthCancels {
+    fn register(&self, token: &str) -> Arc<Notify> {
+        self.tokens
+            .lock()
+            .unwrap()
+            .entry(token.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Stops the poll registered under `token`; false if there is none.
+    pub fn cancel(&self, token: &str) -> bool {
+        match self.tokens.lock().unwrap().get(token) {
+            Some(cancel) => {
+                // Stored as a permit if the poll hasn't started waiting yet
+                cancel.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Runs `poll`, ending it early when `token` is cancelled.
+    pub async fn run<F>(&self, token: Option<&str>, poll: F) -> Result<(), String>
+    where
+        F: Future<Output = Result<(), String>>,
+    {
+        let Some(token) = token else {
+            return poll.await;
+        };
+        let cancel = self.register(token);
+        let result = tokio::select! {
+            result = poll => result,
+            _ = cancel.notified() => Err("Health check cancelled".to_string()),
+        };
+        self.tokens.lock().unwrap().remove(token);
+        result
+    }
+}
+
+/// The outcome of `check_once`.
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthCheckResult {
+    pub healthy: bool,
+    /// Whether anything answered in time, healthy or not.
+    pub answered: bool,
+    /// Why it isn't healthy.
+    pub detail: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+/// A single check, without retries, for a status indicator.
+pub async fn check_once(
+    probe: &ProbeSpec,
+    settings: &PollSettings,
+) -> Result<HealthCheckResult, String> {
+    let probe = probe.build(settings)?;
+    let check_timeout = Duration::from_millis(settings.connect_timeout_ms + 1000);
+    let start = Instant::now();
+    let (healthy, answered, detail) =
+        match tokio::time::timeout(check_timeout, probe.check(None)).await {
+            Ok(Probe::Healthy) => (true, true, None),
+            Ok(Probe::Unhealthy(detail)) => (false, true, Some(detail)),
+            Ok(Probe::Unreachable) | Err(_) => (false, false, Some("no answer".to_string())),
+        };
+    Ok(HealthCheckResult {
+        healthy,
+        answered,
+        detail,
+        elapsed_ms: start.elapsed().as_millis() as u64,
+    })
+}
+
+/// What a single check saw.
+pub enum Probe {
+    Healthy,
+    /// Answered, but not healthy; the detail is logged.
+    Unhealthy(String),
+    /// No answer, which is expected while a service starts.
+    Unreachable,
+}
+
+pub type ProbeFuture<'a> = Pin<Box<dyn Future<Output = Probe> + Send + 'a>>;
+
+pub trait HealthProbe: Send + Sync {
+    fn check<'a>(&'a self, trace_id: Option<&'a str>) -> ProbeFuture<'a>;
+
+    /// Lets a probe explain why polling gave up.
+    fn timeout_error(&self, error: String) -> String {
+        error
+    }
+}
 
 pub async fn poll_health(
-    url: &str,
+    probe: &ProbeSpec,
     interval_ms: u64,
     timeout_ms: u64,
+    trace_id: Option<&str>,
 ) -> Result<(), String> {
-    let client = reqwest::Client::builder()
-        .connect_timeout(Duration::from_secs(2))
-        .timeout(Duration::from_secs(3))
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let settings = PollSettings {
+        backoff: Backoff {
+            initial_delay_ms: interval_ms,
+            max_elapsed_ms: Some(timeout_ms),
+            ..Backoff::health()
+        },
+        ..PollSettings::default()
+    };
+    poll_health_with(probe, &settings, trace_id).await
+}
 
-    let start = tokio::time::Instant::now();
-    let timeout = Duration::from_millis(timeout_ms);
-    let interval = Duration::from_millis(interval_ms);
+pub async fn poll_health_with(
+    probe: &ProbeSpec,
+    settings: &PollSettings,
+    trace_id: Option<&str>,
+) -> Result<(), String> {
+    let probe = probe.build(settings)?;
+    let check_timeout = Duration::from_millis(settings.connect_timeout_ms + 1000);
+    let mut attempts = settings.backoff.start();
 
     loop {
-        if start.elapsed() > timeout {
-            return Err(format!(
-                "Backend health check timed out after {}ms",
-                timeout_ms
-            ));
-        }
-
-        match client.get(url).send().await {
-            Ok(resp) if resp.status().is_success() => {
+        match tokio::time::timeout(check_timeout, probe.check(trace_id)).await {
+            Ok(Probe::Healthy) => {
                 println!("[tauri] Backend health check passed");
                 return Ok(());
             }
-            Ok(resp) => {
-                println!(
-                    "[tauri] Health check returned status {}, retrying...",
-                    resp.status()
-                );
+            Ok(Probe::Unhealthy(detail)) => {
+                println!("[tauri] Health check returned {}, retrying...", detail);
             }
-            Err(_) => {
+            Ok(Probe::Unreachable) | Err(_) => {
                 // Connection refused is expected while backend is starting
             }
         }
 
-        tokio::time::sleep(interval).await;
+        let Some(delay) = attempts.next_delay() else {
+            return Err(probe.timeout_error(format!(
+                "Backend health check timed out after {}ms ({} attempts)",
+                attempts.elapsed().as_millis(),
+                attempts.failures()
+            )));
+        };
+        tokio::time::sleep(delay).await;
     }
 }
+
+/// The outer error is a bad config; the inner one means the system HTTP
+/// stack couldn't produce a client.
+fn http_client(
+    settings: &PollSettings,
+    check: &HealthCheckConfig,
+) -> Result<Result<reqwest::Client, String>, String> {
+    let headers = check.header_map()?;
+    Ok(proxy::client_builder()
+        .connect_timeout(Duration::from_millis(settings.connect_timeout_ms))
+        .timeout(Duration::from_millis(settings.connect_timeout_ms + 1000))
+        .default_headers(headers)
+        .danger_accept_invalid_certs(check.danger_accept_invalid_certs)
+        .build()
+        .map_err(|e| e.to_string()))
+}
+
+struct TcpProbe {
+    address: String,
+}
+
+impl HealthProbe for TcpProbe {
+    fn check<'a>(&'a self, _trace_id: Option<&'a str>) -> ProbeFuture<'a> {
+        Box::pin(async move {
+            match TcpStream::connect(self.address.as_str()).await {
+                Ok(_) => Probe::Healthy,
+                Err(_) => Probe::Unreachable,
+            }
+        })
+    }
+}
+
+/// `RawHttp` is the fallback when reqwest can't build a client (usually
+/// broken system TLS or proxy setup): a bare HTTP/1.1 GET over TCP, which is
+/// all a loopback health check needs.
+enum HttpTransport {
+    Client(reqwest::Client),
+    RawHttp { reason: String },
+}
+
+struct HttpProbe {
+    url: reqwest::Url,
+    status: Option<u16>,
+    check: HealthCheckConfig,
+    transport: HttpTransport,
+}
+
+impl HttpProbe {
+    fn new(
+        url: &str,
+        status: Option<u16>,
+        check: &HealthCheckConfig,
+        settings: &PollSettings,
+    ) -> Result<Self, String> {
+        let url = check.url(url)?;
+        let transport = match http_client(settings, check)? {
+            Ok(client) => HttpTransport::Client(client),
+            Err(reason) => {
+                eprintln!(
+                    "[tauri] Failed to create HTTP client ({}); falling back to raw HTTP probes",
+                    reason
+                );
+                if url.scheme() != "http" {
+                    return Err(http_stack_error(
+                        &reason,
+                        &format!("The fallback probe can't check {} without TLS", url),
+                    ));
+                }
+                HttpTransport::RawHttp { reason }
+            }
+        };
+        Ok(Self {
+            url,
+            status,
+            check: check.clone(),
+            transport,
+        })
+    }
+}
+
+impl HealthProbe for HttpProbe {
+    fn check<'a>(&'a self, trace_id: Option<&'a str>) -> ProbeFuture<'a> {
+        Box::pin(async move {
+            let response = match &self.transport {
+                HttpTransport::Client(client) => {
+                    let mut request = client.get(self.url.clone());
+                    if let Some(id) = trace_id {
+                        request = request.header(TRACE_HEADER, id);
+                    }
+                    match request.send().await {
+                        Ok(resp) => {
+                            let status = resp.status().as_u16();
+                            let body = match self.check.body_contains {
+                                Some(_) => resp.text().await.unwrap_or_default(),
+                                None => String::new(),
+                            };
+                            Some((status, body))
+                        }
+                        Err(_) => None,
+                    }
+                }
+                HttpTransport::RawHttp { .. } => {
+                    raw_get(&self.url, &self.check.headers, trace_id).await
+                }
+            };
+            match response {
+                Some((status, body)) if self.check.status_matches(status, self.status) => {
+                    match self.check.body_matches(&body) {
+                        Ok(()) => Probe::Healthy,
+                        Err(detail) => Probe::Unhealthy(detail),
+                    }
+                }
+                Some((status, _)) => Probe::Unhealthy(format!("status {}", status)),
+                None => Probe::Unreachable,
+            }
+        })
+    }
+
+    fn timeout_error(&self, error: String) -> String {
+        match &self.transport {
+            HttpTransport::Client(_) => error,
+            HttpTransport::RawHttp { reason, .. } => http_stack_error(reason, &error),
+        }
+    }
+}
+
+struct HttpJsonProbe {
+    client: reqwest::Client,
+    url: reqwest::Url,
+    pointer: String,
+    equals: Value,
+    check: HealthCheckConfig,
+}
+
+impl HealthProbe for HttpJsonProbe {
+    fn check<'a>(&'a self, trace_id: Option<&'a str>) -> ProbeFuture<'a> {
+        Box::pin(async move {
+            let mut request = self.client.get(self.url.clone());
+            if let Some(id) = trace_id {
+                request = request.header(TRACE_HEADER, id);
+            }
+            let resp = match request.send().await {
+                Ok(resp) if self.check.status_matches(resp.status().as_u16(), None) => resp,
+                Ok(resp) => return Probe::Unhealthy(format!("status {}", resp.status())),
+                Err(_) => return Probe::Unreachable,
+            };
+            let body = resp.text().await.unwrap_or_default();
+            if let Err(detail) = self.check.body_matches(&body) {
+                return Probe::Unhealthy(detail);
+            }
+            match serde_json::from_str::<Value>(&body) {
+                Ok(body) => match body.pointer(&self.pointer) {
+                    Some(value) if *value == self.equals => Probe::Healthy,
+                    Some(value) => Probe::Unhealthy(format!("{} = {}", self.pointer, value)),
+                    None => Probe::Unhealthy(format!("no {} in response", self.pointer)),
+                },
+                Err(e) => Probe::Unhealthy(format!("invalid JSON ({})", e)),
+            }
+        })
+    }
+}
+
+struct SocketProbe {
+    socket: PathBuf,
+    path: String,
+    status: Option<u16>,
+}
+
+impl HealthProbe for SocketProbe {
+    fn check<'a>(&'a self, trace_id: Option<&'a str>) -> ProbeFuture<'a> {
+        Box::pin(async move {
+            let mut request = tauri::http::Request::get(self.path.as_str());
+            if let Some(id) = trace_id {
+                request = request.header(TRACE_HEADER, id);
+            }
+            let Ok(request) = request.body(Vec::new()) else {
+                return Probe::Unhealthy(format!("invalid health check path {}", self.path));
+            };
+            match crate::bridge::send(&self.socket, &request).await {
+                Some(resp) if expected_status(resp.status().as_u16(), self.status) => {
+                    Probe::Healthy
+                }
+                Some(resp) => Probe::Unhealthy(format!("status {}", resp.status())),
+                None => Probe::Unreachable,
+            }
+        })
+    }
+}
+
+struct CommandProbe {
+    command: String,
+    args: Vec<String>,
+    cwd: Option<PathBuf>,
+}
+
+impl HealthProbe for CommandProbe {
+    fn check<'a>(&'a self, _trace_id: Option<&'a str>) -> ProbeFuture<'a> {
+        Box::pin(async move {
+            let mut command = tokio::process::Command::new(&self.command);
+            command
+                .args(&self.args)
+                .stdin(std::process::Stdio::null())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                // Polling gives up on slow checks; don't leave them behind
+                .kill_on_drop(true);
+            if let Some(ref cwd) = self.cwd {
+                command.current_dir(cwd);
+            }
+            match command.status().await {
+                Ok(status) if status.success() => Probe::Healthy,
+                Ok(status) => Probe::Unhealthy(format!("{} exited with {}", self.command, status)),
+                Err(e) => Probe::Unhealthy(format!("failed to run {}: {}", self.command, e)),
+            }
+        })
+    }
+}
+
+struct HeartbeatProbe {
+    path: PathBuf,
+    max_age: Duration,
+}
+
+impl HealthProbe for HeartbeatProbe {
+    fn check<'a>(&'a self, _trace_id: Option<&'a str>) -> ProbeFuture<'a> {
+        Box::pin(async move {
+            let Ok(modified) = tokio::fs::metadata(&self.path)
+                .await
+                .and_then(|m| m.modified())
+            else {
+                return Probe::Unreachable;
+            };
+            // A timestamp in the future counts as fresh
+            let age = SystemTime::now()
+                .duration_since(modified)
+                .unwrap_or_default();
+            if age <= self.max_age {
+                Probe::Healthy
+            } else {
+                Probe::Unhealthy(format!("heartbeat {}ms old", age.as_millis()))
+            }
+        })
+    }
+}
+
+/// Largest response read by `raw_get`; health answers are tiny.
+const RAW_RESPONSE_LIMIT: u64 = 64 * 1024;
+
+/// Sends a plain GET and returns the response status code and body, if a
+/// response came back.
+async fn raw_get(
+    url: &reqwest::Url,
+    headers: &BTreeMap<String, String>,
+    trace_id: Option<&str>,
+) -> Option<(u16, String)> {
+    let host = url.host_str()?;
+    let port = url.port_or_known_default()?;
+    let mut stream = TcpStream::connect((host, port)).await.ok()?;
+
+    let target = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+    // HTTP/1.0 so the body is never chunked and ends when the server closes
+    let mut request = format!(
+        "GET {} HTTP/1.0\r\nHost: {}:{}\r\nConnection: close\r\n",
+        target, host, port
+    );
+    // Names and values were validated when the probe was built
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    if let Some(id) = trace_id {
+        request.push_str(&format!("{}: {}\r\n", TRACE_HEADER, id));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await.ok()?;
+
+    let mut response = Vec::new();
+    stream
+        .take(RAW_RESPONSE_LIMIT)
+        .read_to_end(&mut response)
+        .await
+        .ok()?;
+    let response = String::from_utf8_lossy(&response);
+    // "HTTP/1.1 200 OK"
+    let status = response.split_whitespace().nth(1)?.parse().ok()?;
+    let body = response
+        .split_once("\r\n\r\n")
+        .map(|(_, body)| body.to_string())
+        .unwrap_or_default();
+    Some((status, body))
+}
+
+fn http_stack_error(reason: &str, detail: &str) -> String {
+    format!(
+        "System HTTP stack problem: the HTTP client could not be created ({}). {}. \
+         Check that the system TLS certificates and proxy settings (HTTP_PROXY, HTTPS_PROXY, \
+         ALL_PROXY) are valid, reinstall the OS certificate bundle if it is damaged, \
+         then restart Local Agent.",
+        reason, detail
+    )
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    pub post_backend_start: Vec<HookScript>,
+    pub pre_shutdown: Vec<HookScript>,
+    pub post_crash: Vec<HookScript>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookScript {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum HookPoint {
+    PostBackendStart,
+    PreShutdown,
+    PostCrash,
+}
+
+impl HookPoint {
+    pub fn name(self) -> &'static str {
+        match self {
+            HookPoint::PostBackendStart => "post-backend-start",
+            HookPoint::PreShutdown => "pre-shutdown",
+            HookPoint::PostCrash => "post-crash",
+        }
+    }
+}
+
+impl HooksConfig {
+    fn scripts(&self, point: HookPoint) -> &[HookScript] {
+        match point {
+            HookPoint::PostBackendStart => &self.post_backend_start,
+            HookPoint::PreShutdown => &self.pre_shutdown,
+            HookPoint::PostCrash => &self.post_crash,
+        }
+    }
+}
+
+/// Runs every script registered for `point` in order, blocking until each
+/// exits or hits its timeout. Output is mirrored into the shell log.
+pub fn run(config: &HooksConfig, point: HookPoint) {
+    for script in config.scripts(point) {
+        if let Err(e) = run_script(script, point) {
+            eprintln!(
+                "[tauri] [hook:{}] {} failed: {}",
+                point.name(),
+                script.path.display(),
+                e
+            );
+        }
+    }
+}
+
+fn pipe_to_log<R: Read + Send + 'static>(
+    reader: Option<R>,
+    tag: String,
+) -> Option<thread::JoinHandle<()>> {
+    reader.map(|reader| {
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines().map_while(Result::ok) {
+                println!("{} {}", tag, line);
+            }
+        })
+    })
+}
+
+fn run_script(script: &HookScript, point: HookPoint) -> Result<(), String> {
+    println!(
+        "[tauri] [hook:{}] Running {}",
+        point.name(),
+        script.path.display()
+    );
+
+    let mut child = Command::new(&script.path)
+        .args(&script.args)
+        .env("LOCAL_AGENT_HOOK", point.name())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to start: {}", e))?;
+
+    let tag = format!("[hook:{}]", point.name());
+    let readers = [
+        pipe_to_log(child.stdout.take(), tag.clone()),
+        pipe_to_log(child.stderr.take(), format!("{}:err", tag)),
+    ];
+
+    let timeout = Duration::from_secs(script.timeout_secs);
+    let start = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if start.elapsed() > timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {}s", script.timeout_secs));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(50)),
+            Err(e) => return Err(format!("wait failed: {}", e)),
+        }
+    };
+
+    for reader in readers.into_iter().flatten() {
+        let _ = reader.join();
+    }
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("exited with {}", status))
+    }
+}
@@ -0,0 +1,179 @@
+//! Power saving for a model-heavy backend: with `enabled` on, once every
+//! app window is hidden or minimized and nothing has gone through the
+//! backend for `suspend_after_minutes`, the backend is stopped gracefully
+//! and the state becomes `suspended`. Opening a window (or a request over
+//! the bridge) starts it again behind the Reconnecting page, which returns
+//! to the app once it is healthy.
+//!
+//! Requests over TCP don't pass through the shell, so backend output counts
+//! as activity too, and a backend that reports work in flight is left
+//! running.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::hooks::HookPoint;
+use crate::state::BackendState;
+use crate::{
+    backend_poll_settings, quit, set_backend_state, show_backend_error, spawn_hooks,
+    wait_for_backend, windows, AppState, BACKEND,
+};
+
+const TICK: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdleConfig {
+    pub enabled: bool,
+    pub suspend_after_minutes: u64,
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            suspend_after_minutes: 15,
+        }
+    }
+}
+
+/// When the backend was last in use, and whether it is suspended.
+pub struct IdleTracker {
+    last_active: Mutex<Instant>,
+    /// The backend output line last seen.
+    last_seq: Mutex<u64>,
+    suspended: AtomicBool,
+}
+
+impl Default for IdleTracker {
+    fn default() -> Self {
+        Self {
+            last_active: Mutex::new(Instant::now()),
+            last_seq: Mutex::new(0),
+            suspended: AtomicBool::new(false),
+        }
+    }
+}
+
+impl IdleTracker {
+    pub fn touch(&self) {
+        *self.last_active.lock().unwrap() = Instant::now();
+    }
+
+    /// Counts new sidecar output, up to `seq`, as activity.
+    fn saw_output(&self, seq: u64) {
+        let mut last_seq = self.last_seq.lock().unwrap();
+        if seq != *last_seq {
+            *last_seq = seq;
+            self.touch();
+        }
+    }
+
+    fn idle_for(&self) -> Duration {
+        self.last_active.lock().unwrap().elapsed()
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.suspended.load(Ordering::SeqCst)
+    }
+}
+
+/// Whether any app window is on screen.
+fn window_shown(app: &AppHandle) -> bool {
+    app.webview_windows()
+        .iter()
+        .filter(|(label, _)| windows::is_app_window(label))
+        .any(|(_, window)| {
+            window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false)
+        })
+}
+
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(TICK).await;
+            let state: State<AppState> = app.state();
+            let config = state.config.lock().unwrap().idle.clone();
+            state.idle.saw_output(state.logs.latest_seq());
+            if !config.enabled || state.idle.is_suspended() || window_shown(&app) {
+                state.idle.touch();
+                continue;
+            }
+            let after = Duration::from_secs(config.suspend_after_minutes.max(1) * 60);
+            let running = state
+                .sidecars
+                .get(BACKEND)
+                .is_some_and(|backend| backend.is_running());
+            if state.idle.idle_for() < after
+                || !running
+                || state.backend_state.current().state != BackendState::Healthy
+            {
+                continue;
+            }
+            if quit::activity(&app)
+                .await
+                .is_some_and(|activity| activity.busy)
+            {
+                state.idle.touch();
+                continue;
+            }
+            suspend(&app, config.suspend_after_minutes).await;
+        }
+    });
+}
+
+async fn suspend(app: &AppHandle, minutes: u64) {
+    let state: State<AppState> = app.state();
+    let Some(backend) = state.sidecars.get(BACKEND) else {
+        return;
+    };
+    println!(
+        "[tauri] No activity for {} minutes with every window hidden; suspending the backend",
+        minutes
+    );
+    state.idle.suspended.store(true, Ordering::SeqCst);
+    set_backend_state(
+        app,
+        BackendState::Suspended,
+        Some(&format!("idle for {} minutes", minutes)),
+    );
+    let _ = tauri::async_runtime::spawn_blocking(move || backend.shutdown()).await;
+}
+
+/// Starts a suspended backend again; nothing happens when it isn't.
+pub fn wake(app: &AppHandle) {
+    let state: State<AppState> = app.state();
+    state.idle.touch();
+    if !state.idle.suspended.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let Some(backend) = state.sidecars.get(BACKEND) else {
+        return;
+    };
+    println!("[tauri] Waking the suspended backend");
+    set_backend_state(app, BackendState::Restarting, Some("waking from idle"));
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let spawned = tauri::async_runtime::spawn_blocking(move || backend.spawn_with_retry())
+            .await
+            .map_err(|e| format!("Failed to start backend: {}", e))
+            .and_then(|result| result);
+        let healthy = match spawned {
+            Ok(()) => wait_for_backend(&app, &backend_poll_settings(&app), None).await,
+            Err(e) => Err(e),
+        };
+        match healthy {
+            Ok(()) => {
+                set_backend_state(&app, BackendState::Healthy, None);
+                spawn_hooks(&app, HookPoint::PostBackendStart);
+            }
+            Err(e) => {
+                set_backend_state(&app, BackendState::Failed, Some(&e));
+                show_backend_error(&app, &e);
+            }
+        }
+    });
+}
@@ -0,0 +1,195 @@
+//! Files dropped on an app window, for the agent to ingest. Each drop is
+//! checked against the allowed types and size, copied into its own folder
+//! under the staging directory so the backend works on a copy the user
+//! can't move or delete mid-ingest, and announced as a "files-dropped"
+//! event. Staged drops older than a day are removed at startup.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::AppHandle;
+
+use crate::{emit_event, fileopen, paths};
+
+pub const EVENT: &str = "files-dropped";
+/// Staged drops are kept this long for the backend to finish with them.
+const STAGING_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IngestConfig {
+    /// Lowercase extensions accepted; others are rejected.
+    pub extensions: Vec<String>,
+    pub max_file_mb: u64,
+    /// Files accepted from one drop.
+    pub max_files: usize,
+}
+
+impl Default for IngestConfig {
+    fn default() -> Self {
+        Self {
+            extensions: fileopen::EXTENSIONS
+                .iter()
+                .chain(&["csv", "json", "html"])
+                .map(|ext| ext.to_string())
+                .collect(),
+            max_file_mb: 100,
+            max_files: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StagedFile {
+    pub name: String,
+    pub extension: String,
+    pub size: u64,
+    /// The copy the backend should read.
+    pub path: PathBuf,
+    /// Where the user dropped it from.
+    pub source: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RejectedFile {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FilesDropped {
+    pub files: Vec<StagedFile>,
+    pub rejected: Vec<RejectedFile>,
+}
+
+pub fn staging_dir() -> PathBuf {
+    match paths::portable_root() {
+        Some(root) => root.join("staging"),
+        None => dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("local-agent")
+            .join("staging"),
+    }
+}
+
+/// Why `path` can't be ingested, if it can't.
+fn check(config: &IngestConfig, path: &Path) -> Result<(String, u64), String> {
+    let metadata = fs::metadata(path).map_err(|e| format!("Cannot read it: {}", e))?;
+    if metadata.is_dir() {
+        return Err("Folders can't be dropped; drop the files inside".to_string());
+    }
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    if !config.extensions.contains(&extension) {
+        return Err(format!("Unsupported file type .{}", extension));
+    }
+    if metadata.len() > config.max_file_mb * 1024 * 1024 {
+        return Err(format!("Larger than {} MB", config.max_file_mb));
+    }
+    Ok((extension, metadata.len()))
+}
+
+/// Checks and copies `paths` into a new folder under `staging`.
+pub fn stage(config: &IngestConfig, staging: &Path, paths: &[PathBuf]) -> FilesDropped {
+    let drop_dir = staging.join(format!(
+        "{}-{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        &uuid::Uuid::new_v4().simple().to_string()[..8]
+    ));
+    let mut dropped = FilesDropped::default();
+    for path in paths {
+        let staged = check(config, path).and_then(|(extension, size)| {
+            if dropped.files.len() >= config.max_files {
+                return Err(format!("More than {} files in one drop", config.max_files));
+            }
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            // Two dropped files can share a name
+            let target = (0..)
+                .map(|n| match n {
+                    0 => drop_dir.join(&name),
+                    n => drop_dir.join(format!("{}-{}", n, name)),
+                })
+                .find(|target| !target.exists())
+                .unwrap();
+            fs::create_dir_all(&drop_dir)
+                .and_then(|()| fs::copy(path, &target))
+                .map_err(|e| format!("Failed to copy it: {}", e))?;
+            Ok(StagedFile {
+                name,
+                extension,
+                size,
+                path: target,
+                source: path.clone(),
+            })
+        });
+        match staged {
+            Ok(file) => dropped.files.push(file),
+            Err(reason) => dropped.rejected.push(RejectedFile {
+                path: path.clone(),
+                reason,
+            }),
+        }
+    }
+    dropped
+}
+
+/// Stages a drop off the main thread and announces it.
+pub fn dropped(app: &AppHandle, config: IngestConfig, paths: Vec<PathBuf>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let staged =
+            tauri::async_runtime::spawn_blocking(move || stage(&config, &staging_dir(), &paths))
+                .await;
+        let dropped = match staged {
+            Ok(dropped) => dropped,
+            Err(e) => {
+                eprintln!("[tauri] Failed to stage dropped files: {}", e);
+                return;
+            }
+        };
+        for rejected in &dropped.rejected {
+            eprintln!(
+                "[tauri] Not ingesting {}: {}",
+                rejected.path.display(),
+                rejected.reason
+            );
+        }
+        println!(
+            "[tauri] Staged {} dropped file(s) for ingestion",
+            dropped.files.len()
+        );
+        emit_event(&app, EVENT, dropped);
+    });
+}
+
+/// Removes staged drops past `STAGING_RETENTION`.
+pub fn clean_staging() {
+    let Ok(entries) = fs::read_dir(staging_dir()) else {
+        return;
+    };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let expired = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age > STAGING_RETENTION);
+        if expired {
+            if let Err(e) = fs::remove_dir_all(entry.path()) {
+                eprintln!(
+                    "[tauri] Failed to remove staged files {}: {}",
+                    entry.path().display(),
+                    e
+                );
+            }
+        }
+    }
+}
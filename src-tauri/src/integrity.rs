@@ -0,0 +1,119 @@
+//! Checks the bundled backend files against `integrity.json`, a manifest of
+//! SHA-256 checksums written at build time by
+//! `scripts/integrity-manifest.js`. A failed update or an antivirus
+//! quarantining a file otherwise surfaces as a confusing spawn failure or a
+//! backend that crashes on some request.
+//!
+//! Builds without a manifest (dev, source checkouts) skip the check.
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+pub const MANIFEST: &str = "integrity.json";
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    /// Path relative to the manifest, with `/` separators → lowercase hex
+    /// SHA-256.
+    files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IntegrityProblem {
+    pub root: PathBuf,
+    pub missing: Vec<String>,
+    /// Present but with the wrong checksum, or unreadable.
+    pub corrupted: Vec<String>,
+}
+
+impl IntegrityProblem {
+    pub fn title(&self) -> &'static str {
+        "Local Agent installation is damaged"
+    }
+
+    pub fn message(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.missing.is_empty() {
+            parts.push(format!("missing: {}", self.missing.join(", ")));
+        }
+        if !self.corrupted.is_empty() {
+            parts.push(format!(
+                "changed or unreadable: {}",
+                self.corrupted.join(", ")
+            ));
+        }
+        format!(
+            "Some backend files in {} don't match this release ({}). An interrupted update or antivirus software usually causes this.",
+            self.root.display(),
+            parts.join("; ")
+        )
+    }
+
+    pub fn remediation(&self) -> Vec<String> {
+        vec![
+            "Reinstall Local Agent, or run the installer again and choose Repair.".to_string(),
+            "If antivirus software quarantined a file, restore it and allow Local Agent's install folder."
+                .to_string(),
+            "Your chats and settings are stored elsewhere and are not affected.".to_string(),
+        ]
+    }
+}
+
+/// Verifies every file listed in `root/integrity.json`. Returns how many
+/// were checked, 0 when there is no manifest.
+pub fn verify(root: &Path) -> Result<usize, IntegrityProblem> {
+    let damaged = |missing: Vec<String>, corrupted: Vec<String>| IntegrityProblem {
+        root: root.to_path_buf(),
+        missing,
+        corrupted,
+    };
+    let contents = match fs::read_to_string(root.join(MANIFEST)) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(_) => return Err(damaged(Vec::new(), vec![MANIFEST.to_string()])),
+    };
+    let manifest: Manifest = serde_json::from_str(&contents)
+        .map_err(|_| damaged(Vec::new(), vec![MANIFEST.to_string()]))?;
+
+    let (mut missing, mut corrupted) = (Vec::new(), Vec::new());
+    for (name, expected) in &manifest.files {
+        let Some(path) = resolve(root, name) else {
+            corrupted.push(name.clone());
+            continue;
+        };
+        match sha256_file(&path) {
+            Ok(actual) if actual.eq_ignore_ascii_case(expected) => {}
+            Ok(_) => corrupted.push(name.clone()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => missing.push(name.clone()),
+            Err(_) => corrupted.push(name.clone()),
+        }
+    }
+    if missing.is_empty() && corrupted.is_empty() {
+        Ok(manifest.files.len())
+    } else {
+        Err(damaged(missing, corrupted))
+    }
+}
+
+/// `name` under `root`; `None` for paths that would leave it.
+fn resolve(root: &Path, name: &str) -> Option<PathBuf> {
+    let relative = Path::new(name);
+    relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+        .then(|| root.join(relative))
+}
+
+fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
@@ -0,0 +1,234 @@
+//! "Report Issue": a GitHub issue pre-filled with what a maintainer asks for
+//! first: versions, OS, the backend's state, the last error and the end of
+//! the backend log, with the home directory and anything that looks like a
+//! credential removed. The report is also saved to the logs folder; when it
+//! is too long for a link, the issue is cut down and asks for that file.
+
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::appinfo::AppInfo;
+use crate::state::BackendStatus;
+use crate::updates::REPOSITORY_URL;
+
+/// Backend log lines included in the report.
+pub const LOG_LINES: usize = 50;
+/// Longer links are cut off by browsers or refused by GitHub.
+const MAX_URL_LEN: usize = 8000;
+pub const REDACTED: &str = "[redacted]";
+/// Followed by `:` or `=` and a value that is hidden.
+const SECRET_KEYS: &[&str] = &[
+    "api_key",
+    "api-key",
+    "apikey",
+    "token",
+    "secret",
+    "password",
+    "authorization",
+];
+/// Prefixes of well-known API key formats.
+const KEY_PREFIXES: &[&str] = &["sk-", "ghp_", "github_pat_", "xoxb-", "xoxp-", "hf_"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueTarget {
+    /// Open a new GitHub issue with the report filled in.
+    #[default]
+    Github,
+    /// Only save the report and show it in the file manager.
+    File,
+}
+
+pub struct IssueReport {
+    pub info: AppInfo,
+    pub backend: BackendStatus,
+    pub last_error: Option<String>,
+    /// Oldest first, already redacted.
+    pub log_lines: Vec<String>,
+}
+
+impl IssueReport {
+    /// The report as Markdown, with the last `log_lines` of the log and a
+    /// pointer to `saved` when lines had to be left out.
+    fn body(&self, log_lines: &[String], saved: Option<&Path>) -> String {
+        let info = &self.info;
+        let unknown = |v: &Option<String>| v.clone().unwrap_or_else(|| "unknown".to_string());
+        let mut body = format!(
+            "**What happened?**\n\n\n**Steps to reproduce**\n\n\n\
+             ---\n\
+             **Version:** {} (Tauri {})\n\
+             **Backend:** {} on Node.js {}\n\
+             **OS:** {} {} ({})\n\
+             **Backend state:** {}\n",
+            info.app_version,
+            info.tauri_version,
+            unknown(&info.backend_version),
+            unknown(&info.node_version),
+            info.os,
+            unknown(&info.os_version),
+            info.arch,
+            self.backend.state.label(),
+        );
+        if let Some(ref error) = self.last_error {
+            body.push_str(&format!("**Last error:** `{}`\n", error.replace('`', "'")));
+        }
+        if let Some(saved) = saved {
+            body.push_str(&format!(
+                "\n_Only the last {} log lines fit here; please attach {} from the logs folder._\n",
+                log_lines.len(),
+                saved
+                    .file_name()
+                    .map(|name| name.to_string_lossy())
+                    .unwrap_or_default()
+            ));
+        }
+        if !log_lines.is_empty() {
+            body.push_str("\n<details><summary>Backend log</summary>\n\n```\n");
+            for line in log_lines {
+                body.push_str(&line.replace("```", "'''"));
+                body.push('\n');
+            }
+            body.push_str("```\n</details>\n");
+        }
+        body
+    }
+
+    pub fn markdown(&self) -> String {
+        self.body(&self.log_lines, None)
+    }
+
+    /// A new-issue link with as much of the log as fits; `saved` is where
+    /// the full report was written.
+    pub fn url(&self, saved: &Path) -> Result<String, String> {
+        let mut skip = 0;
+        loop {
+            let lines = &self.log_lines[skip..];
+            let body = self.body(lines, (skip > 0).then_some(saved));
+            let url = reqwest::Url::parse_with_params(
+                &format!("{}/issues/new", REPOSITORY_URL),
+                [("body", body.as_str())],
+            )
+            .map_err(|e| format!("Failed to build issue link: {}", e))?;
+            if url.as_str().len() <= MAX_URL_LEN || lines.is_empty() {
+                return Ok(url.into());
+            }
+            skip += lines.len().div_ceil(4);
+        }
+    }
+
+    /// Writes the full report to `dir` and returns its path.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, String> {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let path = dir.join(format!(
+            "local-agent-issue-{}.md",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        fs::write(&path, self.markdown())
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+}
+
+/// `text` with the home directory shortened to `~` and credentials hidden.
+pub fn redact(text: &str) -> String {
+    let mut text = text.to_string();
+    if let Some(home) = dirs::home_dir().map(|h| h.to_string_lossy().into_owned()) {
+        if home.len() > 1 {
+            text = text.replace(&home, "~");
+            // Paths quoted the other way round on Windows, e.g. in JSON
+            text = text.replace(&home.replace('\\', "/"), "~");
+        }
+    }
+    redact_secrets(&text)
+}
+
+/// `text` with bearer tokens, `key=value` credentials and API-key-shaped
+/// words hidden.
+pub fn redact_secrets(text: &str) -> String {
+    let mut text = redact_bearer(text);
+    for key in SECRET_KEYS {
+        text = redact_assignments(&text, key);
+    }
+    redact_key_formats(&text)
+}
+
+fn ends_value(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '"' | '\'' | ',' | ';' | '&' | '}' | ')' | ']')
+}
+
+/// Replaces the value after `start` (skipping quotes and spaces) up to the
+/// next delimiter; returns where scanning continues.
+fn replace_value(text: &str, out: &mut String, start: usize) -> usize {
+    let rest = &text[start..];
+    let lead = rest.len() - rest.trim_start_matches(['"', '\'', ' ']).len();
+    out.push_str(&rest[..lead]);
+    let value = &rest[lead..];
+    let len = value.find(ends_value).unwrap_or(value.len());
+    if len > 0 {
+        out.push_str(REDACTED);
+    }
+    start + lead + len
+}
+
+/// `Bearer <token>` → `Bearer [redacted]`.
+fn redact_bearer(text: &str) -> String {
+    let lower = text.to_ascii_lowercase();
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    while let Some(found) = lower[pos..].find("bearer ") {
+        let end = pos + found + "bearer ".len();
+        out.push_str(&text[pos..end]);
+        pos = replace_value(text, &mut out, end);
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
+/// `key: value`, `key=value` and `"key": "value"` → the value hidden.
+fn redact_assignments(text: &str, key: &str) -> String {
+    let lower = text.to_ascii_lowercase();
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    while let Some(found) = lower[pos..].find(key) {
+        let key_end = pos + found + key.len();
+        // The key may end a longer name (`TOOL_API_KEY`, `x-api-key`), but
+        // its value has to follow right after
+        let after = &text[key_end..];
+        let separator = after.trim_start_matches(['"', '\'', ' ']);
+        let value_start = match separator.chars().next() {
+            Some(':' | '=') => key_end + (after.len() - separator.len()) + 1,
+            _ => {
+                out.push_str(&text[pos..key_end]);
+                pos = key_end;
+                continue;
+            }
+        };
+        out.push_str(&text[pos..value_start]);
+        pos = replace_value(text, &mut out, value_start);
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
+/// Words shaped like known API keys (`sk-…`, `ghp_…`).
+fn redact_key_formats(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut word = String::new();
+    let flush = |word: &mut String, out: &mut String| {
+        let is_key = word.len() >= 20 && KEY_PREFIXES.iter().any(|prefix| word.starts_with(prefix));
+        out.push_str(if is_key { REDACTED } else { word });
+        word.clear();
+    };
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut out);
+            out.push(c);
+        }
+    }
+    flush(&mut word, &mut out);
+    out
+}
@@ -1,145 +1,3066 @@
+mod about;
+mod api;
+mod appinfo;
+mod autostart;
+mod backoff;
+mod backup;
+mod bench;
+mod binaries;
+mod bridge;
+mod cli;
+mod compat;
+mod config;
+mod control;
+mod crash;
+mod crashes;
+mod deeplink;
+mod deps;
+mod diagnosis;
+mod diskspace;
+mod environment;
 mod error_page;
+mod events;
+mod exposure;
+mod fileopen;
+mod hang;
+mod hardware;
 mod health;
+mod hooks;
+mod idle;
+mod ingest;
+mod integrity;
+mod issue;
+mod lifecycle;
+mod logfile;
+mod logstream;
+mod logviewer;
+mod mdns;
+mod menu;
+mod metrics;
+mod models;
+mod monitor;
+mod navigation;
+mod network;
+mod node;
+mod offline;
+mod ollama;
+mod pages;
+mod paths;
+mod power;
+mod preferences;
+mod preflight;
+mod profiles;
+mod proxy;
+mod quit;
+mod readiness;
+mod reaper;
+mod reconnect;
+mod redact;
+mod registry;
+mod remote;
+mod restarts;
+mod scheduler;
+mod screenshot;
+mod secrets;
+mod selftest;
+mod settings;
+mod shutdown;
 mod sidecar;
+mod splash;
+mod startup;
+mod state;
+mod syslog;
+mod tail;
+mod template;
+mod terminal;
+mod trace;
+mod tray;
+mod uninstall;
+mod updates;
+mod upgrade;
+mod wake;
+mod windows;
+mod workspace;
 
-use sidecar::SidecarManager;
-use std::path::PathBuf;
-use std::sync::Mutex;
-use tauri::{Manager, State};
+/// The sidecar internals, for the integration tests in `tests/`.
+#[cfg(feature = "mock-sidecar")]
+pub mod testing {
+    pub use crate::backoff::Backoff;
+    pub use crate::health::{poll_health, ProbeSpec};
+    pub use crate::logstream::LogBatcher;
+    pub use crate::registry::{SidecarExit, SidecarRegistry};
+    pub use crate::sidecar::{RestartPolicy, SidecarManager, SidecarSpec, StopOutcome, MOCK_ENV};
+}
+
+use config::ShellConfig;
+use registry::{SidecarRegistry, BACKEND};
+use sidecar::{SidecarManager, SidecarSpec, TemplateVars};
+use state::BackendState;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{Emitter, Manager, State};
+use tauri_plugin_opener::OpenerExt;
+
+struct AppState {
+    sidecars: Arc<SidecarRegistry>,
+    config: Mutex<ShellConfig>,
+    remote: remote::RemoteState,
+    mdns: mdns::MdnsState,
+    traces: Arc<trace::TraceStore>,
+    terminal: terminal::TerminalState,
+    monitor: Arc<monitor::ResourceMonitor>,
+    metrics: metrics::MetricsHistory,
+    boot: Mutex<startup::BootTimer>,
+    logs: Arc<logstream::LogBatcher>,
+    events: events::EventLog,
+    deep_links: deeplink::DeepLinkRouter,
+    backend_state: state::BackendStateMachine,
+    reconnect: reconnect::Interstitial,
+    /// Restarts, profile switches and upgrades take turns.
+    lifecycle: lifecycle::Lifecycle,
+    navigation: navigation::Navigator,
+    health_cancels: health::HealthCancels,
+    restarts: restarts::RestartHistory,
+    maintenance: scheduler::Scheduler,
+    /// The backend an upgrade replaced, kept for a rollback.
+    previous_backend: upgrade::Previous,
+    streams: bridge::StreamTracker,
+    windows: windows::WindowTracker,
+    /// Services were stopped for exit.
+    shutdown: shutdown::ShutdownOnce,
+    quit: quit::QuitGuard,
+    tails: tail::Tails,
+    workspace_watches: workspace::Watches,
+    screenshot: screenshot::ScreenshotPrompt,
+    network: network::NetworkMonitor,
+    idle: idle::IdleTracker,
+}
+
+/// Recent output kept in a sidecar's crash snapshot.
+const CRASH_SNAPSHOT_LINES: usize = 200;
+
+/// How often due maintenance tasks are looked for.
+const MAINTENANCE_TICK: Duration = Duration::from_secs(60);
+
+fn is_dev() -> bool {
+    cfg!(debug_assertions)
+}
+
+/// Where the main window loads the UI from: the backend in production, the
+/// Vite dev server in dev.
+fn app_url(app: &tauri::AppHandle) -> String {
+    match app.config().build.dev_url {
+        Some(ref url) if is_dev() => url.to_string(),
+        _ => backend_url(app),
+    }
+}
+
+/// The backend on its configured port, the one it announced, or through the
+/// socket bridge; it also serves the UI in production.
+fn backend_url(app: &tauri::AppHandle) -> String {
+    if bridge::active().is_some() {
+        return bridge::app_url();
+    }
+    let state: State<AppState> = app.state();
+    // A port the backend announced wins over the one it was started with,
+    // which an upgrade may have moved off the configured one
+    let port = state
+        .sidecars
+        .get(BACKEND)
+        .and_then(|backend| backend.announced_port().or_else(|| backend.spec().port()))
+        .unwrap_or_else(|| state.config.lock().unwrap().backend_port());
+    format!("http://127.0.0.1:{}", port)
+}
+
+/// The repo root in dev: `tauri dev` runs the shell from `src-tauri`, while
+/// npm scripts need the directory holding package.json.
+fn dev_project_root() -> PathBuf {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    cwd.ancestors()
+        .find(|dir| dir.join("package.json").is_file())
+        .map(PathBuf::from)
+        .unwrap_or(cwd)
+}
+
+/// Where the backend runs from: a directory the user chose (see `paths`),
+/// the repo in dev, or the bundled resources.
+fn project_root(app: &tauri::AppHandle) -> PathBuf {
+    let chosen = paths::project_root_override(&app.state::<AppState>().config.lock().unwrap());
+    if let Some((root, _)) = chosen {
+        return root;
+    }
+    if is_dev() {
+        return dev_project_root();
+    }
+    app.path()
+        .resource_dir()
+        .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
+}
+
+/// Values for the `${…}` variables in configured sidecars.
+fn template_vars(app: &tauri::AppHandle, project_root: &Path) -> TemplateVars {
+    let state: State<AppState> = app.state();
+    let (data_dir, port) = {
+        let config = state.config.lock().unwrap();
+        (config.backend_data_dir(), config.backend_port())
+    };
+    TemplateVars {
+        data_dir: data_dir.unwrap_or_else(|| project_root.to_path_buf()),
+        port,
+        resource_dir: app
+            .path()
+            .resource_dir()
+            .unwrap_or_else(|_| project_root.to_path_buf()),
+    }
+}
+
+/// Runs the user's hook scripts for `point` on a background thread.
+fn spawn_hooks(app: &tauri::AppHandle, point: hooks::HookPoint) {
+    let config = app.state::<AppState>().config.lock().unwrap().hooks.clone();
+    std::thread::spawn(move || hooks::run(&config, point));
+}
+
+/// Registers the additional sidecars declared in the shell config; they are
+/// started with the backend (see `deps`).
+fn register_extra_sidecars(app: &tauri::AppHandle) {
+    let vars = template_vars(app, &project_root(app));
+    let state: State<AppState> = app.state();
+    let config = state.config.lock().unwrap().clone();
+    for spec in config.sidecars.iter().cloned() {
+        if spec.name == BACKEND {
+            eprintln!("[tauri] Ignoring configured sidecar named {:?}", BACKEND);
+            continue;
+        }
+        let name = spec.name.clone();
+        let mut spec = match vars.expand_spec(spec) {
+            Ok(spec) => spec,
+            Err(e) => {
+                eprintln!("[tauri] Not starting sidecar {}: {}", name, e);
+                continue;
+            }
+        };
+        // After expansion, so a `${` in a secret is left alone
+        spec.env.extend(secrets::resolve_only(
+            &config,
+            config.active_profile(),
+            &spec.secrets,
+        ));
+        spec.inherit_env.extend(config.env.allow.iter().cloned());
+        for (name, value) in proxy::current().env() {
+            spec.env.entry(name).or_insert(value);
+        }
+        spec.show_console |= config.dev.show_console;
+        state
+            .sidecars
+            .insert(SidecarManager::new(spec).with_backoff(config.retry.spawn));
+    }
+}
+
+/// Emits `event` to every window and records it for `get_recent_events`.
+fn emit_event<S: serde::Serialize + Clone>(app: &tauri::AppHandle, event: &str, payload: S) {
+    match serde_json::to_value(&payload) {
+        Ok(value) => {
+            app.state::<AppState>().events.record(event, value);
+        }
+        Err(e) => eprintln!("[tauri] Failed to record {} event: {}", event, e),
+    }
+    let _ = app.emit(event, payload);
+}
+
+/// Events emitted after `since` (a `latest_seq` from an earlier call, or 0),
+/// so a reloaded webview can catch up instead of starting blind.
+#[tauri::command]
+fn get_recent_events(state: State<'_, AppState>, since: Option<u64>) -> events::EventReplay {
+    state.events.since(since.unwrap_or(0))
+}
+
+#[derive(Clone, serde::Serialize)]
+struct SidecarExitEvent {
+    name: String,
+    status: String,
+    restarting: bool,
+    /// Restarting too often; left stopped until restarted by hand.
+    throttled: bool,
+}
+
+fn start_sidecar_watchdog(app: &tauri::AppHandle) {
+    let app = app.clone();
+    let registry = app.state::<AppState>().sidecars.clone();
+    let throttle_app = app.clone();
+    registry.start_watchdog(
+        move |name| {
+            let state: State<AppState> = throttle_app.state();
+            let config = state.config.lock().unwrap().restarts.clone();
+            state.restarts.allows(name, &config)
+        },
+        move |exit| {
+            let state: State<AppState> = app.state();
+            let status = exit.status.to_string();
+            save_crash_snapshot(&app, exit);
+            if exit.restarting {
+                state
+                    .restarts
+                    .record(&exit.name, restarts::RestartReason::Crash, &status);
+                deps::restart_dependents(&app, &exit.name);
+            }
+            emit_event(
+                &app,
+                "sidecar-exited",
+                SidecarExitEvent {
+                    name: exit.name.clone(),
+                    status: status.clone(),
+                    restarting: exit.restarting,
+                    throttled: exit.throttled,
+                },
+            );
+            if exit.name == BACKEND {
+                let detail = format!("exited with {}", status);
+                if exit.restarting {
+                    set_backend_state(&app, BackendState::Restarting, Some(&detail));
+                    watch_backend_recovery(&app);
+                } else if exit.throttled {
+                    set_backend_state(
+                        &app,
+                        BackendState::Failed,
+                        Some(&format!("{}; restarting too often", detail)),
+                    );
+                    show_crash_loop(&app);
+                } else {
+                    set_backend_state(&app, BackendState::Failed, Some(&detail));
+                }
+            }
+            spawn_hooks(&app, hooks::HookPoint::PostCrash);
+        },
+    );
+}
+
+/// Moves the backend to `state` and tells the tray and every window.
+fn set_backend_state(app: &tauri::AppHandle, state: BackendState, detail: Option<&str>) {
+    let Some(status) = app
+        .state::<AppState>()
+        .backend_state
+        .transition(state, detail)
+    else {
+        return;
+    };
+    match detail {
+        Some(detail) if state == BackendState::Failed || state == BackendState::Degraded => {
+            eprintln!("[tauri] Backend {}: {}", state.label(), detail)
+        }
+        Some(detail) => println!("[tauri] Backend {}: {}", state.label(), detail),
+        None => println!("[tauri] Backend {}", state.label()),
+    }
+    tray::set_status(app, state);
+    let offline_url = offline_url(app);
+    app.state::<AppState>()
+        .reconnect
+        .follow(app, &status, &app_url(app), offline_url.as_deref());
+    emit_event(app, state::EVENT, status);
+}
+
+/// Follows a watchdog restart until the backend answers again or gives up.
+fn watch_backend_recovery(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let probe = backend_probe(&app);
+        match health::poll_health(&probe, 250, 30000, None).await {
+            Ok(()) => set_backend_state(&app, BackendState::Healthy, None),
+            Err(e) => set_backend_state(&app, BackendState::Failed, Some(&e)),
+        }
+    });
+}
+
+#[tauri::command]
+fn get_backend_state(state: State<'_, AppState>) -> state::BackendStatus {
+    state.backend_state.current()
+}
+
+/// Where the main window goes while the backend restarts, when the offline
+/// app is enabled and built in.
+fn offline_url(app: &tauri::AppHandle) -> Option<String> {
+    let enabled = app
+        .state::<AppState>()
+        .config
+        .lock()
+        .unwrap()
+        .offline
+        .enabled;
+    (enabled && offline::available(app)).then(offline::app_url)
+}
+
+#[tauri::command]
+fn get_offline_mode(state: State<'_, AppState>) -> bool {
+    state.reconnect.is_offline()
+}
+
+/// The error page for a backend the watchdog gave up restarting.
+fn show_crash_loop(app: &tauri::AppHandle) {
+    let state: State<AppState> = app.state();
+    let config = state.config.lock().unwrap().restarts.clone();
+    let recent = state.restarts.recent(BACKEND, &config);
+    let context = error_context(&state, None, None);
+    show_error_page(
+        app,
+        &error_page::generate_crash_loop_html(
+            &config,
+            &recent,
+            &context.lines,
+            Some(context.source),
+        ),
+    );
+}
+
+/// Sidecar restarts this session, oldest first, with why each happened.
+#[tauri::command]
+fn get_restart_history(state: State<'_, AppState>) -> Vec<restarts::RestartRecord> {
+    state.restarts.entries()
+}
+
+/// Keeps what the sidecar printed before it died, for the error page, and
+/// files the exit under `crashes/`.
+fn save_crash_snapshot(app: &tauri::AppHandle, exit: &registry::SidecarExit) {
+    let state: State<AppState> = app.state();
+    let Some(sidecar) = state.sidecars.get(&exit.name) else {
+        return;
+    };
+    let lines = state.logs.recent_lines(&exit.name, CRASH_SNAPSHOT_LINES);
+    if let Err(e) = sidecar.save_crash_snapshot(&exit.status.to_string(), &lines) {
+        eprintln!("[tauri] {}", e);
+    }
+    let Some(exit_info) = sidecar.last_exit_info() else {
+        return;
+    };
+    let crash = crashes::SidecarCrash::new(&sidecar, &exit_info, lines);
+    match crashes::save(&crash) {
+        Ok(path) => println!("[tauri] Crash report saved to {}", path.display()),
+        Err(e) => eprintln!("[tauri] {}", e),
+    }
+}
+
+/// Unexpected sidecar exits saved under `crashes/`, newest first.
+#[tauri::command]
+fn get_crash_reports() -> Vec<crashes::SidecarCrash> {
+    crashes::list()
+}
+
+/// Backend log lines for the error page from `source`, or the configured
+/// source, sanitized for display.
+fn error_context(
+    state: &AppState,
+    source: Option<error_page::LogSource>,
+    lines: Option<usize>,
+) -> ErrorContext {
+    let config = state.config.lock().unwrap().error_page.clone();
+    let source = source.unwrap_or(config.log_source);
+    let n = lines.unwrap_or(config.log_lines);
+    let lines = match (source, state.sidecars.get(BACKEND)) {
+        (error_page::LogSource::Memory, _) => state.logs.recent_lines(BACKEND, n),
+        (error_page::LogSource::File, Some(backend)) => backend.recent_log_lines(n),
+        (error_page::LogSource::Crash, Some(backend)) => backend.read_last_crash_lines(n),
+        (_, None) => Vec::new(),
+    };
+    ErrorContext {
+        source,
+        levels: lines
+            .iter()
+            .map(|line| logstream::Level::of_logged(line))
+            .collect(),
+        lines: lines
+            .iter()
+            .map(|line| template::sanitize_log_line(line))
+            .collect(),
+    }
+}
+
+#[derive(serde::Serialize)]
+struct ErrorContext {
+    source: error_page::LogSource,
+    lines: Vec<String>,
+    /// Level of each entry in `lines`.
+    levels: Vec<logstream::Level>,
+}
+
+#[tauri::command]
+fn get_error_context(
+    state: State<'_, AppState>,
+    source: Option<error_page::LogSource>,
+    lines: Option<usize>,
+) -> ErrorContext {
+    error_context(&state, source, lines)
+}
+
+/// The backend's last log file lines, from memory when it has any.
+#[tauri::command]
+fn get_recent_logs(state: State<'_, AppState>, lines: Option<usize>) -> Vec<String> {
+    let n = lines.unwrap_or(sidecar::RECENT_LINES);
+    state
+        .sidecars
+        .get(BACKEND)
+        .map(|backend| backend.recent_log_lines(n))
+        .unwrap_or_default()
+}
+
+/// The backend log's last `lines`; with `follow`, new lines keep coming to
+/// this window as "log-appended" events until `untail_logs`.
+#[tauri::command]
+fn tail_logs(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    state: State<'_, AppState>,
+    lines: Option<usize>,
+    follow: Option<bool>,
+) -> Result<tail::TailSnapshot, String> {
+    let backend = state
+        .sidecars
+        .get(BACKEND)
+        .ok_or_else(|| "Backend not started".to_string())?;
+    Ok(state.tails.tail(
+        &app,
+        window.label(),
+        &backend,
+        lines.unwrap_or(100),
+        follow.unwrap_or(false),
+    ))
+}
+
+#[tauri::command]
+fn untail_logs(
+    window: tauri::WebviewWindow,
+    state: State<'_, AppState>,
+    subscription: u64,
+) -> bool {
+    state.tails.untail(window.label(), subscription)
+}
+
+#[tauri::command]
+fn list_sidecars(state: State<'_, AppState>) -> Vec<sidecar::SidecarStatus> {
+    state.sidecars.statuses()
+}
+
+#[tauri::command]
+fn get_sidecar_status(
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<sidecar::SidecarStatus, String> {
+    state
+        .sidecars
+        .get(&name)
+        .map(|s| s.status())
+        .ok_or_else(|| format!("No sidecar named {:?}", name))
+}
+
+#[tauri::command]
+async fn restart_sidecar(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<sidecar::SidecarStatus, String> {
+    let operation = format!("restart_sidecar {}", name);
+    state
+        .lifecycle
+        .run(&operation, restart_sidecar_and_wait(&app, &state, &name))
+        .await?;
+    state
+        .sidecars
+        .get(&name)
+        .map(|s| s.status())
+        .ok_or_else(|| format!("No sidecar named {:?}", name))
+}
+
+async fn restart_sidecar_and_wait(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    name: &str,
+) -> Result<(), String> {
+    state
+        .restarts
+        .record(name, restarts::RestartReason::Manual, "restart_sidecar");
+    let is_backend = name == BACKEND;
+    let set_state = |backend_state, detail: Option<&str>| {
+        if is_backend {
+            set_backend_state(app, backend_state, detail);
+        }
+    };
+    set_state(BackendState::Restarting, Some("restart requested"));
+
+    let sidecars = state.sidecars.clone();
+    let restarted = name.to_string();
+    let sidecar = tauri::async_runtime::spawn_blocking(move || sidecars.restart(&restarted))
+        .await
+        .map_err(|e| format!("Restart task failed: {}", e))
+        .and_then(|restarted| restarted)
+        .inspect_err(|e| set_state(BackendState::Failed, Some(e)))?;
+
+    if let Some(probe) = sidecar.spec().probe() {
+        health::poll_health(&probe, 250, 15000, None)
+            .await
+            .inspect_err(|e| set_state(BackendState::Failed, Some(e)))?;
+    }
+    set_state(BackendState::Healthy, None);
+    deps::restart_dependents(app, name);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_log_streaming(state: State<'_, AppState>, enabled: bool) {
+    state.logs.set_enabled(enabled);
+}
+
+#[tauri::command]
+fn open_log_viewer(app: tauri::AppHandle) -> Result<(), String> {
+    logviewer::open_window(&app)
+}
+
+/// Recent sidecar output from the in-memory ring, after line `since`.
+#[tauri::command]
+fn get_log_history(state: State<'_, AppState>, since: Option<u64>) -> Vec<logstream::LogLine> {
+    state.logs.history_since(since.unwrap_or(0))
+}
+
+/// A page of the backend log file starting at byte `offset`; pass the
+/// returned `next_offset` to read on.
+#[tauri::command]
+fn read_log_range(
+    app: tauri::AppHandle,
+    offset: Option<u64>,
+    limit: Option<usize>,
+) -> Result<logfile::LogRange, String> {
+    logfile::read_range(
+        &current_log_path(&app),
+        offset.unwrap_or(0),
+        limit.unwrap_or(500),
+    )
+}
+
+#[tauri::command]
+fn get_log_metadata(app: tauri::AppHandle) -> logfile::LogMetadata {
+    logfile::metadata(&current_log_path(&app))
+}
+
+/// Flushes batched sidecar output to the UI as "sidecar-log" events, at most
+/// once per flush interval no matter how chatty the sidecars are.
+fn start_log_stream(app: &tauri::AppHandle) {
+    let app = app.clone();
+    let logs = app.state::<AppState>().logs.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(logstream::FLUSH_INTERVAL);
+        if let Some(batch) = logs.take_batch() {
+            emit_event(&app, "sidecar-log", batch);
+        }
+    });
+}
+
+#[tauri::command]
+fn get_resource_usage(state: State<'_, AppState>) -> Vec<monitor::SidecarUsage> {
+    state.monitor.latest()
+}
+
+#[derive(Clone, serde::Serialize)]
+struct MemoryRestartEvent {
+    name: String,
+    memory_mb: u64,
+    limit_mb: u64,
+}
+
+/// Samples sidecar resource usage and restarts the backend when it stays
+/// above the configured memory ceiling, before it can push the machine
+/// into swap.
+fn start_resource_monitor(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let (config, sidecars, monitor) = {
+                let state: State<AppState> = app.state();
+                let config = state.config.lock().unwrap().resources.clone();
+                (config, state.sidecars.clone(), state.monitor.clone())
+            };
+
+            let sampling = monitor.clone();
+            let usage = tauri::async_runtime::spawn_blocking(move || sampling.sample(&sidecars))
+                .await
+                .unwrap_or_default();
+            if let Some(limit_mb) = config.memory_limit_mb {
+                let over = usage.into_iter().find(|u| {
+                    u.name == BACKEND && monitor.check_limit(u, limit_mb, config.over_limit_samples)
+                });
+                if let Some(over) = over {
+                    restart_over_memory_limit(&app, &over, limit_mb).await;
+                }
+            }
+
+            tokio::time::sleep(Duration::from_secs(config.sample_interval_secs.max(1))).await;
+        }
+    });
+}
+
+/// Restarts the backend for staying over its memory ceiling, in its
+/// lifecycle turn so it can't interleave with a restart, profile switch or
+/// upgrade.
+async fn restart_over_memory_limit(
+    app: &tauri::AppHandle,
+    over: &monitor::SidecarUsage,
+    limit_mb: u64,
+) {
+    let state: State<AppState> = app.state();
+    let _lifecycle = state.lifecycle.turn().await;
+    // Whatever held the turn may have replaced the process already
+    let current = state
+        .sidecars
+        .get(BACKEND)
+        .and_then(|backend| backend.pid());
+    if current != Some(over.pid) {
+        return;
+    }
+
+    let restarts = state.config.lock().unwrap().restarts.clone();
+    if !state.restarts.allows(BACKEND, &restarts) {
+        let detail = format!(
+            "over its {} MB memory ceiling; restarted too often, leaving it running",
+            limit_mb
+        );
+        set_backend_state(app, BackendState::Degraded, Some(&detail));
+        return;
+    }
+
+    let detail = format!("over its {} MB memory ceiling", limit_mb);
+    set_backend_state(app, BackendState::Restarting, Some(&detail));
+    emit_event(
+        app,
+        "sidecar-memory-restart",
+        MemoryRestartEvent {
+            name: over.name.clone(),
+            memory_mb: over.memory_bytes / (1024 * 1024),
+            limit_mb,
+        },
+    );
+    let restarted = respawn_sidecar(
+        &state,
+        restarts::RestartReason::MemoryLimit,
+        &format!("over {} MB", limit_mb),
+    );
+    let healthy = match restarted {
+        Ok(()) => wait_for_backend(app, &backend_poll_settings(app), None).await,
+        Err(e) => Err(e),
+    };
+    // Healthy takes the window back to the app
+    match healthy {
+        Ok(()) => set_backend_state(app, BackendState::Healthy, None),
+        Err(e) => set_backend_state(app, BackendState::Failed, Some(&e)),
+    }
+}
+
+/// Scrapes the backend's `/metrics` into `AppState::metrics` while it runs.
+fn start_metrics_scraper(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = app
+                .state::<AppState>()
+                .config
+                .lock()
+                .unwrap()
+                .metrics
+                .clone();
+            let running = app
+                .state::<AppState>()
+                .sidecars
+                .get(BACKEND)
+                .is_some_and(|backend| backend.is_running());
+            // An external backend in dev or with --no-sidecar is scraped too
+            if config.enabled && (running || is_dev() || cli::options().no_sidecar) {
+                if let Some((200, body)) = api::call(&app, "GET", "/metrics").await {
+                    let sample = metrics::parse(&String::from_utf8_lossy(&body));
+                    app.state::<AppState>()
+                        .metrics
+                        .push(sample, config.history_samples);
+                }
+            }
+            tokio::time::sleep(config.interval()).await;
+        }
+    });
+}
+
+#[tauri::command]
+fn get_metrics_history(state: State<'_, AppState>) -> Vec<metrics::MetricsSample> {
+    state.metrics.samples()
+}
+
+/// Calls the backend's maintenance endpoints as they fall due (see
+/// `scheduler`), unless the backend is down or busy or the machine is on
+/// battery, in which case they wait for a later tick.
+fn start_maintenance_scheduler(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(MAINTENANCE_TICK).await;
+            let config = app
+                .state::<AppState>()
+                .config
+                .lock()
+                .unwrap()
+                .maintenance
+                .clone();
+            let state: State<AppState> = app.state();
+            let due = state.maintenance.due(&config.tasks, chrono::Local::now());
+            if due.is_empty() {
+                continue;
+            }
+
+            let held = if state.backend_state.current().state != BackendState::Healthy {
+                Some("backend is not running")
+            } else if config.skip_on_battery
+                && tauri::async_runtime::spawn_blocking(power::on_battery)
+                    .await
+                    .unwrap_or(false)
+            {
+                Some("on battery")
+            } else if config.skip_when_busy && quit::activity(&app).await.is_some_and(|a| a.busy) {
+                Some("backend is busy")
+            } else {
+                None
+            };
+            for task in due {
+                if let Some(reason) = held {
+                    state.maintenance.hold(&task.name, reason);
+                    continue;
+                }
+                let handle = &app;
+                let run = scheduler::run(task, |method, path, timeout| async move {
+                    api::call_with_timeout(handle, &method, &path, timeout).await
+                })
+                .await;
+                state.maintenance.record(run.clone());
+                emit_event(&app, scheduler::EVENT, run);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+fn get_maintenance_status(state: State<'_, AppState>) -> Vec<scheduler::TaskStatus> {
+    let tasks = state.config.lock().unwrap().maintenance.tasks.clone();
+    state.maintenance.status(&tasks)
+}
+
+/// The backend's configured health check, or a GET of its health endpoint.
+fn backend_probe(app: &tauri::AppHandle) -> health::ProbeSpec {
+    let state: State<AppState> = app.state();
+    // Released before `backend_url`, which locks the config too
+    let configured = state
+        .config
+        .lock()
+        .unwrap()
+        .health_probes
+        .get(BACKEND)
+        .cloned();
+    configured.unwrap_or_else(|| match bridge::active() {
+        Some(socket) => health::ProbeSpec::Socket {
+            socket: socket.to_path_buf(),
+            path: "/health".to_string(),
+            status: None,
+        },
+        None => health::ProbeSpec::http(&format!("{}/health", backend_url(app))),
+    })
+}
+
+/// Waits for the backend to announce it is ready on stdout or to pass its
+/// health check.
+async fn wait_for_backend(
+    app: &tauri::AppHandle,
+    settings: &health::PollSettings,
+    trace_id: Option<&str>,
+) -> Result<(), String> {
+    let options = health::HealthCheckOptions::default();
+    wait_for_backend_with(app, settings, trace_id, &options).await
+}
+
+/// `wait_for_backend` with the caller's overrides. An announcement doesn't
+/// count when the caller asked for a specific path.
+async fn wait_for_backend_with(
+    app: &tauri::AppHandle,
+    settings: &health::PollSettings,
+    trace_id: Option<&str>,
+    options: &health::HealthCheckOptions,
+) -> Result<(), String> {
+    let (probe, settings) = options.apply(&backend_probe(app), settings)?;
+    let state = app.state::<AppState>();
+    let announced = state
+        .sidecars
+        .get(BACKEND)
+        .filter(|_| options.path.is_none())
+        .map(|backend| backend.readiness());
+    state
+        .health_cancels
+        .run(
+            options.cancel_token.as_deref(),
+            readiness::wait(announced, &probe, &settings, trace_id),
+        )
+        .await
+}
+
+/// Polling for an already-started backend, per `retry.health`.
+fn backend_poll_settings(app: &tauri::AppHandle) -> health::PollSettings {
+    let state: State<AppState> = app.state();
+    let backoff = state.config.lock().unwrap().retry.health;
+    health::PollSettings {
+        backoff,
+        ..health::PollSettings::default()
+    }
+}
+
+/// Stops every sidecar in dependency order within the configured budget and
+/// keeps the report for `get_last_shutdown_report`.
+fn shutdown_services(state: &AppState) -> shutdown::ShutdownReport {
+    let budget = state.config.lock().unwrap().shutdown.budget_ms;
+    let report = shutdown::run(&state.sidecars, Duration::from_millis(budget));
+    if let Err(e) = shutdown::save(&report) {
+        eprintln!("[tauri] {}", e);
+    }
+    report
+}
+
+/// Runs the pre-shutdown hooks and stops every service.
+fn stop_all(state: &AppState) -> shutdown::ShutdownReport {
+    let hooks_config = state.config.lock().unwrap().hooks.clone();
+    hooks::run(&hooks_config, hooks::HookPoint::PreShutdown);
+
+    state.terminal.stop();
+    if let Some(previous) = state.previous_backend.take() {
+        previous.shutdown();
+    }
+    shutdown_services(state)
+}
+
+/// Stops every service once per run, waiting for a shutdown already
+/// running in the background.
+fn stop_services(state: &AppState) {
+    state.shutdown.run(|| {
+        stop_all(state);
+    });
+}
+
+/// Stops every service off the event loop, so closing the last window never
+/// waits on grace periods, and emits `services-stopped` when done.
+fn stop_services_in_background(app: &tauri::AppHandle) {
+    let handle = app.clone();
+    app.state::<AppState>().shutdown.spawn(move || {
+        let report = stop_all(&handle.state::<AppState>());
+        emit_event(&handle, shutdown::EVENT, &report);
+    });
+}
+
+/// Builds the main window from its tauri.conf.json entry, pointed at the
+/// app. It starts hidden; boot shows it.
+fn create_main_window(app: &tauri::AppHandle) -> Result<tauri::WebviewWindow, String> {
+    let mut config = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|w| w.label == "main")
+        .cloned()
+        .ok_or_else(|| "No main window in tauri.conf.json".to_string())?;
+    // In production the backend serves the UI
+    if !is_dev() {
+        let url = backend_url(app)
+            .parse()
+            .map_err(|e| format!("Invalid backend URL: {}", e))?;
+        config.url = tauri::WebviewUrl::External(url);
+    }
+    if let Some(dir) = paths::portable_webview_dir() {
+        config.data_directory = Some(dir);
+    }
+    let handle = app.clone();
+    let window = tauri::WebviewWindowBuilder::from_config(app, &config)
+        .and_then(|builder| {
+            builder
+                .on_navigation(move |url| guard_navigation(&handle, url))
+                .build()
+        })
+        .map_err(|e| format!("Failed to create the main window: {}", e))?;
+    app.state::<AppState>().windows.opened(windows::MAIN_LABEL);
+    Ok(window)
+}
+
+/// Keeps app windows on the app: the backend, the dev server and the
+/// shell's own protocols load in place, other web links open in the
+/// browser and anything else is refused.
+fn guard_navigation(app: &tauri::AppHandle, url: &tauri::Url) -> bool {
+    let origins = [
+        app_url(app),
+        backend_url(app),
+        bridge::app_url(),
+        offline::app_url(),
+        pages::page_url("/").to_string(),
+    ];
+    if navigation::allowed(url, &origins) {
+        return true;
+    }
+    if navigation::is_external(url) {
+        if let Err(e) = app.opener().open_url(url.as_str(), None::<&str>) {
+            eprintln!("[tauri] Failed to open {} in the browser: {}", url, e);
+        }
+    } else {
+        eprintln!("[tauri] Blocked navigation to {}", url);
+    }
+    false
+}
+
+/// Brings the main window forward, creating it first when this run has
+/// none (headless, or closed while running in the background).
+fn show_main_window(app: &tauri::AppHandle) {
+    let main_window = match app.get_webview_window("main") {
+        Some(window) => window,
+        None => match create_main_window(app) {
+            Ok(window) => window,
+            Err(e) => {
+                eprintln!("[tauri] {}", e);
+                return;
+            }
+        },
+    };
+    let _ = main_window.show();
+    let _ = main_window.unminimize();
+    let _ = main_window.set_focus();
+}
+
+/// How the previous session's services went down.
+#[tauri::command]
+fn get_last_shutdown_report() -> Option<shutdown::ShutdownReport> {
+    shutdown::load_last()
+}
+
+/// Sends a control message (`graceful-shutdown`, `reload-config`, `flush`)
+/// to the backend over its stdin.
+#[tauri::command]
+fn send_backend_command(
+    state: State<'_, AppState>,
+    command: control::ControlCommand,
+) -> Result<(), String> {
+    state
+        .sidecars
+        .get(BACKEND)
+        .ok_or_else(|| "The backend is not managed by Local Agent".to_string())?
+        .send_control(command)
+}
+
+/// Stops the backend sidecar and spawns a fresh one.
+fn respawn_sidecar(
+    state: &AppState,
+    reason: restarts::RestartReason,
+    detail: &str,
+) -> Result<(), String> {
+    state.restarts.record(BACKEND, reason, detail);
+    state.sidecars.restart(BACKEND).map(|_| ())
+}
+
+/// Restarts the backend process and waits until it answers again. Callers
+/// hold the lifecycle turn.
+async fn restart_and_wait(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    detail: &str,
+    options: &health::HealthCheckOptions,
+) -> Result<(), String> {
+    let trace_id = state.traces.start(detail);
+    let failed = |e: &String| {
+        state.traces.record(&trace_id, "shell", e);
+        set_backend_state(app, BackendState::Failed, Some(e));
+    };
+
+    set_backend_state(app, BackendState::Restarting, Some("restart requested"));
+    respawn_sidecar(state, restarts::RestartReason::Manual, detail).inspect_err(failed)?;
+    state.traces.record(&trace_id, "shell", "sidecar respawned");
+
+    let poll_settings = backend_poll_settings(app);
+    wait_for_backend_with(app, &poll_settings, Some(&trace_id), options)
+        .await
+        .inspect_err(failed)?;
+    state
+        .traces
+        .record(&trace_id, "shell", "health check passed");
+    set_backend_state(app, BackendState::Healthy, None);
+    spawn_hooks(app, hooks::HookPoint::PostBackendStart);
+    deps::restart_dependents(app, BACKEND);
+    // Links that arrived while boot had failed can be opened now
+    state.deep_links.mark_ready(app);
+    Ok(())
+}
+
+/// Restarts the backend and waits for it, with `options` overriding how
+/// long and where to check its health.
+#[tauri::command]
+async fn restart_backend(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    options: Option<health::HealthCheckOptions>,
+) -> Result<String, String> {
+    let options = options.unwrap_or_default();
+    let restart = restart_and_wait(&app, &state, "restart_backend", &options);
+    state.lifecycle.run("restart_backend", restart).await?;
+    Ok("Backend restarted".to_string())
+}
+
+/// Restarts the backend without navigating the main window, so the app
+/// keeps its state; it reconnects on `backend-ready`.
+#[tauri::command]
+async fn restart_backend_soft(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    options: Option<health::HealthCheckOptions>,
+) -> Result<String, String> {
+    let options = options.unwrap_or_default();
+    let restart = async {
+        state.reconnect.hold(true);
+        let result = restart_and_wait(&app, &state, "restart_backend_soft", &options).await;
+        state.reconnect.hold(false);
+        result
+    };
+    state.lifecycle.run("restart_backend_soft", restart).await?;
+
+    if state.reconnect.announce_ready(&app, &app_url(&app)).await {
+        Ok("Backend restarted".to_string())
+    } else {
+        Ok("Backend restarted; the app was reloaded".to_string())
+    }
+}
+
+/// Points every app window at the backend's current origin, keeping each
+/// one's route.
+fn move_app_windows(app: &tauri::AppHandle) {
+    let script = navigation::move_origin_script(&backend_url(app));
+    for (label, window) in app.webview_windows() {
+        if windows::is_app_window(&label) {
+            let _ = window.eval(&script);
+        }
+    }
+}
+
+/// Stops a backend that is no longer in the registry, off the async runtime.
+async fn retire_backend(backend: Arc<SidecarManager>) {
+    println!("[tauri] Stopping the previous backend");
+    let _ = tauri::async_runtime::spawn_blocking(move || backend.shutdown()).await;
+}
+
+/// Starts the backend from `source` next to the running one and moves the
+/// app over once it is healthy (see `upgrade`).
+#[tauri::command]
+async fn upgrade_backend(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    source: Option<upgrade::UpgradeSource>,
+) -> Result<upgrade::UpgradeResult, String> {
+    if bridge::active().is_some() {
+        return Err("Upgrades need the backend on a TCP port; restart it instead".to_string());
+    }
+    if is_dev() {
+        return Err("The dev server proxies to a fixed port; restart the backend".to_string());
+    }
+    let _lifecycle = state.lifecycle.turn().await;
+    let current = state
+        .sidecars
+        .get(BACKEND)
+        .ok_or_else(|| "The backend is not run by Local Agent".to_string())?;
+    let previous_port = current.announced_port().or_else(|| current.spec().port());
+    let (configured, backoff, retire_after) = {
+        let config = state.config.lock().unwrap();
+        (
+            config.backend_port(),
+            config.retry.spawn,
+            config.upgrade.retire_after_secs,
+        )
+    };
+    let port = upgrade::free_port(configured, previous_port)
+        .ok_or_else(|| format!("No free port near {} for the new backend", configured))?;
+
+    let spec = upgrade::candidate_spec(current.spec(), &source.unwrap_or_default(), port);
+    let probe = spec
+        .probe()
+        .ok_or_else(|| "The new backend has no health check".to_string())?;
+    let candidate = state.sidecars.prepare(
+        SidecarManager::new(spec)
+            .with_trace_store(state.traces.clone())
+            .with_backoff(backoff),
+    );
+    let trace_id = state.traces.start("upgrade_backend");
+    println!("[tauri] Starting the new backend on port {}", port);
+    let spawned = candidate.clone();
+    tauri::async_runtime::spawn_blocking(move || spawned.spawn_with_retry())
+        .await
+        .map_err(|e| format!("Backend start task failed: {}", e))??;
+    let poll_settings = backend_poll_settings(&app);
+    if let Err(e) = health::poll_health_with(&probe, &poll_settings, Some(&trace_id)).await {
+        state.traces.record(&trace_id, "shell", &e);
+        retire_backend(candidate).await;
+        return Err(format!(
+            "The new backend did not become healthy, so the current one keeps running: {}",
+            e
+        ));
+    }
+    state
+        .traces
+        .record(&trace_id, "shell", "health check passed");
+
+    // Requests from the shell go to the new backend from here on
+    let previous = state.sidecars.swap(candidate);
+    move_app_windows(&app);
+    println!("[tauri] Switched to the new backend on port {}", port);
+    let rollback_until = chrono::Local::now() + chrono::Duration::seconds(retire_after as i64);
+    if let Some(previous) = previous {
+        // An older one still kept for a rollback can go now
+        if let Some(older) = state.previous_backend.keep(previous.clone()) {
+            retire_backend(older).await;
+        }
+        let handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(retire_after)).await;
+            let kept = handle
+                .state::<AppState>()
+                .previous_backend
+                .take_if(&previous);
+            if let Some(previous) = kept {
+                retire_backend(previous).await;
+            }
+        });
+    }
+
+    let result = upgrade::UpgradeResult {
+        port,
+        previous_port,
+        rollback_until: rollback_until.to_rfc3339(),
+    };
+    emit_event(&app, "backend-upgraded", &result);
+    Ok(result)
+}
+
+/// Moves the app back to the backend the last upgrade replaced, while it
+/// is still kept, and stops the new one.
+#[tauri::command]
+async fn rollback_backend_upgrade(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let _lifecycle = state.lifecycle.turn().await;
+    let previous = state
+        .previous_backend
+        .take()
+        .filter(|previous| previous.is_running())
+        .ok_or_else(|| "The previous backend is no longer running".to_string())?;
+    let replaced = state.sidecars.swap(previous);
+    move_app_windows(&app);
+    if let Some(replaced) = replaced {
+        retire_backend(replaced).await;
+    }
+    Ok(backend_url(&app))
+}
+
+/// One health check of the backend right now, without retries.
+#[tauri::command]
+async fn check_backend_health_once(
+    app: tauri::AppHandle,
+    options: Option<health::HealthCheckOptions>,
+) -> Result<health::HealthCheckResult, String> {
+    let (probe, settings) = options
+        .unwrap_or_default()
+        .apply(&backend_probe(&app), &backend_poll_settings(&app))?;
+    health::check_once(&probe, &settings).await
+}
+
+/// Stops the health poll started with `token` as its `cancel_token`.
+#[tauri::command]
+fn cancel_health_check(state: State<'_, AppState>, token: String) -> bool {
+    state.health_cancels.cancel(&token)
+}
+
+/// The app reconnected after `backend-ready`.
+#[tauri::command]
+fn ack_backend_ready(state: State<'_, AppState>) {
+    state.reconnect.ack();
+}
+
+/// Sends a request to the backend over the socket bridge and streams the
+/// response to `on_event` chunk by chunk (see `bridge::stream`). Returns an
+/// id for `cancel_backend_stream`. Over TCP the webview streams with plain
+/// `fetch` and doesn't need this.
+#[tauri::command]
+fn stream_backend(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Option<String>,
+    on_event: tauri::ipc::Channel<bridge::StreamEvent>,
+) -> Result<u64, String> {
+    let socket =
+        bridge::active().ok_or_else(|| "The backend is not running on a socket".to_string())?;
+    let mut request = tauri::http::Request::builder()
+        .method(method.as_str())
+        .uri(&path);
+    for (name, value) in &headers {
+        request = request.header(name, value);
+    }
+    let request = request
+        .body(body.unwrap_or_default().into_bytes())
+        .map_err(|e| format!("Invalid backend request: {}", e))?;
+
+    let id = state.streams.next_id();
+    state.streams.spawn(id, async move {
+        // A closed window can't take events; stopping drops the request
+        bridge::stream(socket, &request, |event| on_event.send(event).is_ok()).await;
+        app.state::<AppState>().streams.finished(id);
+    });
+    Ok(id)
+}
+
+/// Stops a `stream_backend` stream; the backend sees the client go away.
+#[tauri::command]
+fn cancel_backend_stream(state: State<'_, AppState>, id: u64) -> bool {
+    state.streams.cancel(id)
+}
+
+/// Opens another window on the frontend at `route` (e.g. `/chat/42`), or
+/// focuses it if `label` is already open. Returns the window's label.
+#[tauri::command]
+fn open_window(app: tauri::AppHandle, label: String, route: String) -> Result<String, String> {
+    let url = windows::resolve_route(&app_url(&app), &route)?;
+    windows::open(&app, &app.state::<AppState>().windows, &label, url)
+}
+
+#[tauri::command]
+fn open_preferences(app: tauri::AppHandle) -> Result<(), String> {
+    preferences::open_window(&app)
+}
+
+/// The About window: versions, license and acknowledgements.
+#[tauri::command]
+fn open_about(app: tauri::AppHandle) -> Result<(), String> {
+    about::open_window(&app)
+}
+
+#[tauri::command]
+fn get_settings(state: State<'_, AppState>) -> BTreeMap<settings::SettingKey, serde_json::Value> {
+    settings::all(&state.config.lock().unwrap())
+}
+
+#[tauri::command]
+fn get_setting(state: State<'_, AppState>, key: settings::SettingKey) -> serde_json::Value {
+    settings::get(&state.config.lock().unwrap(), key)
+}
+
+/// Validates and saves one setting, applies what can change at runtime and
+/// emits "setting-changed".
+#[tauri::command]
+fn set_setting(
+    app: tauri::AppHandle,
+    key: settings::SettingKey,
+    value: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let state: State<AppState> = app.state();
+    let (value, theme, zoom, run_in_background) = {
+        let mut config = state.config.lock().unwrap();
+        let mut updated = config.clone();
+        let value = settings::set(&mut updated, key, value)?;
+        let (old, new) = (&config.settings, &updated.settings);
+        if new.autostart && (!old.autostart || new.start_minimized != old.start_minimized) {
+            autostart::enable(new.start_minimized)?;
+        } else if old.autostart && !new.autostart {
+            autostart::disable()?;
+        }
+        updated.save()?;
+        *config = updated;
+        (
+            value,
+            config.settings.theme,
+            config.settings.zoom,
+            config.settings.run_in_background,
+        )
+    };
+
+    if key == settings::SettingKey::Theme {
+        app.set_theme(theme.window_theme());
+    }
+    if key == settings::SettingKey::Zoom {
+        for (label, window) in app.webview_windows() {
+            if let Err(e) = window.set_zoom(zoom) {
+                eprintln!("[tauri] Failed to set zoom on {}: {}", label, e);
+            }
+        }
+    }
+    if let Some(toggle) = app.try_state::<tray::BackgroundToggle>() {
+        let _ = toggle.0.set_checked(run_in_background);
+    }
+    emit_event(
+        &app,
+        "setting-changed",
+        settings::SettingChanged {
+            key,
+            value: value.clone(),
+            needs_restart: key.needs_restart(),
+        },
+    );
+    Ok(value)
+}
+
+/// Zooms every window to `factor` (1.0 is 100%) and keeps it for new
+/// windows and future launches.
+#[tauri::command]
+fn set_zoom(app: tauri::AppHandle, factor: f64) -> Result<f64, String> {
+    let value = set_setting(app, settings::SettingKey::Zoom, factor.into())?;
+    Ok(value.as_f64().unwrap_or(factor))
+}
+
+#[tauri::command]
+fn get_zoom(state: State<'_, AppState>) -> f64 {
+    state.config.lock().unwrap().settings.zoom
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct AutostartStatus {
+    /// The setting.
+    enabled: bool,
+    /// A login entry actually exists.
+    registered: bool,
+    start_minimized: bool,
+}
+
+#[tauri::command]
+fn get_autostart(state: State<'_, AppState>) -> AutostartStatus {
+    let settings = state.config.lock().unwrap().settings.clone();
+    AutostartStatus {
+        enabled: settings.autostart,
+        registered: autostart::is_enabled(),
+        start_minimized: settings.start_minimized,
+    }
+}
+
+/// Registers or removes the login entry, through the same path as the
+/// Preferences window.
+#[tauri::command]
+fn set_autostart(
+    app: tauri::AppHandle,
+    enabled: bool,
+    start_minimized: Option<bool>,
+) -> Result<AutostartStatus, String> {
+    if let Some(minimized) = start_minimized {
+        set_setting(
+            app.clone(),
+            settings::SettingKey::StartMinimized,
+            minimized.into(),
+        )?;
+    }
+    set_setting(app.clone(), settings::SettingKey::Autostart, enabled.into())?;
+    Ok(get_autostart(app.state()))
+}
+
+#[tauri::command]
+async fn check_for_updates(app: tauri::AppHandle) -> Result<updates::UpdateInfo, String> {
+    let network = network::refresh(&app).await;
+    if !network.online || network.captive_portal {
+        let reason = network.defer_reason.unwrap_or_default();
+        return Err(format!("Can't check for updates: {}", reason));
+    }
+    let info = updates::check(&app.package_info().version.to_string()).await?;
+    emit_event(&app, "update-status", info.clone());
+    Ok(info)
+}
+
+/// Makes one request to `url` (GitHub's API by default) through the
+/// configured proxies, to tell whether the internet can be reached.
+#[tauri::command]
+async fn test_connectivity(url: Option<String>) -> Result<proxy::ConnectivityResult, String> {
+    proxy::test(url.as_deref().unwrap_or(proxy::DEFAULT_TEST_URL)).await
+}
+
+/// Whether the internet is reachable, through a captive portal or on a
+/// metered connection, and why large downloads are held if they are.
+#[tauri::command]
+async fn get_network_status(app: tauri::AppHandle) -> network::NetworkStatus {
+    network::refresh(&app).await
+}
+
+/// Starts a throwaway backend and checks each layer of it (see `selftest`).
+#[tauri::command]
+async fn run_self_test(app: tauri::AppHandle) -> selftest::SelfTestReport {
+    selftest::run(&app).await
+}
+
+/// Whether other devices on the network can reach the backend.
+#[tauri::command]
+fn get_network_exposure(state: State<'_, AppState>) -> exposure::ExposureStatus {
+    let config = state.config.lock().unwrap();
+    exposure::status(&config.exposure, config.backend_port())
+}
+
+/// Lets other devices on the network reach the backend (`lan`), with an
+/// optional PIN, or only this computer (`local`); restarts the backend on
+/// the new address.
+#[tauri::command]
+async fn set_network_exposure(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    mode: exposure::NetworkExposure,
+    pin: Option<String>,
+) -> Result<exposure::ExposureStatus, String> {
+    if mode == exposure::NetworkExposure::Lan && bridge::active().is_some() {
+        return Err(
+            "LAN access needs the backend on a TCP port; change the transport first".to_string(),
+        );
+    }
+    let pin_hash = match pin.filter(|pin| !pin.is_empty()) {
+        Some(pin) if mode == exposure::NetworkExposure::Lan => Some(
+            tauri::async_runtime::spawn_blocking(move || exposure::hash_pin(&pin))
+                .await
+                .map_err(|e| format!("PIN task failed: {}", e))??,
+        ),
+        _ => None,
+    };
+    let _lifecycle = state.lifecycle.turn().await;
+    let (exposure, port, backoff) = {
+        let mut config = state.config.lock().unwrap();
+        config.exposure.set(mode, pin_hash);
+        config.save()?;
+        (
+            config.exposure.clone(),
+            config.backend_port(),
+            config.retry.spawn,
+        )
+    };
+    let status = exposure::status(&exposure, port);
+    if let Some(ref warning) = status.warning {
+        eprintln!("[tauri] LAN access is on: {}", warning);
+    }
+    emit_event(&app, exposure::EVENT, status.clone());
+
+    // The backend reads HOST when it starts
+    let Some(backend) = state.sidecars.get(BACKEND) else {
+        return Ok(status);
+    };
+    let mut spec = backend.spec().clone();
+    spec.env.remove(exposure::PIN_ENV);
+    spec.env.extend(exposure.env());
+    state.sidecars.insert(
+        SidecarManager::new(spec)
+            .with_trace_store(state.traces.clone())
+            .with_backoff(backoff),
+    );
+    let options = health::HealthCheckOptions::default();
+    restart_and_wait(&app, &state, "set_network_exposure", &options).await?;
+    Ok(status)
+}
+
+/// Opens the latest release's page, or the list of releases when GitHub
+/// can't be asked.
+#[tauri::command]
+async fn open_update_page(app: tauri::AppHandle) -> Result<(), String> {
+    let url = match updates::check(&app.package_info().version.to_string()).await {
+        Ok(info) => info.url,
+        Err(e) => {
+            eprintln!("[tauri] {}", e);
+            format!("{}/releases", updates::REPOSITORY_URL)
+        }
+    };
+    app.opener()
+        .open_url(url, None::<&str>)
+        .map_err(|e| format!("Failed to open release page: {}", e))
+}
+
+/// Saves a diagnostics report to the logs folder and opens a GitHub issue
+/// pre-filled with it (or, with `target: file`, shows the saved file).
+/// Returns the report's path.
+#[tauri::command]
+async fn open_issue_report(
+    app: tauri::AppHandle,
+    target: Option<issue::IssueTarget>,
+) -> Result<String, String> {
+    let info = appinfo::collect(&app, backend_package_dir(&app).as_deref()).await;
+    let report = {
+        let state: State<AppState> = app.state();
+        let backend = state.backend_state.current();
+        let context = error_context(&state, None, Some(issue::LOG_LINES));
+        let failed = matches!(backend.state, BackendState::Failed | BackendState::Degraded);
+        let last_error = backend
+            .detail
+            .clone()
+            .filter(|_| failed)
+            .or_else(|| {
+                context
+                    .lines
+                    .iter()
+                    .zip(&context.levels)
+                    .rev()
+                    .find(|(_, level)| **level == logstream::Level::Error)
+                    .map(|(line, _)| line.clone())
+            })
+            .map(|error| issue::redact(&error));
+        issue::IssueReport {
+            info,
+            backend,
+            last_error,
+            log_lines: context
+                .lines
+                .iter()
+                .map(|line| issue::redact(line))
+                .collect(),
+        }
+    };
+
+    let saved = report.save(&SidecarManager::resolve_log_dir())?;
+    match target.unwrap_or_default() {
+        issue::IssueTarget::Github => app
+            .opener()
+            .open_url(report.url(&saved)?, None::<&str>)
+            .map_err(|e| format!("Failed to open issue page: {}", e))?,
+        issue::IssueTarget::File => app
+            .opener()
+            .reveal_item_in_dir(&saved)
+            .map_err(|e| format!("Failed to reveal issue report: {}", e))?,
+    }
+    Ok(saved.to_string_lossy().into_owned())
+}
+
+/// The backend log lives next to the sidecar in production; in dev there is no
+/// manager, so fall back to the same resolved location.
+fn current_log_path(app: &tauri::AppHandle) -> PathBuf {
+    let state: State<AppState> = app.state();
+    state
+        .sidecars
+        .get(BACKEND)
+        .map(|s| s.log_path().clone())
+        .unwrap_or_else(SidecarManager::resolve_log_path)
+}
+
+#[tauri::command]
+fn open_logs_folder(app: tauri::AppHandle) -> Result<(), String> {
+    let log_path = current_log_path(&app);
+
+    if log_path.exists() {
+        return app
+            .opener()
+            .reveal_item_in_dir(&log_path)
+            .map_err(|e| format!("Failed to reveal log file: {}", e));
+    }
+
+    let logs_dir = log_path
+        .parent()
+        .ok_or_else(|| "Log path has no parent directory".to_string())?;
+    app.opener()
+        .open_path(logs_dir.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open logs folder: {}", e))
+}
+
+#[tauri::command]
+fn open_log_file(app: tauri::AppHandle) -> Result<(), String> {
+    let log_path = current_log_path(&app);
+    if !log_path.exists() {
+        return Err(format!(
+            "Log file does not exist yet: {}",
+            log_path.display()
+        ));
+    }
+
+    app.opener()
+        .open_path(log_path.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open log file: {}", e))
+}
+
+#[tauri::command]
+fn get_wake_status(state: State<'_, AppState>) -> wake::WakeStatus {
+    let config = state.config.lock().unwrap();
+    wake::status(&config.wake)
+}
+
+#[tauri::command]
+fn set_wake_on_schedule(
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<wake::WakeStatus, String> {
+    let mut config = state.config.lock().unwrap();
+    let mut wake = config.wake.clone();
+    wake.enabled = enabled;
+
+    // Before saving, so a machine that can't be woken doesn't keep the
+    // setting on
+    if enabled {
+        let capability = wake::capability();
+        if !capability.supported {
+            return Err(capability.detail);
+        }
+        if let Some(at) = wake::next_wake_time(&wake, chrono::Local::now()) {
+            wake::schedule_wake(at)?;
+        }
+    }
+
+    let previous = std::mem::replace(&mut config.wake, wake);
+    if let Err(e) = config.save() {
+        config.wake = previous;
+        return Err(e);
+    }
+    Ok(wake::status(&config.wake))
+}
+
+#[tauri::command]
+fn set_remote_control_enabled(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    {
+        let state: State<AppState> = app.state();
+        let mut config = state.config.lock().unwrap();
+        config.remote.enabled = enabled;
+        config.save()?;
+    }
+
+    if enabled {
+        remote::start_server(&app);
+    } else {
+        remote::stop_server(&app);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn start_remote_pairing(app: tauri::AppHandle) -> Result<remote::PairingInfo, String> {
+    remote::begin_pairing(&app)
+}
+
+#[tauri::command]
+fn list_paired_devices(app: tauri::AppHandle) -> Vec<remote::DeviceInfo> {
+    remote::list_devices(&app)
+}
+
+#[tauri::command]
+fn revoke_paired_device(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    remote::revoke_device(&app, &id)
+}
+
+#[tauri::command]
+fn push_remote_prompt(app: tauri::AppHandle, title: String, detail: String) -> String {
+    remote::push_prompt(&app, title, detail)
+}
+
+#[tauri::command]
+fn clear_remote_prompt(app: tauri::AppHandle, id: String) {
+    remote::clear_prompt(&app, &id)
+}
+
+#[tauri::command]
+fn set_mdns_advertise(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    let state: State<AppState> = app.state();
+    let (port, remote_running) = {
+        let mut config = state.config.lock().unwrap();
+        config.mdns.advertise = enabled;
+        config.save()?;
+        (config.remote.port, config.remote.enabled)
+    };
+
+    if enabled && remote_running {
+        state.mdns.advertise(port)
+    } else {
+        state.mdns.stop();
+        Ok(())
+    }
+}
+
+#[tauri::command]
+async fn discover_agents(timeout_ms: Option<u64>) -> Result<Vec<mdns::DiscoveredAgent>, String> {
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(3000));
+    tauri::async_runtime::spawn_blocking(move || mdns::discover(timeout))
+        .await
+        .map_err(|e| format!("Discovery task failed: {}", e))?
+}
+
+#[tauri::command]
+fn start_trace(state: State<'_, AppState>, action: String) -> String {
+    state.traces.start(&action)
+}
+
+#[tauri::command]
+fn record_trace_event(state: State<'_, AppState>, id: String, message: String) {
+    state.traces.record(&id, "frontend", &message)
+}
+
+#[tauri::command]
+fn get_trace(state: State<'_, AppState>, id: String) -> Result<trace::Trace, String> {
+    state
+        .traces
+        .get(&id)
+        .ok_or_else(|| format!("Unknown trace id: {}", id))
+}
+
+#[tauri::command]
+fn open_terminal(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    if !state.config.lock().unwrap().terminal.enabled {
+        return Err("The maintenance terminal is disabled in the shell config".to_string());
+    }
+    terminal::open_window(&app)
+}
 
-struct AppState {
-    sidecar: Mutex<Option<SidecarManager>>,
+/// Starts the PTY for the terminal window in the backend's working directory
+/// with the backend's environment, so npm scripts and db tools behave the
+/// same as they do for the sidecar.
+#[tauri::command]
+fn terminal_start(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    if !state.config.lock().unwrap().terminal.enabled {
+        return Err("The maintenance terminal is disabled in the shell config".to_string());
+    }
+    let spec = state.sidecars.get(BACKEND).map(|s| s.spec().clone());
+    let cwd = spec
+        .as_ref()
+        .and_then(|s| s.cwd.clone())
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("."));
+    let env = spec.map(|s| s.env).unwrap_or_default();
+    state.terminal.start(&app, cwd, &env)
 }
 
-const BACKEND_URL: &str = "http://127.0.0.1:3001";
-const HEALTH_URL: &str = "http://127.0.0.1:3001/health";
+#[tauri::command]
+fn terminal_write(state: State<'_, AppState>, data: String) -> Result<(), String> {
+    state.terminal.write(&data)
+}
 
-fn is_dev() -> bool {
-    cfg!(debug_assertions)
+#[tauri::command]
+fn terminal_resize(state: State<'_, AppState>, cols: u16, rows: u16) -> Result<(), String> {
+    state.terminal.resize(cols, rows)
 }
 
 #[tauri::command]
-async fn restart_backend(
+async fn get_ollama_status(state: State<'_, AppState>) -> Result<ollama::OllamaStatus, String> {
+    let config = state.config.lock().unwrap().ollama.clone();
+    Ok(ollama::status(&config, &state.sidecars).await)
+}
+
+#[tauri::command]
+async fn start_ollama(state: State<'_, AppState>) -> Result<ollama::OllamaStatus, String> {
+    let (config, probe) = {
+        let config = state.config.lock().unwrap();
+        (
+            config.ollama.clone(),
+            config.health_probes.get(ollama::OLLAMA_SIDECAR).cloned(),
+        )
+    };
+    ollama::ensure_running(&config, probe, &state.sidecars).await
+}
+
+/// The first-run checklist: Node.js, disk space, the backend port, the data
+/// directory and, when asked (or auto-started), Ollama.
+#[tauri::command]
+async fn run_preflight_checks(
+    app: tauri::AppHandle,
+    include_ollama: Option<bool>,
+) -> preflight::PreflightReport {
+    let state: State<AppState> = app.state();
+    let config = state.config.lock().unwrap().clone();
+    let backend = state.sidecars.get(BACKEND);
+    // Dev always runs the backend from source
+    let node_required = is_dev() || !matches!(binaries::select(&project_root(&app)), Ok(Some(_)));
+    let uses_port = is_dev() || config.settings.transport == settings::BackendTransport::Tcp;
+    let inputs = preflight::PreflightInputs {
+        node_required,
+        port: uses_port.then_some(config.backend_port()),
+        port_in_use_by_backend: backend.as_ref().is_some_and(|b| b.is_running()),
+        data_dir: backend_data_dir(&state),
+        ollama: include_ollama
+            .unwrap_or(config.ollama.auto_start)
+            .then(|| config.ollama.clone()),
+    };
+    preflight::run(inputs).await
+}
+
+/// Versions of the app, backend, Node.js and OS, for the About dialog and
+/// bug reports.
+#[tauri::command]
+async fn get_app_info(app: tauri::AppHandle) -> appinfo::AppInfo {
+    appinfo::collect(&app, backend_package_dir(&app).as_deref()).await
+}
+
+/// Where the backend's package.json is: its working directory, or the repo
+/// in dev.
+fn backend_package_dir(app: &tauri::AppHandle) -> Option<PathBuf> {
+    app.state::<AppState>()
+        .sidecars
+        .get(BACKEND)
+        .and_then(|backend| backend.spec().cwd.clone())
+        .or_else(|| is_dev().then(dev_project_root))
+}
+
+#[tauri::command]
+async fn list_local_models(state: State<'_, AppState>) -> Result<Vec<models::LocalModel>, String> {
+    let config = state.config.lock().unwrap().models.clone();
+    tauri::async_runtime::spawn_blocking(move || models::scan(&config))
+        .await
+        .map_err(|e| format!("Model scan failed: {}", e))
+}
+
+/// Free space on the log and data volumes, with what the app's files take
+/// up when `usage` is set or space is low.
+fn disk_report(app: &tauri::AppHandle, usage: bool) -> diskspace::DiskReport {
+    let state: State<AppState> = app.state();
+    let (disk, models) = {
+        let config = state.config.lock().unwrap();
+        (config.disk.clone(), config.models.clone())
+    };
+    let log_path = current_log_path(app);
+    let data_dir = backend_data_dir(&state);
+    let models_bytes = if usage {
+        models::scan(&models).iter().map(|m| m.size_bytes).sum()
+    } else {
+        0
+    };
+    let locations = diskspace::Locations {
+        log_path: &log_path,
+        data_dir: &data_dir,
+        models_bytes,
+    };
+    let report = diskspace::report(&disk, &locations, usage);
+    if report.low && !usage {
+        // Low space needs the breakdown, models included
+        return disk_report(app, true);
+    }
+    report
+}
+
+/// Warns when the log or data volume is nearly full; the backend is still
+/// started.
+fn check_disk_space(app: &tauri::AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let report = disk_report(&app, false);
+        for volume in report.volumes.iter().filter(|volume| volume.low) {
+            eprintln!(
+                "[tauri] Low disk space: {} free on {}",
+                uninstall::format_bytes(volume.free_bytes),
+                volume.mount_point.display()
+            );
+        }
+        if report.low {
+            emit_event(&app, diskspace::EVENT, report);
+        }
+    });
+}
+
+#[tauri::command]
+async fn get_disk_report(app: tauri::AppHandle) -> Result<diskspace::DiskReport, String> {
+    tauri::async_runtime::spawn_blocking(move || disk_report(&app, true))
+        .await
+        .map_err(|e| format!("Disk check failed: {}", e))
+}
+
+/// Asked before downloading `bytes` into `dir` (the data directory by
+/// default); refuses with the space breakdown sent as "disk-space-low", or
+/// while the network holds large downloads (see `network`).
+#[tauri::command]
+async fn check_download_space(
+    app: tauri::AppHandle,
+    bytes: u64,
+    dir: Option<PathBuf>,
+) -> Result<(), String> {
+    network::check_transfer(&app, bytes).await?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let state: State<AppState> = app.state();
+        let dir = dir.unwrap_or_else(|| backend_data_dir(&state));
+        let config = state.config.lock().unwrap().disk.clone();
+        diskspace::check_download(&config, &dir, bytes).inspect_err(|_| {
+            emit_event(&app, diskspace::EVENT, disk_report(&app, true));
+        })
+    })
+    .await
+    .map_err(|e| format!("Disk check failed: {}", e))?
+}
+
+#[tauri::command]
+fn clean_rotated_logs(app: tauri::AppHandle) -> diskspace::CleanupResult {
+    diskspace::clean_rotated_logs(&current_log_path(&app))
+}
+
+#[tauri::command]
+fn clean_crash_reports() -> diskspace::CleanupResult {
+    diskspace::clean_crash_reports()
+}
+
+/// Reports CPU, memory, GPU and disk so the frontend can recommend which
+/// local models this machine can run.
+#[tauri::command]
+async fn get_system_capabilities() -> Result<hardware::SystemCapabilities, String> {
+    tauri::async_runtime::spawn_blocking(hardware::detect)
+        .await
+        .map_err(|e| format!("Capability detection failed: {}", e))
+}
+
+/// What uninstalling with `data` would delete and which folders the user
+/// chose are kept.
+#[tauri::command]
+fn get_uninstall_plan(state: State<'_, AppState>) -> uninstall::UninstallPlan {
+    uninstall_plan(&state)
+}
+
+fn uninstall_plan(state: &AppState) -> uninstall::UninstallPlan {
+    let backend_root = state
+        .sidecars
+        .get(BACKEND)
+        .and_then(|s| s.spec().cwd.clone());
+    let config = state.config.lock().unwrap();
+    uninstall::plan(&config, backend_root.as_deref())
+}
+
+/// Stops every service the shell runs, then removes the categories the
+/// user selected so an uninstall leaves nothing behind.
+#[tauri::command]
+async fn prepare_uninstall(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
-) -> Result<String, String> {
-    {
-        let guard = state.sidecar.lock().unwrap();
-        if let Some(ref sidecar) = *guard {
-            sidecar.shutdown();
+    options: uninstall::UninstallOptions,
+) -> Result<uninstall::UninstallReport, String> {
+    let _lifecycle = state.lifecycle.turn().await;
+    tauri::async_runtime::spawn_blocking(move || {
+        let state: State<AppState> = app.state();
+        state.terminal.stop();
+        remote::stop_server(&app);
+        shutdown_services(&state);
+        let plan = uninstall_plan(&state);
+        let mut config = state.config.lock().unwrap();
+        uninstall::run(&options, &plan, &mut config)
+    })
+    .await
+    .map_err(|e| format!("Uninstall task failed: {}", e))
+}
+
+/// Where the backend keeps its data: the profile's data directory, else
+/// the backend's working directory.
+fn backend_data_dir(state: &AppState) -> PathBuf {
+    let backend = state.sidecars.get(BACKEND);
+    let configured = state.config.lock().unwrap().backend_data_dir();
+    configured
+        .or_else(|| backend.and_then(|s| s.spec().cwd.clone()))
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
+}
+
+/// Runs `task` on the backend's data directory with the backend stopped,
+/// then starts it again. An error from `task` wins over a failed restart.
+async fn with_backend_stopped<T, F>(app: &tauri::AppHandle, task: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&AppState, &Path) -> Result<T, String> + Send + 'static,
+{
+    let state: State<AppState> = app.state();
+    // Held until the backend is back, so nothing restarts it mid-task
+    let _lifecycle = state.lifecycle.turn().await;
+    let handle = app.clone();
+    let (result, restarted) = tauri::async_runtime::spawn_blocking(move || {
+        let state = handle.state::<AppState>();
+        let data_dir = backend_data_dir(&state);
+        let backend = state.sidecars.get(BACKEND).filter(|b| b.is_running());
+        if let Some(ref backend) = backend {
+            backend.shutdown();
+        }
+        let result = task(&state, &data_dir);
+        let restarted = backend.map(|b| b.spawn_with_retry());
+        (result, restarted)
+    })
+    .await
+    .map_err(|e| format!("Failed to run data task: {}", e))?;
+
+    let restarted = match restarted {
+        Some(Ok(())) => wait_for_backend(app, &backend_poll_settings(app), None).await,
+        Some(Err(e)) => Err(e),
+        None => return result,
+    };
+    match restarted {
+        Ok(()) => {
+            spawn_hooks(app, hooks::HookPoint::PostBackendStart);
+            navigation::load_app(app, &app_url(app));
         }
+        Err(ref e) => eprintln!("[tauri] Backend did not come back: {}", e),
     }
+    result.and_then(|value| restarted.map(|()| value))
+}
 
-    {
-        let guard = state.sidecar.lock().unwrap();
-        if let Some(ref sidecar) = *guard {
-            sidecar.spawn_with_retry()?;
-        } else {
-            return Err("No sidecar manager available".to_string());
+/// Writes the shell config and the backend's chats to a zip archive at
+/// `path`, with the backend stopped so nothing changes mid-copy.
+#[tauri::command]
+async fn export_app_data(
+    app: tauri::AppHandle,
+    path: PathBuf,
+) -> Result<backup::BackupSummary, String> {
+    let backend_root = project_root(&app);
+    with_backend_stopped(&app, move |_, data_dir| {
+        backup::export(&path, &ShellConfig::path(), data_dir, &backend_root)
+    })
+    .await
+}
+
+/// Restores an archive from `export_app_data` and restarts the backend on
+/// it. The data directory setting stays this machine's, and secrets the
+/// keychain here doesn't have are dropped and listed in the summary.
+#[tauri::command]
+async fn import_app_data(
+    app: tauri::AppHandle,
+    path: PathBuf,
+) -> Result<backup::BackupSummary, String> {
+    let backend_root = project_root(&app);
+    with_backend_stopped(&app, move |state, data_dir| {
+        // As saved, without this run's --data-dir
+        let local_data_dir = ShellConfig::load().data_dir;
+        let mut summary = backup::import(&path, &ShellConfig::path(), data_dir, &backend_root)?;
+        let mut restored = ShellConfig::load();
+        restored.data_dir = local_data_dir;
+        summary.missing_secrets = secrets::prune_missing(&mut restored);
+        if !summary.missing_secrets.is_empty() {
+            println!(
+                "[tauri] Secrets not in this keychain, set them again: {}",
+                summary.missing_secrets.join(", ")
+            );
         }
+        restored.save()?;
+        *state.config.lock().unwrap() = cli::load_config();
+        println!("[tauri] Restored app data from {}", path.display());
+        Ok(summary)
+    })
+    .await
+}
+
+/// What the backend is working on, for the quit prompt.
+#[tauri::command]
+async fn get_backend_activity(app: tauri::AppHandle) -> Result<quit::Activity, String> {
+    quit::activity(&app)
+        .await
+        .ok_or_else(|| "The backend is not responding".to_string())
+}
+
+/// The user's answer to the quit prompt.
+#[tauri::command]
+fn confirm_quit(app: tauri::AppHandle, choice: quit::QuitChoice) {
+    quit::choose(&app, choice);
+}
+
+/// Environment for the backend from the active profile: its data directory
+/// and the secrets it can see.
+fn backend_profile_env(app: &tauri::AppHandle) -> BTreeMap<String, String> {
+    let config = app.state::<AppState>().config.lock().unwrap().clone();
+    let profile = config.active_profile();
+    let mut env = secrets::resolve(&config, profile);
+    env.insert("LOCAL_AGENT_PROFILE".to_string(), profile.to_string());
+    if let Some(ref dir) = config.backend_data_dir() {
+        env.insert(
+            "LOCAL_AGENT_DATA_DIR".to_string(),
+            dir.to_string_lossy().into_owned(),
+        );
     }
+    env
+}
+
+/// The merged config for the active profile and which values it overrides.
+#[tauri::command]
+fn get_effective_config(state: State<'_, AppState>) -> Result<profiles::EffectiveConfig, String> {
+    state.config.lock().unwrap().effective()
+}
 
-    health::poll_health(HEALTH_URL, 250, 15000).await?;
+#[derive(serde::Serialize)]
+struct ProfileInfo {
+    name: String,
+    active: bool,
+    /// `None` for the backend's working directory.
+    data_dir: Option<PathBuf>,
+    port: u16,
+}
 
-    // Navigate main window to backend URL
-    if let Some(main_window) = app.get_webview_window("main") {
-        let url: tauri::Url = BACKEND_URL.parse().unwrap();
-        let _ = main_window.navigate(url);
+/// Every profile with the data directory and port its backend uses.
+#[tauri::command]
+fn list_profiles(state: State<'_, AppState>) -> Result<Vec<ProfileInfo>, String> {
+    let config = state.config.lock().unwrap();
+    let active = config.active_profile().to_string();
+    config
+        .profile_names()
+        .into_iter()
+        .map(|name| {
+            let resolved = if name == active {
+                config.clone()
+            } else {
+                config.as_profile(&name)?
+            };
+            Ok(ProfileInfo {
+                active: name == active,
+                data_dir: resolved.backend_data_dir(),
+                port: resolved.backend_port(),
+                name,
+            })
+        })
+        .collect()
+}
+
+#[tauri::command]
+fn create_profile(state: State<'_, AppState>, name: String) -> Result<(), String> {
+    state.config.lock().unwrap().create_profile(&name)
+}
+
+/// Makes `profile` active and restarts the backend on its data directory,
+/// port and secrets.
+#[tauri::command]
+async fn switch_profile(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    profile: String,
+) -> Result<profiles::EffectiveConfig, String> {
+    let _lifecycle = state.lifecycle.turn().await;
+    let previous_env = backend_profile_env(&app);
+    let effective = {
+        let mut config = state.config.lock().unwrap();
+        config.switch_profile(&profile)?;
+        config.effective()?
+    };
+    let Some(backend) = state.sidecars.get(BACKEND) else {
+        return Ok(effective);
+    };
+
+    let mut spec = backend.spec().clone();
+    for name in previous_env.keys() {
+        spec.env.remove(name);
+    }
+    spec.env.extend(backend_profile_env(&app));
+    if bridge::active().is_none() {
+        let port = state.config.lock().unwrap().backend_port();
+        spec = spec.with_port(port);
     }
+    spec.health_probe = Some(backend_probe(&app));
+    let backoff = state.config.lock().unwrap().retry.spawn;
+    // Replacing the manager stops the old profile's backend
+    state.sidecars.insert(
+        SidecarManager::new(spec)
+            .with_trace_store(state.traces.clone())
+            .with_backoff(backoff),
+    );
+    let options = health::HealthCheckOptions::default();
+    restart_and_wait(&app, &state, "switch_profile", &options).await?;
+    Ok(effective)
+}
 
-    Ok("Backend restarted".to_string())
+/// Secret names visible to the active profile and the scope each comes from.
+/// Values are only returned by `get_secret`.
+#[tauri::command]
+fn list_secrets(state: State<'_, AppState>) -> BTreeMap<String, String> {
+    let config = state.config.lock().unwrap();
+    secrets::visible_names(&config, config.active_profile())
+}
+
+/// Stores a secret for the active profile, or for every profile when
+/// `scope` is "global".
+#[tauri::command]
+fn set_secret(
+    state: State<'_, AppState>,
+    name: String,
+    value: String,
+    scope: Option<String>,
+) -> Result<(), String> {
+    let mut config = state.config.lock().unwrap();
+    let scope = scope.unwrap_or_else(|| config.active_profile().to_string());
+    secrets::set(&mut config, &scope, &name, &value)?;
+    config.save()
+}
+
+/// The value of a secret the active profile sees, for settings screens that
+/// show or test a key; `null` when it isn't set.
+#[tauri::command]
+fn get_secret(state: State<'_, AppState>, name: String) -> Result<Option<String>, String> {
+    let config = state.config.lock().unwrap();
+    secrets::get(&config, config.active_profile(), &name)
+}
+
+#[tauri::command]
+fn delete_secret(
+    state: State<'_, AppState>,
+    name: String,
+    scope: Option<String>,
+) -> Result<(), String> {
+    let mut config = state.config.lock().unwrap();
+    let scope = scope.unwrap_or_else(|| config.active_profile().to_string());
+    secrets::delete(&mut config, &scope, &name)?;
+    config.save()
+}
+
+/// Asks for a project folder with the OS picker and lets the agent use it;
+/// `null` when the user cancels.
+#[tauri::command]
+async fn pick_directory(state: State<'_, AppState>) -> Result<Option<PathBuf>, String> {
+    let picked = tauri::async_runtime::spawn_blocking(workspace::pick_directory)
+        .await
+        .map_err(|e| format!("Folder picker failed: {}", e))??;
+    let Some(dir) = picked else {
+        return Ok(None);
+    };
+    let mut config = state.config.lock().unwrap();
+    if config.workspaces.grant(&dir)? {
+        config.save()?;
+    }
+    config.workspaces.resolve(&dir).map(Some)
+}
+
+/// Folders the user has let the agent use.
+#[tauri::command]
+fn list_workspaces(state: State<'_, AppState>) -> Vec<PathBuf> {
+    state.config.lock().unwrap().workspaces.folders.clone()
+}
+
+/// Takes a folder back, stopping any watch inside it.
+#[tauri::command]
+fn revoke_workspace(state: State<'_, AppState>, path: PathBuf) -> Result<bool, String> {
+    let mut config = state.config.lock().unwrap();
+    if !config.workspaces.revoke(&path) {
+        return Ok(false);
+    }
+    config.save()?;
+    state.workspace_watches.retain_granted(&config.workspaces);
+    Ok(true)
+}
+
+/// A text file inside a folder the user chose.
+#[tauri::command]
+fn read_workspace_file(state: State<'_, AppState>, path: PathBuf) -> Result<String, String> {
+    let workspaces = state.config.lock().unwrap().workspaces.clone();
+    workspace::read_file(&workspaces, &path)
+}
+
+/// Sends `workspace-changed` events to this window as files under `path`
+/// change; returns the subscription to pass to `unwatch_directory`.
+#[tauri::command]
+fn watch_directory(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    state: State<'_, AppState>,
+    path: PathBuf,
+) -> Result<u64, String> {
+    let root = state.config.lock().unwrap().workspaces.resolve(&path)?;
+    if !root.is_dir() {
+        return Err(format!("{} is not a folder", root.display()));
+    }
+    state.workspace_watches.watch(&app, window.label(), root)
+}
+
+#[tauri::command]
+fn unwatch_directory(
+    window: tauri::WebviewWindow,
+    state: State<'_, AppState>,
+    subscription: u64,
+) -> bool {
+    state
+        .workspace_watches
+        .unwatch(window.label(), subscription)
+}
+
+/// Captures the screen, the active window or a selected region once the
+/// user agrees; `null` when they decline or cancel the selection.
+#[tauri::command]
+async fn capture_screenshot(
+    app: tauri::AppHandle,
+    mode: screenshot::CaptureMode,
+) -> Result<Option<screenshot::Screenshot>, String> {
+    screenshot::capture(&app, mode).await
+}
+
+/// The user's answer to the screenshot prompt.
+#[tauri::command]
+fn answer_screenshot_prompt(state: State<'_, AppState>, allow: bool) {
+    state.screenshot.answer(allow);
+}
+
+/// Advances the splash to `stage` and starts timing it.
+fn boot_stage(app: &tauri::AppHandle, stage: &'static str, message: &str) {
+    app.state::<AppState>().boot.lock().unwrap().mark(stage);
+    splash::set_status(app, stage, message);
+}
+
+/// Records that the boot reached `milestone`.
+fn boot_milestone(app: &tauri::AppHandle, milestone: &str) {
+    app.state::<AppState>()
+        .boot
+        .lock()
+        .unwrap()
+        .milestone(milestone);
+}
+
+/// Persists the boot timeline; only the first call per launch records.
+fn finish_boot(app: &tauri::AppHandle, success: bool) {
+    let state = app.state::<AppState>();
+    let keep = state.config.lock().unwrap().startup.history;
+    let record = {
+        let mut boot = state.boot.lock().unwrap();
+        if let Some(at) = state.logs.first_line_at(BACKEND) {
+            boot.milestone_at("first_log_line", at);
+        }
+        boot.finish(success)
+    };
+    if let Some(record) = record {
+        std::thread::spawn(move || {
+            if let Err(e) = startup::save_record(record, keep) {
+                eprintln!("[tauri] {}", e);
+            }
+        });
+    }
+}
+
+#[derive(serde::Serialize)]
+struct StartupStats {
+    records: Vec<startup::BootRecord>,
+    poll_settings: health::PollSettings,
+}
+
+#[tauri::command]
+fn get_startup_stats(state: State<'_, AppState>) -> StartupStats {
+    let (config, policy) = {
+        let config = state.config.lock().unwrap();
+        (config.startup.clone(), config.retry.health)
+    };
+    let records = startup::load_records();
+    StartupStats {
+        poll_settings: startup::tune(&config, &policy, &records),
+        records,
+    }
+}
+
+#[derive(serde::Serialize)]
+struct StartupTimings {
+    /// Milestones reached so far this launch.
+    current: Vec<startup::Milestone>,
+    /// Previous boots, oldest first.
+    history: Vec<startup::BootRecord>,
+}
+
+/// Cold-start milestones for this launch and the ones before it.
+#[tauri::command]
+fn get_startup_timings(state: State<'_, AppState>) -> StartupTimings {
+    StartupTimings {
+        current: state.boot.lock().unwrap().milestones().to_vec(),
+        history: startup::load_records(),
+    }
+}
+
+/// Keeps one OS wake armed ahead of the next scheduled job. Re-checks
+/// periodically so config edits and passed wake times are picked up.
+fn start_wake_scheduler(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_scheduled = None;
+        loop {
+            let wake_config = {
+                let state: State<AppState> = app.state();
+                let config = state.config.lock().unwrap();
+                config.wake.clone()
+            };
+
+            if wake_config.enabled && wake::capability().supported {
+                let next = wake::next_wake_time(&wake_config, chrono::Local::now());
+                if let Some(at) = next.filter(|at| Some(*at) != last_scheduled) {
+                    match wake::schedule_wake(at) {
+                        Ok(()) => last_scheduled = Some(at),
+                        Err(e) => eprintln!("[tauri] Failed to schedule wake: {}", e),
+                    }
+                }
+            }
+
+            tokio::time::sleep(Duration::from_secs(15 * 60)).await;
+        }
+    });
+}
+
+fn show_error(
+    app: &tauri::AppHandle,
+    message: &str,
+    log_lines: &[String],
+    source: Option<error_page::LogSource>,
+) {
+    show_error_page(
+        app,
+        &error_page::generate_error_html(message, log_lines, source),
+    );
+}
+
+/// Shows the backend failure page with log lines from the configured
+/// source, tailored to the failure when it is a known kind.
+fn show_backend_error(app: &tauri::AppHandle, message: &str) {
+    let state = app.state::<AppState>();
+    let context = error_context(&state, None, None);
+    let exit = state
+        .sidecars
+        .get(BACKEND)
+        .and_then(|backend| backend.last_exit_info());
+    // Classified on the latest output whatever source the page shows
+    let recent = state.logs.recent_lines(BACKEND, CRASH_SNAPSHOT_LINES);
+    match diagnosis::classify(exit.as_ref(), &recent) {
+        Some(class) => {
+            eprintln!("[tauri] Backend failure looks like: {}", class.title());
+            show_error_page(
+                app,
+                &error_page::generate_diagnosed_error_html(
+                    &class,
+                    &context.lines,
+                    Some(context.source),
+                ),
+            );
+        }
+        None => show_error(app, message, &context.lines, Some(context.source)),
+    }
 }
 
-fn show_error(app: &tauri::AppHandle, message: &str, log_lines: &[String]) {
-    let error_html = error_page::generate_error_html(message, log_lines);
+fn show_error_page(app: &tauri::AppHandle, error_html: &str) {
+    // Any error page shown before the UI came up ends the boot as failed
+    finish_boot(app, false);
+    app.state::<AppState>().reconnect.dismiss();
+    navigation::load_error(app, error_html);
     if let Some(main_window) = app.get_webview_window("main") {
-        let js = format!(
-            "document.open(); document.write({}); document.close();",
-            serde_json::to_string(&error_html).unwrap()
-        );
-        let _ = main_window.eval(&js);
         let _ = main_window.show();
     }
+    splash::close(app);
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    startup::mark_launch();
+    crash::install();
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(options) = bench::BenchOptions::from_args(&args) {
+        let code = match options {
+            Ok(options) => bench::run(options),
+            Err(e) => {
+                eprintln!("[tauri] {}", e);
+                2
+            }
+        };
+        std::process::exit(code);
+    }
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    match cli::CliOptions::parse(&args, &cwd) {
+        Ok(options) if options.help => {
+            println!("{}", cli::USAGE);
+            std::process::exit(0);
+        }
+        Ok(options) => cli::init(options),
+        Err(e) => {
+            eprintln!("[tauri] {}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    }
+
+    let mut builder = tauri::Builder::default();
+    // First, so a second launch hands over its arguments (and any deep link)
+    // to the running instance and exits before starting anything
+    #[cfg(desktop)]
+    {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            if let Some(main_window) = app.get_webview_window("main") {
+                let _ = main_window.unminimize();
+                let _ = main_window.set_focus();
+            }
+            let files = fileopen::from_args(&argv, Path::new(&cwd));
+            app.state::<AppState>().deep_links.handle_files(app, files);
+        }));
+    }
+
+    builder
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_shell::init())
-        .manage(AppState {
-            sidecar: Mutex::new(None),
+        .plugin(tauri_plugin_opener::init())
+        .register_uri_scheme_protocol(pages::SCHEME, |_ctx, request| pages::handle(request))
+        .register_asynchronous_uri_scheme_protocol(bridge::SCHEME, |ctx, request, responder| {
+            idle::wake(ctx.app_handle());
+            bridge::handle(request, responder)
+        })
+        .register_uri_scheme_protocol(offline::SCHEME, |ctx, request| {
+            let app = ctx.app_handle();
+            let chats_dir = backend_data_dir(&app.state::<AppState>()).join("chats");
+            offline::handle(app, &request, &chats_dir)
+        })
+        .manage({
+            let logs = Arc::new(logstream::LogBatcher::default());
+            AppState {
+                sidecars: Arc::new(SidecarRegistry::with_log_stream(logs.clone())),
+                config: Mutex::new(cli::load_config()),
+                remote: remote::RemoteState::default(),
+                mdns: mdns::MdnsState::default(),
+                traces: Arc::new(trace::TraceStore::default()),
+                terminal: terminal::TerminalState::default(),
+                monitor: Arc::new(monitor::ResourceMonitor::default()),
+                metrics: metrics::MetricsHistory::default(),
+                boot: Mutex::new(startup::BootTimer::default()),
+                logs,
+                events: events::EventLog::default(),
+                deep_links: deeplink::DeepLinkRouter::default(),
+                backend_state: state::BackendStateMachine::default(),
+                reconnect: reconnect::Interstitial::default(),
+                lifecycle: lifecycle::Lifecycle::default(),
+                navigation: navigation::Navigator::default(),
+                health_cancels: health::HealthCancels::default(),
+                restarts: restarts::RestartHistory::default(),
+                maintenance: scheduler::Scheduler::default(),
+                previous_backend: upgrade::Previous::default(),
+                streams: bridge::StreamTracker::default(),
+                windows: windows::WindowTracker::default(),
+                shutdown: shutdown::ShutdownOnce::default(),
+                quit: quit::QuitGuard::default(),
+                tails: tail::Tails::default(),
+                workspace_watches: workspace::Watches::default(),
+                screenshot: screenshot::ScreenshotPrompt::default(),
+                network: network::NetworkMonitor::default(),
+                idle: idle::IdleTracker::default(),
+            }
+        })
+        .invoke_handler(tauri::generate_handler![
+            restart_backend,
+            restart_backend_soft,
+            upgrade_backend,
+            rollback_backend_upgrade,
+            check_backend_health_once,
+            cancel_health_check,
+            ack_backend_ready,
+            send_backend_command,
+            open_logs_folder,
+            open_log_file,
+            get_wake_status,
+            set_wake_on_schedule,
+            set_remote_control_enabled,
+            start_remote_pairing,
+            list_paired_devices,
+            revoke_paired_device,
+            push_remote_prompt,
+            clear_remote_prompt,
+            set_mdns_advertise,
+            discover_agents,
+            start_trace,
+            record_trace_event,
+            get_trace,
+            list_sidecars,
+            get_sidecar_status,
+            restart_sidecar,
+            open_terminal,
+            terminal_start,
+            terminal_write,
+            terminal_resize,
+            get_ollama_status,
+            start_ollama,
+            list_local_models,
+            get_disk_report,
+            check_download_space,
+            clean_rotated_logs,
+            clean_crash_reports,
+            get_uninstall_plan,
+            prepare_uninstall,
+            export_app_data,
+            import_app_data,
+            get_app_info,
+            get_system_capabilities,
+            run_preflight_checks,
+            get_resource_usage,
+            get_metrics_history,
+            get_maintenance_status,
+            get_startup_stats,
+            get_startup_timings,
+            set_log_streaming,
+            open_log_viewer,
+            get_log_history,
+            read_log_range,
+            get_log_metadata,
+            get_recent_events,
+            get_error_context,
+            get_recent_logs,
+            tail_logs,
+            untail_logs,
+            get_effective_config,
+            list_profiles,
+            create_profile,
+            switch_profile,
+            list_secrets,
+            set_secret,
+            get_secret,
+            delete_secret,
+            pick_directory,
+            list_workspaces,
+            revoke_workspace,
+            read_workspace_file,
+            watch_directory,
+            unwatch_directory,
+            capture_screenshot,
+            answer_screenshot_prompt,
+            open_window,
+            stream_backend,
+            cancel_backend_stream,
+            open_preferences,
+            open_about,
+            get_settings,
+            set_zoom,
+            get_zoom,
+            get_setting,
+            set_setting,
+            get_autostart,
+            set_autostart,
+            get_last_shutdown_report,
+            get_restart_history,
+            get_crash_reports,
+            get_backend_state,
+            get_offline_mode,
+            get_backend_activity,
+            confirm_quit,
+            check_for_updates,
+            test_connectivity,
+            get_network_status,
+            get_network_exposure,
+            set_network_exposure,
+            run_self_test,
+            open_update_page,
+            open_issue_report
+        ])
+        // New windows start at 100%, and some platforms reset the zoom when
+        // a page loads (the app after a restart, the error page). Main window
+        // loads also confirm `navigation::load_app` worked
+        .on_page_load(|webview, payload| {
+            if payload.event() == tauri::webview::PageLoadEvent::Finished {
+                let state = webview.state::<AppState>();
+                if webview.label() == "main" {
+                    state.navigation.finished(payload.url());
+                }
+                let zoom = state.config.lock().unwrap().settings.zoom;
+                if let Err(e) = webview.set_zoom(zoom) {
+                    eprintln!("[tauri] Failed to set zoom on {}: {}", webview.label(), e);
+                }
+            }
         })
-        .invoke_handler(tauri::generate_handler![restart_backend])
         .setup(|app| {
             let app_handle = app.handle().clone();
+            crash::attach(&app_handle);
+            syslog::init(&app.state::<AppState>().config.lock().unwrap().system_log);
+            redact::init(&app.state::<AppState>().config.lock().unwrap().redaction);
+            tauri::async_runtime::spawn_blocking(ingest::clean_staging);
+            // Before anything is spawned, so sidecars and the Node.js check
+            // see the login shell's PATH
+            environment::resolve_login_shell(&app.state::<AppState>().config.lock().unwrap().env);
+            proxy::init(&app.state::<AppState>().config.lock().unwrap().proxy);
+
+            match menu::create_menu(&app_handle) {
+                Ok(app_menu) => {
+                    if let Err(e) = app.set_menu(app_menu) {
+                        eprintln!("[tauri] Failed to set app menu: {}", e);
+                    }
+                }
+                Err(e) => eprintln!("[tauri] Failed to create app menu: {}", e),
+            }
+            app.on_menu_event(menu::handle_event);
+
+            let theme = app
+                .state::<AppState>()
+                .config
+                .lock()
+                .unwrap()
+                .settings
+                .theme;
+            app.set_theme(theme.window_theme());
+
+            let run_in_background = app
+                .state::<AppState>()
+                .config
+                .lock()
+                .unwrap()
+                .settings
+                .run_in_background;
+            if let Err(e) = tray::create_tray(&app_handle, run_in_background) {
+                eprintln!("[tauri] Failed to create tray icon: {}", e);
+            }
+
+            start_wake_scheduler(app_handle.clone());
+            power::start_resume_watcher(app_handle.clone());
+            start_sidecar_watchdog(&app_handle);
+            start_resource_monitor(&app_handle);
+            hang::start(app_handle.clone());
+            idle::start(app_handle.clone());
+            network::start(app_handle.clone());
+            start_metrics_scraper(&app_handle);
+            start_maintenance_scheduler(&app_handle);
+            start_log_stream(&app_handle);
+            deeplink::start(&app_handle);
+
+            let crash_reports = app
+                .state::<AppState>()
+                .config
+                .lock()
+                .unwrap()
+                .crash_reports
+                .clone();
+            tauri::async_runtime::spawn(async move {
+                crash::upload_pending(&crash_reports).await;
+            });
+
+            let remote_enabled = app.state::<AppState>().config.lock().unwrap().remote.enabled;
+            if remote_enabled {
+                remote::start_server(&app_handle);
+            }
 
             // Resolve project root
-            let project_root = if is_dev() {
-                std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
-            } else {
-                app_handle
-                    .path()
-                    .resource_dir()
-                    .unwrap_or_else(|_| {
-                        std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
-                    })
-            };
+            let project_root = project_root(&app_handle);
+            let chosen =
+                paths::project_root_override(&app.state::<AppState>().config.lock().unwrap());
+            if let Some((_, source)) = chosen {
+                println!(
+                    "[tauri] Running the backend from {} (set by {})",
+                    project_root.display(),
+                    source
+                );
+                if !project_root.is_dir() {
+                    eprintln!("[tauri] {} is not a directory", project_root.display());
+                }
+            }
+            if let Some(root) = paths::portable_root() {
+                println!("[tauri] Portable mode: keeping files in {}", root.display());
+            }
 
-            let spawn_sidecar = !is_dev();
+            // Headless runs and logins into the tray get no window (and no
+            // splash) until the tray opens one
+            if !autostart::launched_minimized() && !cli::options().headless {
+                if let Err(e) = create_main_window(&app_handle) {
+                    eprintln!("[tauri] {}", e);
+                }
+                splash::show(&app_handle);
+            }
+
+            let spawn_sidecar = !cli::options().no_sidecar
+                && (!is_dev()
+                    || app
+                        .state::<AppState>()
+                        .config
+                        .lock()
+                        .unwrap()
+                        .dev
+                        .spawns_backend());
 
             if spawn_sidecar {
-                let manager = SidecarManager::new(project_root);
+                check_disk_space(&app_handle);
+                if !is_dev() {
+                    match integrity::verify(&project_root) {
+                        Ok(0) => {}
+                        Ok(count) => println!("[tauri] Verified {} backend files", count),
+                        Err(problem) => {
+                            let detail = format!("integrity check failed: {}", problem.message());
+                            set_backend_state(&app_handle, BackendState::Failed, Some(&detail));
+                            show_error_page(
+                                &app_handle,
+                                &error_page::generate_titled_error_html(
+                                    problem.title(),
+                                    &problem.message(),
+                                    &problem.remediation(),
+                                ),
+                            );
+                            return Ok(());
+                        }
+                    }
+                }
 
-                match manager.spawn_with_retry() {
-                    Ok(()) => {
-                        println!("[tauri] Sidecar spawned, starting health check...");
+                // Dev always runs the backend from source
+                let selection = if is_dev() {
+                    Ok(None)
+                } else {
+                    binaries::select(&project_root)
+                };
+                let backend_binary = match selection {
+                    Ok(binary) => binary,
+                    Err(problem) => {
+                        let detail = format!("binary selection failed: {}", problem.message());
+                        set_backend_state(&app_handle, BackendState::Failed, Some(&detail));
+                        show_error_page(
+                            &app_handle,
+                            &error_page::generate_titled_error_html(
+                                problem.title(),
+                                &problem.message(),
+                                &problem.remediation(),
+                            ),
+                        );
+                        return Ok(());
                     }
+                };
+                let vars = template_vars(&app_handle, &project_root);
+                let spec = match backend_binary {
+                    Some(ref binary) => SidecarSpec::backend_binary(binary.clone(), project_root),
+                    None if is_dev() => SidecarSpec::dev_backend(project_root),
+                    None => SidecarSpec::backend(project_root),
+                };
+                let user_settings = app
+                    .state::<AppState>()
+                    .config
+                    .lock()
+                    .unwrap()
+                    .settings
+                    .clone();
+                let port = app
+                    .state::<AppState>()
+                    .config
+                    .lock()
+                    .unwrap()
+                    .backend_port();
+                let backend_command = app
+                    .state::<AppState>()
+                    .config
+                    .lock()
+                    .unwrap()
+                    .backend
+                    .clone();
+                let mut spec = match backend_command.apply(spec.with_port(port), &vars) {
+                    Ok(spec) => spec,
                     Err(e) => {
-                        eprintln!("[tauri] Sidecar spawn failed: {}", e);
-                        let log_lines = manager.read_last_log_lines(20);
-                        let state: State<AppState> = app.state();
-                        *state.sidecar.lock().unwrap() = Some(manager);
-                        show_error(&app_handle, &e, &log_lines);
+                        let detail = format!("Invalid backend command in config: {}", e);
+                        set_backend_state(&app_handle, BackendState::Failed, Some(&detail));
+                        show_backend_error(&app_handle, &detail);
                         return Ok(());
                     }
+                };
+                if backend_command.is_set() {
+                    println!(
+                        "[tauri] Running the backend as configured: {} {}",
+                        spec.command,
+                        spec.args.join(" ")
+                    );
                 }
+                if user_settings.transport == settings::BackendTransport::Socket && !is_dev() {
+                    let socket = bridge::socket_path();
+                    match bridge::prepare(&socket) {
+                        Ok(()) => {
+                            spec = spec.with_socket(&socket);
+                            bridge::activate(socket);
+                        }
+                        Err(e) => eprintln!("[tauri] {}; using a TCP port instead", e),
+                    }
+                }
+                spec.env.insert(
+                    "LOG_LEVEL".to_string(),
+                    user_settings.log_level.as_str().to_string(),
+                );
+                spec.env.extend(backend_profile_env(&app_handle));
+                spec.env.extend(proxy::current().env());
+                let (allow_env, show_console, exposure_env) = {
+                    let state = app.state::<AppState>();
+                    let config = state.config.lock().unwrap();
+                    (
+                        config.env.allow.clone(),
+                        config.dev.show_console,
+                        config.exposure.env(),
+                    )
+                };
+                spec.env.extend(exposure_env);
+                spec.inherit_env.extend(allow_env);
+                spec.show_console = show_console;
+                spec.health_probe = Some(backend_probe(&app_handle));
 
+                // Registered up front so Retry works even if the first start fails
                 let state: State<AppState> = app.state();
-                *state.sidecar.lock().unwrap() = Some(manager);
+                state.sidecars.insert(
+                    SidecarManager::new(spec)
+                        .with_trace_store(state.traces.clone())
+                        .with_backoff(state.config.lock().unwrap().retry.spawn),
+                );
+
+                // Compiled backends bundle their own runtime, and a custom
+                // command is the user's to vouch for
+                if backend_binary.is_none() && backend_command.command.is_none() {
+                    boot_stage(&app_handle, "preflight", "Checking Node.js…");
+                    if let Err(problem) = node::check() {
+                        let detail = format!("Node.js preflight failed: {}", problem.message());
+                        set_backend_state(&app_handle, BackendState::Failed, Some(&detail));
+                        show_error_page(
+                            &app_handle,
+                            &error_page::generate_titled_error_html(
+                                problem.title(),
+                                &problem.message(),
+                                &problem.remediation(),
+                            ),
+                        );
+                        return Ok(());
+                    }
+                }
+
+                boot_stage(&app_handle, "spawning", "Starting backend…");
+                set_backend_state(&app_handle, BackendState::Spawning, None);
             }
 
-            // Health check then show UI
+            register_extra_sidecars(&app_handle);
+            // What the backend depends on starts first, what depends on
+            // the backend once it is healthy
+            let (before_backend, after_backend) = deps::plan(&app_handle);
+
+            // Wait for the backend's ready line or health check, then show
+            // the UI; polling is tuned by previous boots
+            let poll_settings = {
+                let state = app.state::<AppState>();
+                let config = state.config.lock().unwrap();
+                startup::tune(&config.startup, &config.retry.health, &startup::load_records())
+            };
+            println!("[tauri] Health polling: {:?}", poll_settings);
             tauri::async_runtime::spawn(async move {
-                match health::poll_health(HEALTH_URL, 250, 15000).await {
+                if let Err(e) = deps::start(&app_handle, before_backend).await {
+                    set_backend_state(&app_handle, BackendState::Failed, Some(&e));
+                    show_backend_error(&app_handle, &e);
+                    spawn_hooks(&app_handle, hooks::HookPoint::PostCrash);
+                    return;
+                }
+                boot_stage(&app_handle, "health", "Waiting for backend…");
+                set_backend_state(&app_handle, BackendState::WaitingHealthy, None);
+                match wait_for_backend(&app_handle, &poll_settings, None).await {
                     Ok(()) => {
+                        boot_milestone(&app_handle, "health_passed");
+                        set_backend_state(&app_handle, BackendState::Healthy, None);
+                        let app = app_handle.clone();
+                        tauri::async_runtime::spawn(async move {
+                            let _ = deps::start(&app, after_backend).await;
+                        });
+                        let package_dir = backend_package_dir(&app_handle);
+                        if let Err(mismatch) =
+                            compat::check(&app_handle, package_dir.as_deref()).await
+                        {
+                            eprintln!(
+                                "[tauri] Backend {} is not supported by this shell (expects {})",
+                                mismatch.backend_version, mismatch.supported
+                            );
+                            show_error_page(
+                                &app_handle,
+                                &error_page::generate_incompatible_html(
+                                    &mismatch,
+                                    &app_url(&app_handle),
+                                ),
+                            );
+                            return;
+                        }
+                        splash::set_status(&app_handle, "ready", "Ready");
                         if let Some(main_window) = app_handle.get_webview_window("main") {
                             // In production, navigate to backend (serves built frontend)
                             if !is_dev() {
-                                let url: tauri::Url = BACKEND_URL.parse().unwrap();
-                                let _ = main_window.navigate(url);
+                                navigation::load_app(&app_handle, &backend_url(&app_handle));
                             }
                             let _ = main_window.show();
                             let _ = main_window.set_focus();
+                            boot_milestone(&app_handle, "window_shown");
                         }
+                        finish_boot(&app_handle, true);
+                        app_handle
+                            .state::<AppState>()
+                            .deep_links
+                            .mark_ready(&app_handle);
+                        splash::close(&app_handle);
+                        spawn_hooks(&app_handle, hooks::HookPoint::PostBackendStart);
                     }
                     Err(e) => {
-                        eprintln!("[tauri] Health check failed: {}", e);
-
-                        let log_lines = if !is_dev() {
-                            let state: State<AppState> = app_handle.state();
-                            let guard = state.sidecar.lock().unwrap();
-                            guard
-                                .as_ref()
-                                .map(|s| s.read_last_log_lines(20))
-                                .unwrap_or_default()
+                        set_backend_state(&app_handle, BackendState::Failed, Some(&e));
+
+                        let supervised = app_handle
+                            .state::<AppState>()
+                            .sidecars
+                            .get(BACKEND)
+                            .is_some();
+                        if supervised {
+                            show_backend_error(&app_handle, &e);
+                        } else if cli::options().no_sidecar {
+                            let log_lines = vec![
+                                "Started with --no-sidecar: no backend answered.".to_string(),
+                                "Start one, or launch without --no-sidecar.".to_string(),
+                            ];
+                            show_error(&app_handle, &e, &log_lines, None);
                         } else {
-                            vec![
+                            let log_lines = vec![
                                 "Dev mode: backend not responding.".to_string(),
                                 "Start it with: npm run dev:backend".to_string(),
-                            ]
-                        };
-
-                        show_error(&app_handle, &e, &log_lines);
+                            ];
+                            show_error(&app_handle, &e, &log_lines, None);
+                        }
+                        spawn_hooks(&app_handle, hooks::HookPoint::PostCrash);
                     }
                 }
             });
@@ -148,13 +3069,100 @@ pub fn run() {
         })
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::Destroyed = event {
-                let state: State<AppState> = window.state();
-                let guard = state.sidecar.lock().unwrap();
-                if let Some(ref sidecar) = *guard {
-                    sidecar.shutdown();
+                // A window's log tails and folder watches end with it
+                let state = window.state::<AppState>();
+                state.tails.close_window(window.label());
+                state.workspace_watches.close_window(window.label());
+            }
+            if window.label() == terminal::TERMINAL_LABEL {
+                if let tauri::WindowEvent::Destroyed = event {
+                    window.state::<AppState>().terminal.stop();
+                }
+                return;
+            }
+            if window.label() == screenshot::SCREENSHOT_LABEL {
+                // Closing the prompt declines
+                if let tauri::WindowEvent::Destroyed = event {
+                    window.state::<AppState>().screenshot.answer(false);
+                }
+                return;
+            }
+            if window.label() == quit::QUIT_LABEL {
+                // Closing the prompt is the same as Cancel
+                if let tauri::WindowEvent::Destroyed = event {
+                    window.state::<AppState>().quit.cancel();
                 }
+                return;
+            }
+            // App windows share the backend; closing the splash or any
+            // auxiliary window must not stop it.
+            if !windows::is_app_window(window.label()) {
+                return;
+            }
+            let state: State<AppState> = window.state();
+            match event {
+                tauri::WindowEvent::Focused(true) => idle::wake(window.app_handle()),
+                tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+                    let config = state.config.lock().unwrap().ingest.clone();
+                    ingest::dropped(window.app_handle(), config, paths.clone());
+                }
+                // Others stay open: this one just closes
+                tauri::WindowEvent::CloseRequested { .. }
+                    if state.windows.others(window.label()) > 0 => {}
+                tauri::WindowEvent::CloseRequested { api, .. } => {
+                    let (close_to_tray, run_in_background) = {
+                        let config = state.config.lock().unwrap();
+                        (
+                            config.settings.close_to_tray,
+                            config.settings.run_in_background,
+                        )
+                    };
+                    if close_to_tray {
+                        // Quit stays in the tray and app menus
+                        api.prevent_close();
+                        let _ = window.hide();
+                    } else if !run_in_background {
+                        // Closing stops the backend; check for work in flight
+                        api.prevent_close();
+                        quit::request(
+                            window.app_handle(),
+                            quit::QuitAction::Close(window.label().to_string()),
+                        );
+                    }
+                }
+                tauri::WindowEvent::Destroyed => {
+                    let remaining = state.windows.closed(window.label());
+                    // In the background the tray can open a new window
+                    if remaining == 0 && !state.config.lock().unwrap().settings.run_in_background {
+                        stop_services_in_background(window.app_handle());
+                    }
+                }
+                _ => {}
             }
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            // Closing the last window doesn't quit while running in the
+            // background; Quit (which sets an exit code) still does
+            tauri::RunEvent::ExitRequested {
+                code: None, api, ..
+            } if app
+                .state::<AppState>()
+                .config
+                .lock()
+                .unwrap()
+                .settings
+                .run_in_background =>
+            {
+                api.prevent_exit();
+            }
+            tauri::RunEvent::Exit => stop_services(&app.state::<AppState>()),
+            #[cfg(target_os = "macos")]
+            tauri::RunEvent::Reopen {
+                has_visible_windows: false,
+                ..
+            } => show_main_window(app),
+            _ => {}
+        });
 }
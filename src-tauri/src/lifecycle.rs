@@ -0,0 +1,87 @@
+//! One lifecycle operation at a time. Restarts, profile switches and
+//! upgrades each stop and spawn processes; two of them interleaving can
+//! leave a duplicate or orphaned backend. Operations take turns, and a
+//! caller asking for an operation that is already running (a second click
+//! on Restart) waits for it and gets its result instead of running it again.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use tokio::sync::{watch, MutexGuard};
+
+type Outcome = Option<Result<(), String>>;
+
+#[derive(Default)]
+pub struct Lifecycle {
+    turn: tokio::sync::Mutex<()>,
+    /// Running or waiting operations, by name, with their result once done.
+    in_flight: Mutex<HashMap<String, watch::Receiver<Outcome>>>,
+}
+
+impl Lifecycle {
+    /// Waits for the running operation, if any, to finish; no other starts
+    /// until the guard is dropped.
+    pub async fn turn(&self) -> MutexGuard<'_, ()> {
+        self.turn.lock().await
+    }
+
+    /// Runs `operation` in its turn, or joins it when it is already running
+    /// or waiting.
+    pub async fn run(
+        &self,
+        operation: &str,
+        work: impl Future<Output = Result<(), String>>,
+    ) -> Result<(), String> {
+        let done = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get(operation) {
+                Some(running) => Err(running.clone()),
+                None => {
+                    let (done, outcome) = watch::channel(None);
+                    in_flight.insert(operation.to_string(), outcome);
+                    Ok(done)
+                }
+            }
+        };
+        let done = match done {
+            Ok(done) => done,
+            Err(mut running) => {
+                println!(
+                    "[tauri] {} is already in progress; waiting for it",
+                    operation
+                );
+                return match running.wait_for(Option::is_some).await {
+                    Ok(outcome) => outcome.clone().unwrap_or(Ok(())),
+                    Err(_) => Err(format!("{} was interrupted", operation)),
+                };
+            }
+        };
+
+        let finished = Finished {
+            lifecycle: self,
+            operation,
+        };
+        let _turn = self.turn().await;
+        let result = work.await;
+        drop(finished);
+        done.send_replace(Some(result.clone()));
+        result
+    }
+}
+
+/// Takes an operation off the in-flight list, also when its caller gives up
+/// on it part way.
+struct Finished<'a> {
+    lifecycle: &'a Lifecycle,
+    operation: &'a str,
+}
+
+impl Drop for Finished<'_> {
+    fn drop(&mut self) {
+        self.lifecycle
+            .in_flight
+            .lock()
+            .unwrap()
+            .remove(self.operation);
+    }
+}
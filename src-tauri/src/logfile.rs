@@ -0,0 +1,163 @@
+//! Paged reads of a log file on disk, so a viewer can walk a large log a
+//! page at a time instead of the shell sending all of it. Pages are addressed
+//! by byte offset: each page returns where the next one starts.
+
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Upper bound on lines per page, whatever the caller asks for.
+pub const MAX_PAGE_LINES: usize = 5000;
+/// Bytes sampled from the start of the file to estimate the line count.
+const SAMPLE_BYTES: u64 = 64 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRange {
+    pub offset: u64,
+    /// Offset to pass for the following page.
+    pub next_offset: u64,
+    pub size: u64,
+    pub lines: Vec<String>,
+    pub eof: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RotatedLog {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogMetadata {
+    pub path: PathBuf,
+    pub exists: bool,
+    pub size: u64,
+    pub modified: Option<String>,
+    /// Exact for files smaller than the sample, extrapolated otherwise.
+    pub estimated_lines: u64,
+    /// Older files next to the log (`local-agent-backend.log.1`,
+    /// `local-agent-backend.1.log`, …), by name.
+    pub rotated: Vec<RotatedLog>,
+}
+
+/// Reads up to `limit` lines starting at byte `offset`. An offset inside a
+/// line starts at the next full line; an offset past the end (the file was
+/// truncated) returns an empty page at the end.
+pub fn read_range(path: &Path, offset: u64, limit: usize) -> Result<LogRange, String> {
+    let file = File::open(path)
+        .map_err(|e| format!("Failed to open log file {}: {}", path.display(), e))?;
+    let size = file
+        .metadata()
+        .map_err(|e| format!("Failed to read log file metadata: {}", e))?
+        .len();
+    let offset = offset.min(size);
+    let mut reader = BufReader::new(file);
+    reader
+        .seek(SeekFrom::Start(offset.saturating_sub(1)))
+        .map_err(|e| format!("Failed to seek log file: {}", e))?;
+
+    let mut position = offset.saturating_sub(1);
+    let mut buf = Vec::new();
+    if offset > 0 {
+        // Skip the rest of a partial line unless `offset` is a line start
+        position += read_line(&mut reader, &mut buf)?;
+    }
+    let start = position;
+
+    let mut lines = Vec::new();
+    while lines.len() < limit.min(MAX_PAGE_LINES) {
+        let read = read_line(&mut reader, &mut buf)?;
+        if read == 0 {
+            break;
+        }
+        position += read;
+        let text = String::from_utf8_lossy(&buf);
+        lines.push(text.trim_end_matches(['\n', '\r']).to_string());
+    }
+
+    Ok(LogRange {
+        offset: start,
+        next_offset: position,
+        size,
+        lines,
+        eof: position >= size,
+    })
+}
+
+fn read_line(reader: &mut BufReader<File>, buf: &mut Vec<u8>) -> Result<u64, String> {
+    buf.clear();
+    reader
+        .read_until(b'\n', buf)
+        .map(|n| n as u64)
+        .map_err(|e| format!("Failed to read log file: {}", e))
+}
+
+pub fn metadata(path: &Path) -> LogMetadata {
+    let meta = fs::metadata(path).ok();
+    let size = meta.as_ref().map(|m| m.len()).unwrap_or(0);
+    LogMetadata {
+        path: path.to_path_buf(),
+        exists: meta.is_some(),
+        size,
+        modified: meta
+            .and_then(|m| m.modified().ok())
+            .map(|t| chrono::DateTime::<chrono::Local>::from(t).to_rfc3339()),
+        estimated_lines: estimate_lines(path, size),
+        rotated: rotated(path),
+    }
+}
+
+fn estimate_lines(path: &Path, size: u64) -> u64 {
+    let mut sample = Vec::new();
+    let Ok(file) = File::open(path) else {
+        return 0;
+    };
+    if file.take(SAMPLE_BYTES).read_to_end(&mut sample).is_err() || sample.is_empty() {
+        return 0;
+    }
+    let newlines = sample.iter().filter(|&&b| b == b'\n').count() as u64;
+    let sampled = sample.len() as u64;
+    if sampled >= size {
+        newlines + u64::from(sample.last() != Some(&b'\n'))
+    } else {
+        (newlines * size).div_ceil(sampled).max(1)
+    }
+}
+
+/// Older copies of the log at `path`, by name.
+pub fn rotated(path: &Path) -> Vec<RotatedLog> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name().and_then(|n| n.to_str())) else {
+        return Vec::new();
+    };
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(name);
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    // The part that varies (a number or a date) comes after the full name
+    // or between the stem and `.log`
+    let is_rotation = |other: &str| {
+        let suffix = other
+            .strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix('.'))
+            .or_else(|| {
+                other
+                    .strip_prefix(stem)
+                    .and_then(|rest| rest.strip_prefix('.'))
+                    .and_then(|rest| rest.strip_suffix(".log"))
+            });
+        suffix.is_some_and(|s| s.starts_with(|c: char| c.is_ascii_digit()))
+    };
+    let mut rotated: Vec<RotatedLog> = entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_str().is_some_and(is_rotation))
+        .filter_map(|entry| {
+            Some(RotatedLog {
+                size: entry.metadata().ok()?.len(),
+                path: entry.path(),
+            })
+        })
+        .collect();
+    rotated.sort_by(|a, b| a.path.cmp(&b.path));
+    rotated
+}
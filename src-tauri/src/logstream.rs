@@ -0,0 +1,276 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::template;
+
+/// Batches are flushed at most this often, i.e. ≤10 IPC events per second.
+pub const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+/// Lines carried by a single event; older lines beyond this are dropped and
+/// counted so a burst can't flood the webview.
+pub const MAX_LINES_PER_EVENT: usize = 200;
+/// Recent lines kept in memory for the log viewer whether or not anything
+/// is subscribed to the stream.
+pub const HISTORY_LINES: usize = 5000;
+/// Lines per second a sidecar's output is mirrored, streamed and kept in
+/// memory at; the log file still gets every line.
+pub const MAX_LINES_PER_SECOND: u32 = 500;
+
+/// Severity read from a line's own markers (`ERROR`, `[warn]`,
+/// `"level":"info"`, …); unmarked stderr output counts as a warning.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    #[default]
+    Unknown,
+}
+
+/// Words looked at for a marker; levels come before the message text.
+const LEVEL_SCAN_WORDS: usize = 6;
+
+impl Level {
+    pub fn detect(stream: &str, line: &str) -> Level {
+        let marked = line
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .take(LEVEL_SCAN_WORDS)
+            .find_map(|word| match word.to_ascii_lowercase().as_str() {
+                "error" | "err" | "fatal" | "panic" | "critical" | "crit" => Some(Level::Error),
+                "warn" | "warning" => Some(Level::Warn),
+                "info" | "notice" => Some(Level::Info),
+                "debug" | "trace" | "verbose" => Some(Level::Debug),
+                _ => None,
+            });
+        match marked {
+            Some(level) => level,
+            None if stream == "stderr" => Level::Warn,
+            None => Level::Unknown,
+        }
+    }
+
+    /// Like `detect`, for a line as written to the log file.
+    pub fn of_logged(line: &str) -> Level {
+        let (stream, text) = parse_logged(line);
+        Level::detect(stream, text)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Unknown => "unknown",
+        }
+    }
+}
+
+pub fn timestamp() -> String {
+    chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false)
+}
+
+/// A line as written to a sidecar log file: `<timestamp> [stdout] <text>`.
+pub fn format_logged(stream: &str, line: &str) -> String {
+    format!("{} [{}] {}", timestamp(), stream, line)
+}
+
+/// Splits a log file line into its stream and text. The timestamp is
+/// optional, as are `[stdout]` tags: older files only tagged stderr.
+pub fn parse_logged(line: &str) -> (&'static str, &str) {
+    let rest = match line.split_once(' ') {
+        Some((first, rest)) if chrono::DateTime::parse_from_rfc3339(first).is_ok() => rest,
+        _ => line,
+    };
+    if let Some(text) = rest.strip_prefix("[stderr] ") {
+        ("stderr", text)
+    } else {
+        ("stdout", rest.strip_prefix("[stdout] ").unwrap_or(rest))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLine {
+    /// Increases by one per line across all sidecars, starting at 1.
+    pub seq: u64,
+    pub sidecar: String,
+    pub stream: &'static str,
+    pub level: Level,
+    pub line: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogBatch {
+    pub lines: Vec<LogLine>,
+    /// Lines dropped since the previous batch; the UI shows an
+    /// "N lines skipped" marker ahead of `lines`.
+    pub skipped: u64,
+}
+
+#[derive(Default)]
+struct Pending {
+    lines: VecDeque<LogLine>,
+    skipped: u64,
+}
+
+#[derive(Default)]
+struct History {
+    latest_seq: u64,
+    lines: VecDeque<LogLine>,
+}
+
+/// Caps how much of a burst of output reaches the console, the UI and the
+/// in-memory rings. Errors always get through; everything else beyond
+/// `MAX_LINES_PER_SECOND` is dropped and counted.
+pub struct LineLimiter {
+    window_start: Instant,
+    passed: u32,
+    suppressed: u64,
+}
+
+impl Default for LineLimiter {
+    fn default() -> Self {
+        Self {
+            window_start: Instant::now(),
+            passed: 0,
+            suppressed: 0,
+        }
+    }
+}
+
+impl LineLimiter {
+    /// `None` when a line at `level` is dropped; otherwise how many were
+    /// dropped since the last line let through.
+    pub fn admit(&mut self, level: Level) -> Option<u64> {
+        let now = Instant::now();
+        if now.duration_since(self.window_start) >= Duration::from_secs(1) {
+            self.window_start = now;
+            self.passed = 0;
+        }
+        if self.passed >= MAX_LINES_PER_SECOND && level != Level::Error {
+            self.suppressed += 1;
+            return None;
+        }
+        self.passed += 1;
+        Some(std::mem::take(&mut self.suppressed))
+    }
+}
+
+/// Stands in for the lines a `LineLimiter` dropped.
+pub fn suppressed_marker(count: u64) -> String {
+    format!(
+        "[tauri] {} lines suppressed (output above {} lines/s; the log file has them all)",
+        count, MAX_LINES_PER_SECOND
+    )
+}
+
+/// Collects sidecar output for the UI between flushes, and keeps a ring of
+/// recent lines for the log viewer. Batches are only collected while a
+/// window has subscribed to the stream.
+#[derive(Default)]
+pub struct LogBatcher {
+    enabled: AtomicBool,
+    pending: Mutex<Pending>,
+    history: Mutex<History>,
+    /// When each sidecar printed its first line, for startup timings.
+    first_lines: Mutex<HashMap<String, Instant>>,
+}
+
+impl LogBatcher {
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+        if !enabled {
+            *self.pending.lock().unwrap() = Pending::default();
+        }
+    }
+
+    pub fn push(&self, sidecar: &str, stream: &'static str, level: Level, line: &str) {
+        {
+            let mut first_lines = self.first_lines.lock().unwrap();
+            if !first_lines.contains_key(sidecar) {
+                first_lines.insert(sidecar.to_string(), Instant::now());
+            }
+        }
+        let line = {
+            let mut history = self.history.lock().unwrap();
+            history.latest_seq += 1;
+            let line = LogLine {
+                seq: history.latest_seq,
+                sidecar: sidecar.to_string(),
+                stream,
+                level,
+                line: template::sanitize_log_line(line),
+            };
+            if history.lines.len() >= HISTORY_LINES {
+                history.lines.pop_front();
+            }
+            history.lines.push_back(line.clone());
+            line
+        };
+
+        if !self.enabled.load(Ordering::Relaxed) {
+            return;
+        }
+        let mut pending = self.pending.lock().unwrap();
+        if pending.lines.len() >= MAX_LINES_PER_EVENT {
+            pending.lines.pop_front();
+            pending.skipped += 1;
+        }
+        pending.lines.push_back(line);
+    }
+
+    pub fn first_line_at(&self, sidecar: &str) -> Option<Instant> {
+        self.first_lines.lock().unwrap().get(sidecar).copied()
+    }
+
+    /// The sequence number of the newest line.
+    pub fn latest_seq(&self) -> u64 {
+        self.history.lock().unwrap().latest_seq
+    }
+
+    /// Retained lines with a sequence number greater than `since`, oldest
+    /// first.
+    pub fn history_since(&self, since: u64) -> Vec<LogLine> {
+        let history = self.history.lock().unwrap();
+        history
+            .lines
+            .iter()
+            .skip_while(|l| l.seq <= since)
+            .cloned()
+            .collect()
+    }
+
+    /// The last `n` retained lines from `sidecar`, tagged like the log file
+    /// (the ring keeps no timestamps).
+    pub fn recent_lines(&self, sidecar: &str, n: usize) -> Vec<String> {
+        let history = self.history.lock().unwrap();
+        let mut lines: Vec<String> = history
+            .lines
+            .iter()
+            .rev()
+            .filter(|l| l.sidecar == sidecar)
+            .take(n)
+            .map(|l| format!("[{}] {}", l.stream, l.line))
+            .collect();
+        lines.reverse();
+        lines
+    }
+
+    /// Drains everything collected since the last call.
+    pub fn take_batch(&self) -> Option<LogBatch> {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.lines.is_empty() && pending.skipped == 0 {
+            return None;
+        }
+        let Pending { lines, skipped } = std::mem::take(&mut *pending);
+        Some(LogBatch {
+            lines: lines.into(),
+            skipped,
+        })
+    }
+}
@@ -0,0 +1,155 @@
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::{logstream, pages, template};
+
+pub const LOG_VIEWER_LABEL: &str = "logs";
+/// How often the page asks for new lines.
+const POLL_INTERVAL_MS: u64 = 500;
+
+pub fn open_window(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(LOG_VIEWER_LABEL) {
+        let _ = window.set_focus();
+        return Ok(());
+    }
+
+    WebviewWindowBuilder::new(
+        app,
+        LOG_VIEWER_LABEL,
+        WebviewUrl::CustomProtocol(pages::page_url("logs")),
+    )
+    .title(template::t("log_viewer_title"))
+    .inner_size(960.0, 600.0)
+    .min_inner_size(420.0, 260.0)
+    .build()
+    .map(|_| ())
+    .map_err(|e| format!("Failed to open log viewer: {}", e))
+}
+
+const LOG_VIEWER_CSS: &str = r#"
+  html, body { height: 100%; }
+  body {
+    display: flex;
+    flex-direction: column;
+  }
+  .toolbar {
+    display: flex;
+    flex-wrap: wrap;
+    align-items: center;
+    gap: 0.5rem 1rem;
+    padding: 0.5rem 0.75rem;
+    border-bottom: 1px solid var(--border);
+    font-size: 0.85rem;
+  }
+  .toolbar input[type=search] {
+    flex: 1 1 12rem;
+    min-width: 0;
+    background: var(--bg-inset);
+    color: var(--fg);
+    border: 1px solid var(--border);
+    border-radius: 0.375rem;
+    padding: 0.35em 0.6em;
+    font: inherit;
+  }
+  .toolbar label { white-space: nowrap; color: var(--fg-muted); }
+  #lines {
+    flex: 1;
+    overflow-y: auto;
+    background: var(--bg-inset);
+    padding: 0.5rem 0.75rem;
+    font-size: 0.75rem;
+    line-height: 1.5;
+    white-space: pre-wrap;
+    word-break: break-all;
+  }
+  .line .source { color: var(--fg-muted); }
+  .line.error { color: var(--accent-error); }
+  .line.warn { color: var(--accent-warn); }
+  .empty { color: var(--fg-muted); }
+"#;
+
+const LOG_VIEWER_BODY: &str = r#"<body>
+  <div class="toolbar">
+    <input id="search" type="search" spellcheck="false" autocomplete="off"
+           placeholder="{{t:log_search}}" aria-label="{{t:log_search}}">
+    <label><input id="stdout" type="checkbox" checked> stdout</label>
+    <label><input id="stderr" type="checkbox" checked> stderr</label>
+    <label><input id="follow" type="checkbox" checked> {{t:log_follow}}</label>
+  </div>
+  <div id="lines" class="mono" role="log" aria-live="polite">
+    <p class="empty">{{t:no_log_output}}</p>
+  </div>
+  <script>
+    const MAX_LINES = {{max_lines}};
+    const container = document.getElementById('lines');
+    const search = document.getElementById('search');
+    const show = {
+      stdout: document.getElementById('stdout'),
+      stderr: document.getElementById('stderr'),
+    };
+    const follow = document.getElementById('follow');
+    let since = 0;
+
+    function visible(line) {
+      const query = search.value.trim().toLowerCase();
+      return show[line.stream].checked &&
+        (!query || line.line.toLowerCase().includes(query) ||
+          line.sidecar.toLowerCase().includes(query));
+    }
+
+    function applyFilter() {
+      for (const el of container.querySelectorAll('.line')) {
+        el.hidden = !visible(el.__line);
+      }
+      if (follow.checked) container.scrollTop = container.scrollHeight;
+    }
+
+    function append(lines) {
+      if (!lines.length) return;
+      container.querySelector('.empty')?.remove();
+      for (const line of lines) {
+        const el = document.createElement('div');
+        el.className = 'line ' + line.stream + ' ' + line.level;
+        const source = document.createElement('span');
+        source.className = 'source';
+        source.textContent = '[' + line.sidecar + '] ';
+        el.append(source, line.line);
+        el.__line = line;
+        el.hidden = !visible(line);
+        container.appendChild(el);
+      }
+      while (container.childElementCount > MAX_LINES) {
+        container.firstElementChild.remove();
+      }
+      if (follow.checked) container.scrollTop = container.scrollHeight;
+    }
+
+    async function poll() {
+      try {
+        const lines = await window.__invoke('get_log_history', { since });
+        if (lines.length) since = lines[lines.length - 1].seq;
+        append(lines);
+      } finally {
+        setTimeout(poll, {{poll_ms}});
+      }
+    }
+
+    search.addEventListener('input', applyFilter);
+    show.stdout.addEventListener('change', applyFilter);
+    show.stderr.addEventListener('change', applyFilter);
+    container.addEventListener('scroll', () => {
+      follow.checked = container.scrollTop + container.clientHeight >= container.scrollHeight - 4;
+    });
+    poll();
+  </script>
+</body>"#;
+
+pub fn generate_log_viewer_html() -> String {
+    let body = template::fill(
+        LOG_VIEWER_BODY,
+        &[
+            ("max_lines", &logstream::HISTORY_LINES.to_string()),
+            ("poll_ms", &POLL_INTERVAL_MS.to_string()),
+        ],
+    );
+    template::render(template::t("log_viewer_title"), LOG_VIEWER_CSS, &body)
+}
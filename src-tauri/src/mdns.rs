@@ -0,0 +1,126 @@
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub const SERVICE_TYPE: &str = "_localagent._tcp.local.";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MdnsConfig {
+    /// Advertise this instance on the LAN while remote control is running.
+    pub advertise: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscoveredAgent {
+    pub name: String,
+    pub host: String,
+    pub addresses: Vec<String>,
+    pub port: u16,
+    pub version: Option<String>,
+}
+
+#[derive(Default)]
+pub struct MdnsState {
+    daemon: Mutex<Option<(ServiceDaemon, String)>>,
+}
+
+pub fn hostname() -> String {
+    if let Ok(name) = std::env::var("COMPUTERNAME") {
+        return name;
+    }
+    if let Ok(name) = std::fs::read_to_string("/etc/hostname") {
+        let name = name.trim();
+        if !name.is_empty() {
+            return name.to_string();
+        }
+    }
+    std::process::Command::new("hostname")
+        .output()
+        .ok()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "local-agent".to_string())
+}
+
+impl MdnsState {
+    pub fn advertise(&self, port: u16) -> Result<(), String> {
+        let mut guard = self.daemon.lock().unwrap();
+        if guard.is_some() {
+            return Ok(());
+        }
+
+        let host = hostname();
+        let host_label = host.split('.').next().unwrap_or(&host).to_string();
+        let instance = format!("Local Agent on {}", host_label);
+        let properties = [("version", env!("CARGO_PKG_VERSION"))];
+
+        let info = ServiceInfo::new(
+            SERVICE_TYPE,
+            &instance,
+            &format!("{}.local.", host_label),
+            "",
+            port,
+            &properties[..],
+        )
+        .map_err(|e| format!("Invalid mDNS service info: {}", e))?
+        .enable_addr_auto();
+        let fullname = info.get_fullname().to_string();
+
+        let daemon =
+            ServiceDaemon::new().map_err(|e| format!("Failed to start mDNS daemon: {}", e))?;
+        daemon
+            .register(info)
+            .map_err(|e| format!("Failed to register mDNS service: {}", e))?;
+
+        println!("[tauri] Advertising {} via mDNS on port {}", fullname, port);
+        *guard = Some((daemon, fullname));
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        if let Some((daemon, fullname)) = self.daemon.lock().unwrap().take() {
+            let _ = daemon.unregister(&fullname);
+            let _ = daemon.shutdown();
+            println!("[tauri] Stopped mDNS advertisement");
+        }
+    }
+}
+
+/// Browses the LAN for other Local Agent instances. Blocks for `timeout`.
+pub fn discover(timeout: Duration) -> Result<Vec<DiscoveredAgent>, String> {
+    let daemon = ServiceDaemon::new().map_err(|e| format!("Failed to start mDNS daemon: {}", e))?;
+    let receiver = daemon
+        .browse(SERVICE_TYPE)
+        .map_err(|e| format!("Failed to browse for agents: {}", e))?;
+
+    let deadline = Instant::now() + timeout;
+    let mut agents: Vec<DiscoveredAgent> = Vec::new();
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        let Ok(event) = receiver.recv_timeout(remaining) else {
+            break;
+        };
+        if let ServiceEvent::ServiceResolved(info) = event {
+            let name = info.get_fullname().to_string();
+            if agents.iter().any(|a| a.name == name) {
+                continue;
+            }
+            agents.push(DiscoveredAgent {
+                name,
+                host: info.get_hostname().to_string(),
+                addresses: info
+                    .get_addresses()
+                    .iter()
+                    .map(|ip| ip.to_string())
+                    .collect(),
+                port: info.get_port(),
+                version: info.get_property_val_str("version").map(str::to_string),
+            });
+        }
+    }
+
+    let _ = daemon.stop_browse(SERVICE_TYPE);
+    let _ = daemon.shutdown();
+    Ok(agents)
+}
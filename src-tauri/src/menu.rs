@@ -0,0 +1,181 @@
+//! The native application menu. Menu events from every menu, the tray's
+//! included, reach app-wide listeners, so [`handle_event`] dispatches both.
+
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Manager, Wry};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::AppState;
+
+pub fn create_menu(app: &AppHandle) -> tauri::Result<Menu<Wry>> {
+    let separator = || PredefinedMenuItem::separator(app);
+    let preferences = MenuItem::with_id(
+        app,
+        "open_preferences",
+        "Preferences…",
+        true,
+        Some("CmdOrCtrl+,"),
+    )?;
+    let quit = MenuItem::with_id(app, "quit", "Quit Local Agent", true, Some("CmdOrCtrl+Q"))?;
+    let about = MenuItem::with_id(app, "open_about", "About Local Agent", true, None::<&str>)?;
+
+    let file = Submenu::new(app, "File", true)?;
+    #[cfg(not(target_os = "macos"))]
+    file.append_items(&[&preferences, &separator()?])?;
+    file.append(&PredefinedMenuItem::close_window(app, None)?)?;
+    #[cfg(not(target_os = "macos"))]
+    file.append_items(&[&separator()?, &quit])?;
+
+    // Without these the webview loses copy/paste shortcuts on macOS
+    let edit = Submenu::with_items(
+        app,
+        "Edit",
+        true,
+        &[
+            &PredefinedMenuItem::undo(app, None)?,
+            &PredefinedMenuItem::redo(app, None)?,
+            &separator()?,
+            &PredefinedMenuItem::cut(app, None)?,
+            &PredefinedMenuItem::copy(app, None)?,
+            &PredefinedMenuItem::paste(app, None)?,
+            &PredefinedMenuItem::select_all(app, None)?,
+        ],
+    )?;
+
+    let backend = Submenu::with_items(
+        app,
+        "Backend",
+        true,
+        &[
+            &MenuItem::with_id(
+                app,
+                "restart_backend",
+                "Restart Backend",
+                true,
+                None::<&str>,
+            )?,
+            &separator()?,
+            &MenuItem::with_id(app, "open_log_viewer", "View Logs", true, None::<&str>)?,
+            &MenuItem::with_id(
+                app,
+                "open_logs_folder",
+                "Open Logs Folder",
+                true,
+                None::<&str>,
+            )?,
+            &MenuItem::with_id(app, "open_log_file", "Open Log File", true, None::<&str>)?,
+        ],
+    )?;
+
+    let help = Submenu::with_items(
+        app,
+        "Help",
+        true,
+        &[
+            &MenuItem::with_id(
+                app,
+                "check_for_updates",
+                "Check for Updates…",
+                true,
+                None::<&str>,
+            )?,
+            &MenuItem::with_id(app, "report_issue", "Report Issue…", true, None::<&str>)?,
+        ],
+    )?;
+    #[cfg(not(target_os = "macos"))]
+    help.append_items(&[&separator()?, &about])?;
+
+    let menu = Menu::new(app)?;
+    #[cfg(target_os = "macos")]
+    menu.append(&Submenu::with_items(
+        app,
+        app.package_info().name.clone(),
+        true,
+        &[
+            &about,
+            &separator()?,
+            &preferences,
+            &separator()?,
+            &PredefinedMenuItem::services(app, None)?,
+            &separator()?,
+            &PredefinedMenuItem::hide(app, None)?,
+            &PredefinedMenuItem::hide_others(app, None)?,
+            &PredefinedMenuItem::show_all(app, None)?,
+            &separator()?,
+            &quit,
+        ],
+    )?)?;
+    menu.append_items(&[&file, &edit, &backend, &help])?;
+    Ok(menu)
+}
+
+pub fn handle_event(app: &AppHandle, event: MenuEvent) {
+    let result = match event.id.as_ref() {
+        "restart_backend" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let state = app.state::<AppState>();
+                if let Err(e) = crate::restart_backend(app.clone(), state, None).await {
+                    eprintln!("[tauri] Failed to restart backend: {}", e);
+                }
+            });
+            Ok(())
+        }
+        "check_for_updates" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                match crate::check_for_updates(app.clone()).await {
+                    Ok(info) if info.available => {
+                        if let Err(e) = app.opener().open_url(&info.url, None::<&str>) {
+                            eprintln!("[tauri] Failed to open release page: {}", e);
+                        }
+                    }
+                    Ok(info) => println!("[tauri] Local Agent {} is up to date", info.current),
+                    Err(e) => eprintln!("[tauri] {}", e),
+                }
+            });
+            Ok(())
+        }
+        "show_window" => {
+            crate::show_main_window(app);
+            Ok(())
+        }
+        "run_in_background" => {
+            let enabled = app
+                .state::<AppState>()
+                .config
+                .lock()
+                .unwrap()
+                .settings
+                .run_in_background;
+            crate::set_setting(
+                app.clone(),
+                crate::settings::SettingKey::RunInBackground,
+                (!enabled).into(),
+            )
+            .map(|_| ())
+        }
+        "open_preferences" => crate::open_preferences(app.clone()),
+        "open_about" => crate::open_about(app.clone()),
+        "report_issue" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::open_issue_report(app, None).await {
+                    eprintln!("[tauri] {}", e);
+                }
+            });
+            Ok(())
+        }
+        "open_log_viewer" => crate::open_log_viewer(app.clone()),
+        "open_logs_folder" => crate::open_logs_folder(app.clone()),
+        "open_log_file" => crate::open_log_file(app.clone()),
+        "quit" => {
+            crate::quit::request(app, crate::quit::QuitAction::Exit);
+            Ok(())
+        }
+        _ => Ok(()),
+    };
+    if let Err(e) = result {
+        eprintln!("[tauri] Menu action failed: {}", e);
+    }
+}
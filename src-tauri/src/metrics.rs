@@ -0,0 +1,105 @@
+//! Periodic scrape of the backend's `/metrics` (Prometheus text format).
+//! Samples are kept in a rolling window for `get_metrics_history`, so the
+//! in-app dashboard can chart them without a metrics server.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+pub const REQUESTS: &str = "local_agent_requests_total";
+pub const TOKENS_GENERATED: &str = "local_agent_tokens_generated_total";
+pub const QUEUE_DEPTH: &str = "local_agent_queue_depth";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// Samples kept; an hour at the default interval.
+    pub history_samples: usize,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 15,
+            history_samples: 240,
+        }
+    }
+}
+
+impl MetricsConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1))
+    }
+}
+
+/// One scrape. Counters are totals since the backend started, so they drop
+/// back when it restarts; `None` means the backend didn't report it.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MetricsSample {
+    pub at: String,
+    pub requests: Option<u64>,
+    pub tokens_generated: Option<u64>,
+    pub queue_depth: Option<u64>,
+}
+
+/// Sums `name` across its label sets in a Prometheus text exposition.
+fn value(text: &str, name: &str) -> Option<u64> {
+    let mut total = None;
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('#') {
+            continue;
+        }
+        let Some(rest) = line.strip_prefix(name) else {
+            continue;
+        };
+        // `name value` or `name{labels} value`, with an optional timestamp
+        let rest = match rest.chars().next() {
+            Some('{') => rest.split_once('}').map_or("", |(_, after)| after),
+            Some(c) if c.is_whitespace() => rest,
+            _ => continue,
+        };
+        let Some(value) = rest
+            .split_whitespace()
+            .next()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| v.is_finite() && *v >= 0.0)
+        else {
+            continue;
+        };
+        *total.get_or_insert(0) += value as u64;
+    }
+    total
+}
+
+pub fn parse(text: &str) -> MetricsSample {
+    MetricsSample {
+        at: chrono::Local::now().to_rfc3339(),
+        requests: value(text, REQUESTS),
+        tokens_generated: value(text, TOKENS_GENERATED),
+        queue_depth: value(text, QUEUE_DEPTH),
+    }
+}
+
+#[derive(Default)]
+pub struct MetricsHistory {
+    samples: Mutex<VecDeque<MetricsSample>>,
+}
+
+impl MetricsHistory {
+    pub fn push(&self, sample: MetricsSample, limit: usize) {
+        let mut samples = self.samples.lock().unwrap();
+        samples.push_back(sample);
+        while samples.len() > limit.max(1) {
+            samples.pop_front();
+        }
+    }
+
+    /// Oldest first.
+    pub fn samples(&self) -> Vec<MetricsSample> {
+        self.samples.lock().unwrap().iter().cloned().collect()
+    }
+}
@@ -0,0 +1,251 @@
+//! Models on disk, found without asking any server: Ollama's store (its
+//! manifests say which blobs make up each model) and any directories the
+//! user keeps GGUF or safetensors files in. Lets the UI show what is using
+//! disk space while the backend or Ollama is down.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::environment;
+
+/// How deep to look inside a configured directory.
+const MAX_DEPTH: usize = 4;
+
+/// Model files by extension. Sharded safetensors count once per directory.
+const FORMATS: &[(&str, &str)] = &[
+    ("gguf", "gguf"),
+    ("ggml", "ggml"),
+    ("safetensors", "safetensors"),
+    ("onnx", "onnx"),
+    ("pt", "pytorch"),
+    ("pth", "pytorch"),
+    ("ckpt", "checkpoint"),
+];
+
+/// Ollama's name for its default registry, left out of model names.
+const OLLAMA_LIBRARY: &str = "registry.ollama.ai/library/";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelsConfig {
+    /// Look in Ollama's model store (`OLLAMA_MODELS`, or `~/.ollama/models`).
+    pub include_ollama: bool,
+    /// Other directories holding model files.
+    pub dirs: Vec<PathBuf>,
+}
+
+impl Default for ModelsConfig {
+    fn default() -> Self {
+        Self {
+            include_ollama: true,
+            dirs: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelSource {
+    Ollama,
+    Directory,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalModel {
+    pub name: String,
+    pub source: ModelSource,
+    /// The model file or directory; for Ollama, its manifest.
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    pub format: String,
+    /// When the weights were last read, where the file system tracks it,
+    /// else last modified.
+    pub last_used: Option<String>,
+}
+
+/// Every model found, largest first.
+pub fn scan(config: &ModelsConfig) -> Vec<LocalModel> {
+    let mut models = Vec::new();
+    if config.include_ollama {
+        if let Some(store) = ollama_store() {
+            models.extend(scan_ollama(&store));
+        }
+    }
+    for dir in &config.dirs {
+        models.extend(scan_dir(dir));
+    }
+    models.sort_by_key(|model| std::cmp::Reverse(model.size_bytes));
+    models
+}
+
+fn ollama_store() -> Option<PathBuf> {
+    if let Some(dir) = environment::var_os("OLLAMA_MODELS") {
+        return Some(PathBuf::from(dir));
+    }
+    let mut candidates: Vec<PathBuf> = dirs::home_dir()
+        .map(|home| home.join(".ollama").join("models"))
+        .into_iter()
+        .collect();
+    // Where the Linux install script's system service keeps them
+    if cfg!(target_os = "linux") {
+        candidates.push(PathBuf::from("/usr/share/ollama/.ollama/models"));
+    }
+    candidates.into_iter().find(|dir| dir.is_dir())
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    config: Option<Layer>,
+    #[serde(default)]
+    layers: Vec<Layer>,
+}
+
+#[derive(Deserialize)]
+struct Layer {
+    #[serde(rename = "mediaType", default)]
+    media_type: String,
+    digest: String,
+    #[serde(default)]
+    size: u64,
+}
+
+/// Models from `manifests/<registry>/<namespace>/<model>/<tag>`.
+fn scan_ollama(store: &Path) -> Vec<LocalModel> {
+    let manifests = store.join("manifests");
+    let mut files = Vec::new();
+    collect_files(&manifests, MAX_DEPTH, &mut files);
+    files
+        .into_iter()
+        .filter_map(|path| {
+            let manifest: Manifest = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+            let relative = path.strip_prefix(&manifests).ok()?;
+            let parts: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            let (tag, repo) = parts.split_last()?;
+            let repo = repo.join("/");
+            let name = format!(
+                "{}:{}",
+                repo.strip_prefix(OLLAMA_LIBRARY).unwrap_or(&repo),
+                tag
+            );
+
+            let weights = manifest
+                .layers
+                .iter()
+                .find(|layer| layer.media_type.ends_with(".model"))
+                .map(|layer| store.join("blobs").join(layer.digest.replace(':', "-")));
+            let size_bytes = manifest
+                .config
+                .iter()
+                .chain(&manifest.layers)
+                .map(|layer| layer.size)
+                .sum();
+            Some(LocalModel {
+                name,
+                source: ModelSource::Ollama,
+                last_used: last_used(weights.as_deref().unwrap_or(&path)),
+                path,
+                size_bytes,
+                // Ollama stores weights as GGUF
+                format: "gguf".to_string(),
+            })
+        })
+        .collect()
+}
+
+fn scan_dir(dir: &Path) -> Vec<LocalModel> {
+    let mut files = Vec::new();
+    collect_files(dir, MAX_DEPTH, &mut files);
+
+    let mut models = Vec::new();
+    let mut sharded: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+    for path in files {
+        let Some(format) = format_of(&path) else {
+            continue;
+        };
+        if format == "safetensors" {
+            if let Some(parent) = path.parent() {
+                sharded.entry(parent.to_path_buf()).or_default().push(path);
+            }
+            continue;
+        }
+        let Ok(metadata) = fs::metadata(&path) else {
+            continue;
+        };
+        models.push(LocalModel {
+            name: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            source: ModelSource::Directory,
+            last_used: last_used(&path),
+            path,
+            size_bytes: metadata.len(),
+            format: format.to_string(),
+        });
+    }
+    // A safetensors model is its directory: config, tokenizer and shards
+    for (dir, shards) in sharded {
+        let size_bytes = shards
+            .iter()
+            .filter_map(|shard| fs::metadata(shard).ok())
+            .map(|metadata| metadata.len())
+            .sum();
+        let last_used = shards.iter().filter_map(|shard| last_used(shard)).max();
+        models.push(LocalModel {
+            name: dir
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            source: ModelSource::Directory,
+            path: dir,
+            size_bytes,
+            format: "safetensors".to_string(),
+            last_used,
+        });
+    }
+    models
+}
+
+fn format_of(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    FORMATS
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(_, format)| *format)
+}
+
+/// Regular files under `dir`, skipping hidden entries and symlinked
+/// directories.
+fn collect_files(dir: &Path, depth: usize, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() && depth > 0 {
+            collect_files(&entry.path(), depth - 1, files);
+        } else if file_type.is_file() {
+            files.push(entry.path());
+        }
+    }
+}
+
+fn last_used(path: &Path) -> Option<String> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok();
+    // Access times can be older than the last write on noatime mounts
+    let at: SystemTime = metadata.accessed().ok().max(modified)?;
+    Some(chrono::DateTime::<chrono::Local>::from(at).to_rfc3339())
+}
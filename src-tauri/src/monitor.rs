@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+use crate::registry::SidecarRegistry;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourcesConfig {
+    pub sample_interval_secs: u64,
+    /// Resident memory ceiling for the backend; unset disables the guard.
+    pub memory_limit_mb: Option<u64>,
+    /// Consecutive samples above the ceiling before the backend is restarted,
+    /// so short allocation spikes are tolerated.
+    pub over_limit_samples: u32,
+}
+
+impl Default for ResourcesConfig {
+    fn default() -> Self {
+        Self {
+            sample_interval_secs: 5,
+            memory_limit_mb: None,
+            over_limit_samples: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SidecarUsage {
+    pub name: String,
+    pub pid: u32,
+    pub memory_bytes: u64,
+    pub cpu_percent: f32,
+}
+
+#[derive(Default)]
+pub struct ResourceMonitor {
+    system: Mutex<System>,
+    latest: Mutex<Vec<SidecarUsage>>,
+    over_limit: Mutex<HashMap<String, u32>>,
+}
+
+impl ResourceMonitor {
+    /// Samples memory and CPU for every running sidecar.
+    pub fn sample(&self, registry: &SidecarRegistry) -> Vec<SidecarUsage> {
+        let running: Vec<(String, u32)> = registry
+            .all()
+            .iter()
+            .filter_map(|s| s.pid().map(|pid| (s.name().to_string(), pid)))
+            .collect();
+        let pids: Vec<Pid> = running.iter().map(|(_, pid)| Pid::from_u32(*pid)).collect();
+
+        let mut system = self.system.lock().unwrap();
+        system.refresh_processes(ProcessesToUpdate::Some(&pids), true);
+
+        let usage: Vec<SidecarUsage> = running
+            .into_iter()
+            .filter_map(|(name, pid)| {
+                let process = system.process(Pid::from_u32(pid))?;
+                Some(SidecarUsage {
+                    name,
+                    pid,
+                    memory_bytes: process.memory(),
+                    cpu_percent: process.cpu_usage(),
+                })
+            })
+            .collect();
+
+        *self.latest.lock().unwrap() = usage.clone();
+        usage
+    }
+
+    pub fn latest(&self) -> Vec<SidecarUsage> {
+        self.latest.lock().unwrap().clone()
+    }
+
+    /// Records one sample against the ceiling. Returns true once `name` has
+    /// been over the limit for `samples` consecutive samples, then resets.
+    pub fn check_limit(&self, usage: &SidecarUsage, limit_mb: u64, samples: u32) -> bool {
+        let mut over_limit = self.over_limit.lock().unwrap();
+        if usage.memory_bytes <= limit_mb * 1024 * 1024 {
+            over_limit.remove(&usage.name);
+            return false;
+        }
+
+        let count = over_limit.entry(usage.name.clone()).or_insert(0);
+        *count += 1;
+        println!(
+            "[tauri] {} sidecar using {} MB, over the {} MB ceiling ({}/{})",
+            usage.name,
+            usage.memory_bytes / (1024 * 1024),
+            limit_mb,
+            count,
+            samples
+        );
+        if *count >= samples.max(1) {
+            over_limit.remove(&usage.name);
+            true
+        } else {
+            false
+        }
+    }
+}
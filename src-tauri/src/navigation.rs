@@ -0,0 +1,348 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Url};
+use tokio::sync::watch;
+
+use crate::diagnosis::FailureClass;
+use crate::{error_page, show_error_page, AppState};
+
+/// Tries at loading the UI before the error page is shown instead.
+const LOAD_ATTEMPTS: u32 = 3;
+/// How long a navigation gets to finish loading.
+const LOAD_TIMEOUT: Duration = Duration::from_secs(15);
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Follows the main window's page loads, so a navigation can be checked
+/// instead of assumed.
+pub struct Navigator {
+    /// Bumped by every load, so an older one stops checking.
+    generation: AtomicU64,
+    /// The last page the main window finished loading.
+    loaded: watch::Sender<Option<Url>>,
+}
+
+impl Default for Navigator {
+    fn default() -> Self {
+        Self {
+            generation: AtomicU64::new(0),
+            loaded: watch::channel(None).0,
+        }
+    }
+}
+
+impl Navigator {
+    /// Called from the page load hook when the main window finishes a load.
+    pub fn finished(&self, url: &Url) {
+        self.loaded.send_replace(Some(url.clone()));
+    }
+
+    fn begin(&self) -> u64 {
+        self.generation.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    fn is_current(&self, generation: u64) -> bool {
+        self.generation.load(Ordering::SeqCst) == generation
+    }
+}
+
+/// Loading the app and showing the error page both go through these scripts
+/// so the main window's history never grows: the error document replaces the
+/// current one in place and recovery uses `location.replace`. Otherwise the
+/// back button lands on a stale `document.write` page whose IPC is gone.
+pub fn replace_script(url: &str) -> String {
+    format!(
+        "window.location.replace({});",
+        serde_json::to_string(url).unwrap()
+    )
+}
+
+pub fn error_script(html: &str) -> String {
+    format!(
+        "document.open(); document.write({}); document.close(); \
+         history.replaceState(null, '', location.href);",
+        serde_json::to_string(html).unwrap()
+    )
+}
+
+/// Moves a page to the same route on `origin`.
+pub fn move_origin_script(origin: &str) -> String {
+    format!(
+        "window.location.replace({} + location.pathname + location.search + location.hash);",
+        serde_json::to_string(origin.trim_end_matches('/')).unwrap()
+    )
+}
+
+/// Loads `url` in the main window without adding a history entry, trying
+/// again when the navigation fails or no page from `url`'s origin finishes
+/// loading. After `LOAD_ATTEMPTS` the error page is shown instead. A later
+/// load or error page takes over from this one.
+pub fn load_app(app: &AppHandle, url: &str) {
+    let generation = app.state::<AppState>().navigation.begin();
+    let app = app.clone();
+    let url = url.to_string();
+    tauri::async_runtime::spawn(async move {
+        let mut failures = Vec::new();
+        for attempt in 1..=LOAD_ATTEMPTS {
+            if !app.state::<AppState>().navigation.is_current(generation) {
+                return;
+            }
+            if attempt > 1 {
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+            match navigate(&app, &url, generation).await {
+                Ok(()) => return,
+                Err(e) => {
+                    eprintln!(
+                        "[tauri] Loading {} failed (attempt {}/{}): {}",
+                        url, attempt, LOAD_ATTEMPTS, e
+                    );
+                    failures.push(format!("Attempt {}: {}", attempt, e));
+                }
+            }
+        }
+        if app.state::<AppState>().navigation.is_current(generation) {
+            show_error_page(
+                &app,
+                &error_page::generate_diagnosed_error_html(
+                    &FailureClass::WindowLoadFailed,
+                    &failures,
+                    None,
+                ),
+            );
+        }
+    });
+}
+
+/// One navigation to `url`, finished when the main window has loaded a page
+/// from its origin.
+async fn navigate(app: &AppHandle, url: &str, generation: u64) -> Result<(), String> {
+    let target: Url = url
+        .parse()
+        .map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+    let Some(main_window) = app.get_webview_window("main") else {
+        return Ok(());
+    };
+    // Subscribed before navigating so a quick load isn't missed
+    let mut loaded = app.state::<AppState>().navigation.loaded.subscribe();
+    loaded.mark_unchanged();
+    main_window
+        .eval(replace_script(url))
+        .map_err(|e| format!("Failed to navigate: {}", e))?;
+    let wait = async {
+        loop {
+            if loaded.changed().await.is_err() {
+                return Err("the window closed".to_string());
+            }
+            if !app.state::<AppState>().navigation.is_current(generation) {
+                return Ok(());
+            }
+            let page = loaded.borrow_and_update().clone();
+            if page.is_some_and(|page| same_origin(&page, &target)) {
+                return Ok(());
+            }
+        }
+    };
+    tokio::time::timeout(LOAD_TIMEOUT, wait)
+        .await
+        .map_err(|_| format!("no page loaded within {:?}", LOAD_TIMEOUT))?
+}
+
+/// Replaces the main window's document with `html`.
+pub fn load_error(app: &AppHandle, html: &str) {
+    app.state::<AppState>().navigation.begin();
+    if let Some(main_window) = app.get_webview_window("main") {
+        let _ = main_window.eval(error_script(html));
+    }
+}
+
+/// Compared by parts: custom schemes (the socket bridge, the shell's pages)
+/// have opaque origins that never match.
+pub fn same_origin(a: &Url, b: &Url) -> bool {
+    a.scheme() == b.scheme()
+        && a.host_str() == b.host_str()
+        && a.port_or_known_default() == b.port_or_known_default()
+}
+
+/// Whether an app window may load `url` in place: a page on one of
+/// `origins`, or the blank page webviews start from.
+pub fn allowed(url: &Url, origins: &[String]) -> bool {
+    url.as_str() == "about:blank"
+        || origins
+            .iter()
+            .filter_map(|origin| origin.parse::<Url>().ok())
+            .any(|origin| same_origin(url, &origin))
+}
+
+/// Links the system browser opens instead.
+pub fn is_external(url: &Url) -> bool {
+    matches!(url.scheme(), "http" | "https" | "mailto")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal model of a webview session history, driven by the scripts the
+    /// shell evaluates.
+    struct History {
+        entries: Vec<String>,
+        index: usize,
+    }
+
+    impl History {
+        fn new(url: &str) -> Self {
+            Self {
+                entries: vec![url.to_string()],
+                index: 0,
+            }
+        }
+
+        fn current(&self) -> &str {
+            &self.entries[self.index]
+        }
+
+        fn eval(&mut self, script: &str) {
+            assert!(!script.contains("pushState"), "script pushes history");
+            assert!(!script.contains("location.assign"), "script pushes history");
+            assert!(!script.contains("location.href ="), "script pushes history");
+
+            if let Some(rest) = script.strip_prefix("window.location.replace(") {
+                let url: String = serde_json::from_str(rest.trim_end_matches(");")).unwrap();
+                self.entries[self.index] = url;
+            } else if script.starts_with("document.open()") {
+                self.entries[self.index] = format!("error@{}", self.current());
+            } else {
+                panic!("unexpected script: {}", script);
+            }
+        }
+
+        fn back(&mut self) {
+            self.index = self.index.saturating_sub(1);
+        }
+
+        fn forward(&mut self) {
+            self.index = (self.index + 1).min(self.entries.len() - 1);
+        }
+    }
+
+    const APP: &str = "http://127.0.0.1:3001";
+
+    #[test]
+    fn startup_does_not_leave_bundled_page_in_history() {
+        let mut history = History::new("tauri://localhost");
+        history.eval(&replace_script(APP));
+
+        history.back();
+        assert_eq!(history.current(), APP);
+        assert_eq!(history.entries.len(), 1);
+    }
+
+    #[test]
+    fn error_page_is_not_reachable_after_recovery() {
+        let mut history = History::new(APP);
+        history.eval(&error_script("<h1>Backend failed</h1>"));
+        assert!(history.current().starts_with("error@"));
+
+        history.eval(&replace_script(APP));
+        history.back();
+        assert_eq!(history.current(), APP);
+        history.forward();
+        assert_eq!(history.current(), APP);
+    }
+
+    #[test]
+    fn repeated_error_recovery_cycles_keep_single_entry() {
+        let mut history = History::new("tauri://localhost");
+        history.eval(&replace_script(APP));
+        for _ in 0..5 {
+            history.eval(&error_script("<p>down</p>"));
+            history.eval(&replace_script(APP));
+        }
+
+        assert_eq!(history.entries.len(), 1);
+        history.back();
+        history.forward();
+        assert_eq!(history.current(), APP);
+    }
+
+    #[test]
+    fn scripts_escape_their_payloads() {
+        let script = error_script("</script><script>alert(1)</script>\"');");
+        assert!(script.contains("\\\"'"));
+        assert_eq!(
+            replace_script("http://x/\"a"),
+            "window.location.replace(\"http://x/\\\"a\");"
+        );
+    }
+
+    #[test]
+    fn only_app_origins_are_allowed() {
+        let origins = vec![
+            "http://127.0.0.1:3001".to_string(),
+            "localagent-app://localhost".to_string(),
+        ];
+        let allowed = |url: &str| allowed(&url.parse().unwrap(), &origins);
+        assert!(allowed("http://127.0.0.1:3001/chat/42?x=1"));
+        assert!(allowed("localagent-app://localhost/settings"));
+        assert!(allowed("about:blank"));
+        assert!(!allowed("http://127.0.0.1:3002/"));
+        assert!(!allowed("http://localhost:3001/"));
+        assert!(!allowed("https://example.com/"));
+        assert!(!allowed("file:///etc/passwd"));
+    }
+
+    /// Production builds load the app from the backend, not the bundled
+    /// assets, so its IPC rests on the `backend` capability's remote URLs.
+    #[test]
+    fn app_on_the_backend_has_the_dev_permissions() {
+        use tauri::utils::acl::{capability::Capability, RemoteUrlPattern};
+
+        let capability: Capability =
+            serde_json::from_str(include_str!("../capabilities/backend.json")).unwrap();
+        assert!(capability.windows.iter().any(|window| window == "main"));
+        let patterns: Vec<RemoteUrlPattern> = capability
+            .remote
+            .as_ref()
+            .expect("backend capability has remote URLs")
+            .urls
+            .iter()
+            .map(|url| url.parse().unwrap())
+            .collect();
+        let reachable = |url: &str| {
+            let url: Url = url.parse().unwrap();
+            patterns.iter().any(|pattern| pattern.test(&url))
+        };
+        assert!(reachable("http://127.0.0.1:3001/"));
+        assert!(reachable("http://127.0.0.1:41234/chat/42?x=1"));
+        assert!(reachable(&format!("{}/settings", crate::bridge::app_url())));
+        assert!(!reachable("http://192.168.1.20:3001/"));
+        assert!(!reachable("https://example.com/"));
+
+        // The same commands as in dev, where `default` covers the dev server
+        let default: Capability =
+            serde_json::from_str(include_str!("../capabilities/default.json")).unwrap();
+        let identifiers = |capability: &Capability| -> Vec<String> {
+            capability
+                .permissions
+                .iter()
+                .map(|permission| permission.identifier().get().to_string())
+                .collect()
+        };
+        let permissions = identifiers(&capability);
+        for permission in identifiers(&default) {
+            assert!(
+                permissions.contains(&permission),
+                "{} is only granted in dev",
+                permission
+            );
+        }
+        assert!(permissions.contains(&"shell-api".to_string()));
+
+        let app_ui = include_str!("../permissions/windows.toml")
+            .split("[[set]]")
+            .find(|set| set.contains("identifier = \"app-ui\""))
+            .unwrap();
+        assert!(app_ui.contains("\"allow-ack-backend-ready\""));
+        assert!(app_ui.contains("\"allow-get-backend-state\""));
+    }
+}
@@ -0,0 +1,194 @@
+//! Whether now is a good time for a big transfer. The network is checked
+//! every `poll_secs`: offline when a plain-HTTP probe gets no answer,
+//! behind a captive portal when the probe's empty 204 comes back as
+//! something else (a login page or a redirect to one), and metered when
+//! the OS says so (NetworkManager on Linux, the connection cost on
+//! Windows; macOS doesn't tell). Large downloads are deferred in any of
+//! those cases and update checks while offline; the UI gets
+//! "network-status" events to explain why.
+
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{emit_event, proxy, AppState};
+
+pub const EVENT: &str = "network-status";
+/// Downloads at least this big wait for a better connection.
+pub const LARGE_TRANSFER_BYTES: u64 = 50 * 1024 * 1024;
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Answers 204 with an empty body when nothing is in the way.
+    pub probe_url: String,
+    pub poll_secs: u64,
+    /// Hold large downloads on a metered connection.
+    pub defer_on_metered: bool,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            probe_url: "http://connectivitycheck.gstatic.com/generate_204".to_string(),
+            poll_secs: 60,
+            defer_on_metered: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NetworkStatus {
+    pub online: bool,
+    pub captive_portal: bool,
+    /// `None` when the OS doesn't say.
+    pub metered: Option<bool>,
+    /// Why large transfers are held right now, if they are.
+    pub defer_reason: Option<String>,
+}
+
+impl NetworkStatus {
+    fn new(
+        online: bool,
+        captive_portal: bool,
+        metered: Option<bool>,
+        config: &NetworkConfig,
+    ) -> Self {
+        let defer_reason = if !online {
+            Some("No internet connection".to_string())
+        } else if captive_portal {
+            Some("The network needs a sign-in (captive portal)".to_string())
+        } else if config.defer_on_metered && metered == Some(true) {
+            Some("The connection is metered".to_string())
+        } else {
+            None
+        };
+        Self {
+            online,
+            captive_portal,
+            metered,
+            defer_reason,
+        }
+    }
+}
+
+/// The last status seen, to tell when it changes.
+#[derive(Default)]
+pub struct NetworkMonitor {
+    last: Mutex<Option<NetworkStatus>>,
+}
+
+impl NetworkMonitor {
+    /// Records `status`; true when it differs from the last one.
+    fn update(&self, status: &NetworkStatus) -> bool {
+        let mut last = self.last.lock().unwrap();
+        let changed = last.as_ref() != Some(status);
+        *last = Some(status.clone());
+        changed
+    }
+}
+
+/// Checks the network now, telling the UI when something changed.
+pub async fn refresh(app: &AppHandle) -> NetworkStatus {
+    let state: State<AppState> = app.state();
+    let config = state.config.lock().unwrap().network.clone();
+    let status = check(&config).await;
+    if state.network.update(&status) {
+        println!(
+            "[tauri] Network: online={} captive_portal={} metered={:?}",
+            status.online, status.captive_portal, status.metered
+        );
+        emit_event(app, EVENT, status.clone());
+    }
+    status
+}
+
+pub async fn check(config: &NetworkConfig) -> NetworkStatus {
+    let (online, captive_portal) = probe(&config.probe_url).await;
+    let metered = tauri::async_runtime::spawn_blocking(metered)
+        .await
+        .unwrap_or(None);
+    NetworkStatus::new(online, captive_portal, metered, config)
+}
+
+/// `(online, captive_portal)` from one request to `url`.
+async fn probe(url: &str) -> (bool, bool) {
+    let client = proxy::client_builder()
+        .timeout(PROBE_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .build();
+    let Ok(client) = client else {
+        return (false, false);
+    };
+    match client.get(url).send().await {
+        Ok(response) => (true, response.status() != reqwest::StatusCode::NO_CONTENT),
+        Err(_) => (false, false),
+    }
+}
+
+fn metered() -> Option<bool> {
+    if cfg!(windows) {
+        let cost = run(
+            "powershell",
+            &[
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                "[Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime] | Out-Null; \
+                 [Windows.Networking.Connectivity.NetworkInformation]::GetInternetConnectionProfile().GetConnectionCost().NetworkCostType",
+            ],
+        )?;
+        return match cost.trim() {
+            "Fixed" | "Variable" => Some(true),
+            "Unrestricted" => Some(false),
+            _ => None,
+        };
+    }
+    if cfg!(target_os = "linux") {
+        // One "GENERAL.METERED:yes (guessed)"-style line per device
+        let devices = run("nmcli", &["-t", "-f", "GENERAL.METERED", "device", "show"])?;
+        let values: Vec<&str> = devices
+            .lines()
+            .filter_map(|line| line.strip_prefix("GENERAL.METERED:"))
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        return Some(values.iter().any(|value| value.starts_with("yes")));
+    }
+    None
+}
+
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Refuses a download of `bytes` while large transfers are held.
+pub async fn check_transfer(app: &AppHandle, bytes: u64) -> Result<(), String> {
+    if bytes < LARGE_TRANSFER_BYTES {
+        return Ok(());
+    }
+    match refresh(app).await.defer_reason {
+        Some(reason) => Err(format!("Download paused: {}", reason)),
+        None => Ok(()),
+    }
+}
+
+/// Re-checks the network every `poll_secs`.
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            refresh(&app).await;
+            let state: State<AppState> = app.state();
+            let poll_secs = state.config.lock().unwrap().network.poll_secs;
+            tokio::time::sleep(Duration::from_secs(poll_secs.max(10))).await;
+        }
+    });
+}
@@ -0,0 +1,125 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::environment;
+
+/// Minimum Node.js version, taken from `engines.node` in package.json at build time.
+pub const MIN_NODE_VERSION: &str = env!("LOCAL_AGENT_MIN_NODE");
+
+#[derive(Debug, Clone)]
+pub struct NodeInfo {
+    pub path: Option<PathBuf>,
+    pub version: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum NodeProblem {
+    NotFound,
+    Unparseable {
+        path: Option<PathBuf>,
+        output: String,
+    },
+    TooOld(NodeInfo),
+}
+
+impl NodeProblem {
+    pub fn title(&self) -> &'static str {
+        match self {
+            NodeProblem::NotFound => "Node.js not found",
+            NodeProblem::Unparseable { .. } => "Node.js version could not be determined",
+            NodeProblem::TooOld(_) => "Node.js version not supported",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            NodeProblem::NotFound => format!(
+                "Local Agent needs Node.js {} or newer, but no `node` executable was found on PATH.",
+                MIN_NODE_VERSION
+            ),
+            NodeProblem::Unparseable { path, output } => format!(
+                "`node --version` at {} printed {:?}, which is not a version Local Agent understands. Node.js {} or newer is required.",
+                display_path(path),
+                output,
+                MIN_NODE_VERSION
+            ),
+            NodeProblem::TooOld(info) => format!(
+                "Found Node.js {} at {}, but Local Agent requires {} or newer.",
+                info.version,
+                display_path(&info.path),
+                MIN_NODE_VERSION
+            ),
+        }
+    }
+
+    pub fn remediation(&self) -> Vec<String> {
+        vec![
+            format!(
+                "Install Node.js {} or newer from https://nodejs.org (or via nvm, fnm, Homebrew).",
+                MIN_NODE_VERSION
+            ),
+            "If several versions are installed, make sure the newer one comes first on PATH."
+                .to_string(),
+            "Then click Retry.".to_string(),
+        ]
+    }
+}
+
+fn display_path(path: &Option<PathBuf>) -> String {
+    path.as_ref()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "an unknown location".to_string())
+}
+
+/// Locates `node` on the PATH the backend is spawned with.
+pub fn find_node() -> Option<PathBuf> {
+    let exe = if cfg!(windows) { "node.exe" } else { "node" };
+    environment::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(exe))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+/// Parses `v20.11.1` (or `20.11`) into comparable numeric parts.
+pub fn parse_version(raw: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = raw.trim().trim_start_matches('v').split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().unwrap_or("0").parse().ok()?;
+    let patch = parts
+        .next()
+        .unwrap_or("0")
+        .split(|c: char| !c.is_ascii_digit())
+        .next()
+        .unwrap_or("0")
+        .parse()
+        .unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+pub fn check() -> Result<NodeInfo, NodeProblem> {
+    let path = find_node();
+    let output = Command::new(path.as_deref().unwrap_or(Path::new("node")))
+        .arg("--version")
+        .output()
+        .map_err(|_| NodeProblem::NotFound)?;
+    let raw = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let found = parse_version(&raw).ok_or_else(|| NodeProblem::Unparseable {
+        path: path.clone(),
+        output: raw.clone(),
+    })?;
+    let required = parse_version(MIN_NODE_VERSION).unwrap_or((0, 0, 0));
+
+    let info = NodeInfo { path, version: raw };
+    if found < required {
+        return Err(NodeProblem::TooOld(info));
+    }
+
+    println!(
+        "[tauri] Using Node.js {} ({})",
+        info.version,
+        display_path(&info.path)
+    );
+    Ok(info)
+}
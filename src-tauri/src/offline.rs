@@ -0,0 +1,151 @@
+//! The app without its backend. While the backend restarts, the main window
+//! loads the frontend built into the shell (Tauri embeds `frontendDist`)
+//! over the `localagent-offline` protocol instead of showing the
+//! Reconnecting page, so saved chats can still be read. The protocol answers
+//! the chat reads the backend would, straight from its data directory; any
+//! other `/api` request fails, so nothing changes until the backend is back.
+//! The app hears `offline-mode` to show a banner.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use tauri::http::{Request, Response, StatusCode};
+use tauri::AppHandle;
+
+pub const SCHEME: &str = "localagent-offline";
+/// Sent with `true` once the main window shows the offline app.
+pub const EVENT: &str = "offline-mode";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OfflineConfig {
+    /// Show the offline app instead of the Reconnecting page while the
+    /// backend restarts.
+    pub enabled: bool,
+}
+
+impl Default for OfflineConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// The offline app's root, like `bridge::app_url`.
+pub fn app_url() -> String {
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://{}.localhost", SCHEME)
+    } else {
+        format!("{}://localhost", SCHEME)
+    }
+}
+
+/// Whether the frontend was built into this binary; dev builds read it from
+/// `frontendDist`, which may not exist.
+pub fn available(app: &AppHandle) -> bool {
+    app.asset_resolver().get("index.html".to_string()).is_some()
+}
+
+/// Serves `request` from the embedded frontend, or from `chats_dir` for the
+/// chat API.
+pub fn handle(app: &AppHandle, request: &Request<Vec<u8>>, chats_dir: &Path) -> Response<Vec<u8>> {
+    let path = request.uri().path();
+    if let Some(api) = path.strip_prefix("/api/") {
+        if request.method() != "GET" {
+            return unavailable();
+        }
+        return match api.split_once('/') {
+            None if api == "chats" => json_response(StatusCode::OK, &list_chats(chats_dir)),
+            Some(("chats", filename)) => read_chat(chats_dir, filename),
+            _ => unavailable(),
+        };
+    }
+
+    let resolver = app.asset_resolver();
+    // Client-side routes have no file of their own
+    let Some(asset) = resolver
+        .get(path.to_string())
+        .or_else(|| resolver.get("index.html".to_string()))
+    else {
+        return Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(b"Not found".to_vec())
+            .unwrap();
+    };
+    let mut response = Response::builder().header("Content-Type", &asset.mime_type);
+    if let Some(ref csp) = asset.csp_header {
+        response = response.header("Content-Security-Policy", csp);
+    }
+    response.body(asset.bytes).unwrap()
+}
+
+fn json_response(status: StatusCode, body: &Value) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(body.to_string().into_bytes())
+        .unwrap()
+}
+
+fn unavailable() -> Response<Vec<u8>> {
+    json_response(
+        StatusCode::SERVICE_UNAVAILABLE,
+        &json!({ "error": "Local Agent is offline while the backend restarts" }),
+    )
+}
+
+/// Chat metadata as `GET /api/chats` returns it: pinned first, then newest.
+fn list_chats(chats_dir: &Path) -> Value {
+    let Ok(entries) = fs::read_dir(chats_dir) else {
+        return json!({ "chats": [] });
+    };
+    let mut chats: Vec<(bool, i64, Value)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let data: Value = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+            let filename = path.file_name()?.to_string_lossy().into_owned();
+            let timestamp = match data["timestamp"].as_str() {
+                Some(timestamp) => timestamp.to_string(),
+                None => {
+                    let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+                    chrono::DateTime::<chrono::Utc>::from(modified).to_rfc3339()
+                }
+            };
+            let pinned = data["pinned"].as_bool().unwrap_or(false);
+            let chat = json!({
+                "filename": filename,
+                "title": data["title"].as_str().unwrap_or("Untitled Chat"),
+                "timestamp": timestamp,
+                "pinned": pinned,
+            });
+            let at = chrono::DateTime::parse_from_rfc3339(&timestamp)
+                .map(|at| at.timestamp_millis())
+                .unwrap_or(0);
+            Some((pinned, at, chat))
+        })
+        .collect();
+    chats.sort_by_key(|(pinned, at, _)| std::cmp::Reverse((*pinned, *at)));
+    json!({ "chats": chats.into_iter().map(|(_, _, chat)| chat).collect::<Vec<_>>() })
+}
+
+fn read_chat(chats_dir: &Path, filename: &str) -> Response<Vec<u8>> {
+    // Same rules as the backend: a bare name ending in .json
+    if filename.contains(['/', '\\']) || filename.starts_with('.') || !filename.ends_with(".json") {
+        return json_response(
+            StatusCode::BAD_REQUEST,
+            &json!({ "error": "Invalid filename" }),
+        );
+    }
+    match fs::read(chats_dir.join(filename)) {
+        Ok(contents) => Response::builder()
+            .header("Content-Type", "application/json")
+            .body(contents)
+            .unwrap(),
+        Err(_) => json_response(
+            StatusCode::NOT_FOUND,
+            &json!({ "error": "Chat file not found" }),
+        ),
+    }
+}
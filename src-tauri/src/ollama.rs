@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::health::ProbeSpec;
+use crate::registry::SidecarRegistry;
+use crate::sidecar::{RestartPolicy, SidecarManager, SidecarSpec};
+
+pub const OLLAMA_SIDECAR: &str = "ollama";
+pub const OLLAMA_URL: &str = "http://127.0.0.1:11434";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OllamaConfig {
+    /// Start `ollama serve` at launch when no server is already running.
+    pub auto_start: bool,
+    /// Explicit path to the ollama executable; searched for when unset.
+    pub binary: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OllamaStatus {
+    pub installed: bool,
+    pub binary_path: Option<PathBuf>,
+    pub running: bool,
+    pub version: Option<String>,
+    /// True when the running server was spawned by this app rather than
+    /// attached to an existing instance.
+    pub managed: bool,
+    pub url: &'static str,
+}
+
+#[derive(Deserialize)]
+struct VersionResponse {
+    version: String,
+}
+
+/// Locates the ollama executable on PATH or in the default install locations.
+pub fn find_ollama(config: &OllamaConfig) -> Option<PathBuf> {
+    if let Some(ref path) = config.binary {
+        return path.is_file().then(|| path.clone());
+    }
+
+    let exe = if cfg!(windows) {
+        "ollama.exe"
+    } else {
+        "ollama"
+    };
+    let on_path = std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(exe))
+            .find(|candidate| candidate.is_file())
+    });
+
+    on_path.or_else(|| {
+        let mut candidates = Vec::new();
+        if cfg!(target_os = "macos") {
+            candidates.push(PathBuf::from("/usr/local/bin/ollama"));
+            candidates.push(PathBuf::from("/opt/homebrew/bin/ollama"));
+            candidates.push(PathBuf::from(
+                "/Applications/Ollama.app/Contents/Resources/ollama",
+            ));
+        } else if cfg!(windows) {
+            if let Some(dir) = dirs::data_local_dir() {
+                candidates.push(dir.join("Programs").join("Ollama").join(exe));
+            }
+        } else {
+            candidates.push(PathBuf::from("/usr/local/bin/ollama"));
+            candidates.push(PathBuf::from("/usr/bin/ollama"));
+        }
+        candidates.into_iter().find(|candidate| candidate.is_file())
+    })
+}
+
+/// Queries the Ollama API for its version; `None` means no server answered.
+pub async fn server_version() -> Option<String> {
+    let client = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(1))
+        .timeout(Duration::from_secs(2))
+        .build()
+        .ok()?;
+    let resp = client
+        .get(format!("{}/api/version", OLLAMA_URL))
+        .send()
+        .await
+        .ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    resp.json::<VersionResponse>().await.ok().map(|v| v.version)
+}
+
+pub async fn status(config: &OllamaConfig, registry: &SidecarRegistry) -> OllamaStatus {
+    let binary_path = find_ollama(config);
+    let version = server_version().await;
+    let managed = registry
+        .get(OLLAMA_SIDECAR)
+        .map(|s| s.is_running())
+        .unwrap_or(false);
+
+    OllamaStatus {
+        installed: binary_path.is_some(),
+        binary_path,
+        running: version.is_some(),
+        version,
+        managed,
+        url: OLLAMA_URL,
+    }
+}
+
+pub fn sidecar_spec(binary: PathBuf, health_probe: Option<ProbeSpec>) -> SidecarSpec {
+    SidecarSpec {
+        name: OLLAMA_SIDECAR.to_string(),
+        command: binary.to_string_lossy().into_owned(),
+        args: vec!["serve".to_string()],
+        cwd: None,
+        env: BTreeMap::new(),
+        // Model location, bind address and GPU selection
+        inherit_env: vec![
+            "OLLAMA_*".to_string(),
+            "CUDA_VISIBLE_DEVICES".to_string(),
+            "HIP_VISIBLE_DEVICES".to_string(),
+            "ROCR_VISIBLE_DEVICES".to_string(),
+        ],
+        secrets: Vec::new(),
+        health_url: Some(format!("{}/api/version", OLLAMA_URL)),
+        health_probe,
+        log_file: Some("local-agent-ollama.log".to_string()),
+        restart_policy: RestartPolicy::OnFailure,
+        max_restarts: 3,
+        depends_on: Vec::new(),
+        shutdown_grace_ms: 5000,
+        shutdown_url: None,
+        shutdown_drain_ms: 0,
+        stdin_control: false,
+        show_console: false,
+    }
+}
+
+/// Attaches to a running Ollama server, or spawns `ollama serve` as a
+/// supervised sidecar when none is reachable.
+pub async fn ensure_running(
+    config: &OllamaConfig,
+    health_probe: Option<ProbeSpec>,
+    registry: &SidecarRegistry,
+) -> Result<OllamaStatus, String> {
+    if server_version().await.is_some() {
+        println!(
+            "[tauri] Attached to running Ollama server at {}",
+            OLLAMA_URL
+        );
+        return Ok(status(config, registry).await);
+    }
+
+    let binary = find_ollama(config).ok_or_else(|| {
+        "Ollama is not installed. Download it from https://ollama.com/download".to_string()
+    })?;
+    let spec = sidecar_spec(binary, health_probe);
+    let probe = spec
+        .probe()
+        .unwrap_or_else(|| ProbeSpec::http(&format!("{}/api/version", OLLAMA_URL)));
+    let sidecar = registry.insert(SidecarManager::new(spec));
+    tauri::async_runtime::spawn_blocking(move || sidecar.spawn_with_retry())
+        .await
+        .map_err(|e| format!("Ollama start task failed: {}", e))??;
+
+    crate::health::poll_health(&probe, 250, 15000, None)
+        .await
+        .map_err(|e| format!("Ollama did not become ready: {}", e))?;
+
+    Ok(status(config, registry).await)
+}
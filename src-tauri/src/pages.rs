@@ -0,0 +1,43 @@
+use tauri::http::{Request, Response, StatusCode};
+use tauri::Url;
+
+use crate::{about, logviewer, preferences, quit, screenshot, splash, terminal};
+
+/// Custom URI scheme serving shell-generated pages to secondary windows.
+pub const SCHEME: &str = "localagent-page";
+
+/// Custom protocols are exposed as `http://<scheme>.localhost` on Windows and
+/// Android and as `<scheme>://localhost` everywhere else.
+pub fn page_url(path: &str) -> Url {
+    let base = if cfg!(any(windows, target_os = "android")) {
+        format!("http://{}.localhost", SCHEME)
+    } else {
+        format!("{}://localhost", SCHEME)
+    };
+    format!("{}/{}", base, path.trim_start_matches('/'))
+        .parse()
+        .unwrap()
+}
+
+pub fn handle(request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let body = match request.uri().path() {
+        "/splash" => splash::generate_splash_html(),
+        "/terminal" => terminal::generate_terminal_html(),
+        "/logs" => logviewer::generate_log_viewer_html(),
+        "/preferences" => preferences::generate_preferences_html(),
+        "/quit" => quit::generate_quit_html(),
+        "/about" => about::generate_about_html(),
+        "/screenshot" => screenshot::generate_screenshot_html(request.uri().query()),
+        _ => {
+            return Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(b"Not found".to_vec())
+                .unwrap()
+        }
+    };
+
+    Response::builder()
+        .header("Content-Type", "text/html; charset=utf-8")
+        .body(body.into_bytes())
+        .unwrap()
+}
@@ -0,0 +1,98 @@
+//! Where the shell finds the backend and keeps its own files.
+//!
+//! The backend normally runs from the resource dir; `--home`,
+//! `LOCAL_AGENT_HOME` or `project_root` in the shell config point it
+//! elsewhere. In portable mode (`--portable`, `LOCAL_AGENT_PORTABLE=1`, or
+//! a `local-agent.portable` file next to the executable) config, logs,
+//! data and the webview profile live in `local-agent-data` beside the
+//! executable, so the app can run from a USB stick without touching the
+//! machine's user profile.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::cli;
+use crate::config::ShellConfig;
+
+pub const HOME_ENV: &str = "LOCAL_AGENT_HOME";
+pub const PORTABLE_ENV: &str = "LOCAL_AGENT_PORTABLE";
+/// Dropping this file next to the executable makes the install portable.
+pub const PORTABLE_MARKER: &str = "local-agent.portable";
+const PORTABLE_DIR: &str = "local-agent-data";
+
+static PORTABLE: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// The directory holding the executable.
+fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+}
+
+/// Where portable mode keeps everything, when it is on. Decided once per
+/// run, after the launch flags are parsed.
+pub fn portable_root() -> Option<&'static Path> {
+    PORTABLE
+        .get_or_init(|| {
+            let dir = exe_dir()?;
+            let enabled = cli::options().portable
+                || std::env::var(PORTABLE_ENV).is_ok_and(|v| v == "1" || v == "true")
+                || dir.join(PORTABLE_MARKER).is_file();
+            enabled.then(|| dir.join(PORTABLE_DIR))
+        })
+        .as_deref()
+}
+
+/// The shell config, startup stats and other settings files.
+pub fn config_dir() -> PathBuf {
+    match portable_root() {
+        Some(root) => root.join("config"),
+        None => dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("local-agent"),
+    }
+}
+
+/// Sidecar logs, crash reports and the shutdown record.
+pub fn log_dir() -> PathBuf {
+    if let Some(root) = portable_root() {
+        return root.join("logs");
+    }
+    if cfg!(target_os = "macos") {
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        let logs_dir = home.join("Library").join("Logs");
+        if logs_dir.exists() {
+            return logs_dir;
+        }
+    }
+    dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// The backend's data directory in portable mode; otherwise the backend
+/// picks its own.
+pub fn portable_data_dir() -> Option<PathBuf> {
+    portable_root().map(|root| root.join("data"))
+}
+
+/// The webview's storage in portable mode; otherwise the OS default.
+pub fn portable_webview_dir() -> Option<PathBuf> {
+    portable_root().map(|root| root.join("webview"))
+}
+
+/// A backend location chosen by the user, and where it came from. Relative
+/// paths are taken from the executable's directory, which is what a
+/// portable install moves around with.
+pub fn project_root_override(config: &ShellConfig) -> Option<(PathBuf, &'static str)> {
+    let (path, source) = if let Some(ref home) = cli::options().home {
+        (home.clone(), "--home")
+    } else if let Some(home) = std::env::var_os(HOME_ENV).filter(|v| !v.is_empty()) {
+        (PathBuf::from(home), HOME_ENV)
+    } else {
+        (config.project_root.clone()?, "project_root")
+    };
+    let path = match exe_dir() {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path,
+    };
+    Some((path, source))
+}
@@ -0,0 +1,147 @@
+use serde::Serialize;
+use std::thread;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager, State};
+
+use crate::hooks::HookPoint;
+use crate::restarts::RestartReason;
+use crate::state::BackendState;
+use crate::{
+    backend_probe, emit_event, health, is_dev, respawn_sidecar, set_backend_state, spawn_hooks,
+    AppState,
+};
+
+const TICK: Duration = Duration::from_secs(5);
+/// A wall-clock jump beyond this (on top of the tick) means the machine slept.
+const RESUME_THRESHOLD: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResumeEvent {
+    pub slept_secs: u64,
+}
+
+/// Detects system resume on every platform by watching for wall-clock jumps:
+/// a thread sleeping on the monotonic clock wakes up "late" by however long
+/// the machine was suspended. This avoids per-OS power notification APIs
+/// (NSWorkspace, WM_POWERBROADCAST, logind) while catching the same events.
+pub fn start_resume_watcher(app: AppHandle) {
+    thread::spawn(move || {
+        let mut last = SystemTime::now();
+        loop {
+            thread::sleep(TICK);
+            let now = SystemTime::now();
+            let elapsed = now.duration_since(last).unwrap_or_default();
+            last = now;
+
+            if elapsed > TICK + RESUME_THRESHOLD {
+                let slept = elapsed - TICK;
+                println!(
+                    "[tauri] System resumed after ~{}s asleep, re-checking backend",
+                    slept.as_secs()
+                );
+                emit_event(
+                    &app,
+                    "system-resumed",
+                    ResumeEvent {
+                        slept_secs: slept.as_secs(),
+                    },
+                );
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move { handle_resume(app).await });
+            }
+        }
+    });
+}
+
+async fn handle_resume(app: AppHandle) {
+    // A suspended backend is started when a window opens
+    if app.state::<AppState>().idle.is_suspended() {
+        return;
+    }
+    if health::poll_health(&backend_probe(&app), 250, 5000, None)
+        .await
+        .is_ok()
+    {
+        println!("[tauri] Backend healthy after resume");
+        return;
+    }
+
+    if is_dev() {
+        set_backend_state(
+            &app,
+            BackendState::Degraded,
+            Some("not responding after resume (dev mode, not restarting)"),
+        );
+        return;
+    }
+
+    set_backend_state(
+        &app,
+        BackendState::Restarting,
+        Some("unhealthy after resume"),
+    );
+    let result = {
+        let state: State<AppState> = app.state();
+        respawn_sidecar(&state, RestartReason::Resume, "unhealthy after resume")
+    };
+    if let Err(e) = result {
+        set_backend_state(&app, BackendState::Failed, Some(&e));
+        spawn_hooks(&app, HookPoint::PostCrash);
+        return;
+    }
+
+    match health::poll_health(&backend_probe(&app), 250, 15000, None).await {
+        Ok(()) => {
+            set_backend_state(&app, BackendState::Healthy, None);
+            emit_event(&app, "backend-reconnected", ());
+            spawn_hooks(&app, HookPoint::PostBackendStart);
+        }
+        Err(e) => {
+            set_backend_state(&app, BackendState::Failed, Some(&e));
+            spawn_hooks(&app, HookPoint::PostCrash);
+        }
+    }
+}
+
+/// Whether the machine is running on battery; `false` when that can't be
+/// told, which includes machines without one.
+#[cfg(target_os = "linux")]
+pub fn on_battery() -> bool {
+    let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    let read = |path: std::path::PathBuf| std::fs::read_to_string(path).unwrap_or_default();
+    let mut discharging = false;
+    for supply in supplies.flatten() {
+        let dir = supply.path();
+        match read(dir.join("type")).trim() {
+            // Mains covers AC adapters and USB-C power
+            "Mains" | "USB" if read(dir.join("online")).trim() == "1" => return false,
+            "Battery" => discharging |= read(dir.join("status")).trim() == "Discharging",
+            _ => {}
+        }
+    }
+    discharging
+}
+
+#[cfg(target_os = "macos")]
+pub fn on_battery() -> bool {
+    std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains("'Battery Power'"))
+}
+
+#[cfg(windows)]
+pub fn on_battery() -> bool {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    let read = unsafe { GetSystemPowerStatus(&mut status) };
+    // ACLineStatus: 0 offline, 1 online, 255 unknown
+    read != 0 && status.ACLineStatus == 0
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn on_battery() -> bool {
+    false
+}
@@ -0,0 +1,156 @@
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::{pages, template};
+
+pub const PREFERENCES_LABEL: &str = "preferences";
+
+pub fn open_window(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(PREFERENCES_LABEL) {
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+        return Ok(());
+    }
+
+    WebviewWindowBuilder::new(
+        app,
+        PREFERENCES_LABEL,
+        WebviewUrl::CustomProtocol(pages::page_url("preferences")),
+    )
+    .title(template::t("preferences_title"))
+    .inner_size(520.0, 480.0)
+    .min_inner_size(380.0, 320.0)
+    .build()
+    .map(|_| ())
+    .map_err(|e| format!("Failed to open preferences: {}", e))
+}
+
+const PREFERENCES_CSS: &str = r#"
+  body { padding: 1rem 1.25rem; }
+  fieldset {
+    border: 1px solid var(--border);
+    border-radius: 0.5rem;
+    margin: 0 0 1rem;
+    padding: 0.5rem 0.75rem 0.25rem;
+  }
+  legend { color: var(--fg-muted); font-size: 0.8rem; padding: 0 0.25rem; }
+  .row {
+    display: grid;
+    grid-template-columns: 1fr auto;
+    align-items: center;
+    gap: 0.25rem 1rem;
+    padding: 0.4rem 0;
+  }
+  .row input[type=number], .row select {
+    width: 9rem;
+    background: var(--bg-inset);
+    color: var(--fg);
+    border: 1px solid var(--border);
+    border-radius: 0.375rem;
+    padding: 0.3em 0.5em;
+    font: inherit;
+  }
+  .status { grid-column: 1 / -1; font-size: 0.75rem; color: var(--fg-muted); min-height: 1em; }
+  .status.error { color: var(--accent-error); }
+"#;
+
+const PREFERENCES_BODY: &str = r#"<body data-saved="{{t:pref_saved}}" data-restart="{{t:pref_restart_needed}}">
+  <fieldset>
+    <legend>{{t:pref_general}}</legend>
+    <label class="row">{{t:pref_autostart}}
+      <input type="checkbox" data-key="autostart">
+      <span class="status"></span></label>
+    <label class="row">{{t:pref_start_minimized}}
+      <input type="checkbox" data-key="start_minimized">
+      <span class="status"></span></label>
+    <label class="row">{{t:pref_run_in_background}}
+      <input type="checkbox" data-key="run_in_background">
+      <span class="status"></span></label>
+    <label class="row">{{t:pref_close_to_tray}}
+      <input type="checkbox" data-key="close_to_tray">
+      <span class="status"></span></label>
+    <label class="row">{{t:pref_theme}}
+      <select data-key="theme">
+        <option value="system">{{t:pref_theme_system}}</option>
+        <option value="light">{{t:pref_theme_light}}</option>
+        <option value="dark">{{t:pref_theme_dark}}</option>
+      </select>
+      <span class="status"></span></label>
+    <label class="row">{{t:pref_zoom}}
+      <input type="number" min="0.5" max="3" step="0.1" data-key="zoom">
+      <span class="status"></span></label>
+    <label class="row">{{t:pref_crash_reports}}
+      <input type="checkbox" data-key="crash_reports">
+      <span class="status"></span></label>
+  </fieldset>
+  <fieldset>
+    <legend>{{t:pref_backend}}</legend>
+    <label class="row">{{t:pref_transport}}
+      <select data-key="backend_transport" data-restart>
+        <option value="tcp">{{t:pref_transport_tcp}}</option>
+        <option value="socket">{{t:pref_transport_socket}}</option>
+      </select>
+      <span class="status"></span></label>
+    <label class="row">{{t:pref_backend_port}}
+      <input type="number" min="1024" max="65535" data-key="backend_port" data-restart>
+      <span class="status"></span></label>
+    <label class="row">{{t:pref_log_level}}
+      <select data-key="log_level" data-restart>
+        <option value="error">error</option>
+        <option value="warn">warn</option>
+        <option value="info">info</option>
+        <option value="debug">debug</option>
+      </select>
+      <span class="status"></span></label>
+  </fieldset>
+  <fieldset>
+    <legend>{{t:pref_health}}</legend>
+    <label class="row">{{t:pref_health_auto_tune}}
+      <input type="checkbox" data-key="health_auto_tune">
+      <span class="status"></span></label>
+    <label class="row">{{t:pref_health_interval}}
+      <input type="number" min="50" max="10000" step="50" data-key="health_interval_ms">
+      <span class="status"></span></label>
+    <label class="row">{{t:pref_health_timeout}}
+      <input type="number" min="1000" max="300000" step="1000" data-key="health_timeout_ms">
+      <span class="status"></span></label>
+  </fieldset>
+  <script>
+    const fields = document.querySelectorAll('[data-key]');
+
+    function show(field, value) {
+      if (field.type === 'checkbox') field.checked = !!value;
+      else field.value = value ?? '';
+    }
+
+    function read(field) {
+      if (field.type === 'checkbox') return field.checked;
+      if (field.type === 'number') return Number(field.value);
+      return field.value;
+    }
+
+    async function save(field) {
+      const status = field.parentElement.querySelector('.status');
+      status.classList.remove('error');
+      try {
+        const value = await window.__invoke('set_setting', { key: field.dataset.key, value: read(field) });
+        show(field, value);
+        status.textContent = field.hasAttribute('data-restart')
+          ? document.body.dataset.restart
+          : document.body.dataset.saved;
+      } catch (e) {
+        status.textContent = String(e);
+        status.classList.add('error');
+      }
+    }
+
+    window.__invoke('get_settings').then((settings) => {
+      for (const field of fields) show(field, settings[field.dataset.key]);
+    });
+    for (const field of fields) field.addEventListener('change', () => save(field));
+  </script>
+</body>"#;
+
+pub fn generate_preferences_html() -> String {
+    let body = template::fill(PREFERENCES_BODY, &[]);
+    template::render(template::t("preferences_title"), PREFERENCES_CSS, &body)
+}
@@ -0,0 +1,252 @@
+//! First-run checks: everything Local Agent needs from the machine, reported
+//! as a checklist the frontend renders, with an action to fix each problem
+//! where there is one.
+
+use serde::Serialize;
+use std::fs;
+use std::net::TcpListener;
+use std::path::PathBuf;
+
+use crate::ollama::{self, OllamaConfig};
+use crate::{hardware, node};
+
+/// Below this the backend can't be expected to keep working.
+const DISK_FAIL_BYTES: u64 = 500 * 1024 * 1024;
+/// Below this there's no room for a local model.
+const DISK_WARN_BYTES: u64 = 5 * 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    /// Works, but something is missing or will run out.
+    Warn,
+    /// The backend won't start or won't keep working.
+    Fail,
+    /// Doesn't apply to this install.
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FixAction {
+    /// A download or help page.
+    OpenUrl { label: String, url: String },
+    /// A shell command to invoke, e.g. `open_preferences`.
+    Invoke { label: String, command: String },
+    /// A folder to show.
+    RevealPath { label: String, path: PathBuf },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub id: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub fixes: Vec<FixAction>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightReport {
+    pub at: String,
+    /// No check failed.
+    pub ok: bool,
+    pub checks: Vec<CheckResult>,
+}
+
+/// What to check, resolved by the caller from the config and the running
+/// backend.
+pub struct PreflightInputs {
+    /// False when the app ships a compiled backend.
+    pub node_required: bool,
+    /// `None` when the backend listens on a socket instead.
+    pub port: Option<u16>,
+    /// The backend is already running on `port`.
+    pub port_in_use_by_backend: bool,
+    pub data_dir: PathBuf,
+    /// Set to include Ollama.
+    pub ollama: Option<OllamaConfig>,
+}
+
+pub async fn run(inputs: PreflightInputs) -> PreflightReport {
+    let ollama = inputs.ollama.clone();
+    // `node --version` and the disk scan block
+    let mut checks = tokio::task::spawn_blocking(move || {
+        vec![
+            check_node(inputs.node_required),
+            check_disk(&inputs.data_dir),
+            check_port(inputs.port, inputs.port_in_use_by_backend),
+            check_data_dir(&inputs.data_dir),
+        ]
+    })
+    .await
+    .unwrap_or_default();
+    if let Some(ref config) = ollama {
+        checks.push(check_ollama(config).await);
+    }
+    PreflightReport {
+        at: chrono::Local::now().to_rfc3339(),
+        ok: checks.iter().all(|c| c.status != CheckStatus::Fail),
+        checks,
+    }
+}
+
+fn result(id: &'static str, status: CheckStatus, detail: String) -> CheckResult {
+    CheckResult {
+        id,
+        status,
+        detail,
+        fixes: Vec::new(),
+    }
+}
+
+fn check_node(required: bool) -> CheckResult {
+    if !required {
+        return result(
+            "node",
+            CheckStatus::Skipped,
+            "The bundled backend includes its own runtime.".to_string(),
+        );
+    }
+    match node::check() {
+        Ok(info) => result(
+            "node",
+            CheckStatus::Pass,
+            format!("Node.js {} found.", info.version),
+        ),
+        Err(problem) => CheckResult {
+            fixes: vec![FixAction::OpenUrl {
+                label: format!("Download Node.js {} or newer", node::MIN_NODE_VERSION),
+                url: "https://nodejs.org/en/download".to_string(),
+            }],
+            ..result("node", CheckStatus::Fail, problem.message())
+        },
+    }
+}
+
+fn check_disk(data_dir: &std::path::Path) -> CheckResult {
+    let Some(disk) = hardware::disk_for(data_dir) else {
+        return result(
+            "disk_space",
+            CheckStatus::Warn,
+            format!(
+                "Could not tell how much space is free for {}.",
+                data_dir.display()
+            ),
+        );
+    };
+    let free_gb = disk.free_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+    let status = if disk.free_bytes < DISK_FAIL_BYTES {
+        CheckStatus::Fail
+    } else if disk.free_bytes < DISK_WARN_BYTES {
+        CheckStatus::Warn
+    } else {
+        CheckStatus::Pass
+    };
+    let detail = match status {
+        CheckStatus::Pass => format!("{:.1} GB free on {}.", free_gb, disk.mount_point.display()),
+        _ => format!(
+            "Only {:.1} GB free on {}; chats and local models need more room.",
+            free_gb,
+            disk.mount_point.display()
+        ),
+    };
+    result("disk_space", status, detail)
+}
+
+fn check_port(port: Option<u16>, in_use_by_backend: bool) -> CheckResult {
+    let Some(port) = port else {
+        return result(
+            "port",
+            CheckStatus::Skipped,
+            "The backend uses a private socket instead of a port.".to_string(),
+        );
+    };
+    if in_use_by_backend {
+        return result(
+            "port",
+            CheckStatus::Pass,
+            format!("Port {} is in use by the Local Agent backend.", port),
+        );
+    }
+    match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(_) => result("port", CheckStatus::Pass, format!("Port {} is free.", port)),
+        Err(e) => CheckResult {
+            fixes: vec![FixAction::Invoke {
+                label: "Choose another port".to_string(),
+                command: "open_preferences".to_string(),
+            }],
+            ..result(
+                "port",
+                CheckStatus::Fail,
+                format!(
+                    "Port {} is not available ({}); another program may be using it.",
+                    port, e
+                ),
+            )
+        },
+    }
+}
+
+fn check_data_dir(data_dir: &std::path::Path) -> CheckResult {
+    let probe = data_dir.join(format!(".local-agent-write-test-{}", uuid::Uuid::new_v4()));
+    let written = fs::create_dir_all(data_dir).and_then(|()| fs::write(&probe, b"ok"));
+    let _ = fs::remove_file(&probe);
+    match written {
+        Ok(()) => result(
+            "data_dir",
+            CheckStatus::Pass,
+            format!("{} is writable.", data_dir.display()),
+        ),
+        Err(e) => CheckResult {
+            fixes: data_dir
+                .ancestors()
+                .find(|dir| dir.is_dir())
+                .map(|dir| FixAction::RevealPath {
+                    label: "Show folder".to_string(),
+                    path: dir.to_path_buf(),
+                })
+                .into_iter()
+                .collect(),
+            ..result(
+                "data_dir",
+                CheckStatus::Fail,
+                format!("Can't write to {}: {}", data_dir.display(), e),
+            )
+        },
+    }
+}
+
+async fn check_ollama(config: &OllamaConfig) -> CheckResult {
+    let installed = ollama::find_ollama(config);
+    let version = ollama::server_version().await;
+    match (installed, version) {
+        (_, Some(version)) => result(
+            "ollama",
+            CheckStatus::Pass,
+            format!("Ollama {} is running.", version),
+        ),
+        (Some(path), None) => CheckResult {
+            fixes: vec![FixAction::Invoke {
+                label: "Start Ollama".to_string(),
+                command: "start_ollama".to_string(),
+            }],
+            ..result(
+                "ollama",
+                CheckStatus::Warn,
+                format!("Ollama is installed at {} but not running.", path.display()),
+            )
+        },
+        (None, None) => CheckResult {
+            fixes: vec![FixAction::OpenUrl {
+                label: "Download Ollama".to_string(),
+                url: "https://ollama.com/download".to_string(),
+            }],
+            ..result(
+                "ollama",
+                CheckStatus::Warn,
+                "Ollama is not installed; local models won't be available.".to_string(),
+            )
+        },
+    }
+}
@@ -0,0 +1,161 @@
+//! Global → profile layering for the shell config.
+//!
+//! The config file holds the global settings plus a `profiles` map of
+//! partial overrides. The active profile's overrides are deep-merged over
+//! the global settings on load; on save every value goes back to the layer
+//! that set it, so changing a setting a profile overrides updates only that
+//! profile.
+//!
+//! Each profile also runs the backend on its own data: a profile that
+//! doesn't set `data_dir` or `settings.backend_port` gets a directory and
+//! a port derived from its name, so an experimental profile never touches
+//! the default profile's chats. `default` keeps the global values.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+
+use crate::paths;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Longest profile name accepted by `create_profile`.
+const MAX_NAME_LEN: usize = 32;
+
+/// Keys only the global layer may set; they are dropped from overrides.
+pub const GLOBAL_ONLY: &[&str] = &["profile", "profiles", "secret_names"];
+
+/// The merged config for the active profile, for display.
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveConfig {
+    pub profile: String,
+    pub profiles: Vec<String>,
+    pub config: Value,
+    /// Dotted paths whose value comes from the profile, e.g. `ollama.auto_start`.
+    pub overridden: Vec<String>,
+}
+
+/// Overrides for `profile` in a raw config document, minus global-only keys.
+pub fn overlay(raw: &Value, profile: &str) -> Map<String, Value> {
+    let mut overlay = raw
+        .get("profiles")
+        .and_then(|p| p.get(profile))
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+    for key in GLOBAL_ONLY {
+        overlay.remove(*key);
+    }
+    overlay
+}
+
+/// Applies `overlay` to `base`: objects merge key by key, anything else
+/// (including arrays) replaces the base value.
+pub fn merge(base: &mut Value, overlay: &Map<String, Value>) {
+    if !base.is_object() {
+        *base = Value::Object(Map::new());
+    }
+    let base = base.as_object_mut().unwrap();
+    for (key, value) in overlay {
+        match (base.get_mut(key), value) {
+            (Some(existing @ Value::Object(_)), Value::Object(nested)) => merge(existing, nested),
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Reverses `merge`: takes current values at the paths `overlay` sets into
+/// `overlay`, and restores `effective` there from `global`, leaving
+/// `effective` as the new global layer.
+pub fn split(effective: &mut Value, global: &Value, overlay: &mut Map<String, Value>) {
+    let Some(effective) = effective.as_object_mut() else {
+        return;
+    };
+    for (key, value) in overlay.iter_mut() {
+        let before = global.get(key);
+        match (effective.get_mut(key), value) {
+            (Some(current @ Value::Object(_)), Value::Object(nested)) => {
+                split(current, before.unwrap_or(&Value::Null), nested)
+            }
+            (current, value) => {
+                if let Some(current) = current {
+                    *value = current.clone();
+                }
+                match before {
+                    Some(before) => {
+                        effective.insert(key.clone(), before.clone());
+                    }
+                    None => {
+                        effective.remove(key);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Dotted paths of every leaf `overlay` sets.
+pub fn overridden_paths(overlay: &Map<String, Value>) -> Vec<String> {
+    let mut paths = Vec::new();
+    collect_paths(overlay, "", &mut paths);
+    paths
+}
+
+fn collect_paths(overlay: &Map<String, Value>, prefix: &str, paths: &mut Vec<String>) {
+    for (key, value) in overlay {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Value::Object(nested) if !nested.is_empty() => collect_paths(nested, &path, paths),
+            _ => paths.push(path),
+        }
+    }
+}
+
+/// Profile names are used as directory names, so they are kept plain.
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(format!(
+            "Profile names are 1 to {} characters long",
+            MAX_NAME_LEN
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Profile name {:?} may only contain letters, digits, - and _",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Where profiles without a `data_dir` of their own keep their data.
+pub fn data_root() -> PathBuf {
+    paths::config_dir().join("profiles")
+}
+
+pub fn derived_data_dir(profile: &str) -> PathBuf {
+    data_root().join(profile)
+}
+
+/// `base` for the default profile; others get a port of their own above
+/// it, stable for the name, so each profile's UI has its own origin and
+/// browser storage.
+pub fn derived_port(base: u16, profile: &str) -> u16 {
+    if profile == DEFAULT_PROFILE {
+        return base;
+    }
+    // FNV-1a: stable across builds, unlike the std hasher
+    let hash = profile.bytes().fold(0x811c9dc5u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    });
+    base.saturating_add(1 + (hash % 100) as u16)
+}
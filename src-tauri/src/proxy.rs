@@ -0,0 +1,183 @@
+//! Proxy settings for networks that only reach the internet through one.
+//! The configured proxies are used by the shell's own HTTP clients (health
+//! checks, the update check, crash uploads) and passed to every sidecar as
+//! `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY`. Loopback addresses always
+//! bypass them, so the backend stays reachable. With nothing configured the
+//! clients keep reqwest's default of following the system's variables.
+//! Read at startup.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Hosts that never go through the proxy.
+const LOOPBACK: &[&str] = &["localhost", "127.0.0.1", "::1"];
+
+/// Checked by `test_connectivity` when no URL is given.
+pub const DEFAULT_TEST_URL: &str = "https://api.github.com";
+
+static CONFIG: OnceLock<ProxyConfig> = OnceLock::new();
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    /// Proxy for plain HTTP requests, e.g. `http://proxy.corp:8080`;
+    /// credentials can go in the URL.
+    pub http: Option<String>,
+    /// Proxy for HTTPS requests.
+    pub https: Option<String>,
+    /// Hosts, domains (`.corp.example`) or CIDR ranges reached directly.
+    pub no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    fn is_set(&self) -> bool {
+        self.http.is_some() || self.https.is_some()
+    }
+
+    fn no_proxy_list(&self) -> String {
+        LOOPBACK
+            .iter()
+            .copied()
+            .chain(self.no_proxy.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// The variables sidecars get; empty when no proxy is configured.
+    pub fn env(&self) -> BTreeMap<String, String> {
+        let mut env = BTreeMap::new();
+        if !self.is_set() {
+            return env;
+        }
+        // Tools disagree on the case, so both are set
+        let mut set = |name: &str, value: &str| {
+            env.insert(name.to_string(), value.to_string());
+            env.insert(name.to_lowercase(), value.to_string());
+        };
+        if let Some(ref http) = self.http {
+            set("HTTP_PROXY", http);
+        }
+        if let Some(ref https) = self.https {
+            set("HTTPS_PROXY", https);
+        }
+        set("NO_PROXY", &self.no_proxy_list());
+        env
+    }
+
+    /// Adds the configured proxies to `builder`. A proxy URL that can't be
+    /// used is logged and left out.
+    pub fn apply(&self, mut builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        let no_proxy = reqwest::NoProxy::from_string(&self.no_proxy_list());
+        if let Some(ref url) = self.http {
+            builder = with_proxy(builder, url, reqwest::Proxy::http(url), &no_proxy);
+        }
+        if let Some(ref url) = self.https {
+            builder = with_proxy(builder, url, reqwest::Proxy::https(url), &no_proxy);
+        }
+        builder
+    }
+}
+
+fn with_proxy(
+    builder: reqwest::ClientBuilder,
+    url: &str,
+    proxy: reqwest::Result<reqwest::Proxy>,
+    no_proxy: &Option<reqwest::NoProxy>,
+) -> reqwest::ClientBuilder {
+    match proxy {
+        Ok(proxy) => builder.proxy(proxy.no_proxy(no_proxy.clone())),
+        Err(e) => {
+            eprintln!("[tauri] Ignoring proxy {}: {}", redact(url), e);
+            builder
+        }
+    }
+}
+
+/// Sets the proxies for this run.
+pub fn init(config: &ProxyConfig) {
+    if CONFIG.set(config.clone()).is_ok() && config.is_set() {
+        println!(
+            "[tauri] Using proxy http={} https={}",
+            config.http.as_deref().map(redact).unwrap_or_default(),
+            config.https.as_deref().map(redact).unwrap_or_default()
+        );
+    }
+}
+
+pub fn current() -> ProxyConfig {
+    CONFIG.get().cloned().unwrap_or_default()
+}
+
+/// A client builder with this run's proxies applied.
+pub fn client_builder() -> reqwest::ClientBuilder {
+    current().apply(reqwest::Client::builder())
+}
+
+/// `url` without its password, for logs and the UI.
+fn redact(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) if parsed.password().is_some() => {
+            let _ = parsed.set_password(Some("***"));
+            parsed.to_string()
+        }
+        _ => url.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectivityResult {
+    pub url: String,
+    /// The proxy the request went through, if any.
+    pub proxy: Option<String>,
+    /// Whether any HTTP response came back, whatever its status.
+    pub reachable: bool,
+    pub status: Option<u16>,
+    pub elapsed_ms: u64,
+    pub error: Option<String>,
+}
+
+/// One request to `url` through the configured proxies.
+pub async fn test(url: &str) -> Result<ConnectivityResult, String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+    let config = current();
+    let bypassed = config
+        .no_proxy_list()
+        .split(',')
+        .any(|host| parsed.host_str().map(|h| h.trim_matches(['[', ']'])) == Some(host));
+    let proxy = match parsed.scheme() {
+        "http" => config.http.as_deref(),
+        "https" => config.https.as_deref(),
+        _ => None,
+    }
+    .filter(|_| !bypassed)
+    .map(redact);
+
+    let client = client_builder()
+        .timeout(Duration::from_secs(10))
+        .user_agent(format!("local-agent/{}", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let start = Instant::now();
+    let response = client.get(parsed).send().await;
+    let elapsed_ms = start.elapsed().as_millis() as u64;
+    Ok(match response {
+        Ok(response) => ConnectivityResult {
+            url: url.to_string(),
+            proxy,
+            reachable: true,
+            status: Some(response.status().as_u16()),
+            elapsed_ms,
+            error: None,
+        },
+        Err(e) => ConnectivityResult {
+            url: url.to_string(),
+            proxy,
+            reachable: false,
+            status: None,
+            elapsed_ms,
+            error: Some(e.to_string()),
+        },
+    })
+}
@@ -0,0 +1,244 @@
+//! Quitting while the backend is busy. Before the shell stops the backend
+//! it asks `/api/activity` for work in flight; if there is any, a small
+//! window offers to wait for it, quit anyway or cancel. The backend itself
+//! is then stopped by the usual shutdown, which lets it drain first.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::{api, pages, template, AppState};
+
+pub const QUIT_LABEL: &str = "quit";
+const WAIT_POLL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Activity {
+    pub busy: bool,
+    #[serde(default)]
+    pub jobs: Vec<Job>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: u64,
+    /// `generation`, `tool` or `sync`.
+    pub kind: String,
+    pub path: String,
+    pub started_at: String,
+}
+
+/// What the backend is working on; `None` when it doesn't answer.
+pub async fn activity(app: &AppHandle) -> Option<Activity> {
+    let (status, body) = api::call(app, "GET", "/api/activity").await?;
+    if !(200..300).contains(&status) {
+        return None;
+    }
+    serde_json::from_slice(&body).ok()
+}
+
+/// What happens once quitting goes ahead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuitAction {
+    /// Quit from the tray or app menu.
+    Exit,
+    /// The last app window, with this label, was closed.
+    Close(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuitChoice {
+    Wait,
+    Force,
+    Cancel,
+}
+
+/// The quit in progress, so a second request doesn't start another.
+#[derive(Default)]
+pub struct QuitGuard {
+    pending: Mutex<Option<QuitAction>>,
+}
+
+impl QuitGuard {
+    fn begin(&self, action: QuitAction) -> bool {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.is_some() {
+            return false;
+        }
+        *pending = Some(action);
+        true
+    }
+
+    fn is_pending(&self) -> bool {
+        self.pending.lock().unwrap().is_some()
+    }
+
+    fn take(&self) -> Option<QuitAction> {
+        self.pending.lock().unwrap().take()
+    }
+
+    /// Drops a quit that hasn't gone ahead yet.
+    pub fn cancel(&self) {
+        self.take();
+    }
+}
+
+/// Quits, or asks first when the backend has work in flight.
+pub fn request(app: &AppHandle, action: QuitAction) {
+    if !app.state::<AppState>().quit.begin(action) {
+        if let Some(window) = app.get_webview_window(QUIT_LABEL) {
+            let _ = window.set_focus();
+        }
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match activity(&app).await {
+            Some(activity) if activity.busy => {
+                println!(
+                    "[tauri] Backend has {} job(s) in flight; asking before quitting",
+                    activity.jobs.len()
+                );
+                if let Err(e) = open_prompt(&app) {
+                    eprintln!("[tauri] {}", e);
+                    finish(&app);
+                }
+            }
+            _ => finish(&app),
+        }
+    });
+}
+
+/// Applies the user's answer from the prompt.
+pub fn choose(app: &AppHandle, choice: QuitChoice) {
+    match choice {
+        QuitChoice::Cancel => {
+            app.state::<AppState>().quit.cancel();
+            if let Some(window) = app.get_webview_window(QUIT_LABEL) {
+                let _ = window.destroy();
+            }
+        }
+        QuitChoice::Force => finish(app),
+        QuitChoice::Wait => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                // Until the work is done, or the user cancels or forces
+                while app.state::<AppState>().quit.is_pending() {
+                    match activity(&app).await {
+                        Some(activity) if activity.busy => tokio::time::sleep(WAIT_POLL).await,
+                        _ => {
+                            finish(&app);
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+    }
+}
+
+fn finish(app: &AppHandle) {
+    let Some(action) = app.state::<AppState>().quit.take() else {
+        return;
+    };
+    if let Some(window) = app.get_webview_window(QUIT_LABEL) {
+        let _ = window.destroy();
+    }
+    match action {
+        QuitAction::Exit => app.exit(0),
+        QuitAction::Close(label) => {
+            if let Some(window) = app.get_webview_window(&label) {
+                let _ = window.destroy();
+            }
+        }
+    }
+}
+
+fn open_prompt(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(QUIT_LABEL) {
+        let _ = window.set_focus();
+        return Ok(());
+    }
+
+    WebviewWindowBuilder::new(
+        app,
+        QUIT_LABEL,
+        WebviewUrl::CustomProtocol(pages::page_url("quit")),
+    )
+    .title(template::t("quit_title"))
+    .inner_size(440.0, 300.0)
+    .resizable(false)
+    .always_on_top(true)
+    .center()
+    .build()
+    .map(|_| ())
+    .map_err(|e| format!("Failed to open quit prompt: {}", e))
+}
+
+const QUIT_CSS: &str = r#"
+  body { padding: 1.25rem; }
+  h1 { font-size: 1.1rem; margin-bottom: 0.5rem; }
+  .message { color: var(--fg-muted); margin-bottom: 0.75rem; }
+  #jobs {
+    background: var(--bg-inset);
+    border: 1px solid var(--border);
+    border-radius: 0.375rem;
+    padding: 0.5rem 0.75rem;
+    margin: 0 0 1rem;
+    list-style: none;
+    font-size: 0.8rem;
+    max-height: 7rem;
+    overflow-y: auto;
+  }
+  #jobs li { display: flex; justify-content: space-between; gap: 1rem; padding: 0.15rem 0; }
+  #jobs .elapsed { color: var(--fg-muted); }
+  #waiting { font-size: 0.8rem; color: var(--fg-muted); margin-bottom: 0.75rem; }
+"#;
+
+const QUIT_BODY: &str = r#"<body>
+  <h1>{{t:quit_title}}</h1>
+  <p class="message">{{t:quit_busy}}</p>
+  <ul id="jobs" class="mono"></ul>
+  <p id="waiting" hidden>{{t:quit_waiting}}</p>
+  <div class="actions">
+    <button id="wait" onclick="choose('wait')">{{t:quit_wait}}</button>
+    <button onclick="choose('force')">{{t:quit_now}}</button>
+    <button onclick="choose('cancel')">{{t:quit_cancel}}</button>
+  </div>
+  <script>
+    const list = document.getElementById('jobs');
+
+    function choose(choice) {
+      if (choice === 'wait') {
+        document.getElementById('wait').disabled = true;
+        document.getElementById('waiting').hidden = false;
+      }
+      window.__invoke('confirm_quit', { choice });
+    }
+
+    async function refresh() {
+      const activity = await window.__invoke('get_backend_activity').catch(() => null);
+      const jobs = activity ? activity.jobs : [];
+      list.replaceChildren(...jobs.map((job) => {
+        const item = document.createElement('li');
+        const what = document.createElement('span');
+        what.textContent = job.kind + ' ' + job.path;
+        const elapsed = document.createElement('span');
+        elapsed.className = 'elapsed';
+        elapsed.textContent = Math.round((Date.now() - Date.parse(job.started_at)) / 1000) + 's';
+        item.append(what, elapsed);
+        return item;
+      }));
+    }
+
+    refresh();
+    setInterval(refresh, 1000);
+  </script>
+</body>"#;
+
+pub fn generate_quit_html() -> String {
+    let body = template::fill(QUIT_BODY, &[]);
+    template::render(template::t("quit_title"), QUIT_CSS, &body)
+}
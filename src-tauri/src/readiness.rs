@@ -0,0 +1,72 @@
+//! Readiness announced by a sidecar on stdout, so boot doesn't have to wait
+//! for the next health poll. Either form works, on a line of its own:
+//!
+//! ```text
+//! ::ready port=3001
+//! {"event": "ready", "port": 3001}
+//! ```
+//!
+//! `port` is optional; when present the shell talks to the sidecar there.
+//! Health polling still runs alongside for sidecars that never announce.
+
+use serde_json::Value;
+use tokio::sync::watch;
+
+use crate::health::{self, PollSettings, ProbeSpec};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ready {
+    pub port: Option<u16>,
+}
+
+/// The announcement in `line`, if it is one.
+pub fn parse(line: &str) -> Option<Ready> {
+    let line = line.trim();
+    if let Some(rest) = line.strip_prefix("::ready") {
+        if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let port = rest
+            .split_whitespace()
+            .filter_map(|field| field.split_once('='))
+            .find(|(key, _)| *key == "port")
+            .and_then(|(_, value)| value.parse().ok());
+        return Some(Ready { port });
+    }
+    if line.starts_with('{') && line.contains("ready") {
+        let event: Value = serde_json::from_str(line).ok()?;
+        if event.get("event")?.as_str()? != "ready" {
+            return None;
+        }
+        let port = event
+            .get("port")
+            .and_then(Value::as_u64)
+            .and_then(|port| u16::try_from(port).ok());
+        return Some(Ready { port });
+    }
+    None
+}
+
+/// Resolves once the sidecar announces it is ready or `probe` passes,
+/// whichever comes first. Without an announcement channel this is plain
+/// health polling.
+pub async fn wait(
+    announced: Option<watch::Receiver<Option<Ready>>>,
+    probe: &ProbeSpec,
+    settings: &PollSettings,
+    trace_id: Option<&str>,
+) -> Result<(), String> {
+    let Some(mut announced) = announced else {
+        return health::poll_health_with(probe, settings, trace_id).await;
+    };
+    tokio::select! {
+        result = health::poll_health_with(probe, settings, trace_id) => result,
+        Ok(ready) = announced.wait_for(Option::is_some) => {
+            match (*ready).and_then(|ready| ready.port) {
+                Some(port) => println!("[tauri] Sidecar announced ready on port {}", port),
+                None => println!("[tauri] Sidecar announced ready"),
+            }
+            Ok(())
+        }
+    }
+}
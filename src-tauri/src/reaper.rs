@@ -0,0 +1,236 @@
+//! Makes sure sidecars cannot outlive the shell.
+//!
+//! A normal quit stops every sidecar through `SidecarManager::shutdown`, but
+//! if the shell itself is killed (Task Manager, `kill -9`, a crash) nothing
+//! runs and the children are orphaned.
+//!
+//! On Windows each child is assigned to a Job Object created with
+//! `JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE`. The shell holds the only handle to
+//! the job and never closes it, so when the shell process dies for any
+//! reason the kernel closes the handle and terminates every process in the
+//! job.
+//!
+//! The Linux equivalent is `prctl(PR_SET_PDEATHSIG, SIGKILL)` in the child
+//! between fork and exec. It is deliberately not used: the signal fires when
+//! the *thread* that spawned the child exits, and sidecars are spawned from
+//! short-lived retry and blocking-pool threads, which would kill healthy
+//! backends. macOS has no equivalent at all.
+//!
+//! The backend also forks its own workers (esbuild, puppeteer, model
+//! runners), and killing node alone orphans them. On Unix every sidecar is
+//! started as the leader of a new process group and signals go to the whole
+//! group; on Windows the process tree is killed with `taskkill /T`. A side
+//! effect on Unix is that Ctrl+C in a dev terminal no longer reaches the
+//! sidecar directly; the shell stops it on the way out instead.
+
+use std::process::{Child, Command};
+
+/// Prepares a sidecar command so its descendants can be signalled together.
+pub fn configure(command: &mut Command) {
+    imp::configure(command)
+}
+
+/// Ties `child`'s lifetime to this process where the OS supports it.
+pub fn adopt(child: &Child) {
+    imp::adopt(child)
+}
+
+/// Asks the child and its descendants to exit so they can flush state;
+/// callers force-kill with [`kill`] after a grace period.
+pub fn terminate(child: &mut Child) {
+    imp::terminate(child)
+}
+
+/// Kills the child and every remaining descendant. Safe to call after the
+/// child itself has exited.
+pub fn kill(child: &mut Child) {
+    imp::kill(child)
+}
+
+#[cfg(windows)]
+mod imp {
+    use std::os::windows::io::AsRawHandle;
+    use std::process::{Child, Command};
+    use std::sync::OnceLock;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+        SetInformationJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+
+    /// The job handle, stored as an integer so it can live in a static.
+    static JOB: OnceLock<Option<usize>> = OnceLock::new();
+
+    fn job() -> Option<HANDLE> {
+        (*JOB.get_or_init(|| unsafe {
+            let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+            if job.is_null() {
+                eprintln!("[tauri] Failed to create sidecar job object");
+                return None;
+            }
+
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+            info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            let ok = SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                &info as *const _ as *const core::ffi::c_void,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            );
+            if ok == 0 {
+                eprintln!("[tauri] Failed to configure sidecar job object");
+                CloseHandle(job);
+                return None;
+            }
+            Some(job as usize)
+        }))
+        .map(|job| job as HANDLE)
+    }
+
+    pub fn configure(_command: &mut Command) {}
+
+    pub fn terminate(child: &mut Child) {
+        // Console processes have no polite close on Windows
+        kill(child)
+    }
+
+    pub fn kill(child: &mut Child) {
+        let _ = Command::new("taskkill")
+            .args(["/PID", &child.id().to_string(), "/T", "/F"])
+            .output();
+        let _ = child.kill();
+    }
+
+    pub fn adopt(child: &Child) {
+        let Some(job) = job() else {
+            return;
+        };
+        let ok = unsafe { AssignProcessToJobObject(job, child.as_raw_handle() as HANDLE) };
+        if ok == 0 {
+            eprintln!(
+                "[tauri] Failed to assign pid {} to the sidecar job object; it may outlive the app",
+                child.id()
+            );
+        }
+    }
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::os::unix::process::CommandExt;
+    use std::process::{Child, Command};
+
+    pub fn configure(command: &mut Command) {
+        // pgid == the child's pid
+        command.process_group(0);
+    }
+
+    pub fn adopt(_child: &Child) {}
+
+    fn signal_group(child: &Child, signal: libc::c_int) {
+        unsafe {
+            libc::kill(-(child.id() as libc::pid_t), signal);
+        }
+    }
+
+    pub fn terminate(child: &mut Child) {
+        signal_group(child, libc::SIGTERM);
+    }
+
+    pub fn kill(child: &mut Child) {
+        signal_group(child, libc::SIGKILL);
+        let _ = child.kill();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    /// Gone or a zombie waiting for init; either way no longer running.
+    fn is_running(pid: u32) -> bool {
+        let output = Command::new("ps")
+            .args(["-o", "stat=", "-p", &pid.to_string()])
+            .output()
+            .unwrap();
+        let stat = String::from_utf8_lossy(&output.stdout);
+        !stat.trim().is_empty() && !stat.trim_start().starts_with('Z')
+    }
+
+    fn wait_until_stopped(pid: u32) -> bool {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(5) {
+            if !is_running(pid) {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        false
+    }
+
+    /// Starts a shell that forks a long-running worker (like server.js
+    /// forking esbuild) and returns it with the worker's pid.
+    fn spawn_with_grandchild(script: &str) -> (Child, u32) {
+        let mut command = Command::new("sh");
+        command.args(["-c", script]).stdout(Stdio::piped());
+        configure(&mut command);
+        let mut child = command.spawn().unwrap();
+
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let grandchild: u32 = line.trim().parse().unwrap();
+        assert!(is_running(grandchild));
+        (child, grandchild)
+    }
+
+    #[test]
+    fn kill_reaps_grandchildren() {
+        let (mut child, grandchild) = spawn_with_grandchild("sleep 60 & echo $!; wait");
+
+        kill(&mut child);
+        let _ = child.wait();
+
+        assert!(
+            wait_until_stopped(grandchild),
+            "grandchild {} survived",
+            grandchild
+        );
+    }
+
+    #[test]
+    fn terminate_reaches_grandchildren() {
+        let (mut child, grandchild) = spawn_with_grandchild("sleep 60 & echo $!; wait");
+
+        terminate(&mut child);
+        let _ = child.wait();
+
+        assert!(
+            wait_until_stopped(grandchild),
+            "grandchild {} survived",
+            grandchild
+        );
+    }
+
+    #[test]
+    fn kill_after_parent_exit_reaps_orphans() {
+        // The parent exits on its own, leaving the worker orphaned in the group
+        let (mut child, grandchild) = spawn_with_grandchild("sleep 60 & echo $!");
+        let _ = child.wait();
+        assert!(is_running(grandchild));
+
+        kill(&mut child);
+
+        assert!(
+            wait_until_stopped(grandchild),
+            "orphan {} survived",
+            grandchild
+        );
+    }
+}
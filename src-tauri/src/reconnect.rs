@@ -0,0 +1,194 @@
+//! The "Reconnecting…" page. When a running backend goes down (crash,
+//! memory restart, resume), the main window would otherwise sit on a page
+//! whose requests all fail. Instead it shows this page, which follows the
+//! backend's state as the shell pushes it in, and goes back to the app once
+//! the backend is healthy again.
+//!
+//! A soft restart (`restart_backend_soft`) holds the page back so drafts
+//! survive: the app stays loaded, hears `backend-ready` when the backend is
+//! back and answers with `ack_backend_ready`. Only an app that doesn't
+//! answer is reloaded.
+//!
+//! With `offline.enabled` and the frontend built in, the window shows the
+//! offline app (see `offline`) in place of the page.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Notify;
+
+use crate::state::{BackendState, BackendStatus};
+use crate::{emit_event, navigation, offline, template};
+
+/// Sent after a soft restart once the backend answers again.
+pub const READY_EVENT: &str = "backend-ready";
+
+/// How long the app gets to acknowledge `backend-ready`.
+const READY_ACK_TIMEOUT: Duration = Duration::from_secs(5);
+
+const RECONNECT_CSS: &str = r#"
+  body { gap: 1rem; text-align: center; }
+  h1 { font-size: 1.2rem; font-weight: 600; }
+  .spinner {
+    width: 1.75rem;
+    height: 1.75rem;
+    border: 0.2rem solid var(--border);
+    border-top-color: var(--fg);
+    border-radius: 50%;
+    animation: spin 0.9s linear infinite;
+  }
+  .message { color: var(--fg-muted); max-width: 28rem; }
+  #status { color: var(--fg-muted); font-size: 0.8rem; }
+  .failed .spinner { display: none; }
+  .failed h1 { color: var(--accent-error); }
+  @keyframes spin {
+    to { transform: rotate(360deg); }
+  }
+"#;
+
+const RECONNECT_BODY: &str = r#"<body class="centered">
+  <div class="spinner" role="progressbar" aria-label="{{t:reconnecting}}"></div>
+  <h1 id="title" data-failed="{{t:reconnect_failed}}">{{t:reconnecting}}</h1>
+  <p class="message">{{t:reconnecting_detail}}</p>
+  <p id="status" class="mono" aria-live="polite">{{status}}</p>
+  <div class="actions">
+    <button id="retry" onclick="window.__invoke('restart_backend')" hidden>{{t:retry}}</button>
+    <button onclick="window.__invoke('open_log_viewer')">{{t:view_logs}}</button>
+  </div>
+  <script>
+    window.__setBackendState = function (status) {
+      const failed = status.state === 'failed';
+      document.body.classList.toggle('failed', failed);
+      document.getElementById('retry').hidden = !failed;
+      const title = document.getElementById('title');
+      if (failed) title.textContent = title.dataset.failed;
+      document.getElementById('status').textContent = status.text;
+    };
+  </script>
+</body>"#;
+
+/// What the page shows for `status`.
+#[derive(Serialize)]
+struct PageStatus {
+    state: BackendState,
+    text: String,
+}
+
+fn page_status(status: &BackendStatus) -> PageStatus {
+    let text = match status.detail {
+        Some(ref detail) => format!("Backend {}: {}", status.state.label(), detail),
+        None => format!("Backend {}", status.state.label()),
+    };
+    PageStatus {
+        state: status.state,
+        text: template::sanitize_log_line(&text),
+    }
+}
+
+pub fn generate_reconnect_html(status: &BackendStatus) -> String {
+    let text = page_status(status).text;
+    let body = template::fill(RECONNECT_BODY, &[("status", &text)]);
+    template::render(template::t("reconnecting"), RECONNECT_CSS, &body)
+}
+
+/// Whether the main window is showing the page.
+#[derive(Default)]
+pub struct Interstitial {
+    shown: AtomicBool,
+    /// What is shown is the offline app rather than the page.
+    offline: AtomicBool,
+    /// A soft restart is running; the app stays loaded.
+    held: AtomicBool,
+    acked: Notify,
+}
+
+impl Interstitial {
+    /// Keeps the page from replacing the app while `held`.
+    pub fn hold(&self, held: bool) {
+        self.held.store(held, Ordering::SeqCst);
+    }
+
+    /// The app reconnected after `backend-ready`.
+    pub fn ack(&self) {
+        self.acked.notify_waiters();
+    }
+
+    /// Emits `backend-ready` and waits for the app to acknowledge it,
+    /// reloading the app at `app_url` if it doesn't. Returns whether it
+    /// answered.
+    pub async fn announce_ready(&self, app: &AppHandle, app_url: &str) -> bool {
+        // Registered before emitting so a quick answer isn't missed
+        let acked = self.acked.notified();
+        tokio::pin!(acked);
+        acked.as_mut().enable();
+        emit_event(app, READY_EVENT, ());
+        if tokio::time::timeout(READY_ACK_TIMEOUT, acked).await.is_ok() {
+            return true;
+        }
+        eprintln!(
+            "[tauri] The app did not answer {} within {:?}; reloading it",
+            READY_EVENT, READY_ACK_TIMEOUT
+        );
+        navigation::load_app(app, app_url);
+        false
+    }
+
+    /// Another page replaced it.
+    pub fn dismiss(&self) {
+        self.shown.store(false, Ordering::SeqCst);
+        self.offline.store(false, Ordering::SeqCst);
+    }
+
+    /// Whether the main window shows the offline app.
+    pub fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::SeqCst)
+    }
+
+    /// Shows, updates or leaves the page for the backend's new state.
+    /// `app_url` is where a recovered backend sends the window back to;
+    /// `offline_url`, when set, is shown instead of the page.
+    pub fn follow(
+        &self,
+        app: &AppHandle,
+        status: &BackendStatus,
+        app_url: &str,
+        offline_url: Option<&str>,
+    ) {
+        let shown = self.shown.load(Ordering::SeqCst);
+        if !shown && self.held.load(Ordering::SeqCst) {
+            return;
+        }
+        match status.state {
+            BackendState::Restarting if !shown => {
+                if app.get_webview_window("main").is_none() {
+                    return;
+                }
+                match offline_url {
+                    Some(url) => {
+                        navigation::load_app(app, url);
+                        self.offline.store(true, Ordering::SeqCst);
+                        emit_event(app, offline::EVENT, true);
+                    }
+                    None => navigation::load_error(app, &generate_reconnect_html(status)),
+                }
+                self.shown.store(true, Ordering::SeqCst);
+            }
+            BackendState::Healthy if shown => {
+                self.shown.store(false, Ordering::SeqCst);
+                self.offline.store(false, Ordering::SeqCst);
+                navigation::load_app(app, app_url);
+            }
+            _ if shown => {
+                if let Some(main_window) = app.get_webview_window("main") {
+                    let js = format!(
+                        "window.__setBackendState && window.__setBackendState({});",
+                        serde_json::to_string(&page_status(status)).unwrap()
+                    );
+                    let _ = main_window.eval(js);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Scrubbing of sidecar output before it is logged anywhere. Lines are
+//! redacted as the piping threads read them, so the log files, the in-memory
+//! ring, `log-line` events, the system log and every report or bundle built
+//! from them only see the redacted text.
+//!
+//! Hidden: bearer tokens, `key=value` credentials and API-key-shaped words
+//! (the same rules as issue reports), email addresses, and whatever the
+//! configured `patterns` match. `raw` turns it all off for debugging; read
+//! at startup.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::issue::{self, REDACTED};
+
+const EMAIL_PATTERN: &str = r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    /// Log lines exactly as the sidecars print them.
+    pub raw: bool,
+    pub emails: bool,
+    /// Extra regular expressions whose matches are hidden, e.g. for
+    /// internal hostnames or customer IDs.
+    pub patterns: Vec<String>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            raw: false,
+            emails: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+pub struct Redactor {
+    patterns: Vec<Regex>,
+}
+
+impl Redactor {
+    /// Compiles the configured patterns; one that doesn't compile is logged
+    /// and left out.
+    pub fn new(config: &RedactionConfig) -> Self {
+        let emails = config.emails.then_some(EMAIL_PATTERN);
+        let patterns = emails
+            .into_iter()
+            .chain(config.patterns.iter().map(String::as_str))
+            .filter_map(|pattern| match Regex::new(pattern) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    eprintln!("[tauri] Ignoring redaction pattern {:?}: {}", pattern, e);
+                    None
+                }
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    pub fn apply(&self, line: &str) -> String {
+        let mut line = issue::redact_secrets(line);
+        for pattern in &self.patterns {
+            if pattern.is_match(&line) {
+                line = pattern.replace_all(&line, REDACTED).into_owned();
+            }
+        }
+        line
+    }
+}
+
+/// `None` when logging raw.
+static REDACTOR: OnceLock<Option<Redactor>> = OnceLock::new();
+
+/// Sets up redaction for this run; call once at startup.
+pub fn init(config: &RedactionConfig) {
+    REDACTOR.get_or_init(|| {
+        if config.raw {
+            println!("[tauri] Log redaction is off; sidecar output is logged as is");
+            return None;
+        }
+        Some(Redactor::new(config))
+    });
+}
+
+/// `line` as it may be logged. Before `init`, the defaults apply.
+pub fn line(line: String) -> String {
+    match REDACTOR.get_or_init(|| Some(Redactor::new(&RedactionConfig::default()))) {
+        Some(redactor) => redactor.apply(&line),
+        None => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hides_credentials_emails_and_custom_patterns() {
+        let redactor = Redactor::new(&RedactionConfig {
+            patterns: vec![r"cust-\d+".to_string()],
+            ..RedactionConfig::default()
+        });
+        assert_eq!(
+            redactor.apply("retrying with Bearer abc.def for ada@example.co.uk"),
+            "retrying with Bearer [redacted] for [redacted]"
+        );
+        assert_eq!(
+            redactor.apply("loaded cust-4821 with api_key=sk-123"),
+            "loaded [redacted] with api_key=[redacted]"
+        );
+        assert_eq!(redactor.apply("listening on 3001"), "listening on 3001");
+    }
+}
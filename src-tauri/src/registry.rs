@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+use std::process::ExitStatus;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::logstream::{Level, LogBatcher};
+use crate::sidecar::{SidecarManager, SidecarStatus};
+use crate::syslog;
+
+pub const BACKEND: &str = "backend";
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Named set of supervised sidecars. Managers are handed out as `Arc`s so
+/// slow operations (spawn retries, shutdown waits) never hold the map lock.
+#[derive(Default)]
+pub struct SidecarRegistry {
+    sidecars: Mutex<BTreeMap<String, Arc<SidecarManager>>>,
+    logs: Option<Arc<LogBatcher>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SidecarExit {
+    pub name: String,
+    pub status: ExitStatus,
+    pub restarting: bool,
+    /// The policy allowed a restart but `allow_restart` held it back.
+    pub throttled: bool,
+}
+
+impl SidecarRegistry {
+    /// Output of every sidecar inserted later is also streamed to `logs`.
+    pub fn with_log_stream(logs: Arc<LogBatcher>) -> Self {
+        Self {
+            logs: Some(logs),
+            ..Self::default()
+        }
+    }
+
+    pub fn insert(&self, manager: SidecarManager) -> Arc<SidecarManager> {
+        let manager = self.prepare(manager);
+        if let Some(previous) = self.swap(manager.clone()) {
+            previous.shutdown();
+        }
+        manager
+    }
+
+    /// Readies `manager` for the registry without adding it, so it can be
+    /// started and checked before it replaces anything.
+    pub fn prepare(&self, manager: SidecarManager) -> Arc<SidecarManager> {
+        Arc::new(match self.logs {
+            Some(ref logs) => manager.with_log_stream(logs.clone()),
+            None => manager,
+        })
+    }
+
+    /// Puts `manager` in place of the sidecar with its name and hands back
+    /// the one it replaced, still running.
+    pub fn swap(&self, manager: Arc<SidecarManager>) -> Option<Arc<SidecarManager>> {
+        self.sidecars
+            .lock()
+            .unwrap()
+            .insert(manager.name().to_string(), manager)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<SidecarManager>> {
+        self.sidecars.lock().unwrap().get(name).cloned()
+    }
+
+    pub fn all(&self) -> Vec<Arc<SidecarManager>> {
+        self.sidecars.lock().unwrap().values().cloned().collect()
+    }
+
+    pub fn statuses(&self) -> Vec<SidecarStatus> {
+        self.all().iter().map(|s| s.status()).collect()
+    }
+
+    /// Stops and respawns one sidecar by name.
+    pub fn restart(&self, name: &str) -> Result<Arc<SidecarManager>, String> {
+        let sidecar = self
+            .get(name)
+            .ok_or_else(|| format!("No sidecar named {:?}", name))?;
+        sidecar.shutdown();
+        sidecar.spawn_with_retry()?;
+        Ok(sidecar)
+    }
+
+    /// Polls every sidecar for unexpected exits and applies its restart
+    /// policy, asking `allow_restart` before each restart. `on_exit` runs
+    /// for each crash before any restart attempt.
+    pub fn start_watchdog<A, F>(self: Arc<Self>, allow_restart: A, on_exit: F)
+    where
+        A: Fn(&str) -> bool + Send + 'static,
+        F: Fn(&SidecarExit) + Send + 'static,
+    {
+        thread::spawn(move || loop {
+            thread::sleep(WATCHDOG_INTERVAL);
+            for sidecar in self.all() {
+                let Some(status) = sidecar.poll_unexpected_exit() else {
+                    continue;
+                };
+                let allowed = sidecar.should_restart(&status);
+                let throttled = allowed && !allow_restart(sidecar.name());
+                let restarting = allowed && !throttled;
+                let message = format!(
+                    "{} sidecar exited unexpectedly ({}){}",
+                    sidecar.name(),
+                    status,
+                    if restarting {
+                        ", restarting"
+                    } else if throttled {
+                        "; restarting too often, leaving it stopped"
+                    } else {
+                        ""
+                    }
+                );
+                eprintln!("[tauri] {}", message);
+                syslog::send("shell", Level::Error, &message);
+
+                on_exit(&SidecarExit {
+                    name: sidecar.name().to_string(),
+                    status,
+                    restarting,
+                    throttled,
+                });
+
+                if restarting {
+                    sidecar.record_restart();
+                    if let Err(e) = sidecar.spawn_with_retry() {
+                        eprintln!("[tauri] Watchdog restart failed: {}", e);
+                    }
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,665 @@
+//! Remote control: a small HTTP server on every interface (port 3101 by
+//! default) that lets a paired phone see the backend's status and answer
+//! permission prompts. Pairing is by a one-time code in a QR code; the
+//! phone then keeps a token in a cookie. It is plain HTTP, so the pairing
+//! code, the token and the prompts travel in cleartext: anyone on the same
+//! network can read them and use the token. Only turn it on for networks
+//! you trust.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::net::{IpAddr, UdpSocket};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager, State};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::{emit_event, AppState};
+
+const PAIRING_TTL: Duration = Duration::from_secs(5 * 60);
+/// Wrong codes after which the pairing is cancelled and must be restarted.
+const MAX_PAIRING_FAILURES: u32 = 5;
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+/// A client that hasn't sent its headers by then is dropped.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const TOKEN_COOKIE: &str = "la_token";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteConfig {
+    pub enabled: bool,
+    pub port: u16,
+    pub devices: Vec<PairedDevice>,
+}
+
+impl Default for RemoteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 3101,
+            devices: Vec::new(),
+        }
+    }
+}
+
+/// A paired phone/browser. Only the SHA-256 of its token is persisted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairedDevice {
+    pub id: String,
+    pub name: String,
+    pub token_hash: String,
+    pub paired_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceInfo {
+    pub id: String,
+    pub name: String,
+    pub paired_at: String,
+    pub last_seen: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PairingInfo {
+    pub url: String,
+    pub qr_svg: String,
+    pub expires_in_secs: u64,
+    pub warning: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionPrompt {
+    pub id: String,
+    pub title: String,
+    pub detail: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptResolution {
+    pub id: String,
+    pub approved: bool,
+    pub device_id: String,
+}
+
+#[derive(Default)]
+pub struct RemoteState {
+    server: Mutex<Option<JoinHandle<()>>>,
+    pairing: Mutex<Option<Pairing>>,
+    prompts: Mutex<Vec<PermissionPrompt>>,
+    last_seen: Mutex<HashMap<String, String>>,
+}
+
+struct Pairing {
+    code: String,
+    expires: Instant,
+    failures: u32,
+}
+
+fn new_token() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Best-effort LAN address: connecting a UDP socket picks the outbound
+/// interface without sending any packets.
+pub fn lan_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+pub fn start_server(app: &AppHandle) {
+    let state: State<AppState> = app.state();
+    let (port, advertise) = {
+        let config = state.config.lock().unwrap();
+        (config.remote.port, config.mdns.advertise)
+    };
+    let mut server = state.remote.server.lock().unwrap();
+    if server.is_some() {
+        return;
+    }
+
+    if advertise {
+        if let Err(e) = state.mdns.advertise(port) {
+            eprintln!("[tauri] {}", e);
+        }
+    }
+
+    let app = app.clone();
+    *server = Some(tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::bind(("0.0.0.0", port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("[tauri] Remote control failed to bind port {}: {}", port, e);
+                return;
+            }
+        };
+        println!("[tauri] Remote control listening on port {}", port);
+
+        loop {
+            let Ok((stream, _)) = listener.accept().await else {
+                continue;
+            };
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = handle_connection(&app, stream).await {
+                    eprintln!("[tauri] Remote control request failed: {}", e);
+                }
+            });
+        }
+    }));
+}
+
+pub fn stop_server(app: &AppHandle) {
+    let state: State<AppState> = app.state();
+    if let Some(handle) = state.remote.server.lock().unwrap().take() {
+        handle.abort();
+        println!("[tauri] Remote control stopped");
+    }
+    state.mdns.stop();
+    *state.remote.pairing.lock().unwrap() = None;
+}
+
+pub fn begin_pairing(app: &AppHandle) -> Result<PairingInfo, String> {
+    let state: State<AppState> = app.state();
+    let port = {
+        let config = state.config.lock().unwrap();
+        if !config.remote.enabled {
+            return Err("Remote control is disabled".to_string());
+        }
+        config.remote.port
+    };
+
+    let ip = lan_ip().ok_or_else(|| "No LAN address available for pairing".to_string())?;
+    let code = new_token();
+    let url = format!("http://{}:{}/pair?code={}", ip, port, code);
+
+    let qr_svg = qrcode::QrCode::new(url.as_bytes())
+        .map_err(|e| format!("Failed to encode pairing QR code: {}", e))?
+        .render::<qrcode::render::svg::Color>()
+        .min_dimensions(220, 220)
+        .build();
+
+    *state.remote.pairing.lock().unwrap() = Some(Pairing {
+        code,
+        expires: Instant::now() + PAIRING_TTL,
+        failures: 0,
+    });
+
+    Ok(PairingInfo {
+        url,
+        qr_svg,
+        expires_in_secs: PAIRING_TTL.as_secs(),
+        warning: "Remote control isn't encrypted: anyone on this network can read the pairing \
+                  code and the device's token. Only pair on networks you trust."
+            .to_string(),
+    })
+}
+
+pub fn list_devices(app: &AppHandle) -> Vec<DeviceInfo> {
+    let state: State<AppState> = app.state();
+    let config = state.config.lock().unwrap();
+    let last_seen = state.remote.last_seen.lock().unwrap();
+    config
+        .remote
+        .devices
+        .iter()
+        .map(|d| DeviceInfo {
+            id: d.id.clone(),
+            name: d.name.clone(),
+            paired_at: d.paired_at.clone(),
+            last_seen: last_seen.get(&d.id).cloned(),
+        })
+        .collect()
+}
+
+pub fn revoke_device(app: &AppHandle, id: &str) -> Result<(), String> {
+    let state: State<AppState> = app.state();
+    let mut config = state.config.lock().unwrap();
+    let before = config.remote.devices.len();
+    config.remote.devices.retain(|d| d.id != id);
+    if config.remote.devices.len() == before {
+        return Err(format!("No paired device with id {}", id));
+    }
+    config.save()?;
+    state.remote.last_seen.lock().unwrap().remove(id);
+    println!("[tauri] Revoked remote device {}", id);
+    Ok(())
+}
+
+pub fn push_prompt(app: &AppHandle, title: String, detail: String) -> String {
+    let state: State<AppState> = app.state();
+    let prompt = PermissionPrompt {
+        id: uuid::Uuid::new_v4().to_string(),
+        title,
+        detail,
+        created_at: chrono::Local::now().to_rfc3339(),
+    };
+    let id = prompt.id.clone();
+    state.remote.prompts.lock().unwrap().push(prompt);
+    id
+}
+
+pub fn clear_prompt(app: &AppHandle, id: &str) {
+    let state: State<AppState> = app.state();
+    state.remote.prompts.lock().unwrap().retain(|p| p.id != id);
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>,
+}
+
+impl Request {
+    fn token(&self) -> Option<&str> {
+        if let Some(auth) = self.headers.get("authorization") {
+            if let Some(token) = auth.strip_prefix("Bearer ") {
+                return Some(token.trim());
+            }
+        }
+        self.headers.get("cookie").and_then(|cookies| {
+            cookies.split(';').find_map(|c| {
+                c.trim()
+                    .strip_prefix(TOKEN_COOKIE)
+                    .and_then(|rest| rest.strip_prefix('='))
+            })
+        })
+    }
+}
+
+async fn read_request(stream: &mut TcpStream) -> Result<Request, String> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        if buf.len() > MAX_REQUEST_BYTES {
+            return Err("Request headers too large".to_string());
+        }
+        let n = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| format!("Read failed: {}", e))?;
+        if n == 0 {
+            return Err("Connection closed".to_string());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let text = String::from_utf8_lossy(&buf);
+    let mut lines = text.split("\r\n");
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or("/");
+
+    let (path, query_str) = target.split_once('?').unwrap_or((target, ""));
+    let query = query_str
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+    let headers = lines
+        .take_while(|l| !l.is_empty())
+        .filter_map(|l| l.split_once(':'))
+        .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+        .collect();
+
+    Ok(Request {
+        method,
+        path: path.to_string(),
+        query,
+        headers,
+    })
+}
+
+async fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    extra_headers: &[(&str, String)],
+    body: &str,
+) -> Result<(), String> {
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    for (name, value) in extra_headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+    response.push_str(body);
+    stream
+        .write_all(response.as_bytes())
+        .await
+        .map_err(|e| format!("Write failed: {}", e))
+}
+
+fn authenticate(app: &AppHandle, request: &Request) -> Option<String> {
+    let hash = hash_token(request.token()?);
+    let state: State<AppState> = app.state();
+    let config = state.config.lock().unwrap();
+    let device = config
+        .remote
+        .devices
+        .iter()
+        .find(|d| d.token_hash == hash)?;
+    state
+        .remote
+        .last_seen
+        .lock()
+        .unwrap()
+        .insert(device.id.clone(), chrono::Local::now().to_rfc3339());
+    Some(device.id.clone())
+}
+
+fn complete_pairing(app: &AppHandle, request: &Request) -> Result<String, String> {
+    let state: State<AppState> = app.state();
+    let code = request
+        .query
+        .get("code")
+        .ok_or_else(|| "Missing pairing code".to_string())?;
+
+    check_pairing(
+        &mut state.remote.pairing.lock().unwrap(),
+        code,
+        Instant::now(),
+    )?;
+
+    let token = new_token();
+    let name = request
+        .headers
+        .get("user-agent")
+        .map(|ua| ua.chars().take(80).collect())
+        .unwrap_or_else(|| "Unknown device".to_string());
+    let device = PairedDevice {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        token_hash: hash_token(&token),
+        paired_at: chrono::Local::now().to_rfc3339(),
+    };
+    println!(
+        "[tauri] Paired remote device {} ({})",
+        device.id, device.name
+    );
+
+    let info = DeviceInfo {
+        id: device.id.clone(),
+        name: device.name.clone(),
+        paired_at: device.paired_at.clone(),
+        last_seen: None,
+    };
+    {
+        let mut config = state.config.lock().unwrap();
+        config.remote.devices.push(device);
+        config.save()?;
+    }
+    emit_event(app, "remote-device-paired", info);
+    Ok(token)
+}
+
+/// Uses up the pending pairing when `code` matches it. Wrong codes count
+/// against it, and enough of them cancel it so the code can't be guessed.
+fn check_pairing(pairing: &mut Option<Pairing>, code: &str, now: Instant) -> Result<(), String> {
+    let invalid = || "Pairing code is invalid or expired".to_string();
+    let current = pairing.as_mut().ok_or_else(invalid)?;
+    if now >= current.expires {
+        *pairing = None;
+        return Err(invalid());
+    }
+    if !constant_time_eq(current.code.as_bytes(), code.as_bytes()) {
+        current.failures += 1;
+        if current.failures >= MAX_PAIRING_FAILURES {
+            *pairing = None;
+            eprintln!("[tauri] Too many wrong pairing codes; pairing cancelled");
+        }
+        return Err(invalid());
+    }
+    *pairing = None;
+    Ok(())
+}
+
+/// Takes as long for a near miss as for a wrong first byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+async fn handle_connection(app: &AppHandle, mut stream: TcpStream) -> Result<(), String> {
+    let request = tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream))
+        .await
+        .map_err(|_| "Timed out waiting for the request".to_string())??;
+
+    if request.method == "GET" && request.path == "/pair" {
+        return match complete_pairing(app, &request) {
+            Ok(token) => {
+                let cookie = format!(
+                    "{}={}; HttpOnly; SameSite=Strict; Path=/; Max-Age=31536000",
+                    TOKEN_COOKIE, token
+                );
+                respond(
+                    &mut stream,
+                    "302 Found",
+                    "text/plain",
+                    &[("Set-Cookie", cookie), ("Location", "/".to_string())],
+                    "Paired",
+                )
+                .await
+            }
+            Err(e) => respond(&mut stream, "403 Forbidden", "text/plain", &[], &e).await,
+        };
+    }
+
+    let Some(device_id) = authenticate(app, &request) else {
+        return respond(
+            &mut stream,
+            "401 Unauthorized",
+            "text/plain",
+            &[],
+            "Not paired. Scan the pairing QR code in Local Agent.",
+        )
+        .await;
+    };
+
+    let state: State<AppState> = app.state();
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/") => {
+            respond(
+                &mut stream,
+                "200 OK",
+                "text/html; charset=utf-8",
+                &[],
+                REMOTE_PAGE,
+            )
+            .await
+        }
+        ("GET", "/api/status") => {
+            let backend_running = state
+                .sidecars
+                .get(crate::registry::BACKEND)
+                .map(|s| s.is_running())
+                .unwrap_or(false);
+            let pending_prompts = state.remote.prompts.lock().unwrap().len();
+            let body = serde_json::json!({
+                "backend_running": backend_running,
+                "pending_prompts": pending_prompts,
+            });
+            respond(
+                &mut stream,
+                "200 OK",
+                "application/json",
+                &[],
+                &body.to_string(),
+            )
+            .await
+        }
+        ("GET", "/api/prompts") => {
+            let prompts = state.remote.prompts.lock().unwrap().clone();
+            let body = serde_json::to_string(&prompts).unwrap_or_else(|_| "[]".to_string());
+            respond(&mut stream, "200 OK", "application/json", &[], &body).await
+        }
+        ("POST", path) if path.starts_with("/api/prompts/") => {
+            let rest = &path["/api/prompts/".len()..];
+            let Some((id, action)) = rest.split_once('/') else {
+                return respond(&mut stream, "404 Not Found", "text/plain", &[], "Not found").await;
+            };
+            let approved = match action {
+                "approve" => true,
+                "deny" => false,
+                _ => {
+                    return respond(&mut stream, "404 Not Found", "text/plain", &[], "Not found")
+                        .await
+                }
+            };
+
+            let removed = {
+                let mut prompts = state.remote.prompts.lock().unwrap();
+                let before = prompts.len();
+                prompts.retain(|p| p.id != id);
+                prompts.len() != before
+            };
+            if !removed {
+                return respond(
+                    &mut stream,
+                    "404 Not Found",
+                    "text/plain",
+                    &[],
+                    "Unknown prompt",
+                )
+                .await;
+            }
+
+            emit_event(
+                app,
+                "remote-prompt-resolved",
+                PromptResolution {
+                    id: id.to_string(),
+                    approved,
+                    device_id,
+                },
+            );
+            respond(&mut stream, "204 No Content", "text/plain", &[], "").await
+        }
+        _ => respond(&mut stream, "404 Not Found", "text/plain", &[], "Not found").await,
+    }
+}
+
+const REMOTE_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Local Agent Remote</title>
+<style>
+  body { background: #1a1a1a; color: #e0e0e0; font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif; padding: 1rem; }
+  h1 { font-size: 1.2rem; margin-bottom: 1rem; }
+  .prompt { background: #111; border: 1px solid #333; border-radius: 6px; padding: 1rem; margin-bottom: 1rem; }
+  .prompt p { color: #aaa; margin: 0.5rem 0 1rem; }
+  button { background: #333; color: #e0e0e0; border: 1px solid #555; border-radius: 6px; padding: 0.6rem 1.2rem; margin-right: 0.5rem; }
+</style>
+</head>
+<body>
+  <h1>Local Agent</h1>
+  <p id="status">Loading…</p>
+  <div id="prompts"></div>
+  <script>
+    async function resolve(id, action) {
+      await fetch('/api/prompts/' + id + '/' + action, { method: 'POST' });
+      refresh();
+    }
+    async function refresh() {
+      const status = await (await fetch('/api/status')).json();
+      document.getElementById('status').textContent =
+        'Backend ' + (status.backend_running ? 'running' : 'stopped') +
+        ' · ' + status.pending_prompts + ' pending prompt(s)';
+      const prompts = await (await fetch('/api/prompts')).json();
+      const list = document.getElementById('prompts');
+      list.replaceChildren(...prompts.map((p) => {
+        const el = document.createElement('div');
+        el.className = 'prompt';
+        const title = document.createElement('strong');
+        title.textContent = p.title;
+        const detail = document.createElement('p');
+        detail.textContent = p.detail;
+        const approve = document.createElement('button');
+        approve.textContent = 'Approve';
+        approve.onclick = () => resolve(p.id, 'approve');
+        const deny = document.createElement('button');
+        deny.textContent = 'Deny';
+        deny.onclick = () => resolve(p.id, 'deny');
+        el.append(title, detail, approve, deny);
+        return el;
+      }));
+    }
+    refresh();
+    setInterval(refresh, 3000);
+  </script>
+</body>
+</html>"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(code: &str, now: Instant) -> Option<Pairing> {
+        Some(Pairing {
+            code: code.to_string(),
+            expires: now + PAIRING_TTL,
+            failures: 0,
+        })
+    }
+
+    #[test]
+    fn pairing_code_works_once() {
+        let now = Instant::now();
+        let mut pairing = pending("abc123", now);
+        assert!(check_pairing(&mut pairing, "abc123", now).is_ok());
+        assert!(pairing.is_none());
+        assert!(check_pairing(&mut pairing, "abc123", now).is_err());
+    }
+
+    #[test]
+    fn expired_pairing_code_is_refused() {
+        let now = Instant::now();
+        let mut pairing = pending("abc123", now);
+        assert!(check_pairing(&mut pairing, "abc123", now + PAIRING_TTL).is_err());
+        assert!(pairing.is_none());
+    }
+
+    #[test]
+    fn wrong_codes_cancel_the_pairing() {
+        let now = Instant::now();
+        let mut pairing = pending("abc123", now);
+        for _ in 0..MAX_PAIRING_FAILURES - 1 {
+            assert!(check_pairing(&mut pairing, "abc124", now).is_err());
+            assert!(pairing.is_some());
+        }
+        assert!(check_pairing(&mut pairing, "abc12", now).is_err());
+        assert!(pairing.is_none());
+        assert!(check_pairing(&mut pairing, "abc123", now).is_err());
+    }
+
+    #[test]
+    fn compares_whole_codes() {
+        assert!(constant_time_eq(b"abc123", b"abc123"));
+        assert!(!constant_time_eq(b"abc123", b"abc124"));
+        assert!(!constant_time_eq(b"abc123", b"abc1234"));
+        assert!(!constant_time_eq(b"", b"a"));
+    }
+}
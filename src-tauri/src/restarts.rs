@@ -0,0 +1,116 @@
+//! Every sidecar restart, kept for `get_restart_history` and to stop a
+//! crash loop: automatic restarts of one sidecar are capped at
+//! `max_restarts` per `window_minutes`, after which it stays down until the
+//! user restarts it, which starts the count over.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Entries kept for `get_restart_history`.
+const HISTORY_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestartConfig {
+    /// Automatic restarts allowed within the window.
+    pub max_restarts: u32,
+    pub window_minutes: u64,
+}
+
+impl Default for RestartConfig {
+    fn default() -> Self {
+        Self {
+            max_restarts: 3,
+            window_minutes: 5,
+        }
+    }
+}
+
+impl RestartConfig {
+    fn window(&self) -> Duration {
+        Duration::from_secs(self.window_minutes * 60)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartReason {
+    /// The process exited and the watchdog started it again.
+    Crash,
+    /// It stayed above the memory ceiling.
+    MemoryLimit,
+    /// It stopped answering while the machine slept.
+    Resume,
+    /// It kept running but stopped answering its health check.
+    Hang,
+    /// A sidecar it depends on was restarted.
+    Dependency,
+    /// Asked for by the user or the UI; never throttled.
+    Manual,
+}
+
+impl RestartReason {
+    fn automatic(self) -> bool {
+        self != RestartReason::Manual
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RestartRecord {
+    pub at: String,
+    pub sidecar: String,
+    pub reason: RestartReason,
+    /// The exit status or what triggered it.
+    pub detail: String,
+    #[serde(skip)]
+    instant: Instant,
+}
+
+#[derive(Default)]
+pub struct RestartHistory {
+    records: Mutex<VecDeque<RestartRecord>>,
+}
+
+impl RestartHistory {
+    pub fn record(&self, sidecar: &str, reason: RestartReason, detail: &str) {
+        let mut records = self.records.lock().unwrap();
+        if records.len() == HISTORY_LIMIT {
+            records.pop_front();
+        }
+        records.push_back(RestartRecord {
+            at: chrono::Local::now().to_rfc3339(),
+            sidecar: sidecar.to_string(),
+            reason,
+            detail: detail.to_string(),
+            instant: Instant::now(),
+        });
+    }
+
+    /// Oldest first.
+    pub fn entries(&self) -> Vec<RestartRecord> {
+        self.records.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Automatic restarts of `sidecar` within the window and since it was
+    /// last restarted by hand, oldest first.
+    pub fn recent(&self, sidecar: &str, config: &RestartConfig) -> Vec<RestartRecord> {
+        let window = config.window();
+        let records = self.records.lock().unwrap();
+        let mut recent: Vec<RestartRecord> = Vec::new();
+        for record in records.iter().filter(|r| r.sidecar == sidecar) {
+            if !record.reason.automatic() {
+                recent.clear();
+            } else if record.instant.elapsed() < window {
+                recent.push(record.clone());
+            }
+        }
+        recent
+    }
+
+    /// Whether `sidecar` may be restarted automatically once more.
+    pub fn allows(&self, sidecar: &str, config: &RestartConfig) -> bool {
+        (self.recent(sidecar, config).len() as u32) < config.max_restarts
+    }
+}
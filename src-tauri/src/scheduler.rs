@@ -0,0 +1,373 @@
+//! Backend maintenance on a timetable: the shell calls endpoints like a
+//! nightly tool-log compaction at the configured times, so the backend
+//! doesn't need a scheduler of its own. A run that falls due while the
+//! machine is on battery or the backend is working waits for a later
+//! check instead. Last runs are kept on disk, so a time missed while the
+//! app was closed is caught up at the next launch.
+
+use chrono::{DateTime, Duration, Local, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::paths;
+
+pub const EVENT: &str = "maintenance-ran";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    pub tasks: Vec<MaintenanceTask>,
+    /// Hold runs back while the machine runs on battery.
+    pub skip_on_battery: bool,
+    /// Hold runs back while the backend reports work in flight.
+    pub skip_when_busy: bool,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            tasks: default_tasks(),
+            skip_on_battery: true,
+            skip_when_busy: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceTask {
+    pub name: String,
+    /// The backend endpoint to call, e.g. `/api/maintenance/compact-tool-log`.
+    pub path: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    /// Local times ("HH:MM") to run at each day; others are ignored.
+    #[serde(default)]
+    pub times: Vec<String>,
+    /// Or run this often.
+    #[serde(default)]
+    pub every_minutes: Option<u64>,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+/// The backend's own housekeeping (see `backend/maintenance.js`), at night
+/// when the app is least likely to be busy.
+fn default_tasks() -> Vec<MaintenanceTask> {
+    [
+        ("compact-tool-log", "03:00"),
+        ("prune-empty-chats", "03:30"),
+    ]
+    .into_iter()
+    .map(|(name, time)| MaintenanceTask {
+        name: name.to_string(),
+        path: format!("/api/maintenance/{}", name),
+        method: default_method(),
+        times: vec![time.to_string()],
+        every_minutes: None,
+        timeout_secs: default_timeout_secs(),
+    })
+    .collect()
+}
+
+fn default_method() -> String {
+    "POST".to_string()
+}
+
+fn default_timeout_secs() -> u64 {
+    300
+}
+
+impl MaintenanceTask {
+    fn times(&self) -> impl Iterator<Item = NaiveTime> + '_ {
+        self.times
+            .iter()
+            .filter_map(|t| NaiveTime::parse_from_str(t.trim(), "%H:%M").ok())
+    }
+
+    /// When the task last fell due at or before `now`, given it last ran at
+    /// `last`. `None` when it isn't due.
+    pub fn due(&self, last: DateTime<Local>, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        let mut due = None;
+        if let Some(minutes) = self.every_minutes.filter(|m| *m > 0) {
+            let at = last + Duration::minutes(minutes as i64);
+            due = (at <= now).then_some(at);
+        }
+        let scheduled = self
+            .times()
+            .filter_map(|time| latest_at(time, now))
+            .filter(|at| *at > last)
+            .max();
+        due.max(scheduled)
+    }
+
+    /// The next time the task falls due after `now`.
+    pub fn next(&self, last: DateTime<Local>, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        let every = self
+            .every_minutes
+            .filter(|m| *m > 0)
+            .map(|minutes| (last + Duration::minutes(minutes as i64)).max(now));
+        let scheduled = self
+            .times()
+            .filter_map(|time| latest_at(time, now).map(|at| at + Duration::days(1)))
+            .min();
+        match (every, scheduled) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// `time` today if it has passed, else yesterday.
+fn latest_at(time: NaiveTime, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let today = Local
+        .from_local_datetime(&now.date_naive().and_time(time))
+        .earliest()?;
+    Some(if today <= now {
+        today
+    } else {
+        today - Duration::days(1)
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRun {
+    pub task: String,
+    pub at: String,
+    /// The backend's answer; `None` when it didn't answer in time.
+    pub status: Option<u16>,
+    pub ok: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskStatus {
+    pub name: String,
+    pub last_run: Option<TaskRun>,
+    pub next_due: Option<String>,
+    /// Why a due run is being held back.
+    pub waiting: Option<String>,
+}
+
+/// Runs `task` through `call`, which sends the request to the backend and
+/// returns its status and body, or `None` when it doesn't answer in time.
+pub async fn run<F, Fut>(task: &MaintenanceTask, call: F) -> TaskRun
+where
+    F: FnOnce(String, String, std::time::Duration) -> Fut,
+    Fut: Future<Output = Option<(u16, Vec<u8>)>>,
+{
+    let timeout = std::time::Duration::from_secs(task.timeout_secs);
+    let status = call(task.method.clone(), task.path.clone(), timeout)
+        .await
+        .map(|(status, _)| status);
+    let run = TaskRun {
+        task: task.name.clone(),
+        at: Local::now().to_rfc3339(),
+        status,
+        ok: status.is_some_and(|status| (200..300).contains(&status)),
+    };
+    match status {
+        _ if run.ok => println!("[tauri] Maintenance task {} done", task.name),
+        Some(status) => eprintln!(
+            "[tauri] Maintenance task {} failed with status {}",
+            task.name, status
+        ),
+        None => eprintln!(
+            "[tauri] Maintenance task {} got no answer within {:?}",
+            task.name, timeout
+        ),
+    }
+    run
+}
+
+fn history_path() -> PathBuf {
+    paths::config_dir().join("maintenance.json")
+}
+
+/// Last runs by task, and the run held back for each, with why.
+pub struct Scheduler {
+    runs: Mutex<BTreeMap<String, TaskRun>>,
+    waiting: Mutex<BTreeMap<String, String>>,
+    started: DateTime<Local>,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        let runs = fs::read_to_string(history_path())
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            runs: Mutex::new(runs),
+            waiting: Mutex::new(BTreeMap::new()),
+            started: Local::now(),
+        }
+    }
+}
+
+impl Scheduler {
+    /// When `task` last ran; a task that never ran counts from launch, so
+    /// adding one doesn't run it straight away.
+    fn last(&self, task: &str) -> DateTime<Local> {
+        self.runs
+            .lock()
+            .unwrap()
+            .get(task)
+            .and_then(|run| DateTime::parse_from_rfc3339(&run.at).ok())
+            .map(|at| at.with_timezone(&Local))
+            .unwrap_or(self.started)
+    }
+
+    /// The tasks due at `now`.
+    pub fn due<'a>(
+        &self,
+        tasks: &'a [MaintenanceTask],
+        now: DateTime<Local>,
+    ) -> Vec<&'a MaintenanceTask> {
+        tasks
+            .iter()
+            .filter(|task| task.due(self.last(&task.name), now).is_some())
+            .collect()
+    }
+
+    /// Notes that `task` is due but held back; logs only when the reason
+    /// changes.
+    pub fn hold(&self, task: &str, reason: &str) {
+        let mut waiting = self.waiting.lock().unwrap();
+        if waiting.get(task).map(String::as_str) != Some(reason) {
+            println!("[tauri] Maintenance task {} waiting: {}", task, reason);
+            waiting.insert(task.to_string(), reason.to_string());
+        }
+    }
+
+    pub fn record(&self, run: TaskRun) {
+        self.waiting.lock().unwrap().remove(&run.task);
+        let mut runs = self.runs.lock().unwrap();
+        runs.insert(run.task.clone(), run);
+        let contents = serde_json::to_string_pretty(&*runs).unwrap();
+        if let Err(e) = fs::create_dir_all(paths::config_dir())
+            .and_then(|()| fs::write(history_path(), contents))
+        {
+            eprintln!("[tauri] Failed to save maintenance history: {}", e);
+        }
+    }
+
+    pub fn status(&self, tasks: &[MaintenanceTask]) -> Vec<TaskStatus> {
+        let now = Local::now();
+        let runs = self.runs.lock().unwrap().clone();
+        let waiting = self.waiting.lock().unwrap().clone();
+        tasks
+            .iter()
+            .map(|task| {
+                let last = self.last(&task.name);
+                let next = match task.due(last, now) {
+                    Some(_) => Some(now),
+                    None => task.next(last, now),
+                };
+                TaskStatus {
+                    name: task.name.clone(),
+                    last_run: runs.get(&task.name).cloned(),
+                    next_due: next.map(|at| at.to_rfc3339()),
+                    waiting: waiting.get(&task.name).cloned(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn at(time: &str) -> DateTime<Local> {
+        let time = NaiveTime::parse_from_str(time, "%H:%M").unwrap();
+        Local
+            .from_local_datetime(&Local::now().date_naive().and_time(time))
+            .earliest()
+            .unwrap()
+    }
+
+    #[test]
+    fn default_tasks_are_the_backends_maintenance_endpoints() {
+        let backend = include_str!("../../backend/maintenance.js");
+        let tasks = SchedulerConfig::default().tasks;
+        assert!(!tasks.is_empty());
+        for task in &tasks {
+            assert_eq!(task.method, "POST");
+            assert_eq!(task.path, format!("/api/maintenance/{}", task.name));
+            assert!(
+                backend.contains(&format!("['{}',", task.name)),
+                "backend has no {} task",
+                task.name
+            );
+            assert!(task.times().next().is_some(), "{} never runs", task.name);
+        }
+    }
+
+    #[test]
+    fn default_tasks_fall_due_nightly() {
+        let tasks = SchedulerConfig::default().tasks;
+        let compact = &tasks[0];
+        assert!(compact.due(at("02:00"), at("02:59")).is_none());
+        assert_eq!(compact.due(at("02:00"), at("03:01")), Some(at("03:00")));
+    }
+
+    /// Serves one request with `204 No Content` and returns its request line.
+    async fn serve_once(listener: TcpListener) -> String {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 1024];
+        while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = stream.read(&mut chunk).await.unwrap();
+            assert!(n > 0, "connection closed before the request ended");
+            buf.extend_from_slice(&chunk[..n]);
+        }
+        stream
+            .write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let request = String::from_utf8_lossy(&buf);
+        request.lines().next().unwrap_or_default().to_string()
+    }
+
+    #[tokio::test]
+    async fn scheduled_task_calls_its_endpoint() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve_once(listener));
+
+        let tasks = SchedulerConfig::default().tasks;
+        let scheduler = Scheduler {
+            runs: Mutex::new(BTreeMap::new()),
+            waiting: Mutex::new(BTreeMap::new()),
+            started: at("02:00"),
+        };
+        let due = scheduler.due(&tasks, at("03:10"));
+        assert_eq!(due.len(), 1);
+
+        let run = run(due[0], |method, path, timeout| async move {
+            let response = reqwest::Client::builder()
+                .timeout(timeout)
+                .build()
+                .ok()?
+                .request(method.parse().ok()?, format!("{}{}", base, path))
+                .send()
+                .await
+                .ok()?;
+            Some((response.status().as_u16(), Vec::new()))
+        })
+        .await;
+
+        assert_eq!(
+            server.await.unwrap(),
+            "POST /api/maintenance/compact-tool-log HTTP/1.1"
+        );
+        assert_eq!(run.task, "compact-tool-log");
+        assert_eq!(run.status, Some(204));
+        assert!(run.ok);
+    }
+}
@@ -0,0 +1,246 @@
+//! `capture_screenshot`: a picture of the screen, the active window or a
+//! region the user drags out, for questions about what is on screen. The
+//! webview can't capture anything itself; the shell asks first in a small
+//! prompt window and only then runs the platform's screenshot tool, saving
+//! a PNG to the temp directory and returning its path.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use tokio::sync::oneshot;
+
+use crate::{pages, template, AppState};
+
+pub const SCREENSHOT_LABEL: &str = "screenshot";
+/// The prompt counts as declined after this long.
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(120);
+/// Lets the prompt window disappear before the screen is captured.
+const PROMPT_CLOSE_DELAY: Duration = Duration::from_millis(400);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureMode {
+    Screen,
+    Window,
+    /// Picked by dragging with the platform's selection tool.
+    Region,
+}
+
+impl CaptureMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            CaptureMode::Screen => "screen",
+            CaptureMode::Window => "window",
+            CaptureMode::Region => "region",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Screenshot {
+    pub path: PathBuf,
+    pub mode: CaptureMode,
+    pub size_bytes: u64,
+}
+
+/// Where the open prompt's answer goes.
+#[derive(Default)]
+pub struct ScreenshotPrompt {
+    pending: Mutex<Option<oneshot::Sender<bool>>>,
+}
+
+impl ScreenshotPrompt {
+    fn begin(&self) -> Result<oneshot::Receiver<bool>, String> {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.is_some() {
+            return Err("Already asking to take a screenshot".to_string());
+        }
+        let (answer, answered) = oneshot::channel();
+        *pending = Some(answer);
+        Ok(answered)
+    }
+
+    /// Passes on the user's answer; closing the prompt declines.
+    pub fn answer(&self, allow: bool) {
+        if let Some(answer) = self.pending.lock().unwrap().take() {
+            let _ = answer.send(allow);
+        }
+    }
+}
+
+/// Asks the user, then captures; `None` when they decline.
+pub async fn capture(app: &AppHandle, mode: CaptureMode) -> Result<Option<Screenshot>, String> {
+    let answered = app.state::<AppState>().screenshot.begin()?;
+    if let Err(e) = open_prompt(app, mode) {
+        app.state::<AppState>().screenshot.answer(false);
+        return Err(e);
+    }
+    let allowed = matches!(
+        tokio::time::timeout(ANSWER_TIMEOUT, answered).await,
+        Ok(Ok(true))
+    );
+    // Also clears an answer that timed out
+    app.state::<AppState>().screenshot.answer(false);
+    if let Some(window) = app.get_webview_window(SCREENSHOT_LABEL) {
+        let _ = window.destroy();
+    }
+    if !allowed {
+        println!("[tauri] Screenshot declined");
+        return Ok(None);
+    }
+    tokio::time::sleep(PROMPT_CLOSE_DELAY).await;
+
+    let path = std::env::temp_dir().join(format!(
+        "local-agent-screenshot-{}.png",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    let target = path.clone();
+    tauri::async_runtime::spawn_blocking(move || run_tool(mode, &target))
+        .await
+        .map_err(|e| format!("Screenshot task failed: {}", e))??;
+    // Region pickers exit without a file when the selection is cancelled
+    let size_bytes = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    if size_bytes == 0 {
+        return Ok(None);
+    }
+    println!("[tauri] Saved screenshot to {}", path.display());
+    Ok(Some(Screenshot {
+        path,
+        mode,
+        size_bytes,
+    }))
+}
+
+fn open_prompt(app: &AppHandle, mode: CaptureMode) -> Result<(), String> {
+    let page = format!("screenshot?mode={}", mode.as_str());
+    WebviewWindowBuilder::new(
+        app,
+        SCREENSHOT_LABEL,
+        WebviewUrl::CustomProtocol(pages::page_url(&page)),
+    )
+    .title(template::t("screenshot_title"))
+    .inner_size(400.0, 200.0)
+    .resizable(false)
+    .always_on_top(true)
+    .center()
+    .build()
+    .map(|_| ())
+    .map_err(|e| format!("Failed to open screenshot prompt: {}", e))
+}
+
+/// Runs the platform's screenshot tool, writing a PNG to `path`.
+fn run_tool(mode: CaptureMode, path: &Path) -> Result<(), String> {
+    let out = path.to_string_lossy().into_owned();
+    if cfg!(target_os = "macos") {
+        let flag = match mode {
+            CaptureMode::Screen => "-x",
+            CaptureMode::Window => "-xw",
+            CaptureMode::Region => "-xi",
+        };
+        return run("screencapture", &[flag, &out]);
+    }
+    if cfg!(windows) {
+        let script = match mode {
+            CaptureMode::Screen => WINDOWS_SCREEN_SCRIPT,
+            CaptureMode::Window => WINDOWS_WINDOW_SCRIPT,
+            CaptureMode::Region => {
+                return Err("Region screenshots aren't supported on Windows".to_string())
+            }
+        };
+        let script = script.replace("{path}", &out.replace('\'', "''"));
+        return run(
+            "powershell",
+            &["-NoProfile", "-NonInteractive", "-Command", &script],
+        );
+    }
+    let (gnome, spectacle, scrot): (&[&str], &[&str], &[&str]) = match mode {
+        CaptureMode::Screen => (&["-f"], &["-b", "-n", "-f", "-o"], &[]),
+        CaptureMode::Window => (&["-w", "-f"], &["-b", "-n", "-a", "-o"], &["-u"]),
+        CaptureMode::Region => (&["-a", "-f"], &["-b", "-n", "-r", "-o"], &["-s"]),
+    };
+    let args = |flags: &[&'static str]| -> Vec<&str> {
+        flags.iter().copied().chain([out.as_str()]).collect()
+    };
+    // The first one installed is used. A cancelled selection exits non-zero
+    // without a file, which `capture` reports as no screenshot
+    for (program, flags) in [
+        ("gnome-screenshot", gnome),
+        ("spectacle", spectacle),
+        ("scrot", scrot),
+    ] {
+        match Command::new(program).args(args(flags)).status() {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Failed to run {}: {}", program, e)),
+            Ok(_) => return Ok(()),
+        }
+    }
+    Err("No screenshot tool found; install gnome-screenshot, spectacle or scrot".to_string())
+}
+
+fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let status = Command::new(program)
+        .args(args)
+        .status()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", program, status));
+    }
+    Ok(())
+}
+
+const WINDOWS_SCREEN_SCRIPT: &str = r#"
+Add-Type -AssemblyName System.Windows.Forms, System.Drawing
+$bounds = [System.Windows.Forms.SystemInformation]::VirtualScreen
+$bitmap = New-Object System.Drawing.Bitmap $bounds.Width, $bounds.Height
+$graphics = [System.Drawing.Graphics]::FromImage($bitmap)
+$graphics.CopyFromScreen($bounds.Left, $bounds.Top, 0, 0, $bitmap.Size)
+$bitmap.Save('{path}', [System.Drawing.Imaging.ImageFormat]::Png)
+"#;
+
+const WINDOWS_WINDOW_SCRIPT: &str = r#"
+Add-Type -AssemblyName System.Drawing
+Add-Type @'
+using System;
+using System.Runtime.InteropServices;
+public struct Rect { public int Left, Top, Right, Bottom; }
+public static class Foreground {
+  [DllImport("user32.dll")] public static extern IntPtr GetForegroundWindow();
+  [DllImport("user32.dll")] public static extern bool GetWindowRect(IntPtr hwnd, out Rect rect);
+}
+'@
+$rect = New-Object Rect
+[Foreground]::GetWindowRect([Foreground]::GetForegroundWindow(), [ref]$rect) | Out-Null
+$bitmap = New-Object System.Drawing.Bitmap ($rect.Right - $rect.Left), ($rect.Bottom - $rect.Top)
+$graphics = [System.Drawing.Graphics]::FromImage($bitmap)
+$graphics.CopyFromScreen($rect.Left, $rect.Top, 0, 0, $bitmap.Size)
+$bitmap.Save('{path}', [System.Drawing.Imaging.ImageFormat]::Png)
+"#;
+
+const SCREENSHOT_CSS: &str = r#"
+  body { padding: 1.25rem; }
+  h1 { font-size: 1.1rem; margin-bottom: 0.5rem; }
+  .message { color: var(--fg-muted); margin-bottom: 1rem; }
+"#;
+
+const SCREENSHOT_BODY: &str = r#"<body>
+  <h1>{{t:screenshot_title}}</h1>
+  <p class="message">{{message}}</p>
+  <div class="actions">
+    <button onclick="window.__invoke('answer_screenshot_prompt', { allow: true })">{{t:screenshot_allow}}</button>
+    <button onclick="window.__invoke('answer_screenshot_prompt', { allow: false })">{{t:quit_cancel}}</button>
+  </div>
+</body>"#;
+
+/// The prompt for a capture of `mode=` in `query`.
+pub fn generate_screenshot_html(query: Option<&str>) -> String {
+    let message = match query.unwrap_or_default() {
+        "mode=window" => "screenshot_window",
+        "mode=region" => "screenshot_region",
+        _ => "screenshot_screen",
+    };
+    let body = template::fill(SCREENSHOT_BODY, &[("message", template::t(message))]);
+    template::render(template::t("screenshot_title"), SCREENSHOT_CSS, &body)
+}
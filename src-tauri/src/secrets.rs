@@ -0,0 +1,173 @@
+//! API keys and other secrets in the OS keychain, scoped per profile.
+//!
+//! Entries live under the app identifier with the account `<scope>/<NAME>`,
+//! where the scope is a profile name or [`GLOBAL_SCOPE`]. A profile sees its
+//! own secrets plus global ones it doesn't override. The keychain can't list
+//! entries, so names are indexed in the shell config (`secret_names`);
+//! values never touch the config file.
+//!
+//! The backend reads keys such as `BRAVE_API_KEY` from its environment, so
+//! the effective secrets are passed to it as environment variables. Other
+//! sidecars only get the ones named in their `secrets` list.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::config::ShellConfig;
+use crate::uninstall::APP_IDENTIFIER;
+
+pub const GLOBAL_SCOPE: &str = "global";
+
+/// Secret names per scope.
+pub type SecretIndex = BTreeMap<String, BTreeSet<String>>;
+
+fn entry(scope: &str, name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(APP_IDENTIFIER, &format!("{}/{}", scope, name))
+        .map_err(|e| format!("Failed to open keychain entry: {}", e))
+}
+
+/// Names become environment variables, so keep them to `A-Z`, `0-9`, `_`.
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid secret name {:?}: use upper-case letters, digits and _",
+            name
+        ))
+    }
+}
+
+/// Stores `value` and indexes the name; the caller saves the config.
+pub fn set(config: &mut ShellConfig, scope: &str, name: &str, value: &str) -> Result<(), String> {
+    validate_name(name)?;
+    entry(scope, name)?
+        .set_password(value)
+        .map_err(|e| format!("Failed to store secret {}: {}", name, e))?;
+    config
+        .secret_names
+        .entry(scope.to_string())
+        .or_default()
+        .insert(name.to_string());
+    Ok(())
+}
+
+/// Removes the secret and its index entry; the caller saves the config.
+pub fn delete(config: &mut ShellConfig, scope: &str, name: &str) -> Result<(), String> {
+    match entry(scope, name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => {}
+        Err(e) => return Err(format!("Failed to delete secret {}: {}", name, e)),
+    }
+    if let Some(names) = config.secret_names.get_mut(scope) {
+        names.remove(name);
+        if names.is_empty() {
+            config.secret_names.remove(scope);
+        }
+    }
+    Ok(())
+}
+
+/// Deletes every indexed secret in every scope. Returns how many were removed.
+pub fn delete_all(config: &mut ShellConfig) -> usize {
+    let indexed: Vec<(String, String)> = config
+        .secret_names
+        .iter()
+        .flat_map(|(scope, names)| names.iter().map(move |n| (scope.clone(), n.clone())))
+        .collect();
+    indexed
+        .iter()
+        .filter(|(scope, name)| delete(config, scope, name).is_ok())
+        .count()
+}
+
+/// Drops indexed names the keychain has no entry for, as after restoring a
+/// config from another machine, and returns them as `<scope>/<NAME>`.
+/// Entries that can't be read for another reason are kept. The caller
+/// saves the config.
+pub fn prune_missing(config: &mut ShellConfig) -> Vec<String> {
+    let mut missing = Vec::new();
+    for (scope, names) in config.secret_names.iter_mut() {
+        names.retain(|name| {
+            let gone = matches!(
+                entry(scope, name).map(|e| e.get_password()),
+                Ok(Err(keyring::Error::NoEntry))
+            );
+            if gone {
+                missing.push(format!("{}/{}", scope, name));
+            }
+            !gone
+        });
+    }
+    config.secret_names.retain(|_, names| !names.is_empty());
+    missing
+}
+
+/// Secret names visible to `profile` and the scope each comes from.
+pub fn visible_names(config: &ShellConfig, profile: &str) -> BTreeMap<String, String> {
+    let mut visible = BTreeMap::new();
+    for scope in [GLOBAL_SCOPE, profile] {
+        for name in config.secret_names.get(scope).into_iter().flatten() {
+            visible.insert(name.clone(), scope.to_string());
+        }
+    }
+    visible
+}
+
+/// The value of `name` as `profile` sees it; `None` when it isn't set.
+pub fn get(config: &ShellConfig, profile: &str, name: &str) -> Result<Option<String>, String> {
+    let Some(scope) = visible_names(config, profile).remove(name) else {
+        return Ok(None);
+    };
+    match entry(&scope, name)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read secret {}: {}", name, e)),
+    }
+}
+
+/// Values of the secrets in `names` that `profile` sees, for a sidecar's
+/// environment. Missing or unreadable ones are logged and skipped.
+pub fn resolve_only(
+    config: &ShellConfig,
+    profile: &str,
+    names: &[String],
+) -> BTreeMap<String, String> {
+    names
+        .iter()
+        .filter_map(|name| match get(config, profile, name) {
+            Ok(Some(value)) => Some((name.clone(), value)),
+            Ok(None) => {
+                eprintln!("[tauri] Secret {} is not set", name);
+                None
+            }
+            Err(e) => {
+                eprintln!("[tauri] {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Values of every secret visible to `profile`, for the backend environment.
+/// Unreadable entries are logged and skipped.
+pub fn resolve(config: &ShellConfig, profile: &str) -> BTreeMap<String, String> {
+    visible_names(config, profile)
+        .into_iter()
+        .filter_map(|(name, scope)| {
+            match entry(&scope, &name).and_then(|e| {
+                e.get_password()
+                    .map_err(|e| format!("Failed to read secret {}: {}", name, e))
+            }) {
+                Ok(value) => Some((name, value)),
+                Err(e) => {
+                    eprintln!("[tauri] {}", e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
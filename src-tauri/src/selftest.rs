@@ -0,0 +1,261 @@
+//! `run_self_test`: one action support can ask for instead of guessing
+//! which layer is broken. It starts a throwaway copy of the backend on a
+//! free port with an empty data directory, waits for its health check,
+//! makes one API request, checks its output reached its own log file and
+//! stops it, reporting each stage separately. The running backend is left
+//! alone.
+
+use serde::Serialize;
+use std::fs;
+use std::future::Future;
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::bridge::SOCKET_ENV;
+use crate::registry::BACKEND;
+use crate::sidecar::{SidecarManager, SidecarSpec, StopOutcome};
+use crate::{api, health, upgrade, AppState};
+
+const STAGES: [&str; 5] = ["spawn", "health", "api", "logs", "shutdown"];
+const LOG_FILE: &str = "local-agent-selftest.log";
+const API_PATH: &str = "/api/version";
+const API_TIMEOUT: Duration = Duration::from_secs(5);
+/// Output reaches the log file a moment after the process writes it.
+const LOG_WAIT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageStatus {
+    Passed,
+    Failed,
+    /// Not run because an earlier stage failed.
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StageResult {
+    pub stage: &'static str,
+    pub status: StageStatus,
+    pub duration_ms: u64,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    pub started_at: String,
+    pub passed: bool,
+    pub port: Option<u16>,
+    pub stages: Vec<StageResult>,
+}
+
+impl SelfTestReport {
+    fn new() -> Self {
+        Self {
+            started_at: chrono::Local::now().to_rfc3339(),
+            passed: false,
+            port: None,
+            stages: Vec::new(),
+        }
+    }
+
+    /// Runs one stage; true when it passed.
+    async fn check(
+        &mut self,
+        stage: &'static str,
+        work: impl Future<Output = Result<String, String>>,
+    ) -> bool {
+        let started = Instant::now();
+        let result = work.await;
+        self.record(stage, started, result)
+    }
+
+    fn record(
+        &mut self,
+        stage: &'static str,
+        started: Instant,
+        result: Result<String, String>,
+    ) -> bool {
+        let passed = result.is_ok();
+        self.stages.push(StageResult {
+            stage,
+            status: if passed {
+                StageStatus::Passed
+            } else {
+                StageStatus::Failed
+            },
+            duration_ms: started.elapsed().as_millis() as u64,
+            detail: result.unwrap_or_else(|e| e),
+        });
+        passed
+    }
+
+    /// Marks the stages that didn't run as skipped, in stage order.
+    fn finish(mut self) -> Self {
+        for stage in STAGES {
+            if !self.stages.iter().any(|result| result.stage == stage) {
+                self.stages.push(StageResult {
+                    stage,
+                    status: StageStatus::Skipped,
+                    duration_ms: 0,
+                    detail: "Skipped because an earlier stage failed".to_string(),
+                });
+            }
+        }
+        self.stages
+            .sort_by_key(|result| STAGES.iter().position(|stage| *stage == result.stage));
+        self.passed = self
+            .stages
+            .iter()
+            .all(|result| result.status == StageStatus::Passed);
+        self
+    }
+}
+
+pub async fn run(app: &AppHandle) -> SelfTestReport {
+    let mut report = SelfTestReport::new();
+    let data_dir =
+        std::env::temp_dir().join(format!("local-agent-selftest-{}", uuid::Uuid::new_v4()));
+    let started = Instant::now();
+    let manager = match throwaway_spec(app, &data_dir) {
+        Ok(spec) => Arc::new(SidecarManager::new(spec)),
+        Err(e) => {
+            report.record("spawn", started, Err(e));
+            return report.finish();
+        }
+    };
+    report.port = manager.spec().port();
+    let port = report.port.unwrap_or_default();
+    // Only this run's output counts in the logs stage
+    let _ = fs::remove_file(manager.log_path());
+
+    let spawning = manager.clone();
+    let spawned = tauri::async_runtime::spawn_blocking(move || spawning.spawn())
+        .await
+        .map_err(|e| format!("Spawn task failed: {}", e))
+        .and_then(|spawned| spawned)
+        .map(|()| format!("Started on port {}", port));
+    if report.record("spawn", started, spawned) {
+        let _ = report.check("health", healthy(app, &manager)).await
+            && report.check("api", round_trip(port)).await
+            && report.check("logs", logged(&manager)).await;
+        // Always, so a failed stage doesn't leave the process behind
+        report.check("shutdown", stop(manager, port)).await;
+    }
+    let _ = fs::remove_dir_all(&data_dir);
+
+    let report = report.finish();
+    for result in &report.stages {
+        println!(
+            "[tauri] Self-test {}: {:?} ({} ms) {}",
+            result.stage, result.status, result.duration_ms, result.detail
+        );
+    }
+    report
+}
+
+/// The running backend's spec, moved to a free port on this computer only
+/// with its own data directory and log file.
+fn throwaway_spec(app: &AppHandle, data_dir: &Path) -> Result<SidecarSpec, String> {
+    let state: State<AppState> = app.state();
+    let current = state
+        .sidecars
+        .get(BACKEND)
+        .ok_or_else(|| "The backend is not run by Local Agent".to_string())?;
+    let configured = state.config.lock().unwrap().backend_port();
+    let in_use = current.announced_port().or_else(|| current.spec().port());
+    let port = upgrade::free_port(configured, in_use)
+        .ok_or_else(|| format!("No free port near {} for the test backend", configured))?;
+
+    let mut spec = current.spec().clone();
+    spec.env.remove(SOCKET_ENV);
+    spec.env.insert("HOST".to_string(), "127.0.0.1".to_string());
+    spec.env.insert(
+        "LOCAL_AGENT_DATA_DIR".to_string(),
+        data_dir.to_string_lossy().into_owned(),
+    );
+    spec.log_file = Some(LOG_FILE.to_string());
+    // A configured probe still points at the running backend
+    spec.health_probe = None;
+    Ok(spec.with_port(port))
+}
+
+async fn healthy(app: &AppHandle, manager: &SidecarManager) -> Result<String, String> {
+    let probe = manager
+        .spec()
+        .probe()
+        .ok_or_else(|| "The backend has no health check".to_string())?;
+    health::poll_health_with(&probe, &crate::backend_poll_settings(app), None).await?;
+    Ok("Answered its health check".to_string())
+}
+
+async fn round_trip(port: u16) -> Result<String, String> {
+    let client = reqwest::Client::builder()
+        .timeout(API_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let response = client
+        .get(format!("http://127.0.0.1:{}{}", port, API_PATH))
+        .header(api::SHELL_HEADER, "1")
+        .send()
+        .await
+        .map_err(|e| format!("GET {} failed: {}", API_PATH, e))?;
+    let status = response.status();
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read the {} response: {}", API_PATH, e))?;
+    if !status.is_success() {
+        return Err(format!("GET {} answered {}", API_PATH, status));
+    }
+    Ok(format!(
+        "GET {} answered {} with {} bytes",
+        API_PATH,
+        status,
+        body.len()
+    ))
+}
+
+/// The process's own output reached its log file, not just the line the
+/// shell writes when it starts it.
+async fn logged(manager: &SidecarManager) -> Result<String, String> {
+    let path = manager.log_path();
+    let deadline = Instant::now() + LOG_WAIT;
+    loop {
+        let lines = fs::read_to_string(path)
+            .map_err(|e| format!("Nothing was written to {}: {}", path.display(), e))?
+            .lines()
+            .filter(|line| !line.contains("[tauri] ----"))
+            .count();
+        if lines > 0 {
+            return Ok(format!("{} line(s) written to {}", lines, path.display()));
+        }
+        if Instant::now() >= deadline {
+            return Err(format!("{} has no output from the backend", path.display()));
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+}
+
+async fn stop(manager: Arc<SidecarManager>, port: u16) -> Result<String, String> {
+    let stopping = manager.clone();
+    let outcome = tauri::async_runtime::spawn_blocking(move || {
+        let grace = Duration::from_millis(stopping.spec().shutdown_grace_ms);
+        stopping.shutdown_within(grace)
+    })
+    .await
+    .map_err(|e| format!("Shutdown task failed: {}", e))?;
+    if manager.is_running() {
+        return Err("Still running after shutdown".to_string());
+    }
+    if TcpListener::bind(("127.0.0.1", port)).is_err() {
+        return Err(format!("Port {} is still in use after shutdown", port));
+    }
+    match outcome {
+        StopOutcome::Exited => Ok("Exited within its grace period".to_string()),
+        StopOutcome::Killed => Err("Had to be killed after its grace period".to_string()),
+        StopOutcome::NotRunning => Err("Exited before it was asked to stop".to_string()),
+    }
+}
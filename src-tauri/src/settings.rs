@@ -0,0 +1,256 @@
+//! The user-facing settings behind the Preferences window: a fixed set of
+//! typed keys over the shell config, validated on write. Most live in the
+//! `settings` section; the health keys edit `retry.health` and `startup`
+//! directly so there is one source of truth for each value, and the
+//! crash report toggle edits `crash_reports`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::config::ShellConfig;
+
+/// Bounds for the `zoom` setting.
+pub const MIN_ZOOM: f64 = 0.5;
+pub const MAX_ZOOM: f64 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingKey {
+    BackendPort,
+    Autostart,
+    LogLevel,
+    Theme,
+    HealthAutoTune,
+    HealthIntervalMs,
+    HealthTimeoutMs,
+    CrashReports,
+    BackendTransport,
+    StartMinimized,
+    RunInBackground,
+    CloseToTray,
+    Zoom,
+}
+
+impl SettingKey {
+    pub const ALL: [SettingKey; 13] = [
+        SettingKey::BackendPort,
+        SettingKey::Autostart,
+        SettingKey::LogLevel,
+        SettingKey::Theme,
+        SettingKey::HealthAutoTune,
+        SettingKey::HealthIntervalMs,
+        SettingKey::HealthTimeoutMs,
+        SettingKey::CrashReports,
+        SettingKey::BackendTransport,
+        SettingKey::StartMinimized,
+        SettingKey::RunInBackground,
+        SettingKey::CloseToTray,
+        SettingKey::Zoom,
+    ];
+
+    /// Whether the new value only takes effect the next time Local Agent
+    /// starts (the backend is launched with it).
+    pub fn needs_restart(self) -> bool {
+        matches!(
+            self,
+            SettingKey::BackendPort | SettingKey::LogLevel | SettingKey::BackendTransport
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    /// `None` follows the OS.
+    pub fn window_theme(self) -> Option<tauri::Theme> {
+        match self {
+            Theme::System => None,
+            Theme::Light => Some(tauri::Theme::Light),
+            Theme::Dark => Some(tauri::Theme::Dark),
+        }
+    }
+}
+
+/// How the shell and webview reach the backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendTransport {
+    #[default]
+    Tcp,
+    /// A Unix socket or named pipe only this user can open (see `bridge`).
+    Socket,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettingsConfig {
+    /// Port the backend listens on; the UI is loaded from it.
+    pub backend_port: u16,
+    /// Ignored by dev builds, which proxy the UI to the port.
+    pub transport: BackendTransport,
+    /// Start Local Agent at login.
+    pub autostart: bool,
+    /// At login, start in the tray without opening the window.
+    pub start_minimized: bool,
+    /// Closing the main window keeps the backend and tray running; the
+    /// tray opens a new window.
+    pub run_in_background: bool,
+    /// Closing the main window only hides it, so it reopens as it was.
+    pub close_to_tray: bool,
+    /// Passed to the backend as `LOG_LEVEL`.
+    pub log_level: LogLevel,
+    pub theme: Theme,
+    /// Webview zoom for every window; 1.0 is 100%.
+    pub zoom: f64,
+}
+
+impl Default for SettingsConfig {
+    fn default() -> Self {
+        Self {
+            backend_port: 3001,
+            transport: BackendTransport::default(),
+            autostart: false,
+            start_minimized: false,
+            run_in_background: false,
+            close_to_tray: false,
+            log_level: LogLevel::default(),
+            theme: Theme::default(),
+            zoom: 1.0,
+        }
+    }
+}
+
+/// Sent as "setting-changed" after every successful write.
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingChanged {
+    pub key: SettingKey,
+    pub value: Value,
+    pub needs_restart: bool,
+}
+
+pub fn get(config: &ShellConfig, key: SettingKey) -> Value {
+    let settings = &config.settings;
+    let health = &config.retry.health;
+    match key {
+        SettingKey::BackendPort => settings.backend_port.into(),
+        SettingKey::Autostart => settings.autostart.into(),
+        SettingKey::StartMinimized => settings.start_minimized.into(),
+        SettingKey::RunInBackground => settings.run_in_background.into(),
+        SettingKey::CloseToTray => settings.close_to_tray.into(),
+        SettingKey::LogLevel => settings.log_level.as_str().into(),
+        SettingKey::Theme => serde_json::to_value(settings.theme).unwrap_or_default(),
+        SettingKey::Zoom => settings.zoom.into(),
+        SettingKey::HealthAutoTune => config.startup.auto_tune.into(),
+        SettingKey::HealthIntervalMs => health.initial_delay_ms.into(),
+        SettingKey::HealthTimeoutMs => health.max_elapsed_ms.into(),
+        SettingKey::CrashReports => config.crash_reports.upload.into(),
+        SettingKey::BackendTransport => {
+            serde_json::to_value(settings.transport).unwrap_or_default()
+        }
+    }
+}
+
+pub fn all(config: &ShellConfig) -> BTreeMap<SettingKey, Value> {
+    SettingKey::ALL
+        .into_iter()
+        .map(|key| (key, get(config, key)))
+        .collect()
+}
+
+/// Validates `value` for `key` and stores it; the caller saves the config.
+/// Returns the stored value.
+pub fn set(config: &mut ShellConfig, key: SettingKey, value: Value) -> Result<Value, String> {
+    match key {
+        SettingKey::BackendPort => {
+            config.settings.backend_port = parse_in(key, value, 1024, 65535)? as u16
+        }
+        SettingKey::Autostart => config.settings.autostart = parse(key, value)?,
+        SettingKey::StartMinimized => config.settings.start_minimized = parse(key, value)?,
+        SettingKey::RunInBackground => config.settings.run_in_background = parse(key, value)?,
+        SettingKey::CloseToTray => config.settings.close_to_tray = parse(key, value)?,
+        SettingKey::LogLevel => config.settings.log_level = parse(key, value)?,
+        SettingKey::Theme => config.settings.theme = parse(key, value)?,
+        SettingKey::Zoom => config.settings.zoom = parse_zoom(key, value)?,
+        SettingKey::HealthAutoTune => config.startup.auto_tune = parse(key, value)?,
+        SettingKey::HealthIntervalMs => {
+            let health = &mut config.retry.health;
+            health.initial_delay_ms = parse_in(key, value, 50, 10_000)?;
+            health.max_delay_ms = health.max_delay_ms.max(health.initial_delay_ms);
+        }
+        SettingKey::HealthTimeoutMs => {
+            config.retry.health.max_elapsed_ms = Some(parse_in(key, value, 1_000, 300_000)?)
+        }
+        SettingKey::CrashReports => config.crash_reports.upload = parse(key, value)?,
+        SettingKey::BackendTransport => config.settings.transport = parse(key, value)?,
+    }
+    Ok(get(config, key))
+}
+
+fn parse<T: serde::de::DeserializeOwned>(key: SettingKey, value: Value) -> Result<T, String> {
+    serde_json::from_value(value.clone())
+        .map_err(|_| format!("Invalid value for {}: {}", key_name(key), value))
+}
+
+fn parse_in(key: SettingKey, value: Value, min: u64, max: u64) -> Result<u64, String> {
+    let n: u64 = parse(key, value)?;
+    if (min..=max).contains(&n) {
+        Ok(n)
+    } else {
+        Err(format!(
+            "{} must be between {} and {}",
+            key_name(key),
+            min,
+            max
+        ))
+    }
+}
+
+fn parse_zoom(key: SettingKey, value: Value) -> Result<f64, String> {
+    let zoom: f64 = parse(key, value)?;
+    if (MIN_ZOOM..=MAX_ZOOM).contains(&zoom) {
+        Ok(zoom)
+    } else {
+        Err(format!(
+            "{} must be between {} and {}",
+            key_name(key),
+            MIN_ZOOM,
+            MAX_ZOOM
+        ))
+    }
+}
+
+fn key_name(key: SettingKey) -> String {
+    serde_json::to_value(key)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
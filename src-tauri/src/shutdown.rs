@@ -0,0 +1,232 @@
+//! Ordered shutdown of every supervised process. Services stop in waves,
+//! dependents before what they depend on, with each wave stopped in
+//! parallel. One overall budget caps the whole sequence: a service gets its
+//! own grace period or whatever is left of the budget, whichever is shorter.
+//!
+//! The backend is treated as depending on every other sidecar (Ollama, a
+//! vector DB, …) unless that sidecar lists `backend` in its own
+//! `depends_on`, so it never loses a service mid-request.
+//!
+//! The report of each shutdown is written next to the logs and read back by
+//! `get_last_shutdown_report` on the next launch.
+//!
+//! Closing the last window runs the shutdown on its own thread and emits
+//! `services-stopped` when it is done; `Exit` waits for it so nothing
+//! outlives the shell.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::deps;
+use crate::registry::SidecarRegistry;
+use crate::sidecar::{SidecarManager, StopOutcome};
+
+/// Emitted with the report once a background shutdown finishes.
+pub const EVENT: &str = "services-stopped";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShutdownConfig {
+    /// Upper bound for stopping everything, grace periods included.
+    pub budget_ms: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self { budget_ms: 15000 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceStop {
+    pub name: String,
+    /// 0 for the first services stopped.
+    pub wave: usize,
+    pub outcome: StopOutcome,
+    pub grace_ms: u64,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShutdownReport {
+    pub at: String,
+    pub budget_ms: u64,
+    pub elapsed_ms: u64,
+    pub services: Vec<ServiceStop>,
+}
+
+impl ShutdownReport {
+    pub fn clean(&self) -> bool {
+        self.services
+            .iter()
+            .all(|s| s.outcome != StopOutcome::Killed)
+    }
+}
+
+/// Groups services into stop waves. `deps` maps each service to the
+/// services it uses. A cycle can't be ordered, so services in or behind one
+/// share the last wave.
+pub fn waves(deps: &BTreeMap<String, BTreeSet<String>>) -> Vec<Vec<String>> {
+    let mut remaining: BTreeSet<&String> = deps.keys().collect();
+    let mut waves = Vec::new();
+    while !remaining.is_empty() {
+        let needed: BTreeSet<&String> = remaining
+            .iter()
+            .flat_map(|name| deps[*name].iter())
+            .collect();
+        let wave: Vec<String> = remaining
+            .iter()
+            .filter(|name| !needed.contains(*name))
+            .map(|name| name.to_string())
+            .collect();
+        if wave.is_empty() {
+            eprintln!(
+                "[tauri] Sidecar dependency cycle among {:?}; stopping them together",
+                remaining
+            );
+            waves.push(remaining.iter().map(|name| name.to_string()).collect());
+            break;
+        }
+        for name in &wave {
+            remaining.remove(name);
+        }
+        waves.push(wave);
+    }
+    waves
+}
+
+/// Stops every sidecar in dependency order within `budget`.
+pub fn run(registry: &SidecarRegistry, budget: Duration) -> ShutdownReport {
+    let sidecars = registry.all();
+    let by_name: BTreeMap<String, _> = sidecars
+        .iter()
+        .map(|s| (s.name().to_string(), s.clone()))
+        .collect();
+    let start = Instant::now();
+    let mut services = Vec::new();
+
+    for (wave, names) in waves(&deps::registry_graph(&sidecars))
+        .into_iter()
+        .enumerate()
+    {
+        let left = budget.saturating_sub(start.elapsed());
+        let stops: Vec<ServiceStop> = thread::scope(|scope| {
+            let handles: Vec<_> = names
+                .iter()
+                .map(|name| {
+                    let sidecar = &by_name[name];
+                    scope.spawn(move || {
+                        let grace =
+                            Duration::from_millis(sidecar.spec().shutdown_grace_ms).min(left);
+                        let began = Instant::now();
+                        let outcome = sidecar.shutdown_within(grace);
+                        ServiceStop {
+                            name: sidecar.name().to_string(),
+                            wave,
+                            outcome,
+                            grace_ms: grace.as_millis() as u64,
+                            elapsed_ms: began.elapsed().as_millis() as u64,
+                        }
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .filter_map(|handle| handle.join().ok())
+                .collect()
+        });
+        services.extend(stops);
+    }
+
+    let report = ShutdownReport {
+        at: chrono::Local::now().to_rfc3339(),
+        budget_ms: budget.as_millis() as u64,
+        elapsed_ms: start.elapsed().as_millis() as u64,
+        services,
+    };
+    log(&report);
+    report
+}
+
+fn log(report: &ShutdownReport) {
+    for service in &report.services {
+        let outcome = match service.outcome {
+            StopOutcome::NotRunning => "was not running",
+            StopOutcome::Exited => "exited cleanly",
+            StopOutcome::Killed => "was killed",
+        };
+        println!(
+            "[tauri] Shutdown wave {}: {} {} ({}ms, grace {}ms)",
+            service.wave, service.name, outcome, service.elapsed_ms, service.grace_ms
+        );
+    }
+    println!(
+        "[tauri] Shutdown {} in {}ms (budget {}ms)",
+        if report.clean() { "clean" } else { "forced" },
+        report.elapsed_ms,
+        report.budget_ms
+    );
+}
+
+#[derive(Default)]
+struct Started {
+    started: bool,
+    task: Option<thread::JoinHandle<()>>,
+}
+
+/// Makes sure services are stopped once per run, either in place or on a
+/// thread of their own.
+#[derive(Default)]
+pub struct ShutdownOnce {
+    inner: Mutex<Started>,
+}
+
+impl ShutdownOnce {
+    /// Runs `stop` here unless a shutdown already started; one running in
+    /// the background is waited for instead.
+    pub fn run(&self, stop: impl FnOnce()) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.started {
+            let task = inner.task.take();
+            drop(inner);
+            if let Some(task) = task {
+                let _ = task.join();
+            }
+            return;
+        }
+        inner.started = true;
+        drop(inner);
+        stop();
+    }
+
+    /// Runs `stop` on its own thread unless a shutdown already started.
+    pub fn spawn(&self, stop: impl FnOnce() + Send + 'static) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.started {
+            return;
+        }
+        inner.started = true;
+        inner.task = Some(thread::spawn(stop));
+    }
+}
+
+pub fn report_path() -> PathBuf {
+    SidecarManager::resolve_log_dir().join("local-agent-shutdown.json")
+}
+
+pub fn save(report: &ShutdownReport) -> Result<(), String> {
+    let contents = serde_json::to_string_pretty(report)
+        .map_err(|e| format!("Failed to serialize shutdown report: {}", e))?;
+    fs::write(report_path(), contents)
+        .map_err(|e| format!("Failed to write shutdown report: {}", e))
+}
+
+pub fn load_last() -> Option<ShutdownReport> {
+    let contents = fs::read_to_string(report_path()).ok()?;
+    serde_json::from_str(&contents).ok()
+}
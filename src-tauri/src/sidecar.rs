@@ -1,75 +1,556 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
 use std::fs::{self, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
-use std::process::{Child, Command, Stdio};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, watch};
+
+use crate::backoff::Backoff;
+use crate::bridge::SOCKET_ENV;
+use crate::control::{ControlCommand, CONTROL_ENV};
+use crate::environment;
+use crate::health::ProbeSpec;
+use crate::logstream::{self, Level, LineLimiter, LogBatcher};
+use crate::paths;
+use crate::readiness::{self, Ready};
+use crate::reaper;
+use crate::redact;
+use crate::syslog;
+use crate::trace::TraceStore;
+
+/// Output lines kept in memory for the error page.
+pub const RECENT_LINES: usize = 1000;
+/// Lines a slow log follower can fall behind by before it skips ahead.
+const FOLLOW_BUFFER: usize = 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartPolicy {
+    Never,
+    #[default]
+    OnFailure,
+    Always,
+}
+
+/// Everything needed to launch and supervise one local process.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SidecarSpec {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Variables from the shell's environment passed through on top of the
+    /// base allowlist (see `environment`); a trailing `*` matches a prefix.
+    #[serde(default)]
+    pub inherit_env: Vec<String>,
+    /// Keychain secrets (see `secrets`) passed in as variables of the same
+    /// name. The backend gets every secret its profile sees instead.
+    #[serde(default)]
+    pub secrets: Vec<String>,
+    #[serde(default)]
+    pub health_url: Option<String>,
+    /// Replaces the plain GET of `health_url` with another kind of check.
+    #[serde(default)]
+    pub health_probe: Option<ProbeSpec>,
+    /// File name inside the shared logs directory; defaults to
+    /// `local-agent-<name>.log`.
+    #[serde(default)]
+    pub log_file: Option<String>,
+    #[serde(default)]
+    pub restart_policy: RestartPolicy,
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+    /// Sidecars this one uses; it is stopped before them on shutdown.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// How long it gets to exit after the polite signal before it is killed.
+    #[serde(default = "default_shutdown_grace_ms")]
+    pub shutdown_grace_ms: u64,
+    /// POSTed on shutdown, before any signal, so the process can finish
+    /// writes and exit by itself.
+    #[serde(default)]
+    pub shutdown_url: Option<String>,
+    /// How much of the grace period the process gets to exit after
+    /// `shutdown_url` answers.
+    #[serde(default = "default_shutdown_drain_ms")]
+    pub shutdown_drain_ms: u64,
+    /// Keep stdin open for control messages (see `control`).
+    #[serde(default)]
+    pub stdin_control: bool,
+    /// Give the process a console window on Windows, for troubleshooting.
+    #[serde(default)]
+    pub show_console: bool,
+}
+
+fn default_max_restarts() -> u32 {
+    5
+}
+
+fn default_shutdown_grace_ms() -> u64 {
+    5000
+}
+
+fn default_shutdown_drain_ms() -> u64 {
+    2000
+}
+
+impl SidecarSpec {
+    /// The node backend that serves the UI and tools API.
+    pub fn backend(project_root: PathBuf) -> Self {
+        Self {
+            name: "backend".to_string(),
+            command: "node".to_string(),
+            args: vec!["server.js".to_string()],
+            cwd: Some(project_root),
+            env: BTreeMap::from([
+                ("NODE_ENV".to_string(), "production".to_string()),
+                ("PORT".to_string(), "3001".to_string()),
+            ]),
+            inherit_env: Vec::new(),
+            secrets: Vec::new(),
+            health_url: Some("http://127.0.0.1:3001/health".to_string()),
+            health_probe: None,
+            log_file: Some("local-agent-backend.log".to_string()),
+            restart_policy: RestartPolicy::OnFailure,
+            max_restarts: default_max_restarts(),
+            depends_on: Vec::new(),
+            shutdown_grace_ms: default_shutdown_grace_ms(),
+            shutdown_url: Some("http://127.0.0.1:3001/api/shutdown".to_string()),
+            shutdown_drain_ms: default_shutdown_drain_ms(),
+            stdin_control: true,
+            show_console: false,
+        }
+    }
+
+    /// `npm run dev:backend` in the repo, for shells started with `tauri dev`.
+    pub fn dev_backend(project_root: PathBuf) -> Self {
+        let npm = if cfg!(windows) { "npm.cmd" } else { "npm" };
+        Self {
+            command: npm.to_string(),
+            args: vec!["run".to_string(), "dev:backend".to_string()],
+            env: BTreeMap::from([("PORT".to_string(), "3001".to_string())]),
+            ..Self::backend(project_root)
+        }
+    }
+
+    /// The backend compiled into a standalone executable (pkg/bun build).
+    pub fn backend_binary(binary: PathBuf, project_root: PathBuf) -> Self {
+        Self {
+            command: binary.to_string_lossy().into_owned(),
+            args: Vec::new(),
+            ..Self::backend(project_root)
+        }
+    }
+
+    /// Moves the backend to `port`, which the node server reads from `PORT`.
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.env.insert("PORT".to_string(), port.to_string());
+        self.health_url = Some(format!("http://127.0.0.1:{}/health", port));
+        if self.shutdown_url.is_some() {
+            self.shutdown_url = Some(format!("http://127.0.0.1:{}/api/shutdown", port));
+        }
+        self
+    }
+
+    /// The port set by `with_port`.
+    pub fn port(&self) -> Option<u16> {
+        self.env.get("PORT").and_then(|port| port.parse().ok())
+    }
+
+    /// Has the node server listen on `socket` instead of `PORT` (see
+    /// `bridge`); the health check has to go over the socket too.
+    pub fn with_socket(mut self, socket: &Path) -> Self {
+        self.env.insert(
+            SOCKET_ENV.to_string(),
+            socket.to_string_lossy().into_owned(),
+        );
+        self.health_url = None;
+        self
+    }
+
+    /// How to check this sidecar is up, if it can be checked at all.
+    pub fn probe(&self) -> Option<ProbeSpec> {
+        self.health_probe
+            .clone()
+            .or_else(|| self.health_url.as_deref().map(ProbeSpec::http))
+    }
+}
+
+/// Set to `1` to have a dev build start the backend itself.
+pub const DEV_SPAWN_ENV: &str = "LOCAL_AGENT_SPAWN_BACKEND";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DevConfig {
+    /// Dev builds normally expect a backend started separately (`npm run
+    /// dev:all`). When set, the shell runs `npm run dev:backend` itself,
+    /// logs it like the production sidecar and stops it on exit.
+    pub spawn_backend: bool,
+    /// Show sidecar console windows on Windows, which are hidden otherwise.
+    pub show_console: bool,
+}
+
+impl DevConfig {
+    /// The config key, or the environment variable for a one-off run.
+    pub fn spawns_backend(&self) -> bool {
+        self.spawn_backend || std::env::var(DEV_SPAWN_ENV).is_ok_and(|v| v == "1" || v == "true")
+    }
+}
+
+/// Replaces parts of the backend's launch from the config file, to run a
+/// custom build or add flags without rebuilding the shell. Values may use
+/// the variables in `TemplateVars`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackendCommand {
+    /// Run instead of `node` or the bundled binary.
+    pub command: Option<String>,
+    /// Replace the default arguments.
+    pub args: Option<Vec<String>>,
+    pub cwd: Option<String>,
+    /// Added to the backend's environment, over the defaults.
+    pub env: BTreeMap<String, String>,
+}
+
+impl BackendCommand {
+    pub fn is_set(&self) -> bool {
+        self.command.is_some() || self.args.is_some() || self.cwd.is_some() || !self.env.is_empty()
+    }
+
+    /// `spec` with the configured values applied.
+    pub fn apply(&self, mut spec: SidecarSpec, vars: &TemplateVars) -> Result<SidecarSpec, String> {
+        if let Some(ref command) = self.command {
+            spec.command = vars.expand(command)?;
+        }
+        if let Some(ref args) = self.args {
+            spec.args = args
+                .iter()
+                .map(|arg| vars.expand(arg))
+                .collect::<Result<_, _>>()?;
+        }
+        if let Some(ref cwd) = self.cwd {
+            spec.cwd = Some(PathBuf::from(vars.expand(cwd)?));
+        }
+        for (name, value) in &self.env {
+            spec.env.insert(name.clone(), vars.expand(value)?);
+        }
+        Ok(spec)
+    }
+}
+
+/// Values for `${data_dir}`, `${port}` and `${resource_dir}` in a
+/// configured sidecar's command, args, cwd and env.
+pub struct TemplateVars {
+    /// The active profile's data directory.
+    pub data_dir: PathBuf,
+    /// The backend's port.
+    pub port: u16,
+    /// The app's bundled resources.
+    pub resource_dir: PathBuf,
+}
+
+impl TemplateVars {
+    /// `text` with each `${name}` replaced; an unknown name is an error
+    /// rather than something passed to the process as is.
+    pub fn expand(&self, text: &str) -> Result<String, String> {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("${") {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let end = after
+                .find('}')
+                .ok_or_else(|| format!("Unclosed ${{ in {:?}", text))?;
+            match &after[..end] {
+                "data_dir" => out.push_str(&self.data_dir.to_string_lossy()),
+                "port" => out.push_str(&self.port.to_string()),
+                "resource_dir" => out.push_str(&self.resource_dir.to_string_lossy()),
+                name => return Err(format!("Unknown variable ${{{}}} in {:?}", name, text)),
+            }
+            rest = &after[end + 1..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    /// A configured sidecar with its command, args, cwd and env expanded.
+    pub fn expand_spec(&self, mut spec: SidecarSpec) -> Result<SidecarSpec, String> {
+        spec.command = self.expand(&spec.command)?;
+        spec.args = spec
+            .args
+            .iter()
+            .map(|arg| self.expand(arg))
+            .collect::<Result<_, _>>()?;
+        if let Some(ref cwd) = spec.cwd {
+            spec.cwd = Some(PathBuf::from(self.expand(&cwd.to_string_lossy())?));
+        }
+        for value in spec.env.values_mut() {
+            *value = self.expand(value)?;
+        }
+        Ok(spec)
+    }
+}
+
+/// How a sidecar went down on shutdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopOutcome {
+    NotRunning,
+    /// Exited within its grace period.
+    Exited,
+    /// Still running after its grace period (or unwaitable) and killed.
+    Killed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SidecarStatus {
+    pub name: String,
+    pub running: bool,
+    pub pid: Option<u32>,
+    pub restarts: u32,
+    pub last_exit: Option<String>,
+    pub health_url: Option<String>,
+    pub log_path: PathBuf,
+    pub restart_policy: RestartPolicy,
+}
+
+/// An unexpected exit, for telling crashes apart.
+#[derive(Debug, Clone, Copy)]
+pub struct ExitInfo {
+    pub status: ExitStatus,
+    /// How long the process had been running.
+    pub uptime: Option<Duration>,
+}
 
 pub struct SidecarManager {
+    spec: SidecarSpec,
     child: Arc<Mutex<Option<Child>>>,
     log_path: PathBuf,
-    project_root: PathBuf,
-    max_retries: u32,
+    backoff: Backoff,
+    traces: Option<Arc<TraceStore>>,
+    logs: Option<Arc<LogBatcher>>,
+    /// Whether the process is supposed to be running; an exit while this is
+    /// set is treated as a crash by the watchdog.
+    wanted: AtomicBool,
+    restarts: AtomicU32,
+    last_exit: Mutex<Option<String>>,
+    /// What the current process announced on stdout (see `readiness`);
+    /// cleared on every spawn.
+    ready: Arc<watch::Sender<Option<Ready>>>,
+    /// The current process's stdin, with `stdin_control`.
+    stdin: Mutex<Option<ChildStdin>>,
+    /// When the current (or last) process was started.
+    spawned_at: Mutex<Option<Instant>>,
+    exit_info: Mutex<Option<ExitInfo>>,
+    /// The last `RECENT_LINES` lines written to the log file, kept across
+    /// restarts like the file.
+    recent: Arc<RecentLines>,
+}
+
+struct RecentLines {
+    lines: Mutex<VecDeque<String>>,
+    /// Every pushed line, for `follow_log`.
+    appended: broadcast::Sender<String>,
+}
+
+impl Default for RecentLines {
+    fn default() -> Self {
+        Self {
+            lines: Mutex::new(VecDeque::new()),
+            appended: broadcast::channel(FOLLOW_BUFFER).0,
+        }
+    }
+}
+
+impl RecentLines {
+    fn push(&self, line: String) {
+        let mut lines = self.lines.lock().unwrap();
+        if lines.len() >= RECENT_LINES {
+            lines.pop_front();
+        }
+        // Sent under the lock so a follower's snapshot and stream meet
+        let _ = self.appended.send(line.clone());
+        lines.push_back(line);
+    }
+
+    fn last(&self, n: usize) -> Vec<String> {
+        let lines = self.lines.lock().unwrap();
+        lines
+            .iter()
+            .skip(lines.len().saturating_sub(n))
+            .cloned()
+            .collect()
+    }
+
+    fn follow(&self, n: usize) -> (Vec<String>, broadcast::Receiver<String>) {
+        let lines = self.lines.lock().unwrap();
+        let last = lines
+            .iter()
+            .skip(lines.len().saturating_sub(n))
+            .cloned()
+            .collect();
+        (last, self.appended.subscribe())
+    }
 }
 
 impl SidecarManager {
-    pub fn new(project_root: PathBuf) -> Self {
-        let log_path = Self::resolve_log_path();
+    pub fn new(spec: SidecarSpec) -> Self {
+        let log_path = Self::resolve_log_dir().join(
+            spec.log_file
+                .clone()
+                .unwrap_or_else(|| format!("local-agent-{}.log", spec.name)),
+        );
         Self {
+            spec,
             child: Arc::new(Mutex::new(None)),
             log_path,
-            project_root,
-            max_retries: 3,
+            backoff: Backoff::spawn(),
+            traces: None,
+            logs: None,
+            wanted: AtomicBool::new(false),
+            restarts: AtomicU32::new(0),
+            last_exit: Mutex::new(None),
+            ready: Arc::new(watch::channel(None).0),
+            stdin: Mutex::new(None),
+            spawned_at: Mutex::new(None),
+            exit_info: Mutex::new(None),
+            recent: Arc::new(RecentLines::default()),
         }
     }
 
-    fn resolve_log_path() -> PathBuf {
-        if cfg!(target_os = "macos") {
-            let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-            let logs_dir = home.join("Library").join("Logs");
-            if logs_dir.exists() {
-                return logs_dir.join("local-agent-backend.log");
-            }
-        }
-        dirs::data_local_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("local-agent-backend.log")
+    /// Backend stdout lines tagged with a trace id are forwarded to `traces`.
+    pub fn with_trace_store(mut self, traces: Arc<TraceStore>) -> Self {
+        self.traces = Some(traces);
+        self
+    }
+
+    /// Retry timing for `spawn_with_retry`.
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Output lines are also queued on `logs` for the UI log view.
+    pub fn with_log_stream(mut self, logs: Arc<LogBatcher>) -> Self {
+        self.logs = Some(logs);
+        self
+    }
+
+    pub fn resolve_log_dir() -> PathBuf {
+        paths::log_dir()
+    }
+
+    pub fn resolve_log_path() -> PathBuf {
+        Self::resolve_log_dir().join("local-agent-backend.log")
+    }
+
+    pub fn name(&self) -> &str {
+        &self.spec.name
+    }
+
+    pub fn spec(&self) -> &SidecarSpec {
+        &self.spec
     }
 
-    #[allow(dead_code)]
     pub fn log_path(&self) -> &PathBuf {
         &self.log_path
     }
 
+    /// Watches for the current process to announce it is ready.
+    pub fn readiness(&self) -> watch::Receiver<Option<Ready>> {
+        self.ready.subscribe()
+    }
+
+    /// The port the current process announced, if it did.
+    pub fn announced_port(&self) -> Option<u16> {
+        self.ready.borrow().and_then(|ready| ready.port)
+    }
+
     pub fn spawn(&self) -> Result<(), String> {
         // Ensure log directory exists
         if let Some(parent) = self.log_path.parent() {
             fs::create_dir_all(parent).ok();
         }
 
-        let mut child = Command::new("node")
-            .arg("server.js")
-            .current_dir(&self.project_root)
-            .env("NODE_ENV", "production")
-            .env("PORT", "3001")
+        let mut command = Command::new(program(&self.spec.command));
+        environment::scrub(&mut command, &self.spec.inherit_env);
+        command
+            .args(&self.spec.args)
+            .envs(&self.spec.env)
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| format!("Failed to spawn node process: {}", e))?;
+            .stderr(Stdio::piped());
+        if let Some(ref cwd) = self.spec.cwd {
+            command.current_dir(cwd);
+        }
+        if self.spec.stdin_control {
+            command.env(CONTROL_ENV, "stdin").stdin(Stdio::piped());
+        }
+        reaper::configure(&mut command);
+        hide_console(&mut command, self.spec.show_console);
+        self.ready.send_replace(None);
+
+        let mut child = command.spawn().map_err(|e| {
+            format!(
+                "Failed to spawn {} process ({}): {}",
+                self.spec.name, self.spec.command, e
+            )
+        })?;
+
+        reaper::adopt(&child);
+        *self.spawned_at.lock().unwrap() = Some(Instant::now());
+        *self.exit_info.lock().unwrap() = None;
 
         let pid = child.id();
-        println!("[tauri] Backend sidecar started (pid: {})", pid);
+        let started = format!("{} sidecar started (pid: {})", self.spec.name, pid);
+        println!("[tauri] {}", started);
+        syslog::send("shell", Level::Info, &started);
+        // Marks where this run's output begins
+        let marker = format!(
+            "{} [tauri] ---- {} session started (pid {}) ----",
+            logstream::timestamp(),
+            self.spec.name,
+            pid
+        );
+        if let Ok(mut file) = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.log_path)
+        {
+            let _ = writeln!(file, "{}", marker);
+        }
+        self.recent.push(marker);
 
         // Take stdout/stderr before storing child
         let stdout = child.stdout.take();
         let stderr = child.stderr.take();
+        *self.stdin.lock().unwrap() = child.stdin.take();
 
         *self.child.lock().unwrap() = Some(child);
+        self.wanted.store(true, Ordering::SeqCst);
+
+        // Both streams share one budget, so a burst on either is capped
+        let limiter = Arc::new(Mutex::new(LineLimiter::default()));
 
         // Pipe stdout to log file in background thread
         if let Some(stdout) = stdout {
             let log_path = self.log_path.clone();
+            let limiter = limiter.clone();
+            let recent = self.recent.clone();
+            let traces = self.traces.clone();
+            let logs = self.logs.clone();
+            let ready = self.ready.clone();
+            let name = self.spec.name.clone();
+            let tag = format!("[{}]", self.spec.name);
             thread::spawn(move || {
                 let reader = BufReader::new(stdout);
                 let mut file = OpenOptions::new()
@@ -77,19 +558,47 @@ impl SidecarManager {
                     .append(true)
                     .open(&log_path)
                     .ok();
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        println!("[backend] {}", line);
-                        if let Some(ref mut f) = file {
-                            let _ = writeln!(f, "{}", line);
+                for line in reader.lines().map_while(Result::ok) {
+                    let line = redact::line(line);
+                    if let Some(announced) = readiness::parse(&line) {
+                        ready.send_replace(Some(announced));
+                    }
+                    if let Some(ref traces) = traces {
+                        traces.ingest_backend_line(&line);
+                    }
+                    let level = Level::detect("stdout", &line);
+                    let logged = logstream::format_logged("stdout", &line);
+                    if let Some(ref mut f) = file {
+                        let _ = writeln!(f, "{}", logged);
+                    }
+                    let Some(suppressed) = limiter.lock().unwrap().admit(level) else {
+                        continue;
+                    };
+                    if suppressed > 0 {
+                        let marker = logstream::suppressed_marker(suppressed);
+                        println!("{} {}", tag, marker);
+                        if let Some(ref logs) = logs {
+                            logs.push(&name, "stdout", Level::Warn, &marker);
                         }
+                        recent.push(logstream::format_logged("stdout", &marker));
                     }
+                    println!("{} {}", tag, line);
+                    if let Some(ref logs) = logs {
+                        logs.push(&name, "stdout", level, &line);
+                    }
+                    syslog::send(&name, level, &line);
+                    recent.push(logged);
                 }
             });
         }
 
         if let Some(stderr) = stderr {
             let log_path = self.log_path.clone();
+            let limiter = limiter.clone();
+            let recent = self.recent.clone();
+            let logs = self.logs.clone();
+            let name = self.spec.name.clone();
+            let tag = format!("[{}:err]", self.spec.name);
             thread::spawn(move || {
                 let reader = BufReader::new(stderr);
                 let mut file = OpenOptions::new()
@@ -97,13 +606,30 @@ impl SidecarManager {
                     .append(true)
                     .open(&log_path)
                     .ok();
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        eprintln!("[backend:err] {}", line);
-                        if let Some(ref mut f) = file {
-                            let _ = writeln!(f, "[stderr] {}", line);
+                for line in reader.lines().map_while(Result::ok) {
+                    let line = redact::line(line);
+                    let level = Level::detect("stderr", &line);
+                    let logged = logstream::format_logged("stderr", &line);
+                    if let Some(ref mut f) = file {
+                        let _ = writeln!(f, "{}", logged);
+                    }
+                    let Some(suppressed) = limiter.lock().unwrap().admit(level) else {
+                        continue;
+                    };
+                    if suppressed > 0 {
+                        let marker = logstream::suppressed_marker(suppressed);
+                        eprintln!("{} {}", tag, marker);
+                        if let Some(ref logs) = logs {
+                            logs.push(&name, "stderr", Level::Warn, &marker);
                         }
+                        recent.push(logstream::format_logged("stderr", &marker));
+                    }
+                    eprintln!("{} {}", tag, line);
+                    if let Some(ref logs) = logs {
+                        logs.push(&name, "stderr", level, &line);
                     }
+                    syslog::send(&name, level, &line);
+                    recent.push(logged);
                 }
             });
         }
@@ -111,7 +637,6 @@ impl SidecarManager {
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub fn is_running(&self) -> bool {
         let mut guard = self.child.lock().unwrap();
         if let Some(ref mut child) = *guard {
@@ -121,53 +646,199 @@ impl SidecarManager {
         }
     }
 
-    pub fn shutdown(&self) {
+    pub fn pid(&self) -> Option<u32> {
+        self.child.lock().unwrap().as_ref().map(|c| c.id())
+    }
+
+    /// Returns the exit status if the process died while it was supposed to
+    /// be running. The dead child is cleared so this reports each exit once.
+    pub fn poll_unexpected_exit(&self) -> Option<ExitStatus> {
+        if !self.wanted.load(Ordering::SeqCst) {
+            return None;
+        }
         let mut guard = self.child.lock().unwrap();
+        let status = guard.as_mut()?.try_wait().ok()??;
+        // The crashed process may leave forked workers behind
         if let Some(mut child) = guard.take() {
-            println!("[tauri] Shutting down backend sidecar...");
+            reaper::kill(&mut child);
+        }
+        self.wanted.store(false, Ordering::SeqCst);
+        *self.last_exit.lock().unwrap() = Some(status.to_string());
+        *self.exit_info.lock().unwrap() = Some(ExitInfo {
+            status,
+            uptime: self.spawned_at.lock().unwrap().map(|at| at.elapsed()),
+        });
+        Some(status)
+    }
 
-            let _ = child.kill();
+    /// How the last process exited unexpectedly; cleared on every spawn.
+    pub fn last_exit_info(&self) -> Option<ExitInfo> {
+        *self.exit_info.lock().unwrap()
+    }
 
-            let start = Instant::now();
-            loop {
-                match child.try_wait() {
-                    Ok(Some(_)) => {
-                        println!("[tauri] Backend sidecar stopped.");
-                        return;
-                    }
-                    Ok(None) => {
-                        if start.elapsed() > Duration::from_secs(5) {
-                            println!("[tauri] Force killing backend sidecar.");
-                            let _ = child.kill();
-                            let _ = child.wait();
-                            return;
-                        }
-                        thread::sleep(Duration::from_millis(100));
+    /// Whether the restart policy allows another automatic restart after `status`.
+    pub fn should_restart(&self, status: &ExitStatus) -> bool {
+        let allowed = match self.spec.restart_policy {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnFailure => !status.success(),
+            RestartPolicy::Always => true,
+        };
+        allowed && self.restarts.load(Ordering::SeqCst) < self.spec.max_restarts
+    }
+
+    pub fn record_restart(&self) {
+        self.restarts.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn status(&self) -> SidecarStatus {
+        SidecarStatus {
+            name: self.spec.name.clone(),
+            running: self.is_running(),
+            pid: self.pid(),
+            restarts: self.restarts.load(Ordering::SeqCst),
+            last_exit: self.last_exit.lock().unwrap().clone(),
+            health_url: self.spec.health_url.clone(),
+            log_path: self.log_path.clone(),
+            restart_policy: self.spec.restart_policy,
+        }
+    }
+
+    pub fn shutdown(&self) {
+        self.shutdown_within(Duration::from_millis(self.spec.shutdown_grace_ms));
+    }
+
+    /// Asks the process to exit and kills it if it is still running after
+    /// `grace`.
+    pub fn shutdown_within(&self, grace: Duration) -> StopOutcome {
+        self.wanted.store(false, Ordering::SeqCst);
+        let mut guard = self.child.lock().unwrap();
+        let Some(mut child) = guard.take() else {
+            return StopOutcome::NotRunning;
+        };
+        println!("[tauri] Shutting down {} sidecar...", self.spec.name);
+
+        let start = Instant::now();
+        if let Some(drain) = self.request_drain(grace) {
+            while start.elapsed() < drain {
+                if let Ok(Some(_)) = child.try_wait() {
+                    reaper::kill(&mut child);
+                    *self.stdin.lock().unwrap() = None;
+                    println!("[tauri] {} sidecar drained and exited.", self.spec.name);
+                    return StopOutcome::Exited;
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+            println!(
+                "[tauri] {} sidecar still running after {:?}; asking it to stop",
+                self.spec.name, drain
+            );
+        }
+
+        // Asked over stdin first, the process gets half of what is left of
+        // the grace period before the signal
+        let grace = grace.saturating_sub(start.elapsed());
+        let start = Instant::now();
+        let mut signalled = self.send_control(ControlCommand::GracefulShutdown).is_err();
+        *self.stdin.lock().unwrap() = None;
+        if signalled {
+            reaper::terminate(&mut child);
+        }
+
+        loop {
+            match child.try_wait() {
+                Ok(Some(_)) => {
+                    // Workers that ignored the polite signal go too
+                    reaper::kill(&mut child);
+                    println!("[tauri] {} sidecar stopped.", self.spec.name);
+                    return StopOutcome::Exited;
+                }
+                Ok(None) if start.elapsed() < grace => {
+                    if !signalled && start.elapsed() >= grace / 2 {
+                        reaper::terminate(&mut child);
+                        signalled = true;
                     }
-                    Err(_) => return,
+                    thread::sleep(Duration::from_millis(100));
+                }
+                Ok(None) | Err(_) => {
+                    println!("[tauri] Force killing {} sidecar.", self.spec.name);
+                    reaper::kill(&mut child);
+                    let _ = child.wait();
+                    return StopOutcome::Killed;
                 }
             }
         }
     }
 
+    /// POSTs `shutdown_url` and returns how long to wait for the process to
+    /// exit, or `None` when there's nothing to wait for.
+    fn request_drain(&self, grace: Duration) -> Option<Duration> {
+        let url = self.spec.shutdown_url.as_deref()?;
+        let drain = Duration::from_millis(self.spec.shutdown_drain_ms).min(grace / 2);
+        if drain.is_zero() {
+            return None;
+        }
+        let socket = self.spec.env.get(SOCKET_ENV).map(PathBuf::from);
+        match post_shutdown(url, self.announced_port(), socket.as_deref(), drain) {
+            Ok(status) if (200..300).contains(&status) => Some(drain),
+            Ok(status) => {
+                eprintln!(
+                    "[tauri] {} refused the shutdown request ({})",
+                    self.spec.name, status
+                );
+                None
+            }
+            Err(e) => {
+                eprintln!("[tauri] {}", e);
+                None
+            }
+        }
+    }
+
+    /// Writes `command` to the process's stdin.
+    pub fn send_control(&self, command: ControlCommand) -> Result<(), String> {
+        let mut stdin = self.stdin.lock().unwrap();
+        let Some(pipe) = stdin.as_mut() else {
+            return Err(format!(
+                "{} sidecar is not running with a control channel",
+                self.spec.name
+            ));
+        };
+        let result = pipe
+            .write_all(command.line().as_bytes())
+            .and_then(|()| pipe.flush());
+        if let Err(e) = result {
+            // The process closed its end; it won't read anything else
+            *stdin = None;
+            return Err(format!(
+                "Failed to send {} to {} sidecar: {}",
+                command.as_str(),
+                self.spec.name,
+                e
+            ));
+        }
+        Ok(())
+    }
+
     pub fn spawn_with_retry(&self) -> Result<(), String> {
-        let mut attempts = 0u32;
+        let mut attempts = self.backoff.start();
 
         loop {
             match self.spawn() {
                 Ok(()) => return Ok(()),
                 Err(e) => {
-                    attempts += 1;
-                    if attempts >= self.max_retries {
+                    let Some(delay) = attempts.next_delay() else {
                         return Err(format!(
-                            "Backend failed to start after {} attempts: {}",
-                            self.max_retries, e
+                            "{} failed to start after {} attempts: {}",
+                            self.spec.name,
+                            attempts.failures(),
+                            e
                         ));
-                    }
-                    let delay = Duration::from_secs(1 << (attempts - 1)); // 1s, 2s, 4s
+                    };
                     println!(
-                        "[tauri] Backend start failed (attempt {}), retrying in {:?}...",
-                        attempts, delay
+                        "[tauri] {} start failed (attempt {}), retrying in {:?}...",
+                        self.spec.name,
+                        attempts.failures(),
+                        delay
                     );
                     thread::sleep(delay);
                 }
@@ -175,22 +846,183 @@ impl SidecarManager {
         }
     }
 
+    /// Output captured the last time this sidecar exited unexpectedly.
+    pub fn crash_snapshot_path(&self) -> PathBuf {
+        Self::resolve_log_dir().join(format!("local-agent-{}-crash.log", self.spec.name))
+    }
+
+    /// Replaces the crash snapshot with `lines`, headed by the exit status.
+    pub fn save_crash_snapshot(&self, status: &str, lines: &[String]) -> Result<(), String> {
+        let mut contents = format!(
+            "{} exited unexpectedly ({}) at {}\n",
+            self.spec.name,
+            status,
+            chrono::Local::now().to_rfc3339()
+        );
+        for line in lines {
+            contents.push_str(line);
+            contents.push('\n');
+        }
+        fs::write(self.crash_snapshot_path(), contents)
+            .map_err(|e| format!("Failed to write crash snapshot: {}", e))
+    }
+
+    pub fn read_last_crash_lines(&self, n: usize) -> Vec<String> {
+        last_lines(&self.crash_snapshot_path(), n)
+    }
+
     pub fn read_last_log_lines(&self, n: usize) -> Vec<String> {
-        fs::read_to_string(&self.log_path)
-            .unwrap_or_default()
-            .lines()
-            .rev()
-            .take(n)
-            .collect::<Vec<_>>()
-            .into_iter()
-            .rev()
-            .map(|s| s.to_string())
-            .collect()
+        last_lines(&self.log_path, n)
+    }
+
+    /// The last `n` log file lines from memory, or from the file itself
+    /// when nothing was piped yet (e.g. the process never started).
+    pub fn recent_log_lines(&self, n: usize) -> Vec<String> {
+        let lines = self.recent.last(n);
+        if lines.is_empty() {
+            self.read_last_log_lines(n)
+        } else {
+            lines
+        }
+    }
+
+    /// Like `recent_log_lines`, plus every line logged after them.
+    pub fn follow_log(&self, n: usize) -> (Vec<String>, broadcast::Receiver<String>) {
+        let (lines, appended) = self.recent.follow(n);
+        if lines.is_empty() {
+            (self.read_last_log_lines(n), appended)
+        } else {
+            (lines, appended)
+        }
     }
 }
 
+/// Runs the stub backend named by this variable in place of every sidecar,
+/// with the `mock-sidecar` feature.
+#[cfg(feature = "mock-sidecar")]
+pub const MOCK_ENV: &str = "LOCAL_AGENT_MOCK_SIDECAR";
+
+#[cfg(feature = "mock-sidecar")]
+fn program(command: &str) -> std::ffi::OsString {
+    std::env::var_os(MOCK_ENV).unwrap_or_else(|| command.into())
+}
+
+#[cfg(not(feature = "mock-sidecar"))]
+fn program(command: &str) -> &str {
+    command
+}
+
+fn last_lines(path: &Path, n: usize) -> Vec<String> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .rev()
+        .take(n)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .map(|s| s.to_string())
+        .collect()
+}
+
 impl Drop for SidecarManager {
+    /// Exit already stopped the process in order; this only makes sure it
+    /// doesn't outlive the shell, without waiting for it.
     fn drop(&mut self) {
-        self.shutdown();
+        let child = self
+            .child
+            .try_lock()
+            .ok()
+            .and_then(|mut guard| guard.take());
+        if let Some(mut child) = child {
+            reaper::kill(&mut child);
+        }
+    }
+}
+
+/// Keeps console programs like node from opening a window of their own.
+#[cfg(windows)]
+fn hide_console(command: &mut Command, show: bool) {
+    use std::os::windows::process::CommandExt;
+    if !show {
+        command.creation_flags(windows_sys::Win32::System::Threading::CREATE_NO_WINDOW);
+    }
+}
+
+#[cfg(not(windows))]
+fn hide_console(_command: &mut Command, _show: bool) {}
+
+/// Marks the shutdown request as the shell's; the backend refuses it
+/// otherwise.
+const SHELL_HEADER: &str = "x-local-agent-shell";
+
+/// Sends an empty POST to `url`, over `socket` when the process listens on
+/// one, and returns the response status. `port` replaces the URL's when the
+/// process announced another.
+fn post_shutdown(
+    url: &str,
+    port: Option<u16>,
+    socket: Option<&Path>,
+    timeout: Duration,
+) -> Result<u16, String> {
+    let mut parsed =
+        reqwest::Url::parse(url).map_err(|e| format!("Invalid shutdown URL {}: {}", url, e))?;
+    if let Some(port) = port {
+        let _ = parsed.set_port(Some(port));
+    }
+    let host = parsed.host_str().unwrap_or("localhost").to_string();
+    let target = match parsed.query() {
+        Some(query) => format!("{}?{}", parsed.path(), query),
+        None => parsed.path().to_string(),
+    };
+    // HTTP/1.0 so the server closes the connection after answering
+    let request = format!(
+        "POST {} HTTP/1.0\r\nHost: {}\r\n{}: 1\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        target, host, SHELL_HEADER
+    );
+    let failed = |e: std::io::Error| format!("Failed to request shutdown at {}: {}", url, e);
+    let response = match socket {
+        Some(socket) => exchange_socket(socket, &request, timeout),
+        None => {
+            let address = (host.as_str(), parsed.port_or_known_default().unwrap_or(80))
+                .to_socket_addrs()
+                .map_err(failed)?
+                .next()
+                .ok_or_else(|| format!("Failed to resolve {}", url))?;
+            let mut stream = TcpStream::connect_timeout(&address, timeout).map_err(failed)?;
+            stream.set_read_timeout(Some(timeout)).map_err(failed)?;
+            stream.set_write_timeout(Some(timeout)).map_err(failed)?;
+            exchange(&mut stream, &request)
+        }
     }
+    .map_err(failed)?;
+    // "HTTP/1.1 202 Accepted"
+    response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| format!("Unexpected answer to shutdown request at {}", url))
+}
+
+fn exchange(stream: &mut (impl Read + Write), request: &str) -> std::io::Result<String> {
+    stream.write_all(request.as_bytes())?;
+    // Only the status line is needed
+    let mut head = [0u8; 64];
+    let read = stream.read(&mut head)?;
+    Ok(String::from_utf8_lossy(&head[..read]).into_owned())
+}
+
+#[cfg(unix)]
+fn exchange_socket(socket: &Path, request: &str, timeout: Duration) -> std::io::Result<String> {
+    let mut stream = std::os::unix::net::UnixStream::connect(socket)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    exchange(&mut stream, request)
+}
+
+#[cfg(windows)]
+fn exchange_socket(socket: &Path, request: &str, _timeout: Duration) -> std::io::Result<String> {
+    // Named pipes open like files
+    let mut pipe = OpenOptions::new().read(true).write(true).open(socket)?;
+    exchange(&mut pipe, request)
 }
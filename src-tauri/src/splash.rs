@@ -0,0 +1,103 @@
+use serde::Serialize;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::{emit_event, pages, template};
+
+const SPLASH_LABEL: &str = "splash";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupProgress {
+    pub stage: &'static str,
+    pub message: String,
+}
+
+const SPLASH_CSS: &str = r#"
+  body {
+    gap: 1.25rem;
+    user-select: none;
+    -webkit-user-select: none;
+    text-align: center;
+  }
+  h1 {
+    font-size: 1.1rem;
+    font-weight: 600;
+  }
+  .spinner {
+    width: 1.75rem;
+    height: 1.75rem;
+    border: 0.2rem solid var(--border);
+    border-top-color: var(--fg);
+    border-radius: 50%;
+    animation: spin 0.9s linear infinite;
+  }
+  #status {
+    color: var(--fg-muted);
+    font-size: 0.8rem;
+  }
+  @keyframes spin {
+    to { transform: rotate(360deg); }
+  }
+"#;
+
+pub fn generate_splash_html() -> String {
+    let body = template::fill(
+        r#"<body class="centered" data-tauri-drag-region>
+  <h1>Local Agent</h1>
+  <div class="spinner" role="progressbar" aria-label="Starting"></div>
+  <p id="status" aria-live="polite">{{t:starting}}</p>
+  <script>
+    window.__setStatus = function (message) {
+      document.getElementById('status').textContent = message;
+    };
+  </script>
+</body>"#,
+        &[],
+    );
+    template::render("Local Agent", SPLASH_CSS, &body)
+}
+
+pub fn show(app: &AppHandle) {
+    let result = WebviewWindowBuilder::new(
+        app,
+        SPLASH_LABEL,
+        WebviewUrl::CustomProtocol(pages::page_url("splash")),
+    )
+    .title("Local Agent")
+    .inner_size(360.0, 220.0)
+    .resizable(false)
+    .decorations(false)
+    .skip_taskbar(true)
+    .center()
+    .build();
+
+    if let Err(e) = result {
+        eprintln!("[tauri] Failed to create splash window: {}", e);
+    }
+}
+
+/// Reports a boot stage both to the splash window and, via the
+/// `startup-progress` event, to any frontend that is already listening.
+pub fn set_status(app: &AppHandle, stage: &'static str, message: &str) {
+    emit_event(
+        app,
+        "startup-progress",
+        StartupProgress {
+            stage,
+            message: message.to_string(),
+        },
+    );
+
+    if let Some(splash) = app.get_webview_window(SPLASH_LABEL) {
+        let js = format!(
+            "window.__setStatus && window.__setStatus({});",
+            serde_json::to_string(message).unwrap()
+        );
+        let _ = splash.eval(&js);
+    }
+}
+
+pub fn close(app: &AppHandle) {
+    if let Some(splash) = app.get_webview_window(SPLASH_LABEL) {
+        let _ = splash.close();
+    }
+}
@@ -0,0 +1,259 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use crate::backoff::Backoff;
+use crate::config::ShellConfig;
+use crate::health::PollSettings;
+
+/// When the process started; set first thing in `run`.
+static LAUNCH: OnceLock<Instant> = OnceLock::new();
+
+/// Marks the launch so milestones count from it, not from setup.
+pub fn mark_launch() -> Instant {
+    *LAUNCH.get_or_init(Instant::now)
+}
+
+/// Bounds for the auto-tuned health polling. Tuning never leaves these, so a
+/// freak fast or slow boot can't produce a useless setting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StartupConfig {
+    pub auto_tune: bool,
+    /// Boot records kept on disk for tuning and `get_startup_timings`.
+    pub history: usize,
+    pub min_poll_interval_ms: u64,
+    pub max_poll_interval_ms: u64,
+    pub min_connect_timeout_ms: u64,
+    pub max_connect_timeout_ms: u64,
+    pub min_timeout_ms: u64,
+    pub max_timeout_ms: u64,
+}
+
+impl Default for StartupConfig {
+    fn default() -> Self {
+        Self {
+            auto_tune: true,
+            history: 20,
+            min_poll_interval_ms: 50,
+            max_poll_interval_ms: 1000,
+            min_connect_timeout_ms: 500,
+            max_connect_timeout_ms: 5000,
+            min_timeout_ms: 10000,
+            max_timeout_ms: 60000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhaseTiming {
+    pub stage: String,
+    pub duration_ms: u64,
+}
+
+/// A point in the boot, in milliseconds since launch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Milestone {
+    pub name: String,
+    pub at_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootRecord {
+    pub started_at: String,
+    pub success: bool,
+    pub total_ms: u64,
+    pub phases: Vec<PhaseTiming>,
+    /// Launch, sidecar spawn, first log line, health pass and window show;
+    /// missing in records from older versions.
+    #[serde(default)]
+    pub milestones: Vec<Milestone>,
+}
+
+impl BootRecord {
+    pub fn phase_ms(&self, stage: &str) -> Option<u64> {
+        self.phases
+            .iter()
+            .find(|p| p.stage == stage)
+            .map(|p| p.duration_ms)
+    }
+}
+
+/// Times the startup stages as the splash advances through them.
+pub struct BootTimer {
+    started_at: chrono::DateTime<chrono::Local>,
+    start: Instant,
+    current: Option<(String, Instant)>,
+    phases: Vec<PhaseTiming>,
+    milestones: Vec<Milestone>,
+    finished: bool,
+}
+
+impl Default for BootTimer {
+    fn default() -> Self {
+        Self {
+            started_at: chrono::Local::now(),
+            start: mark_launch(),
+            current: None,
+            phases: Vec::new(),
+            milestones: vec![Milestone {
+                name: "launch".to_string(),
+                at_ms: 0,
+            }],
+            finished: false,
+        }
+    }
+}
+
+impl BootTimer {
+    /// Ends the running stage and starts `stage`.
+    pub fn mark(&mut self, stage: &str) {
+        self.close_current();
+        self.current = Some((stage.to_string(), Instant::now()));
+    }
+
+    /// Records `name` as reached now, unless it already was.
+    pub fn milestone(&mut self, name: &str) {
+        self.milestone_at(name, Instant::now());
+    }
+
+    /// Records `name` as reached at `at`, unless it already was.
+    pub fn milestone_at(&mut self, name: &str, at: Instant) {
+        if self.finished || self.milestones.iter().any(|m| m.name == name) {
+            return;
+        }
+        self.milestones.push(Milestone {
+            name: name.to_string(),
+            at_ms: at.saturating_duration_since(self.start).as_millis() as u64,
+        });
+        self.milestones.sort_by_key(|m| m.at_ms);
+    }
+
+    /// The milestones reached so far this launch.
+    pub fn milestones(&self) -> &[Milestone] {
+        &self.milestones
+    }
+
+    fn close_current(&mut self) {
+        if let Some((stage, began)) = self.current.take() {
+            self.phases.push(PhaseTiming {
+                stage,
+                duration_ms: began.elapsed().as_millis() as u64,
+            });
+        }
+    }
+
+    /// Completes the boot once; later calls (e.g. from a Retry) return `None`.
+    pub fn finish(&mut self, success: bool) -> Option<BootRecord> {
+        if self.finished {
+            return None;
+        }
+        self.finished = true;
+        self.close_current();
+        Some(BootRecord {
+            started_at: self.started_at.to_rfc3339(),
+            success,
+            total_ms: self.start.elapsed().as_millis() as u64,
+            phases: std::mem::take(&mut self.phases),
+            milestones: self.milestones.clone(),
+        })
+    }
+}
+
+pub fn stats_path() -> PathBuf {
+    ShellConfig::path().with_file_name("startup-stats.json")
+}
+
+pub fn load_records() -> Vec<BootRecord> {
+    fs::read_to_string(stats_path())
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Appends `record`, keeping the last `keep` boots.
+pub fn save_record(record: BootRecord, keep: usize) -> Result<(), String> {
+    println!(
+        "[tauri] Boot {} in {}ms ({}; {})",
+        if record.success {
+            "completed"
+        } else {
+            "failed"
+        },
+        record.total_ms,
+        record
+            .phases
+            .iter()
+            .map(|p| format!("{} {}ms", p.stage, p.duration_ms))
+            .collect::<Vec<_>>()
+            .join(", "),
+        record
+            .milestones
+            .iter()
+            .map(|m| format!("{} at {}ms", m.name, m.at_ms))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    let mut records = load_records();
+    records.push(record);
+    let excess = records.len().saturating_sub(keep.max(1));
+    records.drain(..excess);
+
+    let path = stats_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    let contents = serde_json::to_string_pretty(&records)
+        .map_err(|e| format!("Failed to serialize startup stats: {}", e))?;
+    fs::write(&path, contents).map_err(|e| format!("Failed to write startup stats: {}", e))
+}
+
+/// Derives health polling settings from past successful boots: poll about
+/// twenty times across a typical wait, allow half a typical wait to connect,
+/// and give up after three times the slowest wait seen.
+/// Health polling for this boot: the configured `policy`, with its first
+/// interval and deadline scaled to how long recent boots took.
+pub fn tune(config: &StartupConfig, policy: &Backoff, records: &[BootRecord]) -> PollSettings {
+    let defaults = PollSettings {
+        backoff: *policy,
+        ..PollSettings::default()
+    };
+    if !config.auto_tune {
+        return defaults;
+    }
+
+    let mut waits: Vec<u64> = records
+        .iter()
+        .filter(|r| r.success)
+        .filter_map(|r| r.phase_ms("health"))
+        .collect();
+    if waits.len() < 3 {
+        return defaults;
+    }
+    waits.sort_unstable();
+    let median = waits[waits.len() / 2];
+    let slowest = waits[waits.len() - 1];
+
+    // max/min rather than clamp: a hand-edited config with min > max must
+    // not panic at startup.
+    PollSettings {
+        connect_timeout_ms: (median / 2)
+            .max(config.min_connect_timeout_ms)
+            .min(config.max_connect_timeout_ms),
+        backoff: Backoff {
+            initial_delay_ms: (median / 20)
+                .max(config.min_poll_interval_ms)
+                .min(config.max_poll_interval_ms),
+            max_elapsed_ms: Some(
+                (slowest * 3)
+                    .max(config.min_timeout_ms)
+                    .min(config.max_timeout_ms),
+            ),
+            ..*policy
+        },
+    }
+}
@@ -0,0 +1,82 @@
+//! The backend's lifecycle as one value. Everything that starts, watches or
+//! restarts the backend moves it through these states, and the tray,
+//! splash, error pages and frontend follow the `backend-state-changed`
+//! event instead of piecing it together from separate signals.
+
+use serde::Serialize;
+use std::sync::Mutex;
+
+pub const EVENT: &str = "backend-state-changed";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendState {
+    /// Not started yet, or not managed by this shell.
+    #[default]
+    NotStarted,
+    Spawning,
+    /// Running, waiting for its ready line or health check.
+    WaitingHealthy,
+    Healthy,
+    /// Running but not answering as it should (unhealthy after a resume,
+    /// over its memory ceiling).
+    Degraded,
+    /// Being stopped and started again, by the watchdog or the user.
+    Restarting,
+    /// Down until the user acts: failed to start, crash loop, bad install.
+    Failed,
+    /// Stopped while idle to save memory; opening a window starts it.
+    Suspended,
+}
+
+impl BackendState {
+    /// Short text for the tray tooltip and log lines.
+    pub fn label(self) -> &'static str {
+        match self {
+            BackendState::NotStarted => "not started",
+            BackendState::Spawning => "starting",
+            BackendState::WaitingHealthy => "waiting to become healthy",
+            BackendState::Healthy => "running",
+            BackendState::Degraded => "not responding normally",
+            BackendState::Restarting => "restarting",
+            BackendState::Failed => "stopped after an error",
+            BackendState::Suspended => "paused while idle",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BackendStatus {
+    pub state: BackendState,
+    pub previous: Option<BackendState>,
+    /// Why it got here: an error, an exit status, what asked for a restart.
+    pub detail: Option<String>,
+    pub since: Option<String>,
+}
+
+#[derive(Default)]
+pub struct BackendStateMachine {
+    current: Mutex<BackendStatus>,
+}
+
+impl BackendStateMachine {
+    pub fn current(&self) -> BackendStatus {
+        self.current.lock().unwrap().clone()
+    }
+
+    /// Moves to `state`. Returns the new status, or `None` when nothing
+    /// changed.
+    pub fn transition(&self, state: BackendState, detail: Option<&str>) -> Option<BackendStatus> {
+        let mut current = self.current.lock().unwrap();
+        if current.state == state && current.detail.as_deref() == detail {
+            return None;
+        }
+        *current = BackendStatus {
+            state,
+            previous: Some(current.state),
+            detail: detail.map(str::to_string),
+            since: Some(chrono::Local::now().to_rfc3339()),
+        };
+        Some(current.clone())
+    }
+}
@@ -0,0 +1,179 @@
+//! Optional copy of log lines into the platform's own log, so Console.app,
+//! Event Viewer, `journalctl` and support scripts can find them without
+//! knowing where the log files are.
+//!
+//! On Linux lines go to journald over its native socket, with the sidecar
+//! name in `LOCAL_AGENT_SOURCE`; without journald they go to syslog. macOS
+//! routes `syslog(3)` into unified logging. On Windows they are written to
+//! the Application event log under the source "Local Agent".
+//!
+//! Forwarded: sidecar output, sidecar starts and exits, and shell panics.
+//! Debug lines stay in the files only.
+
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::logstream::Level;
+
+/// Identifier the lines carry in the system log.
+const IDENTIFIER: &str = "local-agent";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SystemLogConfig {
+    pub enabled: bool,
+}
+
+static SINK: OnceLock<Option<imp::Sink>> = OnceLock::new();
+
+/// Opens the system log when the config asks for it; call once at startup.
+pub fn init(config: &SystemLogConfig) {
+    SINK.get_or_init(|| {
+        if !config.enabled {
+            return None;
+        }
+        let sink = imp::Sink::open();
+        if sink.is_none() {
+            eprintln!("[tauri] Failed to open the system log; logging to files only");
+        }
+        sink
+    });
+}
+
+/// Forwards one line from `source` (`shell` or a sidecar name).
+pub fn send(source: &str, level: Level, message: &str) {
+    if level == Level::Debug {
+        return;
+    }
+    if let Some(Some(sink)) = SINK.get() {
+        sink.send(source, level, message);
+    }
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::ffi::CString;
+    use std::os::unix::net::UnixDatagram;
+
+    use super::IDENTIFIER;
+    use crate::logstream::Level;
+
+    const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+
+    pub enum Sink {
+        Journal(UnixDatagram),
+        Syslog,
+    }
+
+    /// Syslog priority; unmarked stdout counts as info.
+    fn priority(level: Level) -> libc::c_int {
+        match level {
+            Level::Error => libc::LOG_ERR,
+            Level::Warn => libc::LOG_WARNING,
+            Level::Info | Level::Unknown => libc::LOG_INFO,
+            Level::Debug => libc::LOG_DEBUG,
+        }
+    }
+
+    /// A journald field in the binary form, which allows any bytes
+    /// (including newlines) in the value.
+    fn field(datagram: &mut Vec<u8>, name: &str, value: &str) {
+        datagram.extend_from_slice(name.as_bytes());
+        datagram.push(b'\n');
+        datagram.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        datagram.extend_from_slice(value.as_bytes());
+        datagram.push(b'\n');
+    }
+
+    impl Sink {
+        pub fn open() -> Option<Self> {
+            if cfg!(target_os = "linux") {
+                let socket = UnixDatagram::unbound().ok();
+                if let Some(socket) = socket.filter(|s| s.connect(JOURNAL_SOCKET).is_ok()) {
+                    return Some(Sink::Journal(socket));
+                }
+            }
+            // openlog keeps the pointer, so the identifier must be static
+            unsafe { libc::openlog(c"local-agent".as_ptr(), libc::LOG_PID, libc::LOG_USER) };
+            Some(Sink::Syslog)
+        }
+
+        pub fn send(&self, source: &str, level: Level, message: &str) {
+            match self {
+                Sink::Journal(socket) => {
+                    let mut datagram = Vec::new();
+                    field(&mut datagram, "MESSAGE", message);
+                    field(&mut datagram, "PRIORITY", &priority(level).to_string());
+                    field(&mut datagram, "SYSLOG_IDENTIFIER", IDENTIFIER);
+                    field(&mut datagram, "LOCAL_AGENT_SOURCE", source);
+                    // Dropped rather than blocking a log reader thread
+                    let _ = socket.send(&datagram);
+                }
+                Sink::Syslog => {
+                    let Ok(line) = CString::new(format!("[{}] {}", source, message)) else {
+                        return;
+                    };
+                    unsafe { libc::syslog(priority(level), c"%s".as_ptr(), line.as_ptr()) };
+                }
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use windows_sys::Win32::Foundation::HANDLE;
+    use windows_sys::Win32::System::EventLog::{
+        RegisterEventSourceW, ReportEventW, EVENTLOG_ERROR_TYPE, EVENTLOG_INFORMATION_TYPE,
+        EVENTLOG_WARNING_TYPE,
+    };
+
+    use crate::logstream::Level;
+
+    const SOURCE: &str = "Local Agent";
+    /// Event Viewer refuses longer strings.
+    const MAX_CHARS: usize = 31_000;
+
+    /// The event source handle, stored as an integer so it can live in a
+    /// static.
+    pub struct Sink(usize);
+
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    impl Sink {
+        pub fn open() -> Option<Self> {
+            let source = wide(SOURCE);
+            let handle = unsafe { RegisterEventSourceW(std::ptr::null(), source.as_ptr()) };
+            (!handle.is_null()).then_some(Sink(handle as usize))
+        }
+
+        pub fn send(&self, source: &str, level: Level, message: &str) {
+            let kind = match level {
+                Level::Error => EVENTLOG_ERROR_TYPE,
+                Level::Warn => EVENTLOG_WARNING_TYPE,
+                _ => EVENTLOG_INFORMATION_TYPE,
+            };
+            let text: String = format!("[{}] {}", source, message)
+                .chars()
+                .take(MAX_CHARS)
+                .collect();
+            let text = wide(&text);
+            let strings = [text.as_ptr()];
+            unsafe {
+                ReportEventW(
+                    self.0 as HANDLE,
+                    kind,
+                    0,
+                    0,
+                    std::ptr::null_mut(),
+                    1,
+                    0,
+                    strings.as_ptr(),
+                    std::ptr::null(),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! `tail_logs`: the backend log's last lines and, when following, every
+//! line logged after them as `log-appended` events to the window that asked,
+//! until it calls `untail_logs` or closes. New lines come straight from the
+//! sidecar's output threads rather than from watching the log file.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::sidecar::SidecarManager;
+
+pub const EVENT: &str = "log-appended";
+/// Lines carried by one event when the backend is chatty.
+const MAX_LINES_PER_EVENT: usize = 200;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TailSnapshot {
+    pub lines: Vec<String>,
+    /// Tags this tail's events; pass it to `untail_logs`. `None` when not
+    /// following.
+    pub subscription: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogAppended {
+    pub subscription: u64,
+    pub lines: Vec<String>,
+    /// Lines dropped because the window fell behind.
+    pub skipped: u64,
+}
+
+/// Followed tails by window label.
+#[derive(Default)]
+pub struct Tails {
+    next: AtomicU64,
+    windows: Mutex<HashMap<String, HashMap<u64, JoinHandle<()>>>>,
+}
+
+impl Tails {
+    /// The last `lines` of `backend`'s log, following it for `window` when
+    /// `follow` is set.
+    pub fn tail(
+        &self,
+        app: &AppHandle,
+        window: &str,
+        backend: &SidecarManager,
+        lines: usize,
+        follow: bool,
+    ) -> TailSnapshot {
+        if !follow {
+            return TailSnapshot {
+                lines: backend.recent_log_lines(lines),
+                subscription: None,
+            };
+        }
+        let (snapshot, appended) = backend.follow_log(lines);
+        let subscription = self.next.fetch_add(1, Ordering::SeqCst) + 1;
+        let task = tauri::async_runtime::spawn(forward(
+            app.clone(),
+            window.to_string(),
+            subscription,
+            appended,
+        ));
+        self.windows
+            .lock()
+            .unwrap()
+            .entry(window.to_string())
+            .or_default()
+            .insert(subscription, task);
+        TailSnapshot {
+            lines: snapshot,
+            subscription: Some(subscription),
+        }
+    }
+
+    /// Stops one of `window`'s tails; false when it has no such tail.
+    pub fn untail(&self, window: &str, subscription: u64) -> bool {
+        let mut windows = self.windows.lock().unwrap();
+        let Some(tails) = windows.get_mut(window) else {
+            return false;
+        };
+        let stopped = tails.remove(&subscription).map(|task| task.abort());
+        if tails.is_empty() {
+            windows.remove(window);
+        }
+        stopped.is_some()
+    }
+
+    /// Stops every tail `window` follows, when it closes.
+    pub fn close_window(&self, window: &str) {
+        if let Some(tails) = self.windows.lock().unwrap().remove(window) {
+            for task in tails.into_values() {
+                task.abort();
+            }
+        }
+    }
+}
+
+/// Sends lines to `window` as they arrive, batching any that queued up
+/// while the last event went out.
+async fn forward(
+    app: AppHandle,
+    window: String,
+    subscription: u64,
+    mut appended: broadcast::Receiver<String>,
+) {
+    let mut skipped = 0;
+    loop {
+        let first = match appended.recv().await {
+            Ok(line) => line,
+            Err(RecvError::Lagged(n)) => {
+                skipped += n;
+                continue;
+            }
+            // The backend was replaced; its successor needs a new tail
+            Err(RecvError::Closed) => return,
+        };
+        let mut lines = vec![first];
+        while lines.len() < MAX_LINES_PER_EVENT {
+            match appended.try_recv() {
+                Ok(line) => lines.push(line),
+                Err(broadcast::error::TryRecvError::Lagged(n)) => skipped += n,
+                Err(_) => break,
+            }
+        }
+        let event = LogAppended {
+            subscription,
+            lines,
+            skipped: std::mem::take(&mut skipped),
+        };
+        if app.emit_to(window.as_str(), EVENT, event).is_err() {
+            return;
+        }
+    }
+}
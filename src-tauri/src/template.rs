@@ -0,0 +1,436 @@
+//! Hand-rolled templating for shell-generated pages: a shared document
+//! layout and theme, `{{placeholder}}` filling with escaping on by default,
+//! and a string catalog so page text can be translated in one place.
+
+/// Styles shared by every shell-generated page (error, splash, terminal,
+/// log viewer).
+/// Sizes are in rem/em so OS text scaling and webview zoom carry through,
+/// and layouts wrap rather than overflow down to ~200px wide windows.
+pub const BASE_CSS: &str = r#"
+  :root {
+    --bg: #1a1a1a;
+    --bg-inset: #111;
+    --fg: #e0e0e0;
+    --fg-muted: #999;
+    --border: #333;
+    --accent-error: #ff6b6b;
+    --accent-warn: #f0b429;
+    --font-ui: -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif;
+    --font-mono: "SF Mono", "Fira Code", monospace;
+  }
+  @media (prefers-color-scheme: light) {
+    :root {
+      --bg: #f5f5f5;
+      --bg-inset: #fff;
+      --fg: #1a1a1a;
+      --fg-muted: #666;
+      --border: #d0d0d0;
+      --accent-error: #c62828;
+      --accent-warn: #a15c00;
+    }
+    button { background: #e8e8e8; border-color: #bbb; }
+    button:hover { background: #ddd; }
+  }
+  * { margin: 0; padding: 0; box-sizing: border-box; }
+  html {
+    font-size: 100%;
+    -webkit-text-size-adjust: 100%;
+    text-size-adjust: 100%;
+  }
+  body {
+    background: var(--bg);
+    color: var(--fg);
+    font-family: var(--font-ui);
+    line-height: 1.5;
+    min-height: 100vh;
+    overflow-wrap: anywhere;
+  }
+  .centered {
+    display: flex;
+    flex-direction: column;
+    align-items: center;
+    justify-content: center;
+    padding: clamp(0.75rem, 5vw, 2rem);
+  }
+  .container {
+    max-width: 40rem;
+    width: 100%;
+  }
+  pre, code, .mono {
+    font-family: var(--font-mono);
+  }
+  button {
+    background: #333;
+    color: var(--fg);
+    border: 1px solid #555;
+    border-radius: 0.375rem;
+    padding: 0.6em 1.5em;
+    font: inherit;
+    font-size: 0.9rem;
+    cursor: pointer;
+    transition: background 0.15s;
+  }
+  button:hover { background: #444; }
+  button:focus-visible { outline: 2px solid var(--fg); outline-offset: 2px; }
+  .actions {
+    display: flex;
+    flex-wrap: wrap;
+    gap: 0.75rem;
+  }
+  @media (max-width: 24rem) {
+    .actions button { flex: 1 1 100%; }
+  }
+  @media (prefers-reduced-motion: reduce) {
+    *, *::before, *::after { animation-duration: 0s !important; transition: none !important; }
+  }
+"#;
+
+/// Calls a shell command from page scripts; the same in every window.
+const INVOKE_SCRIPT: &str =
+    "window.__invoke = (cmd, args) => window.__TAURI_INTERNALS__.invoke(cmd, args);";
+
+/// What shell-generated pages may load: only their own inline styles and
+/// scripts, data images and the IPC bridge. The error page is written over
+/// whatever the window showed, so this also cuts it off from that origin.
+const PAGE_CSP: &str = "default-src 'none'; script-src 'unsafe-inline'; \
+    style-src 'unsafe-inline'; img-src data:; connect-src ipc: http://ipc.localhost; \
+    base-uri 'none'; form-action 'none'";
+
+/// Wraps a page's own styles and body in the shared document shell.
+pub fn render(title: &str, page_css: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="{}">
+<head>
+<meta charset="utf-8">
+<meta http-equiv="Content-Security-Policy" content="{}">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{}</title>
+<style>{}{}</style>
+<script>{}</script>
+</head>
+{}
+</html>"#,
+        language(),
+        PAGE_CSP,
+        html_escape(title),
+        BASE_CSS,
+        page_css,
+        INVOKE_SCRIPT,
+        body
+    )
+}
+
+/// Replaces `{{name}}` with the matching value and `{{t:key}}` with the
+/// translated string for `key`, both HTML-escaped. Unknown names render as
+/// nothing. Trusted markup is composed around the filled template instead.
+pub fn fill(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let name = rest[start + 2..start + 2 + len].trim();
+        if let Some(key) = name.strip_prefix("t:") {
+            out.push_str(&html_escape(t(key)));
+        } else {
+            match vars.iter().find(|(k, _)| *k == name) {
+                Some((_, value)) => out.push_str(&html_escape(value)),
+                None => eprintln!("[tauri] Template placeholder {:?} has no value", name),
+            }
+        }
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// English strings; other catalogs are added next to it and picked in
+/// `catalog`. Missing keys fall back to English, then to the key itself.
+const EN: &[(&str, &str)] = &[
+    ("backend_failed", "Backend failed to start"),
+    ("retry", "Retry"),
+    ("open_logs_folder", "Open Logs Folder"),
+    ("open_log_file", "Open Log File"),
+    ("report_issue", "Report Issue…"),
+    ("no_log_output", "No log output available."),
+    ("starting", "Starting…"),
+    (
+        "terminal_placeholder",
+        "Type a command and press Enter (Ctrl+C to interrupt)",
+    ),
+    ("log_viewer_title", "Local Agent Logs"),
+    ("log_search", "Search logs"),
+    ("log_follow", "Follow"),
+    ("log_source", "Show"),
+    ("log_source_memory", "Recent output"),
+    ("log_source_file", "Log file"),
+    ("log_source_crash", "Last crash"),
+    ("last_error", "Most recent error"),
+    ("crash_loop", "The backend keeps crashing"),
+    (
+        "backend_incompatible",
+        "The backend doesn't match this version of Local Agent",
+    ),
+    ("get_update", "Get the Update"),
+    ("continue_anyway", "Continue Anyway"),
+    ("recent_restarts", "Recent restarts"),
+    ("suggested_fix", "How to fix it"),
+    ("reconnecting", "Reconnecting…"),
+    (
+        "reconnecting_detail",
+        "The backend is restarting. You'll be back in your chats as soon as it answers.",
+    ),
+    ("reconnect_failed", "The backend didn't come back"),
+    ("view_logs", "View Logs"),
+    ("shell_crashed", "Local Agent crashed"),
+    (
+        "shell_crashed_detail",
+        "An internal error stopped the app. Details were written to local-agent-shell.log in the logs folder; restart Local Agent to continue.",
+    ),
+    ("preferences_title", "Local Agent Preferences"),
+    ("pref_general", "General"),
+    ("pref_autostart", "Start at login"),
+    ("pref_start_minimized", "Start minimized to the tray"),
+    ("pref_run_in_background", "Keep running when the window is closed"),
+    ("pref_close_to_tray", "Hide the window to the tray on close"),
+    ("pref_theme", "Theme"),
+    ("pref_theme_system", "System"),
+    ("pref_theme_light", "Light"),
+    ("pref_theme_dark", "Dark"),
+    ("pref_zoom", "Zoom"),
+    ("pref_backend", "Backend"),
+    ("pref_transport", "Connection"),
+    ("pref_transport_tcp", "Local port"),
+    ("pref_transport_socket", "Private socket"),
+    ("pref_backend_port", "Port"),
+    ("pref_log_level", "Log level"),
+    ("pref_health", "Health checks"),
+    ("pref_health_auto_tune", "Tune from previous launches"),
+    ("pref_health_interval", "Poll interval (ms)"),
+    ("pref_health_timeout", "Give up after (ms)"),
+    ("pref_crash_reports", "Send crash reports"),
+    ("pref_saved", "Saved"),
+    (
+        "pref_restart_needed",
+        "Saved; takes effect when Local Agent restarts",
+    ),
+    ("quit_title", "Quit Local Agent?"),
+    (
+        "quit_busy",
+        "The agent is still working. Quitting now will interrupt it.",
+    ),
+    ("quit_wait", "Wait and quit"),
+    ("quit_now", "Quit now"),
+    ("quit_cancel", "Cancel"),
+    ("quit_waiting", "Quitting once the work finishes…"),
+    ("about_title", "About Local Agent"),
+    ("about_app", "Version"),
+    ("about_backend", "Backend"),
+    ("about_os", "System"),
+    ("about_license", "License"),
+    ("about_unknown", "Unknown"),
+    ("about_acknowledgements", "Acknowledgements"),
+    ("check_for_updates", "Check for Updates"),
+    ("about_checking", "Checking for updates…"),
+    ("about_up_to_date", "Local Agent is up to date."),
+    ("about_update_available", "Version {version} is available."),
+    ("pick_workspace", "Choose a project folder for Local Agent"),
+    ("screenshot_title", "Take a screenshot?"),
+    (
+        "screenshot_screen",
+        "Local Agent wants to capture your whole screen to answer your question.",
+    ),
+    (
+        "screenshot_window",
+        "Local Agent wants to capture a window to answer your question.",
+    ),
+    (
+        "screenshot_region",
+        "Local Agent wants to capture an area of the screen you select to answer your question.",
+    ),
+    ("screenshot_allow", "Take Screenshot"),
+];
+
+/// Two-letter UI language from the usual locale variables (`de_DE.UTF-8` → `de`).
+pub fn language() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+        .and_then(|value| value.get(..2).map(str::to_ascii_lowercase))
+        .unwrap_or_else(|| "en".to_string())
+}
+
+fn catalog(_language: &str) -> &'static [(&'static str, &'static str)] {
+    EN
+}
+
+pub fn t(key: &str) -> &str {
+    let lookup = |table: &'static [(&'static str, &'static str)]| {
+        table.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+    };
+    lookup(catalog(&language()))
+        .or_else(|| lookup(EN))
+        .unwrap_or(key)
+}
+
+/// Escapes text for use in element content and in single- or double-quoted
+/// attribute values.
+pub fn html_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Makes an untrusted log line (backend output may echo model text) safe to
+/// show: drops ANSI escape sequences (colors, cursor movement, OSC titles and
+/// hyperlinks) and every other control character except tab. The result
+/// still needs `html_escape` before it goes into markup.
+pub fn sanitize_log_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{1b}' => match chars.next() {
+                // CSI: parameters and intermediates up to a final byte
+                Some('[') => skip_csi(&mut chars),
+                // OSC, DCS, SOS, PM, APC: a string up to BEL or ST
+                Some(']' | 'P' | 'X' | '^' | '_') => skip_string(&mut chars),
+                // Two-character sequences such as ESC 7 / ESC c
+                _ => {}
+            },
+            '\u{9b}' => skip_csi(&mut chars),
+            '\u{9d}' | '\u{90}' | '\u{98}' | '\u{9e}' | '\u{9f}' => skip_string(&mut chars),
+            '\t' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn skip_csi(chars: &mut std::iter::Peekable<std::str::Chars>) {
+    while let Some(&c) = chars.peek() {
+        match c {
+            // Parameter and intermediate bytes
+            '\u{20}'..='\u{3f}' => {
+                chars.next();
+            }
+            // Final byte
+            '\u{40}'..='\u{7e}' => {
+                chars.next();
+                break;
+            }
+            // Malformed; keep whatever follows
+            _ => break,
+        }
+    }
+}
+
+fn skip_string(chars: &mut std::iter::Peekable<std::str::Chars>) {
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{07}' | '\u{9c}' => break,
+            '\u{1b}' if chars.peek() == Some(&'\\') => {
+                chars.next();
+                break;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn unescape(s: &str) -> String {
+        s.replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&#39;", "'")
+            .replace("&amp;", "&")
+    }
+
+    #[test]
+    fn strips_ansi_sequences() {
+        assert_eq!(
+            sanitize_log_line("\u{1b}[1;31merror\u{1b}[0m: failed"),
+            "error: failed"
+        );
+        assert_eq!(
+            sanitize_log_line("\u{1b}]8;;https://x.test\u{7}link\u{1b}]8;;\u{1b}\\ done"),
+            "link done"
+        );
+        assert_eq!(sanitize_log_line("a\rb\u{0}c\td\u{7f}"), "abc\td");
+    }
+
+    #[test]
+    fn escapes_single_quotes() {
+        assert_eq!(
+            html_escape(r#"<a href='x' title="y">&</a>"#),
+            "&lt;a href=&#39;x&#39; title=&quot;y&quot;&gt;&amp;&lt;/a&gt;"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn escaped_text_has_no_markup(s in any::<String>()) {
+            let escaped = html_escape(&s);
+            prop_assert!(!escaped.contains(['<', '>', '"', '\'']));
+            for (i, _) in escaped.match_indices('&') {
+                let rest = &escaped[i..];
+                prop_assert!(
+                    ["&amp;", "&lt;", "&gt;", "&quot;", "&#39;"]
+                        .iter()
+                        .any(|entity| rest.starts_with(entity))
+                );
+            }
+        }
+
+        #[test]
+        fn escaping_round_trips(s in any::<String>()) {
+            prop_assert_eq!(unescape(&html_escape(&s)), s);
+        }
+
+        #[test]
+        fn sanitized_lines_have_no_controls(s in any::<String>()) {
+            let clean = sanitize_log_line(&s);
+            prop_assert!(!clean.chars().any(|c| c.is_control() && c != '\t'));
+        }
+
+        #[test]
+        fn sanitizing_is_idempotent(s in any::<String>()) {
+            let clean = sanitize_log_line(&s);
+            prop_assert_eq!(sanitize_log_line(&clean), clean);
+        }
+
+        #[test]
+        fn printable_text_is_unchanged(s in "[^\\p{Cc}]*") {
+            prop_assert_eq!(sanitize_log_line(&s), s);
+        }
+
+        #[test]
+        fn text_around_escapes_survives(
+            before in "[a-z ]{0,16}",
+            params in "[0-9;]{0,8}",
+            after in "[a-z ]{0,16}",
+        ) {
+            let line = format!("{}\u{1b}[{}m{}", before, params, after);
+            prop_assert_eq!(sanitize_log_line(&line), format!("{}{}", before, after));
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::{pages, template};
+
+pub const TERMINAL_LABEL: &str = "terminal";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalConfig {
+    /// The maintenance terminal is opt-in; it grants a full shell.
+    pub enabled: bool,
+}
+
+struct TerminalSession {
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    child: Box<dyn Child + Send + Sync>,
+}
+
+#[derive(Default)]
+pub struct TerminalState {
+    session: Mutex<Option<TerminalSession>>,
+}
+
+impl TerminalState {
+    /// Starts the user's default shell in a PTY rooted at `cwd` with the
+    /// backend's environment applied on top of the inherited one.
+    pub fn start(
+        &self,
+        app: &AppHandle,
+        cwd: PathBuf,
+        env: &BTreeMap<String, String>,
+    ) -> Result<(), String> {
+        let mut session = self.session.lock().unwrap();
+        if session.is_some() {
+            return Ok(());
+        }
+
+        let pair = native_pty_system()
+            .openpty(PtySize {
+                rows: 30,
+                cols: 100,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(|e| format!("Failed to open PTY: {}", e))?;
+
+        let mut cmd = CommandBuilder::new_default_prog();
+        cmd.cwd(&cwd);
+        for (key, value) in env {
+            cmd.env(key, value);
+        }
+
+        let child = pair
+            .slave
+            .spawn_command(cmd)
+            .map_err(|e| format!("Failed to start shell: {}", e))?;
+        let mut reader = pair
+            .master
+            .try_clone_reader()
+            .map_err(|e| format!("Failed to read PTY: {}", e))?;
+        let writer = pair
+            .master
+            .take_writer()
+            .map_err(|e| format!("Failed to write PTY: {}", e))?;
+
+        println!("[tauri] Terminal session started in {}", cwd.display());
+
+        let app = app.clone();
+        thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        let chunk = String::from_utf8_lossy(&buf[..n]);
+                        if let Some(window) = app.get_webview_window(TERMINAL_LABEL) {
+                            let js = format!(
+                                "window.__termWrite && window.__termWrite({});",
+                                serde_json::to_string(&chunk).unwrap()
+                            );
+                            let _ = window.eval(&js);
+                        }
+                    }
+                }
+            }
+            println!("[tauri] Terminal session ended");
+        });
+
+        *session = Some(TerminalSession {
+            master: pair.master,
+            writer,
+            child,
+        });
+        Ok(())
+    }
+
+    pub fn write(&self, data: &str) -> Result<(), String> {
+        let mut session = self.session.lock().unwrap();
+        let session = session
+            .as_mut()
+            .ok_or_else(|| "No terminal session".to_string())?;
+        session
+            .writer
+            .write_all(data.as_bytes())
+            .and_then(|_| session.writer.flush())
+            .map_err(|e| format!("Failed to write to terminal: {}", e))
+    }
+
+    pub fn resize(&self, cols: u16, rows: u16) -> Result<(), String> {
+        let session = self.session.lock().unwrap();
+        let session = session
+            .as_ref()
+            .ok_or_else(|| "No terminal session".to_string())?;
+        session
+            .master
+            .resize(PtySize {
+                rows,
+                cols,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(|e| format!("Failed to resize terminal: {}", e))
+    }
+
+    pub fn stop(&self) {
+        if let Some(mut session) = self.session.lock().unwrap().take() {
+            let _ = session.child.kill();
+            let _ = session.child.wait();
+        }
+    }
+}
+
+pub fn open_window(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(TERMINAL_LABEL) {
+        let _ = window.set_focus();
+        return Ok(());
+    }
+
+    WebviewWindowBuilder::new(
+        app,
+        TERMINAL_LABEL,
+        WebviewUrl::CustomProtocol(pages::page_url("terminal")),
+    )
+    .title("Local Agent Terminal")
+    .inner_size(860.0, 520.0)
+    .min_inner_size(480.0, 280.0)
+    .build()
+    .map(|_| ())
+    .map_err(|e| format!("Failed to open terminal window: {}", e))
+}
+
+const TERMINAL_CSS: &str = r#"
+  html, body { height: 100%; }
+  body {
+    background: var(--bg-inset);
+    font-family: var(--font-mono);
+    font-size: 0.8rem;
+    display: flex;
+    flex-direction: column;
+  }
+  #output {
+    flex: 1;
+    overflow-y: auto;
+    padding: 0.75rem;
+    white-space: pre-wrap;
+    word-break: break-all;
+    line-height: 1.45;
+  }
+  #input {
+    background: var(--bg);
+    color: var(--fg);
+    border: none;
+    border-top: 1px solid var(--border);
+    padding: 0.6rem 0.75rem;
+    font: inherit;
+    outline: none;
+    min-width: 0;
+  }
+"#;
+
+pub fn generate_terminal_html() -> String {
+    let body = template::fill(
+        r#"<body>
+  <div id="output" role="log" aria-live="polite"></div>
+  <input id="input" autofocus spellcheck="false" autocomplete="off"
+         aria-label="Command input"
+         placeholder="{{t:terminal_placeholder}}">
+  <script>
+    const output = document.getElementById('output');
+    const input = document.getElementById('input');
+    const ansi = /\x1b\[[0-9;?]*[ -\/]*[@-~]|\x1b\][^\x07]*\x07|\r(?!\n)/g;
+    const invoke = window.__invoke;
+
+    window.__termWrite = function (chunk) {
+      const atBottom = output.scrollTop + output.clientHeight >= output.scrollHeight - 4;
+      output.textContent += chunk.replace(ansi, '');
+      if (output.textContent.length > 200000) {
+        output.textContent = output.textContent.slice(-150000);
+      }
+      if (atBottom) output.scrollTop = output.scrollHeight;
+    };
+
+    input.addEventListener('keydown', (e) => {
+      if (e.key === 'Enter') {
+        invoke('terminal_write', { data: input.value + '\r' });
+        input.value = '';
+      } else if (e.key === 'c' && e.ctrlKey) {
+        invoke('terminal_write', { data: '\x03' });
+        e.preventDefault();
+      }
+    });
+
+    // Measure a character cell so the PTY size follows zoom and text scaling
+    function fit() {
+      const probe = document.createElement('span');
+      probe.textContent = 'M';
+      output.appendChild(probe);
+      const cell = probe.getBoundingClientRect();
+      probe.remove();
+      const cols = Math.max(20, Math.floor(output.clientWidth / cell.width));
+      const rows = Math.max(5, Math.floor(output.clientHeight / cell.height));
+      invoke('terminal_resize', { cols, rows });
+    }
+    window.addEventListener('resize', fit);
+    invoke('terminal_start').then(fit);
+  </script>
+</body>"#,
+        &[],
+    );
+    template::render("Local Agent Terminal", TERMINAL_CSS, &body)
+}
@@ -0,0 +1,89 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Header carrying the trace id from the shell/frontend to the backend.
+pub const TRACE_HEADER: &str = "x-trace-id";
+const MAX_TRACES: usize = 200;
+const MAX_EVENTS_PER_TRACE: usize = 500;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceEvent {
+    pub at: String,
+    pub source: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Trace {
+    pub id: String,
+    pub action: String,
+    pub started_at: String,
+    pub events: Vec<TraceEvent>,
+}
+
+/// Bounded store of recent traces. Shell-side steps are recorded directly;
+/// backend steps arrive as `[trace <id>] ...` lines on the sidecar's stdout.
+#[derive(Default)]
+pub struct TraceStore {
+    traces: Mutex<VecDeque<Trace>>,
+}
+
+fn now() -> String {
+    chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false)
+}
+
+impl TraceStore {
+    pub fn start(&self, action: &str) -> String {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        println!("[tauri] [trace {}] start {}", id, action);
+
+        let mut traces = self.traces.lock().unwrap();
+        if traces.len() >= MAX_TRACES {
+            traces.pop_front();
+        }
+        traces.push_back(Trace {
+            id: id.clone(),
+            action: action.to_string(),
+            started_at: now(),
+            events: Vec::new(),
+        });
+        id
+    }
+
+    pub fn record(&self, id: &str, source: &str, message: &str) {
+        if source == "shell" {
+            println!("[tauri] [trace {}] {}", id, message);
+        }
+
+        let mut traces = self.traces.lock().unwrap();
+        if let Some(trace) = traces.iter_mut().rev().find(|t| t.id == id) {
+            if trace.events.len() < MAX_EVENTS_PER_TRACE {
+                trace.events.push(TraceEvent {
+                    at: now(),
+                    source: source.to_string(),
+                    message: message.to_string(),
+                });
+            }
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<Trace> {
+        self.traces
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|t| t.id == id)
+            .cloned()
+    }
+
+    /// Picks up correlated backend log lines of the form `[trace <id>] message`.
+    pub fn ingest_backend_line(&self, line: &str) {
+        let Some(rest) = line.trim_start().strip_prefix("[trace ") else {
+            return;
+        };
+        if let Some((id, message)) = rest.split_once(']') {
+            self.record(id.trim(), "backend", message.trim());
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Manager};
+
+use crate::state::BackendState;
+
+/// The "Keep Running in Background" item, kept in step with the setting.
+pub struct BackgroundToggle(pub CheckMenuItem<tauri::Wry>);
+
+pub fn create_tray(app: &AppHandle, run_in_background: bool) -> tauri::Result<()> {
+    let show_window =
+        MenuItem::with_id(app, "show_window", "Open Local Agent", true, None::<&str>)?;
+    let view_logs = MenuItem::with_id(app, "open_log_viewer", "View Logs", true, None::<&str>)?;
+    let open_logs = MenuItem::with_id(
+        app,
+        "open_logs_folder",
+        "Open Logs Folder",
+        true,
+        None::<&str>,
+    )?;
+    let open_log_file =
+        MenuItem::with_id(app, "open_log_file", "Open Log File", true, None::<&str>)?;
+    let background = CheckMenuItem::with_id(
+        app,
+        "run_in_background",
+        "Keep Running in Background",
+        true,
+        run_in_background,
+        None::<&str>,
+    )?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+
+    let menu = Menu::with_items(
+        app,
+        &[
+            &show_window,
+            &view_logs,
+            &open_logs,
+            &open_log_file,
+            &separator,
+            &background,
+            &quit,
+        ],
+    )?;
+
+    // Clicks go through menu::handle_event, which serves the app menu too
+    let mut builder = TrayIconBuilder::with_id("main")
+        .tooltip("Local Agent")
+        .menu(&menu);
+
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+
+    builder.build(app)?;
+    app.manage(BackgroundToggle(background));
+    Ok(())
+}
+
+/// Shows the backend's state in the tray tooltip.
+pub fn set_status(app: &AppHandle, state: BackendState) {
+    if let Some(tray) = app.tray_by_id("main") {
+        let _ = tray.set_tooltip(Some(format!("Local Agent — backend {}", state.label())));
+    }
+}
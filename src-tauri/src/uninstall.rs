@@ -0,0 +1,339 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::autostart;
+use crate::config::ShellConfig;
+use crate::sidecar::SidecarManager;
+use crate::{paths, profiles, secrets};
+
+/// Bundle identifier from tauri.conf.json; used for OS registrations and
+/// keychain entries.
+pub const APP_IDENTIFIER: &str = "com.local-agent.app";
+pub const URL_SCHEME: &str = "localagent";
+
+/// Which categories `prepare_uninstall` should clean up. Services are always
+/// stopped first; everything else is opt-in.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct UninstallOptions {
+    /// Directory to copy user data into before anything is deleted.
+    pub backup_dir: Option<PathBuf>,
+    pub autostart: bool,
+    pub url_schemes: bool,
+    pub secrets: bool,
+    pub logs: bool,
+    pub data: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UninstallStep {
+    pub category: &'static str,
+    pub ok: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UninstallReport {
+    pub steps: Vec<UninstallStep>,
+    pub bytes_removed: u64,
+    /// Folders the user chose for their data, left for them to remove.
+    pub kept: Vec<PathBuf>,
+}
+
+/// What the `data` option removes and what it leaves, for the
+/// confirmation.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UninstallPlan {
+    /// Directories the app created and owns.
+    pub removed: Vec<PathBuf>,
+    /// Folders the user chose (a `data_dir`, or `chats` in their backend
+    /// checkout). They can hold anything, so they are never deleted.
+    pub kept: Vec<PathBuf>,
+}
+
+pub fn plan(config: &ShellConfig, backend_root: Option<&Path>) -> UninstallPlan {
+    let mut removed = vec![paths::config_dir(), profiles::data_root()];
+    removed.extend(
+        config
+            .profiles
+            .keys()
+            .map(|p| profiles::derived_data_dir(p)),
+    );
+    removed.extend(paths::portable_data_dir());
+
+    let chosen = config
+        .data_dir
+        .iter()
+        .cloned()
+        .chain(
+            config
+                .profiles
+                .values()
+                .filter_map(|overrides| overrides.get("data_dir")?.as_str())
+                .map(PathBuf::from),
+        )
+        .chain(backend_root.map(|root| root.join("chats")));
+    let mut kept: Vec<PathBuf> = Vec::new();
+    for dir in chosen {
+        // Inside a directory the app owns, it goes with that directory
+        let owned = removed.iter().any(|owned| dir.starts_with(owned));
+        if dir.exists() && !owned && !kept.contains(&dir) {
+            kept.push(dir);
+        }
+    }
+    UninstallPlan { removed, kept }
+}
+
+impl UninstallReport {
+    fn push(&mut self, category: &'static str, result: Result<String, String>) {
+        let (ok, detail) = match result {
+            Ok(detail) => (true, detail),
+            Err(detail) => (false, detail),
+        };
+        println!(
+            "[tauri] Uninstall {}: {}{}",
+            category,
+            if ok { "" } else { "failed: " },
+            detail
+        );
+        self.steps.push(UninstallStep {
+            category,
+            ok,
+            detail,
+        });
+    }
+}
+
+/// Runs the selected cleanup steps; `plan.removed` is what the `data`
+/// option deletes. Services must already be stopped.
+pub fn run(
+    options: &UninstallOptions,
+    plan: &UninstallPlan,
+    config: &mut ShellConfig,
+) -> UninstallReport {
+    let mut report = UninstallReport {
+        kept: plan.kept.clone(),
+        ..UninstallReport::default()
+    };
+
+    if let Some(ref backup_dir) = options.backup_dir {
+        let result = export_backup(backup_dir, &plan.removed);
+        let failed = result.is_err();
+        report.push("backup", result);
+        if failed {
+            // Never delete data that could not be backed up.
+            return report;
+        }
+    }
+    if options.autostart {
+        report.push("autostart", remove_autostart());
+    }
+    if options.url_schemes {
+        report.push("url_schemes", remove_url_schemes());
+    }
+    if options.secrets {
+        report.push("secrets", remove_secrets(config));
+    }
+    if options.logs {
+        let result = remove_logs().map(|bytes| {
+            report.bytes_removed += bytes;
+            format!("Removed {} of logs", format_bytes(bytes))
+        });
+        report.push("logs", result);
+    }
+    if options.data {
+        let result = remove_data(&plan.removed).map(|bytes| {
+            report.bytes_removed += bytes;
+            match plan.kept.len() {
+                0 => format!("Removed {} of data", format_bytes(bytes)),
+                kept => format!(
+                    "Removed {} of data; kept {} folder(s) you chose",
+                    format_bytes(bytes),
+                    kept
+                ),
+            }
+        });
+        report.push("data", result);
+    }
+
+    report
+}
+
+fn export_backup(backup_dir: &Path, data_dirs: &[PathBuf]) -> Result<String, String> {
+    let target = backup_dir.join(format!(
+        "local-agent-backup-{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    for dir in data_dirs.iter().filter(|d| d.exists()) {
+        let name = dir
+            .file_name()
+            .map(|n| n.to_os_string())
+            .unwrap_or_else(|| "data".into());
+        copy_dir(dir, &target.join(name))?;
+    }
+    Ok(format!("Exported data to {}", target.display()))
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), String> {
+    fs::create_dir_all(to).map_err(|e| format!("Failed to create {}: {}", to.display(), e))?;
+    let entries =
+        fs::read_dir(from).map_err(|e| format!("Failed to read {}: {}", from.display(), e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let dest = to.join(entry.file_name());
+        if path.is_dir() {
+            copy_dir(&path, &dest)?;
+        } else {
+            fs::copy(&path, &dest)
+                .map_err(|e| format!("Failed to copy {}: {}", path.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+fn remove_autostart() -> Result<String, String> {
+    autostart::disable().map(|removed| summarize(removed, "No autostart entries found"))
+}
+
+fn remove_url_schemes() -> Result<String, String> {
+    let mut removed = Vec::new();
+    if cfg!(windows) {
+        let output = Command::new("reg")
+            .args([
+                "delete",
+                &format!(r"HKCU\Software\Classes\{}", URL_SCHEME),
+                "/f",
+            ])
+            .output()
+            .map_err(|e| format!("Failed to run reg: {}", e))?;
+        if output.status.success() {
+            removed.push(format!("{}:// handler", URL_SCHEME));
+        }
+    } else if cfg!(target_os = "linux") {
+        if let Some(data) = dirs::data_dir() {
+            // The deep-link plugin names the handler after the executable
+            let exe_name = std::env::current_exe()
+                .ok()
+                .and_then(|exe| exe.file_name().map(|n| n.to_string_lossy().into_owned()));
+            for name in std::iter::once(URL_SCHEME.to_string()).chain(exe_name) {
+                let handler = data
+                    .join("applications")
+                    .join(format!("{}-handler.desktop", name));
+                if remove_file_if_exists(&handler)? {
+                    removed.push(handler.display().to_string());
+                }
+            }
+        }
+    }
+    // On macOS the scheme is declared in the app bundle's Info.plist and is
+    // dropped by Launch Services when the bundle is deleted.
+    Ok(summarize(removed, "No URL scheme registrations found"))
+}
+
+fn remove_secrets(config: &mut ShellConfig) -> Result<String, String> {
+    let output = if cfg!(target_os = "macos") {
+        Command::new("security")
+            .args(["delete-generic-password", "-s", APP_IDENTIFIER])
+            .output()
+    } else if cfg!(windows) {
+        Command::new("cmdkey")
+            .arg(format!("/delete:{}", APP_IDENTIFIER))
+            .output()
+    } else {
+        Command::new("secret-tool")
+            .args(["clear", "service", APP_IDENTIFIER])
+            .output()
+    };
+    let removed = output.map(|o| o.status.success()).unwrap_or(false);
+    let indexed = secrets::delete_all(config);
+
+    // Paired-device tokens live in the shell config; revoke them even when
+    // the data directory itself is kept.
+    let devices = config.remote.devices.len();
+    if devices > 0 || indexed > 0 {
+        config.remote.devices.clear();
+        config.save()?;
+    }
+
+    Ok(format!(
+        "{}; revoked {} paired device(s)",
+        if removed || indexed > 0 {
+            "Removed keychain entries"
+        } else {
+            "No keychain entries found"
+        },
+        devices
+    ))
+}
+
+/// Deletes only this app's log files; the log directory is shared with
+/// other applications.
+fn remove_logs() -> Result<u64, String> {
+    let dir = SidecarManager::resolve_log_dir();
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(0),
+    };
+    let mut bytes = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with("local-agent") && name.ends_with(".log") {
+            bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+            fs::remove_file(entry.path())
+                .map_err(|e| format!("Failed to remove {}: {}", name, e))?;
+        }
+    }
+    Ok(bytes)
+}
+
+fn remove_data(data_dirs: &[PathBuf]) -> Result<u64, String> {
+    let mut bytes = 0;
+    for dir in data_dirs.iter().filter(|d| d.exists()) {
+        bytes += dir_size(dir);
+        fs::remove_dir_all(dir)
+            .map_err(|e| format!("Failed to remove {}: {}", dir.display(), e))?;
+    }
+    Ok(bytes)
+}
+
+pub fn dir_size(path: &Path) -> u64 {
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| match entry.metadata() {
+                    Ok(m) if m.is_dir() => dir_size(&entry.path()),
+                    Ok(m) => m.len(),
+                    Err(_) => 0,
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+pub fn remove_file_if_exists(path: &Path) -> Result<bool, String> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(format!("Failed to remove {}: {}", path.display(), e)),
+    }
+}
+
+fn summarize(removed: Vec<String>, none: &str) -> String {
+    if removed.is_empty() {
+        none.to_string()
+    } else {
+        format!("Removed {}", removed.join(", "))
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const MB: u64 = 1024 * 1024;
+    if bytes >= MB * 1024 {
+        format!("{:.1} GB", bytes as f64 / (MB * 1024) as f64)
+    } else {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    }
+}
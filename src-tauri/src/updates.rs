@@ -0,0 +1,82 @@
+//! "Check for Updates", against the GitHub repository (which "Report
+//! Issue" files into too, see `issue`). Releases are compared by their `vX.Y.Z` tag; there is no
+//! in-place updater, so an available update opens its release page.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::proxy;
+
+pub const REPOSITORY_URL: &str = "https://github.com/coltonbatts/Local_agent-";
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/coltonbatts/Local_agent-/releases/latest";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+    pub current: String,
+    /// `None` when the repository has no published release yet.
+    pub latest: Option<String>,
+    pub available: bool,
+    pub url: String,
+}
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    html_url: String,
+}
+
+pub async fn check(current: &str) -> Result<UpdateInfo, String> {
+    let client = proxy::client_builder()
+        .timeout(Duration::from_secs(10))
+        // GitHub rejects API requests without a user agent
+        .user_agent(format!("local-agent/{}", current))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let resp = client
+        .get(LATEST_RELEASE_URL)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to check for updates: {}", e))?;
+
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(UpdateInfo {
+            current: current.to_string(),
+            latest: None,
+            available: false,
+            url: format!("{}/releases", REPOSITORY_URL),
+        });
+    }
+    let release: Release = resp
+        .error_for_status()
+        .map_err(|e| format!("Failed to check for updates: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to read release info: {}", e))?;
+
+    let latest = release.tag_name.trim_start_matches('v').to_string();
+    Ok(UpdateInfo {
+        available: is_newer(&latest, current),
+        current: current.to_string(),
+        latest: Some(latest),
+        url: release.html_url,
+    })
+}
+
+/// Compares `major.minor.patch`; pre-release suffixes are ignored.
+fn is_newer(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+    Some((
+        parts.next()??,
+        parts.next().unwrap_or(Some(0))?,
+        parts.next().unwrap_or(Some(0))?,
+    ))
+}
@@ -0,0 +1,104 @@
+//! Backend upgrades without downtime: the new version starts on a spare
+//! port next to the running one, and only once it answers its health check
+//! do the registry and the app windows move over to it. The old backend is
+//! kept for a while afterwards, so `rollback_backend_upgrade` can move back
+//! to it at once; then it is stopped. A new version that never gets healthy
+//! is stopped and nothing else changes.
+//!
+//! The windows follow the backend to its new port, which reloads the app.
+//! Upgrades need TCP: over the socket bridge there is only one socket.
+
+use serde::{Deserialize, Serialize};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::sidecar::{SidecarManager, SidecarSpec};
+
+/// Ports after the configured one tried for the new backend.
+const PORT_RANGE: u16 = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpgradeConfig {
+    /// How long the previous backend is kept for a rollback.
+    pub retire_after_secs: u64,
+}
+
+impl Default for UpgradeConfig {
+    fn default() -> Self {
+        Self {
+            retire_after_secs: 60,
+        }
+    }
+}
+
+/// Where the new version runs from; unset fields keep the running
+/// backend's.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct UpgradeSource {
+    /// A project directory holding the new `server.js`.
+    pub project_root: Option<PathBuf>,
+    /// Or a standalone backend executable.
+    pub binary: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpgradeResult {
+    pub port: u16,
+    pub previous_port: Option<u16>,
+    /// Until when `rollback_backend_upgrade` can go back.
+    pub rollback_until: String,
+}
+
+/// The running backend's spec, pointed at `source` and moved to `port`.
+pub fn candidate_spec(current: &SidecarSpec, source: &UpgradeSource, port: u16) -> SidecarSpec {
+    let mut spec = current.clone();
+    if let Some(ref root) = source.project_root {
+        spec.cwd = Some(root.clone());
+    }
+    if let Some(ref binary) = source.binary {
+        spec.command = binary.to_string_lossy().into_owned();
+        spec.args = Vec::new();
+    }
+    // A configured probe still points at the old port
+    spec.health_probe = None;
+    spec.with_port(port)
+}
+
+/// A free port to start the new backend on: the configured one when the
+/// backend has moved off it, else the next free one after it.
+pub fn free_port(configured: u16, in_use: Option<u16>) -> Option<u16> {
+    (0..=PORT_RANGE)
+        .filter_map(|offset| configured.checked_add(offset))
+        .filter(|port| Some(*port) != in_use)
+        .find(|port| TcpListener::bind(("127.0.0.1", *port)).is_ok())
+}
+
+/// The backend an upgrade replaced, until it is retired.
+#[derive(Default)]
+pub struct Previous {
+    backend: Mutex<Option<Arc<SidecarManager>>>,
+}
+
+impl Previous {
+    pub fn keep(&self, backend: Arc<SidecarManager>) -> Option<Arc<SidecarManager>> {
+        self.backend.lock().unwrap().replace(backend)
+    }
+
+    pub fn take(&self) -> Option<Arc<SidecarManager>> {
+        self.backend.lock().unwrap().take()
+    }
+
+    /// Takes the kept backend if it is still `backend`, so a timer doesn't
+    /// retire one kept by a later upgrade.
+    pub fn take_if(&self, backend: &Arc<SidecarManager>) -> Option<Arc<SidecarManager>> {
+        let mut kept = self.backend.lock().unwrap();
+        if kept.as_ref().is_some_and(|kept| Arc::ptr_eq(kept, backend)) {
+            kept.take()
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use chrono::{DateTime, Duration, Local, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WakeConfig {
+    pub enabled: bool,
+    /// Local times ("HH:MM") at which scheduled agent jobs run each day.
+    pub job_times: Vec<String>,
+    /// How long before a job the machine should be woken.
+    pub lead_minutes: u32,
+}
+
+impl Default for WakeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            job_times: Vec::new(),
+            lead_minutes: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WakeCapability {
+    pub platform: &'static str,
+    pub supported: bool,
+    pub mechanism: Option<&'static str>,
+    pub requires_elevation: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WakeStatus {
+    pub enabled: bool,
+    pub capability: WakeCapability,
+    pub next_wake: Option<String>,
+}
+
+pub fn capability() -> WakeCapability {
+    if cfg!(target_os = "macos") {
+        let available = std::path::Path::new("/usr/bin/pmset").exists();
+        WakeCapability {
+            platform: "macos",
+            supported: available,
+            mechanism: Some("pmset schedule wake"),
+            requires_elevation: true,
+            detail: if available {
+                "Wake events are scheduled with pmset, which requires administrator rights."
+                    .to_string()
+            } else {
+                "pmset was not found at /usr/bin/pmset.".to_string()
+            },
+        }
+    } else if cfg!(target_os = "windows") {
+        WakeCapability {
+            platform: "windows",
+            supported: true,
+            mechanism: Some("Task Scheduler (WakeToRun)"),
+            requires_elevation: false,
+            detail: "A one-shot scheduled task with WakeToRun is registered. \"Allow wake timers\" must be enabled in the power plan."
+                .to_string(),
+        }
+    } else if cfg!(target_os = "linux") {
+        let available = Command::new("rtcwake").arg("--version").output().is_ok();
+        WakeCapability {
+            platform: "linux",
+            supported: available,
+            mechanism: Some("rtcwake"),
+            requires_elevation: true,
+            detail: if available {
+                "The RTC alarm is set with rtcwake, which requires root.".to_string()
+            } else {
+                "rtcwake (util-linux) is not installed.".to_string()
+            },
+        }
+    } else {
+        WakeCapability {
+            platform: std::env::consts::OS,
+            supported: false,
+            mechanism: None,
+            requires_elevation: false,
+            detail: "Scheduled wake is not supported on this platform.".to_string(),
+        }
+    }
+}
+
+/// Returns the earliest upcoming wake time (job time minus lead) after `now`.
+pub fn next_wake_time(config: &WakeConfig, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let lead = Duration::minutes(config.lead_minutes as i64);
+
+    config
+        .job_times
+        .iter()
+        .filter_map(|t| match NaiveTime::parse_from_str(t.trim(), "%H:%M") {
+            Ok(time) => Some(time),
+            Err(_) => {
+                eprintln!("[tauri] Ignoring invalid wake job time: {:?}", t);
+                None
+            }
+        })
+        .filter_map(|time| {
+            let today = Local
+                .from_local_datetime(&now.date_naive().and_time(time))
+                .earliest()?
+                - lead;
+            if today > now {
+                Some(today)
+            } else {
+                Some(today + Duration::days(1))
+            }
+        })
+        .min()
+}
+
+pub fn schedule_wake(at: DateTime<Local>) -> Result<(), String> {
+    let output = if cfg!(target_os = "macos") {
+        Command::new("pmset")
+            .args([
+                "schedule",
+                "wake",
+                &at.format("%m/%d/%y %H:%M:%S").to_string(),
+            ])
+            .output()
+    } else if cfg!(target_os = "windows") {
+        let script = format!(
+            "$a = New-ScheduledTaskAction -Execute 'cmd.exe' -Argument '/c exit'; \
+             $t = New-ScheduledTaskTrigger -Once -At '{}'; \
+             $s = New-ScheduledTaskSettingsSet -WakeToRun; \
+             Register-ScheduledTask -TaskName 'LocalAgentWake' -Action $a -Trigger $t -Settings $s -Force | Out-Null",
+            at.format("%Y-%m-%dT%H:%M:%S")
+        );
+        Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .output()
+    } else if cfg!(target_os = "linux") {
+        Command::new("rtcwake")
+            .args(["-m", "no", "-t", &at.timestamp().to_string()])
+            .output()
+    } else {
+        return Err("Scheduled wake is not supported on this platform".to_string());
+    };
+
+    let output = output.map_err(|e| format!("Failed to run wake scheduler: {}", e))?;
+    if output.status.success() {
+        println!("[tauri] Scheduled system wake at {}", at.to_rfc3339());
+        Ok(())
+    } else {
+        Err(format!(
+            "Wake scheduler exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+pub fn status(config: &WakeConfig) -> WakeStatus {
+    WakeStatus {
+        enabled: config.enabled,
+        capability: capability(),
+        next_wake: if config.enabled {
+            next_wake_time(config, Local::now()).map(|t| t.to_rfc3339())
+        } else {
+            None
+        },
+    }
+}
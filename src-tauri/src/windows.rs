@@ -0,0 +1,116 @@
+//! App windows: the main window plus any chat windows opened with
+//! `open_window`. They all load the frontend and share one backend, so the
+//! backend only stops once the last of them is gone.
+
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::{guard_navigation, navigation, paths};
+
+pub const MAIN_LABEL: &str = "main";
+/// Chat windows are labelled `chat-<name>`, which the default capability
+/// matches.
+pub const CHAT_PREFIX: &str = "chat-";
+
+/// Whether `label` is one of the windows showing the frontend.
+pub fn is_app_window(label: &str) -> bool {
+    label == MAIN_LABEL || label.starts_with(CHAT_PREFIX)
+}
+
+/// App windows that currently exist, hidden ones included.
+#[derive(Default)]
+pub struct WindowTracker {
+    open: Mutex<BTreeSet<String>>,
+}
+
+impl WindowTracker {
+    pub fn opened(&self, label: &str) {
+        self.open.lock().unwrap().insert(label.to_string());
+    }
+
+    /// Forgets `label` and returns how many app windows remain.
+    pub fn closed(&self, label: &str) -> usize {
+        let mut open = self.open.lock().unwrap();
+        open.remove(label);
+        open.len()
+    }
+
+    /// App windows other than `label`.
+    pub fn others(&self, label: &str) -> usize {
+        self.open
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|l| *l != label)
+            .count()
+    }
+}
+
+/// `chat-<name>` for a name of letters, digits, `-` and `_`; a name that
+/// already has the prefix is kept as is.
+pub fn chat_label(name: &str) -> Result<String, String> {
+    let name = name.strip_prefix(CHAT_PREFIX).unwrap_or(name);
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!(
+            "Invalid window label {:?}: use letters, digits, '-' and '_'",
+            name
+        ));
+    }
+    Ok(format!("{}{}", CHAT_PREFIX, name))
+}
+
+/// Where a chat window goes: `route` is a frontend path like `/chat/42`,
+/// resolved against `app_url`, or a full URL on the same origin.
+pub fn resolve_route(app_url: &str, route: &str) -> Result<tauri::Url, String> {
+    let base: tauri::Url = app_url
+        .parse()
+        .map_err(|e| format!("Invalid app URL {}: {}", app_url, e))?;
+    let url = base
+        .join(route)
+        .map_err(|e| format!("Invalid route {:?}: {}", route, e))?;
+    if !navigation::same_origin(&url, &base) {
+        return Err(format!(
+            "{} is not served by Local Agent; windows can only show its own pages",
+            url
+        ));
+    }
+    Ok(url)
+}
+
+/// Opens (or focuses) the chat window `label` on `url` and returns its full
+/// label.
+pub fn open(
+    app: &AppHandle,
+    tracker: &WindowTracker,
+    label: &str,
+    url: tauri::Url,
+) -> Result<String, String> {
+    let label = chat_label(label)?;
+    if let Some(window) = app.get_webview_window(&label) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+        return Ok(label);
+    }
+
+    let handle = app.clone();
+    let mut builder = WebviewWindowBuilder::new(app, &label, WebviewUrl::External(url))
+        .on_navigation(move |url| guard_navigation(&handle, url))
+        .title(app.package_info().name.clone())
+        .inner_size(900.0, 700.0)
+        .min_inner_size(480.0, 400.0);
+    // Same storage as the main window, or chats wouldn't show up here
+    if let Some(dir) = paths::portable_webview_dir() {
+        builder = builder.data_directory(dir);
+    }
+    builder
+        .build()
+        .map_err(|e| format!("Failed to open window {}: {}", label, e))?;
+    tracker.opened(&label);
+    Ok(label)
+}
@@ -0,0 +1,497 @@
+//! Project folders the agent may work in. The user grants a folder by
+//! choosing it in the OS folder picker; granted folders are saved in the
+//! config and every read or watch is checked against them after resolving
+//! `..` and symlinks, so the UI and the backend only see what was chosen.
+//! `watch_directory` watches a folder with the OS's change notifications
+//! (inotify, FSEvents, ReadDirectoryChangesW) and sends debounced
+//! `workspace-changed` events to the window that asked, until it calls
+//! `unwatch_directory` or closes.
+
+use notify::event::{EventKind, ModifyKind, RenameMode};
+use notify::{Event, RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, RecommendedCache};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::template;
+
+pub const EVENT: &str = "workspace-changed";
+/// Larger files are refused by `read_workspace_file`.
+const MAX_READ_BYTES: u64 = 5 * 1024 * 1024;
+/// Changes are batched until a folder has been quiet this long.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+/// Changes under these aren't reported; they change constantly and are
+/// rarely what the agent is asked about.
+const SKIPPED_DIRS: &[&str] = &[".git", "node_modules", "target"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceConfig {
+    /// Folders the user chose; everything under them may be read.
+    pub folders: Vec<PathBuf>,
+}
+
+impl WorkspaceConfig {
+    /// Adds `dir`, resolved; false when it was already granted.
+    pub fn grant(&mut self, dir: &Path) -> Result<bool, String> {
+        let dir = canonical(dir)?;
+        if self.folders.contains(&dir) {
+            return Ok(false);
+        }
+        self.folders.push(dir);
+        Ok(true)
+    }
+
+    /// Removes `dir`; false when it wasn't granted.
+    pub fn revoke(&mut self, dir: &Path) -> bool {
+        let before = self.folders.len();
+        self.folders
+            .retain(|folder| folder != dir && canonical(dir).ok().as_ref() != Some(folder));
+        self.folders.len() != before
+    }
+
+    /// `path` resolved, when it is inside a granted folder.
+    pub fn resolve(&self, path: &Path) -> Result<PathBuf, String> {
+        let resolved = canonical(path)?;
+        let granted = self
+            .folders
+            .iter()
+            .filter_map(|folder| canonical(folder).ok())
+            .any(|folder| resolved.starts_with(folder));
+        if !granted {
+            return Err(format!(
+                "{} is outside the folders Local Agent may use",
+                path.display()
+            ));
+        }
+        Ok(resolved)
+    }
+}
+
+fn canonical(path: &Path) -> Result<PathBuf, String> {
+    fs::canonicalize(path).map_err(|e| format!("Failed to resolve {}: {}", path.display(), e))
+}
+
+/// A granted file's text.
+pub fn read_file(config: &WorkspaceConfig, path: &Path) -> Result<String, String> {
+    let path = config.resolve(path)?;
+    let metadata =
+        fs::metadata(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if !metadata.is_file() {
+        return Err(format!("{} is not a file", path.display()));
+    }
+    if metadata.len() > MAX_READ_BYTES {
+        return Err(format!(
+            "{} is larger than {} MB",
+            path.display(),
+            MAX_READ_BYTES / 1024 / 1024
+        ));
+    }
+    let bytes = fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    String::from_utf8(bytes).map_err(|_| format!("{} is not a text file", path.display()))
+}
+
+/// Asks for a folder with the OS picker; `None` when the user cancels.
+/// Blocks until the picker closes.
+pub fn pick_directory() -> Result<Option<PathBuf>, String> {
+    let title = template::t("pick_workspace");
+    if cfg!(target_os = "macos") {
+        let script = format!(
+            "POSIX path of (choose folder with prompt {})",
+            serde_json::to_string(title).unwrap()
+        );
+        return run_picker("osascript", &["-e", &script])
+            .map_err(|e| format!("Failed to open the folder picker: {}", e));
+    }
+    if cfg!(windows) {
+        let script = format!(
+            "Add-Type -AssemblyName System.Windows.Forms; \
+             $dialog = New-Object System.Windows.Forms.FolderBrowserDialog; \
+             $dialog.Description = '{}'; \
+             if ($dialog.ShowDialog() -eq 'OK') {{ $dialog.SelectedPath }}",
+            title.replace('\'', "''")
+        );
+        return run_picker(
+            "powershell",
+            &["-NoProfile", "-NonInteractive", "-STA", "-Command", &script],
+        )
+        .map_err(|e| format!("Failed to open the folder picker: {}", e));
+    }
+    let title_arg = format!("--title={}", title);
+    run_picker("zenity", &["--file-selection", "--directory", &title_arg])
+        .or_else(|_| {
+            run_picker(
+                "kdialog",
+                &["--getexistingdirectory", ".", "--title", title],
+            )
+        })
+        .map_err(|_| "No folder picker found; install zenity or kdialog".to_string())
+}
+
+/// The path a picker printed; `None` when it was cancelled.
+fn run_picker(program: &str, args: &[&str]) -> std::io::Result<Option<PathBuf>> {
+    let output = Command::new(program).args(args).output()?;
+    let picked = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok((output.status.success() && !picked.is_empty()).then(|| PathBuf::from(picked)))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceChanged {
+    pub subscription: u64,
+    pub root: PathBuf,
+    pub created: Vec<PathBuf>,
+    pub modified: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+}
+
+struct Watch {
+    root: PathBuf,
+    /// Dropping it stops the OS watch.
+    _debouncer: Debouncer<RecommendedWatcher, RecommendedCache>,
+}
+
+/// Watched folders by window label.
+#[derive(Default)]
+pub struct Watches {
+    next: AtomicU64,
+    windows: Mutex<HashMap<String, HashMap<u64, Watch>>>,
+}
+
+impl Watches {
+    /// Starts watching `root` (already resolved) for `window`.
+    pub fn watch(&self, app: &AppHandle, window: &str, root: PathBuf) -> Result<u64, String> {
+        let subscription = self.next.fetch_add(1, Ordering::SeqCst) + 1;
+        let (app, label, watched) = (app.clone(), window.to_string(), root.clone());
+        let mut debouncer =
+            new_debouncer(
+                DEBOUNCE,
+                None,
+                move |result: DebounceEventResult| match result {
+                    Ok(events) => {
+                        let events: Vec<Event> = events.into_iter().map(|e| e.event).collect();
+                        if let Some(event) = changes(subscription, &watched, &events) {
+                            let _ = app.emit_to(label.as_str(), EVENT, event);
+                        }
+                    }
+                    Err(errors) => {
+                        for e in errors {
+                            eprintln!("[tauri] Watching {} failed: {}", watched.display(), e);
+                        }
+                    }
+                },
+            )
+            .map_err(|e| format!("Failed to watch {}: {}", root.display(), e))?;
+        debouncer
+            .watch(&root, RecursiveMode::Recursive)
+            .map_err(|e| format!("Failed to watch {}: {}", root.display(), e))?;
+        self.windows
+            .lock()
+            .unwrap()
+            .entry(window.to_string())
+            .or_default()
+            .insert(
+                subscription,
+                Watch {
+                    root,
+                    _debouncer: debouncer,
+                },
+            );
+        Ok(subscription)
+    }
+
+    /// Stops one of `window`'s watches; false when it has no such watch.
+    pub fn unwatch(&self, window: &str, subscription: u64) -> bool {
+        let mut windows = self.windows.lock().unwrap();
+        let Some(watches) = windows.get_mut(window) else {
+            return false;
+        };
+        let stopped = watches.remove(&subscription).is_some();
+        if watches.is_empty() {
+            windows.remove(window);
+        }
+        stopped
+    }
+
+    /// Stops every watch inside folders no longer granted.
+    pub fn retain_granted(&self, config: &WorkspaceConfig) {
+        for watches in self.windows.lock().unwrap().values_mut() {
+            watches.retain(|_, watch| config.resolve(&watch.root).is_ok());
+        }
+    }
+
+    /// Stops every watch `window` has, when it closes.
+    pub fn close_window(&self, window: &str) {
+        self.windows.lock().unwrap().remove(window);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Change {
+    Created,
+    Modified,
+    Removed,
+}
+
+/// What a batch of OS events did to the files under `root`, with each
+/// file's changes merged: created then written is created, created then
+/// removed didn't happen.
+fn changes(subscription: u64, root: &Path, events: &[Event]) -> Option<WorkspaceChanged> {
+    let mut files: BTreeMap<PathBuf, Change> = BTreeMap::new();
+    let mut record = |path: &Path, change: Change| {
+        let skipped = path.strip_prefix(root).is_ok_and(|relative| {
+            relative
+                .components()
+                .any(|part| SKIPPED_DIRS.iter().any(|name| part.as_os_str() == *name))
+        });
+        // Only files; a removed path can't be told apart any more
+        if skipped || (change != Change::Removed && path.is_dir()) {
+            return;
+        }
+        let merged = match (files.get(path).copied(), change) {
+            (Some(Change::Created), Change::Removed) => {
+                files.remove(path);
+                return;
+            }
+            (Some(Change::Created), _) => Change::Created,
+            (Some(Change::Removed), Change::Created) => Change::Modified,
+            (_, change) => change,
+        };
+        files.insert(path.to_path_buf(), merged);
+    };
+    for event in events {
+        match event.kind {
+            EventKind::Create(_) => event
+                .paths
+                .iter()
+                .for_each(|path| record(path, Change::Created)),
+            EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => event
+                .paths
+                .iter()
+                .for_each(|path| record(path, Change::Removed)),
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => event
+                .paths
+                .iter()
+                .for_each(|path| record(path, Change::Created)),
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+                if let [from, to] = event.paths.as_slice() {
+                    record(from, Change::Removed);
+                    record(to, Change::Created);
+                }
+            }
+            // Platforms that don't say which end of a rename this is
+            EventKind::Modify(ModifyKind::Name(_)) => event.paths.iter().for_each(|path| {
+                let change = if path.exists() {
+                    Change::Created
+                } else {
+                    Change::Removed
+                };
+                record(path, change)
+            }),
+            EventKind::Modify(_) | EventKind::Any => event
+                .paths
+                .iter()
+                .for_each(|path| record(path, Change::Modified)),
+            EventKind::Access(_) | EventKind::Other => {}
+        }
+    }
+
+    let list = |wanted: Change| -> Vec<PathBuf> {
+        files
+            .iter()
+            .filter(|(_, change)| **change == wanted)
+            .map(|(path, _)| path.clone())
+            .collect()
+    };
+    let (created, modified, removed) = (
+        list(Change::Created),
+        list(Change::Modified),
+        list(Change::Removed),
+    );
+    if created.is_empty() && modified.is_empty() && removed.is_empty() {
+        return None;
+    }
+    Some(WorkspaceChanged {
+        subscription,
+        root: root.to_path_buf(),
+        created,
+        modified,
+        removed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, DataChange, RemoveKind};
+
+    /// A scratch directory with a granted folder and a secret file beside it.
+    struct Scratch {
+        root: PathBuf,
+        granted: PathBuf,
+        secret: PathBuf,
+        config: WorkspaceConfig,
+    }
+
+    impl Scratch {
+        fn new() -> Self {
+            let root = canonical(&std::env::temp_dir())
+                .unwrap()
+                .join(format!("local-agent-workspace-{}", uuid::Uuid::new_v4()));
+            let granted = root.join("granted");
+            fs::create_dir_all(granted.join("sub")).unwrap();
+            fs::write(granted.join("notes.txt"), "notes").unwrap();
+            let secret = root.join("secret.txt");
+            fs::write(&secret, "secret").unwrap();
+            let mut config = WorkspaceConfig::default();
+            assert!(config.grant(&granted).unwrap());
+            Self {
+                root,
+                granted,
+                secret,
+                config,
+            }
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    #[test]
+    fn resolves_files_inside_granted_folders() {
+        let scratch = Scratch::new();
+        let notes = scratch.granted.join("notes.txt");
+        assert_eq!(scratch.config.resolve(&notes).unwrap(), notes);
+        let roundabout = scratch.granted.join("sub").join("..").join("notes.txt");
+        assert_eq!(scratch.config.resolve(&roundabout).unwrap(), notes);
+    }
+
+    #[test]
+    fn refuses_dot_dot_out_of_a_granted_folder() {
+        let scratch = Scratch::new();
+        let escape = scratch.granted.join("..").join("secret.txt");
+        assert!(scratch.config.resolve(&escape).is_err());
+        assert!(read_file(&scratch.config, &escape).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_symlinks_pointing_outside() {
+        let scratch = Scratch::new();
+        let link = scratch.granted.join("link.txt");
+        std::os::unix::fs::symlink(&scratch.secret, &link).unwrap();
+        assert!(scratch.config.resolve(&link).is_err());
+
+        let dir_link = scratch.granted.join("outside");
+        std::os::unix::fs::symlink(&scratch.root, &dir_link).unwrap();
+        assert!(scratch
+            .config
+            .resolve(&dir_link.join("secret.txt"))
+            .is_err());
+    }
+
+    #[test]
+    fn refuses_siblings_sharing_a_name_prefix() {
+        let scratch = Scratch::new();
+        let sibling = scratch.root.join("grantedbc");
+        fs::create_dir_all(&sibling).unwrap();
+        fs::write(sibling.join("file.txt"), "not granted").unwrap();
+        assert!(scratch.config.resolve(&sibling.join("file.txt")).is_err());
+        assert!(scratch.config.resolve(&sibling).is_err());
+    }
+
+    #[test]
+    fn refuses_folders_once_revoked() {
+        let mut scratch = Scratch::new();
+        let notes = scratch.granted.join("notes.txt");
+        // Revoking by a path that resolves to the granted folder
+        assert!(scratch
+            .config
+            .revoke(&scratch.granted.join("sub").join("..")));
+        assert!(scratch.config.resolve(&notes).is_err());
+        assert!(!scratch.config.revoke(&scratch.granted));
+    }
+
+    #[test]
+    fn refuses_missing_paths() {
+        let scratch = Scratch::new();
+        assert!(scratch
+            .config
+            .resolve(&scratch.granted.join("missing.txt"))
+            .is_err());
+    }
+
+    fn event(kind: EventKind, paths: &[&Path]) -> Event {
+        paths.iter().fold(Event::new(kind), |event, path| {
+            event.add_path(path.to_path_buf())
+        })
+    }
+
+    #[test]
+    fn merges_each_files_changes() {
+        let root = Path::new("/project");
+        let (a, b, c, d) = (
+            root.join("a.txt"),
+            root.join("b.txt"),
+            root.join("c.txt"),
+            root.join("d.txt"),
+        );
+        let written = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+        let events = [
+            // Written right after it was made
+            event(EventKind::Create(CreateKind::File), &[&a]),
+            event(written, &[&a]),
+            // A temporary file
+            event(EventKind::Create(CreateKind::File), &[&b]),
+            event(EventKind::Remove(RemoveKind::File), &[&b]),
+            // Replaced
+            event(EventKind::Remove(RemoveKind::File), &[&c]),
+            event(EventKind::Create(CreateKind::File), &[&c]),
+            event(written, &[&d]),
+        ];
+        let changed = changes(7, root, &events).unwrap();
+        assert_eq!(changed.subscription, 7);
+        assert_eq!(changed.created, vec![a]);
+        assert_eq!(changed.modified, vec![c, d]);
+        assert!(changed.removed.is_empty());
+    }
+
+    #[test]
+    fn renames_remove_one_path_and_create_another() {
+        let root = Path::new("/project");
+        let (from, to) = (root.join("old.txt"), root.join("new.txt"));
+        let rename = EventKind::Modify(ModifyKind::Name(RenameMode::Both));
+        let changed = changes(1, root, &[event(rename, &[&from, &to])]).unwrap();
+        assert_eq!(changed.created, vec![to]);
+        assert_eq!(changed.removed, vec![from]);
+    }
+
+    #[test]
+    fn ignores_skipped_folders_and_reads() {
+        let root = Path::new("/project");
+        let events = [
+            event(
+                EventKind::Create(CreateKind::File),
+                &[&root.join("node_modules").join("x").join("index.js")],
+            ),
+            event(
+                EventKind::Modify(ModifyKind::Any),
+                &[&root.join(".git").join("index")],
+            ),
+            event(
+                EventKind::Access(notify::event::AccessKind::Any),
+                &[&root.join("a.txt")],
+            ),
+        ];
+        assert!(changes(1, root, &events).is_none());
+    }
+}
@@ -0,0 +1,249 @@
+//! The sidecar manager against the stub backend in `support/mock_sidecar.rs`.
+//! Run with `cargo test --features mock-sidecar`.
+#![cfg(feature = "mock-sidecar")]
+
+use std::collections::BTreeMap;
+use std::net::TcpListener;
+use std::sync::{mpsc, Arc, Once};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use local_agent_lib::testing::{
+    poll_health, Backoff, LogBatcher, ProbeSpec, RestartPolicy, SidecarManager, SidecarRegistry,
+    SidecarSpec, StopOutcome,
+};
+
+const STUB: &str = env!("CARGO_BIN_EXE_mock-sidecar");
+
+/// Keeps the test logs next to the test binary instead of the user's.
+fn portable() {
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| std::env::set_var("LOCAL_AGENT_PORTABLE", "1"));
+}
+
+fn spec(name: &str, env: &[(&str, String)]) -> SidecarSpec {
+    portable();
+    SidecarSpec {
+        name: name.to_string(),
+        command: STUB.to_string(),
+        args: Vec::new(),
+        env: env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect::<BTreeMap<_, _>>(),
+        health_url: None,
+        log_file: Some(format!("mock-{}.log", name)),
+        shutdown_url: None,
+        shutdown_grace_ms: 2000,
+        stdin_control: false,
+        ..SidecarSpec::backend(std::env::temp_dir())
+    }
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind(("127.0.0.1", 0))
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+fn wait_until(timeout: Duration, mut check: impl FnMut() -> bool) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        if check() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    check()
+}
+
+fn health(port: u16, timeout_ms: u64) -> Result<(), String> {
+    let probe = ProbeSpec::http(&format!("http://127.0.0.1:{}/health", port));
+    tokio::runtime::Runtime::new()
+        .unwrap()
+        .block_on(poll_health(&probe, 50, timeout_ms, None))
+}
+
+#[test]
+fn spawn_with_retry_starts_the_stub() {
+    let sidecar = SidecarManager::new(spec("start", &[]));
+    sidecar.spawn_with_retry().unwrap();
+    assert!(sidecar.is_running());
+    assert!(sidecar.pid().is_some());
+    sidecar.shutdown();
+    assert!(!sidecar.is_running());
+}
+
+#[test]
+fn spawn_with_retry_gives_up_on_a_missing_binary() {
+    let spec = SidecarSpec {
+        command: format!("{}-missing", STUB),
+        ..spec("missing", &[])
+    };
+    let sidecar = SidecarManager::new(spec).with_backoff(Backoff {
+        initial_delay_ms: 10,
+        multiplier: 1.0,
+        max_delay_ms: 10,
+        jitter: 0.0,
+        max_attempts: Some(2),
+        max_elapsed_ms: None,
+    });
+    let error = sidecar.spawn_with_retry().unwrap_err();
+    assert!(
+        error.contains("failed to start after 2 attempts"),
+        "{}",
+        error
+    );
+    assert!(!sidecar.is_running());
+}
+
+#[test]
+fn output_is_piped_to_the_log_file_and_stream() {
+    let logs = Arc::new(LogBatcher::default());
+    let sidecar = SidecarManager::new(spec("logs", &[("MOCK_LOG_LINES", "500".to_string())]))
+        .with_log_stream(logs.clone());
+    let _ = std::fs::remove_file(sidecar.log_path());
+    sidecar.spawn_with_retry().unwrap();
+
+    assert!(wait_until(Duration::from_secs(5), || {
+        sidecar
+            .read_last_log_lines(1000)
+            .iter()
+            .any(|line| line.contains("mock error line"))
+            && logs
+                .recent_lines("logs", 600)
+                .iter()
+                .any(|line| line.contains("mock log line 499"))
+    }));
+    let logged = sidecar.read_last_log_lines(1000);
+    assert!(logged.iter().any(|line| line.contains("mock log line 0")));
+    assert!(logged.iter().any(|line| line.contains("session started")));
+    assert!(logs.first_line_at("logs").is_some());
+    let recent = sidecar.recent_log_lines(1000);
+    assert!(recent[0].contains("session started"));
+    assert!(recent.iter().any(|line| line.contains("mock error line")));
+    sidecar.shutdown();
+}
+
+#[test]
+fn readiness_announces_the_port_and_health_passes() {
+    let port = free_port();
+    let sidecar = SidecarManager::new(spec("ready", &[]).with_port(port));
+    sidecar.spawn_with_retry().unwrap();
+
+    assert!(wait_until(Duration::from_secs(5), || sidecar
+        .announced_port()
+        .is_some()));
+    assert_eq!(sidecar.announced_port(), Some(port));
+    health(port, 5000).unwrap();
+    sidecar.shutdown();
+}
+
+#[test]
+fn slow_startup_is_waited_for() {
+    let port = free_port();
+    let spec = spec("slow", &[("MOCK_STARTUP_DELAY_MS", "500".to_string())]).with_port(port);
+    let sidecar = SidecarManager::new(spec);
+    let started = Instant::now();
+    sidecar.spawn_with_retry().unwrap();
+    health(port, 5000).unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(500));
+    sidecar.shutdown();
+}
+
+#[test]
+fn bad_health_responses_fail_the_health_check() {
+    let port = free_port();
+    let spec = spec("unhealthy", &[("MOCK_HEALTH_STATUS", "503".to_string())]).with_port(port);
+    let sidecar = SidecarManager::new(spec);
+    sidecar.spawn_with_retry().unwrap();
+    assert!(health(port, 1000).is_err());
+    assert!(sidecar.is_running());
+    sidecar.shutdown();
+}
+
+#[test]
+fn shutdown_asks_over_stdin_first() {
+    let spec = SidecarSpec {
+        stdin_control: true,
+        ..spec("stdin", &[])
+    };
+    let sidecar = SidecarManager::new(spec);
+    let _ = std::fs::remove_file(sidecar.log_path());
+    sidecar.spawn_with_retry().unwrap();
+    let started = Instant::now();
+    assert_eq!(
+        sidecar.shutdown_within(Duration::from_secs(5)),
+        StopOutcome::Exited
+    );
+    // Well within the half of the grace period before the signal
+    assert!(started.elapsed() < Duration::from_millis(2500));
+    assert!(wait_until(Duration::from_secs(2), || sidecar
+        .read_last_log_lines(5)
+        .iter()
+        .any(|line| line.contains("mock sidecar shutting down"))));
+    assert_eq!(
+        sidecar.shutdown_within(Duration::from_secs(1)),
+        StopOutcome::NotRunning
+    );
+}
+
+#[test]
+fn watchdog_restarts_a_crashed_sidecar() {
+    let spec = SidecarSpec {
+        restart_policy: RestartPolicy::OnFailure,
+        max_restarts: 1,
+        ..spec("crash", &[("MOCK_CRASH_AFTER_MS", "300".to_string())])
+    };
+    let registry = Arc::new(SidecarRegistry::default());
+    let sidecar = registry.insert(SidecarManager::new(spec));
+    sidecar.spawn_with_retry().unwrap();
+
+    let (exits, exited) = mpsc::channel();
+    registry
+        .clone()
+        .start_watchdog(|_| true, move |exit| exits.send(exit.clone()).unwrap());
+
+    let first = exited.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(first.name, "crash");
+    assert_eq!(first.status.code(), Some(1));
+    assert!(first.restarting);
+    assert!(wait_until(Duration::from_secs(5), || sidecar
+        .status()
+        .restarts
+        == 1
+        && sidecar.is_running()));
+    assert!(sidecar.last_exit_info().is_none());
+
+    // Out of restarts, the second crash leaves it stopped
+    let second = exited.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert!(!second.restarting);
+    assert!(!second.throttled);
+    assert!(wait_until(Duration::from_secs(5), || !sidecar.is_running()));
+    assert!(sidecar.last_exit_info().is_some());
+}
+
+#[test]
+fn watchdog_holds_back_a_throttled_restart() {
+    let spec = SidecarSpec {
+        restart_policy: RestartPolicy::Always,
+        ..spec("throttled", &[("MOCK_CRASH_AFTER_MS", "200".to_string())])
+    };
+    let registry = Arc::new(SidecarRegistry::default());
+    let sidecar = registry.insert(SidecarManager::new(spec));
+    sidecar.spawn_with_retry().unwrap();
+
+    let (exits, exited) = mpsc::channel();
+    registry
+        .clone()
+        .start_watchdog(|_| false, move |exit| exits.send(exit.clone()).unwrap());
+
+    let exit = exited.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert!(exit.throttled);
+    assert!(!exit.restarting);
+    thread::sleep(Duration::from_millis(500));
+    assert!(!sidecar.is_running());
+    assert_eq!(sidecar.status().restarts, 0);
+}
@@ -0,0 +1,81 @@
+//! A stand-in backend for the sidecar tests, built with the `mock-sidecar`
+//! feature. Everything it does is set through the environment:
+//!
+//! - `PORT`: serve `/health` on 127.0.0.1 and announce `::ready port=N`
+//! - `MOCK_STARTUP_DELAY_MS`: wait this long before listening
+//! - `MOCK_HEALTH_STATUS`: what `/health` answers (200)
+//! - `MOCK_CRASH_AFTER_MS`: exit with `MOCK_EXIT_CODE` (1) after this long
+//! - `MOCK_LOG_LINES`: print this many numbered lines to stdout at start,
+//!   and one to stderr
+//!
+//! With `LOCAL_AGENT_CONTROL=stdin` it exits on `::graceful-shutdown`.
+
+use std::io::{BufRead, Read, Write};
+use std::net::TcpListener;
+use std::time::Duration;
+use std::{env, io, process, thread};
+
+fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+    env::var(name).ok().and_then(|value| value.parse().ok())
+}
+
+fn main() {
+    if let Some(delay) = var::<u64>("MOCK_STARTUP_DELAY_MS") {
+        thread::sleep(Duration::from_millis(delay));
+    }
+
+    if let Some(after) = var::<u64>("MOCK_CRASH_AFTER_MS") {
+        let code = var::<i32>("MOCK_EXIT_CODE").unwrap_or(1);
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(after));
+            eprintln!("mock sidecar crashing with code {}", code);
+            process::exit(code);
+        });
+    }
+
+    if env::var("LOCAL_AGENT_CONTROL").is_ok_and(|v| v == "stdin") {
+        thread::spawn(|| {
+            for line in io::stdin().lock().lines().map_while(Result::ok) {
+                if line.trim() == "::graceful-shutdown" {
+                    println!("mock sidecar shutting down");
+                    process::exit(0);
+                }
+            }
+        });
+    }
+
+    let lines = var::<usize>("MOCK_LOG_LINES").unwrap_or(0);
+    for n in 0..lines {
+        println!("mock log line {}", n);
+    }
+    if lines > 0 {
+        eprintln!("mock error line");
+    }
+
+    let Some(port) = var::<u16>("PORT") else {
+        loop {
+            thread::sleep(Duration::from_secs(60));
+        }
+    };
+    let listener = TcpListener::bind(("127.0.0.1", port)).expect("mock sidecar could not listen");
+    println!("::ready port={}", listener.local_addr().unwrap().port());
+    let _ = io::stdout().flush();
+
+    let status = var::<u16>("MOCK_HEALTH_STATUS").unwrap_or(200);
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        // Every request gets the health answer; only the status matters
+        let mut request = [0u8; 1024];
+        let _ = stream.read(&mut request);
+        let body = r#"{"status":"mock"}"#;
+        let _ = write!(
+            stream,
+            "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+    }
+}
@@ -1,3 +1,4 @@
+import { backendFetch } from '../utils/backendFetch';
 import {
   ProviderRequestError,
   extractErrorMessage,
@@ -106,7 +107,7 @@ export function createLocalOpenAICompatibleProvider(): ProviderModelApi {
         body.tools = request.tools;
       }
 
-      const response = await fetch(LOCAL_CHAT_COMPLETIONS_ENDPOINT, {
+      const response = await backendFetch(LOCAL_CHAT_COMPLETIONS_ENDPOINT, {
         method: 'POST',
         headers,
         body: JSON.stringify(body),
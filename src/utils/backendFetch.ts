@@ -0,0 +1,116 @@
+import { Channel, invoke } from '@tauri-apps/api/core';
+
+type StreamEvent =
+  | { event: 'head'; status: number; headers: [string, string][] }
+  | { event: 'data'; bytes: number[] }
+  | { event: 'end' }
+  | { event: 'error'; message: string };
+
+/**
+ * Whether the app is loaded through the desktop shell's socket bridge. Its
+ * protocol hands responses over whole, so streamed model output would only
+ * appear once generation ends.
+ */
+function usesSocketBridge(): boolean {
+  return (
+    typeof window !== 'undefined' &&
+    '__TAURI_INTERNALS__' in window &&
+    (window.location.protocol === 'localagent-app:' ||
+      window.location.hostname === 'localagent-app.localhost')
+  );
+}
+
+/**
+ * `fetch` for backend paths whose response is streamed. Over the socket
+ * bridge the shell forwards each chunk as it arrives; cancelling the body or
+ * aborting `init.signal` drops the backend request.
+ */
+export async function backendFetch(path: string, init: RequestInit = {}): Promise<Response> {
+  if (!usesSocketBridge()) {
+    return fetch(path, init);
+  }
+
+  const signal = init.signal;
+  signal?.throwIfAborted();
+
+  let id: number | undefined;
+  let cancelled = false;
+  const cancel = () => {
+    cancelled = true;
+    if (id !== undefined) {
+      void invoke('cancel_backend_stream', { id });
+    }
+  };
+
+  let body!: ReadableStreamDefaultController<Uint8Array>;
+  const stream = new ReadableStream<Uint8Array>({
+    start(controller) {
+      body = controller;
+    },
+    cancel,
+  });
+
+  return new Promise<Response>((resolve, reject) => {
+    let started = false;
+    const onEvent = new Channel<StreamEvent>();
+    onEvent.onmessage = (message) => {
+      switch (message.event) {
+        case 'head': {
+          started = true;
+          // These statuses can't carry a body
+          const empty = [204, 205, 304].includes(message.status);
+          resolve(
+            new Response(empty ? null : stream, {
+              status: message.status,
+              headers: message.headers,
+            })
+          );
+          break;
+        }
+        case 'data':
+          body.enqueue(new Uint8Array(message.bytes));
+          break;
+        case 'end':
+          body.close();
+          break;
+        case 'error':
+          if (started) {
+            body.error(new Error(message.message));
+          } else {
+            reject(new Error(message.message));
+          }
+          break;
+      }
+    };
+
+    signal?.addEventListener(
+      'abort',
+      () => {
+        cancel();
+        const reason = signal.reason as unknown;
+        if (started) {
+          body.error(reason);
+        } else {
+          reject(reason);
+        }
+      },
+      { once: true }
+    );
+
+    invoke<number>('stream_backend', {
+      method: init.method ?? 'GET',
+      path,
+      headers: [...new Headers(init.headers).entries()],
+      body: typeof init.body === 'string' ? init.body : null,
+      onEvent,
+    })
+      .then((streamId) => {
+        id = streamId;
+        // Aborted while the stream was starting
+        if (cancelled) {
+          cancel();
+        }
+      })
+      .catch(reject);
+  });
+}
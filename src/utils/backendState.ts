@@ -0,0 +1,106 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+
+export type BackendState =
+  | 'not_started'
+  | 'spawning'
+  | 'waiting_healthy'
+  | 'healthy'
+  | 'degraded'
+  | 'restarting'
+  | 'failed'
+  | 'suspended';
+
+export interface BackendStatus {
+  state: BackendState;
+  previous: BackendState | null;
+  detail: string | null;
+  since: string | null;
+}
+
+function inShell(): boolean {
+  return typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window;
+}
+
+/**
+ * Calls `onChange` with the desktop shell's view of the backend, now and on
+ * every change. Outside the shell nothing is reported.
+ */
+export async function watchBackendState(
+  onChange: (status: BackendStatus) => void
+): Promise<UnlistenFn> {
+  if (!inShell()) {
+    return () => {};
+  }
+  const unlisten = await listen<BackendStatus>('backend-state-changed', (event) =>
+    onChange(event.payload)
+  );
+  onChange(await invoke<BackendStatus>('get_backend_state'));
+  return unlisten;
+}
+
+/**
+ * Calls `onReady` when the backend is back after a soft restart
+ * (`restart_backend_soft`), then tells the shell the app reconnected so it
+ * doesn't reload the page.
+ */
+export async function onBackendReady(onReady: () => void | Promise<void>): Promise<UnlistenFn> {
+  if (!inShell()) {
+    return () => {};
+  }
+  return listen('backend-ready', async () => {
+    await onReady();
+    await invoke('ack_backend_ready');
+  });
+}
+
+/**
+ * Calls `onChange(true)` when the app is running offline from the shell
+ * while the backend restarts: saved chats can be read but nothing can be
+ * changed. The app is reloaded once the backend is back.
+ */
+export async function watchOfflineMode(onChange: (offline: boolean) => void): Promise<UnlistenFn> {
+  if (!inShell()) {
+    return () => {};
+  }
+  const unlisten = await listen<boolean>('offline-mode', (event) => onChange(event.payload));
+  onChange(await invoke<boolean>('get_offline_mode'));
+  return unlisten;
+}
+
+interface TailSnapshot {
+  lines: string[];
+  subscription: number | null;
+}
+
+interface LogAppended {
+  subscription: number;
+  lines: string[];
+  skipped: number;
+}
+
+/**
+ * Calls `onLines` with the backend log's last `lines` lines, then with each
+ * batch logged after them until the returned function is called.
+ */
+export async function followBackendLogs(
+  lines: number,
+  onLines: (lines: string[], skipped: number) => void,
+): Promise<UnlistenFn> {
+  if (!inShell()) {
+    return () => {};
+  }
+  let subscription: number | null = null;
+  const unlisten = await listen<LogAppended>('log-appended', (event) => {
+    if (event.payload.subscription === subscription) {
+      onLines(event.payload.lines, event.payload.skipped);
+    }
+  });
+  const snapshot = await invoke<TailSnapshot>('tail_logs', { lines, follow: true });
+  subscription = snapshot.subscription;
+  onLines(snapshot.lines, 0);
+  return () => {
+    unlisten();
+    void invoke('untail_logs', { subscription });
+  };
+}
@@ -0,0 +1,155 @@
+import { describe, it, before, after } from 'node:test';
+import assert from 'node:assert';
+import crypto from 'crypto';
+import http from 'http';
+import {
+  createLanAccess,
+  createShellOnly,
+  isLoopback,
+  parsePinHash,
+  UNLOCK_PATH,
+} from '../backend/lanAccess.js';
+
+function pinHash(pin, iterations = 1000) {
+  const salt = crypto.randomBytes(16);
+  const hash = crypto.pbkdf2Sync(pin, salt, iterations, 32, 'sha256');
+  return `pbkdf2-sha256$${iterations}$${salt.toString('hex')}$${hash.toString('hex')}`;
+}
+
+// Every request counts as coming from another device
+function serve(options, middleware = createLanAccess({ isLocal: () => false, ...options })) {
+  const server = http.createServer((req, res) =>
+    middleware(req, res, () => {
+      res.writeHead(200, { 'Content-Type': 'application/json' });
+      res.end('{"ok":true}');
+    })
+  );
+  return new Promise((resolve) =>
+    server.listen(0, '127.0.0.1', () =>
+      resolve({ server, url: `http://127.0.0.1:${server.address().port}` })
+    )
+  );
+}
+
+const unlock = (url, pin) =>
+  fetch(`${url}${UNLOCK_PATH}`, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify({ pin }),
+  });
+
+describe('isLoopback', () => {
+  it('recognizes IPv4, IPv6 and mapped loopback addresses', () => {
+    assert.ok(isLoopback('127.0.0.1'));
+    assert.ok(isLoopback('::1'));
+    assert.ok(isLoopback('::ffff:127.0.0.1'));
+    assert.ok(!isLoopback('192.168.1.20'));
+    assert.ok(!isLoopback('::ffff:10.0.0.2'));
+    assert.ok(!isLoopback(undefined));
+  });
+});
+
+describe('parsePinHash', () => {
+  it('accepts the shell format and rejects anything else', () => {
+    assert.ok(parsePinHash(pinHash('1234')));
+    assert.strictEqual(parsePinHash('03ac674216f3e15c761ee1a5e255f067'), null);
+    assert.strictEqual(parsePinHash('pbkdf2-sha256$0$00$00'), null);
+    assert.strictEqual(parsePinHash('pbkdf2-sha256$1000$$'), null);
+  });
+});
+
+describe('LAN PIN', () => {
+  let server;
+  let url;
+
+  before(async () => {
+    ({ server, url } = await serve({ pinHash: pinHash('4821') }));
+  });
+
+  after(() => server.close());
+
+  it('asks other devices for the PIN', async () => {
+    const res = await fetch(`${url}/api/chats`);
+    assert.strictEqual(res.status, 401);
+    assert.strictEqual((await res.json()).code, 'LAN_PIN_REQUIRED');
+
+    const page = await fetch(url, { headers: { Accept: 'text/html' } });
+    assert.strictEqual(page.status, 401);
+    assert.ok((await page.text()).includes(UNLOCK_PATH));
+  });
+
+  it('lets a device in with the right PIN', async () => {
+    const wrong = await unlock(url, '0000');
+    assert.strictEqual(wrong.status, 401);
+
+    const right = await unlock(url, '4821');
+    assert.strictEqual(right.status, 200);
+    const cookie = right.headers.get('set-cookie').split(';')[0];
+    const res = await fetch(`${url}/api/chats`, { headers: { Cookie: cookie } });
+    assert.strictEqual(res.status, 200);
+
+    const forged = await fetch(`${url}/api/chats`, {
+      headers: { Cookie: 'la_lan_session=' + 'a'.repeat(64) },
+    });
+    assert.strictEqual(forged.status, 401);
+  });
+
+  it('locks a device out after repeated wrong guesses', async () => {
+    const { server: locked, url: lockedUrl } = await serve({ pinHash: pinHash('4821') });
+    try {
+      for (let i = 0; i < 5; i += 1) {
+        assert.strictEqual((await unlock(lockedUrl, '1111')).status, 401);
+      }
+      const res = await unlock(lockedUrl, '4821');
+      assert.strictEqual(res.status, 429);
+      assert.ok(Number(res.headers.get('retry-after')) > 0);
+    } finally {
+      locked.close();
+    }
+  });
+
+  it('refuses every PIN when the hash is unusable', async () => {
+    const { server: broken, url: brokenUrl } = await serve({ pinHash: 'not-a-hash' });
+    try {
+      assert.strictEqual((await unlock(brokenUrl, '4821')).status, 401);
+    } finally {
+      broken.close();
+    }
+  });
+
+  it('lets everything through without a PIN or from this computer', async () => {
+    const open = await serve({ pinHash: '' });
+    const local = await serve({ pinHash: pinHash('4821'), isLocal: () => true });
+    try {
+      assert.strictEqual((await fetch(`${open.url}/api/chats`)).status, 200);
+      assert.strictEqual((await fetch(`${local.url}/api/chats`)).status, 200);
+    } finally {
+      open.server.close();
+      local.server.close();
+    }
+  });
+});
+
+describe('shell-only endpoints', () => {
+  const shutdown = (url, headers) => fetch(`${url}/api/shutdown`, { method: 'POST', headers });
+
+  it('take the shell header from this computer', async () => {
+    const { server, url } = await serve({}, createShellOnly());
+    try {
+      assert.strictEqual((await shutdown(url, { 'x-local-agent-shell': '1' })).status, 200);
+      assert.strictEqual((await shutdown(url, {})).status, 403);
+    } finally {
+      server.close();
+    }
+  });
+
+  it('refuse other devices even with the shell header', async () => {
+    const { server, url } = await serve({}, createShellOnly({ isLocal: () => false }));
+    try {
+      const res = await shutdown(url, { 'x-local-agent-shell': '1' });
+      assert.strictEqual(res.status, 403);
+    } finally {
+      server.close();
+    }
+  });
+});
@@ -0,0 +1,127 @@
+import { describe, it, after } from 'node:test';
+import assert from 'node:assert';
+import { spawn } from 'child_process';
+import crypto from 'crypto';
+import fs from 'fs';
+import net from 'net';
+import os from 'os';
+import path from 'path';
+import { fileURLToPath } from 'url';
+
+const SERVER = path.join(path.dirname(fileURLToPath(import.meta.url)), '..', 'server.js');
+const dataDir = fs.mkdtempSync(path.join(os.tmpdir(), 'lanExposure-test-'));
+
+// This machine's address on the network, which other devices would use
+const lanAddress = Object.values(os.networkInterfaces())
+  .flat()
+  .find((iface) => iface && iface.family === 'IPv4' && !iface.internal)?.address;
+
+// The packages server.js imports; unlike the unit tests, this one needs
+// `npm install`
+const missingDependencies = [
+  'express',
+  'cors',
+  'dotenv',
+  'http-proxy-middleware',
+  'zod',
+  '@modelcontextprotocol/sdk/client/index.js',
+].filter((name) => {
+  try {
+    import.meta.resolve(name);
+    return false;
+  } catch {
+    return true;
+  }
+});
+const skip = missingDependencies.length
+  ? `server.js dependencies are not installed (${missingDependencies.join(', ')}); run npm install`
+  : !lanAddress && 'no non-loopback network interface';
+
+function freePort() {
+  return new Promise((resolve, reject) => {
+    const probe = net.createServer();
+    probe.on('error', reject);
+    probe.listen(0, '127.0.0.1', () => {
+      const { port } = probe.address();
+      probe.close(() => resolve(port));
+    });
+  });
+}
+
+function pinHash(pin) {
+  const salt = crypto.randomBytes(16);
+  const hash = crypto.pbkdf2Sync(pin, salt, 1000, 32, 'sha256');
+  return `pbkdf2-sha256$1000$${salt.toString('hex')}$${hash.toString('hex')}`;
+}
+
+const running = [];
+
+// Starts the backend the way the desktop shell does and waits for `::ready`
+async function startServer(env) {
+  const port = await freePort();
+  const child = spawn(process.execPath, [SERVER], {
+    env: { ...process.env, PORT: String(port), LOCAL_AGENT_DATA_DIR: dataDir, ...env },
+    stdio: ['ignore', 'pipe', 'inherit'],
+  });
+  running.push(child);
+  await new Promise((resolve, reject) => {
+    let output = '';
+    child.stdout.on('data', (chunk) => {
+      output += chunk;
+      if (output.includes('::ready')) resolve();
+    });
+    child.on('exit', (code) => reject(new Error(`server.js exited with ${code}`)));
+  });
+  return port;
+}
+
+after(() => {
+  for (const child of running) child.kill();
+  fs.rmSync(dataDir, { recursive: true, force: true });
+});
+
+describe('LAN exposure', { skip }, () => {
+  it('stays on loopback by default', async () => {
+    const port = await startServer({ HOST: '' });
+    assert.strictEqual((await fetch(`http://127.0.0.1:${port}/health`)).status, 200);
+    await assert.rejects(fetch(`http://${lanAddress}:${port}/health`));
+  });
+
+  it('is reachable off-loopback with HOST=0.0.0.0', async () => {
+    const port = await startServer({ HOST: '0.0.0.0' });
+    const res = await fetch(`http://${lanAddress}:${port}/health`);
+    assert.strictEqual(res.status, 200);
+    assert.deepStrictEqual(await res.json(), { status: 'ok' });
+  });
+
+  it("keeps the shell's own endpoints to this computer", async () => {
+    const port = await startServer({ HOST: '0.0.0.0' });
+    const res = await fetch(`http://${lanAddress}:${port}/api/shutdown`, {
+      method: 'POST',
+      headers: { 'x-local-agent-shell': '1' },
+    });
+    assert.strictEqual(res.status, 403);
+  });
+
+  it('asks off-loopback requests for the PIN', async () => {
+    const port = await startServer({
+      HOST: '0.0.0.0',
+      LOCAL_AGENT_LAN_PIN_HASH: pinHash('4821'),
+    });
+    const lan = `http://${lanAddress}:${port}`;
+
+    assert.strictEqual((await fetch(`${lan}/api/chats`)).status, 401);
+    // This computer doesn't need it
+    assert.strictEqual((await fetch(`http://127.0.0.1:${port}/api/chats`)).status, 200);
+
+    const unlocked = await fetch(`${lan}/lan/unlock`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ pin: '4821' }),
+    });
+    assert.strictEqual(unlocked.status, 200);
+    const cookie = unlocked.headers.get('set-cookie').split(';')[0];
+    const res = await fetch(`${lan}/api/chats`, { headers: { Cookie: cookie } });
+    assert.strictEqual(res.status, 200);
+  });
+});
@@ -0,0 +1,64 @@
+import { describe, it, before, after } from 'node:test';
+import assert from 'node:assert';
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { createMaintenanceTasks, pruneEmptyChats } from '../backend/maintenance.js';
+import { createToolEventLogger } from '../backend/toolEventLogger.js';
+
+const DAY_MS = 24 * 60 * 60 * 1000;
+
+function writeChat(dir, name, data, ageMs) {
+  const filepath = path.join(dir, name);
+  fs.writeFileSync(filepath, JSON.stringify(data));
+  const mtime = new Date(Date.now() - ageMs);
+  fs.utimesSync(filepath, mtime, mtime);
+}
+
+describe('pruneEmptyChats', () => {
+  let dir;
+
+  before(() => {
+    dir = fs.mkdtempSync(path.join(os.tmpdir(), 'maintenance-test-'));
+  });
+
+  after(() => fs.rmSync(dir, { recursive: true, force: true }));
+
+  it('removes only old, empty, unpinned chats', () => {
+    writeChat(dir, 'abandoned.json', { messages: [], pinned: false }, 2 * DAY_MS);
+    writeChat(dir, 'fresh.json', { messages: [] }, 60 * 1000);
+    writeChat(dir, 'pinned.json', { messages: [], pinned: true }, 2 * DAY_MS);
+    writeChat(dir, 'talked.json', { messages: [{ role: 'user', content: 'hi' }] }, 2 * DAY_MS);
+    fs.writeFileSync(path.join(dir, 'broken.json'), '{');
+
+    assert.deepStrictEqual(pruneEmptyChats(dir), { removed: 1, kept: 4 });
+    assert.deepStrictEqual(fs.readdirSync(dir).sort(), [
+      'broken.json',
+      'fresh.json',
+      'pinned.json',
+      'talked.json',
+    ]);
+  });
+
+  it('does nothing without a chats directory', () => {
+    assert.deepStrictEqual(pruneEmptyChats(path.join(dir, 'missing')), { removed: 0, kept: 0 });
+  });
+});
+
+describe('maintenance tasks', () => {
+  it('are named as the shell schedules them', () => {
+    const root = fs.mkdtempSync(path.join(os.tmpdir(), 'maintenance-tasks-'));
+    try {
+      const tasks = createMaintenanceTasks({
+        toolEventLogger: createToolEventLogger(root),
+        chatsDir: path.join(root, 'chats'),
+      });
+      assert.deepStrictEqual([...tasks.keys()], ['compact-tool-log', 'prune-empty-chats']);
+      assert.deepStrictEqual(tasks.get('compact-tool-log')(), { kept: 0, removed: 0 });
+      assert.deepStrictEqual(tasks.get('prune-empty-chats')(), { removed: 0, kept: 0 });
+      assert.strictEqual(tasks.get('constructor'), undefined);
+    } finally {
+      fs.rmSync(root, { recursive: true, force: true });
+    }
+  });
+});
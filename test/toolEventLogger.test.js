@@ -73,3 +73,25 @@ describe('tool event logger redaction', () => {
     assert.strictEqual(event.args_preview.filePath, 'ok');
   });
 });
+
+describe('tool event log compaction', () => {
+  it('keeps only the newest events', () => {
+    const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'toolEventLogger-compact-'));
+    const logger = createToolEventLogger(dir);
+    const logPath = path.join(dir, 'logs', 'tool-calls.jsonl');
+    for (let i = 0; i < 5; i += 1) {
+      logger.persist(logger.startEvent({ tool_name: `tool_${i}`, args: {}, source: 'native' }));
+    }
+    fs.appendFileSync(logPath, 'not json\n');
+
+    assert.deepStrictEqual(logger.compact(2), { kept: 2, removed: 4 });
+    const names = logger.listEvents().map((event) => event.tool_name);
+    assert.deepStrictEqual(names, ['tool_4', 'tool_3']);
+    assert.ok(!fs.existsSync(`${logPath}.tmp`));
+
+    // Numbering carries on after the dropped events
+    const next = logger.startEvent({ tool_name: 'next', args: {}, source: 'native' });
+    assert.strictEqual(next.sequence, 6);
+    fs.rmSync(dir, { recursive: true, force: true });
+  });
+});